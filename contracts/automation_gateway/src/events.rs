@@ -0,0 +1,148 @@
+//! Events published by AutomationGateway, all under the `gateway` namespace.
+//!
+//! Topics follow `quipay_common::events`. Agent management names the
+//! employer as the actor; routed actions name the agent, and approvals or
+//! rejections of proposed actions the approver.
+
+use crate::{Permission, ProposedAction};
+use soroban_sdk::{Address, Bytes, Vec, contracttype};
+
+pub(crate) use quipay_common::events::emit;
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AgentRegistered {
+    pub agent: Address,
+    pub employer: Address,
+    pub permissions: Vec<Permission>,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AgentRevoked {
+    pub agent: Address,
+    pub employer: Address,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PermissionsSet {
+    pub agent: Address,
+    pub employer: Address,
+    pub permissions: Vec<Permission>,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PermissionGranted {
+    pub agent: Address,
+    pub employer: Address,
+    pub permission: Permission,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PermissionRevoked {
+    pub agent: Address,
+    pub employer: Address,
+    pub permission: Permission,
+}
+
+/// An automated action handed to off-chain executors.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ActionExecuted {
+    pub agent: Address,
+    pub employer: Address,
+    pub action: Permission,
+    pub data: Bytes,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StreamCreated {
+    pub stream_id: u64,
+    pub agent: Address,
+    pub employer: Address,
+    pub worker: Address,
+    pub rate: i128,
+    pub start_ts: u64,
+    pub end_ts: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StreamCanceled {
+    pub stream_id: u64,
+    pub agent: Address,
+    pub employer: Address,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ActionProposed {
+    pub proposal_id: u64,
+    pub agent: Address,
+    pub employer: Address,
+    pub action: ProposedAction,
+    pub expires_at: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ActionApproved {
+    pub proposal_id: u64,
+    pub agent: Address,
+    pub employer: Address,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ActionRejected {
+    pub proposal_id: u64,
+    pub agent: Address,
+    pub employer: Address,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BondPosted {
+    pub agent: Address,
+    pub employer: Address,
+    pub amount: i128,
+    pub total: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BondSlashed {
+    pub agent: Address,
+    pub employer: Address,
+    pub amount: i128,
+    pub remaining: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BondWithdrawn {
+    pub agent: Address,
+    pub employer: Address,
+    pub amount: i128,
+}
+
+quipay_common::event_names!("gateway" {
+    AgentRegistered => "agent_registered",
+    AgentRevoked => "agent_revoked",
+    PermissionsSet => "permissions_set",
+    PermissionGranted => "permission_granted",
+    PermissionRevoked => "permission_revoked",
+    ActionExecuted => "executed",
+    StreamCreated => "stream_created",
+    StreamCanceled => "stream_canceled",
+    ActionProposed => "action_proposed",
+    ActionApproved => "action_approved",
+    ActionRejected => "action_rejected",
+    BondPosted => "bond_posted",
+    BondSlashed => "bond_slashed",
+    BondWithdrawn => "bond_withdrawn",
+});
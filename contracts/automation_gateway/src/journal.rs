@@ -0,0 +1,134 @@
+//! Replay protection and an on-chain journal of agent actions.
+//!
+//! Every agent has a monotonic nonce; `execute_automation`,
+//! `agent_create_stream` and `agent_cancel_stream` only accept the agent's
+//! next nonce and an optional expiry timestamp, so a signed action cannot be
+//! replayed or submitted late. Each action an agent performs
+//! through the gateway is appended to the employer's journal, which the
+//! employer can page through to audit automation decisions.
+
+use super::*;
+use soroban_sdk::xdr::ToXdr;
+
+/// Maximum entries returned by one `get_journal` call.
+pub(crate) const MAX_JOURNAL_PAGE: u32 = 100;
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub enum ActionResult {
+    Executed,
+    StreamCreated(u64),
+    StreamCanceled(u64),
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct JournalEntry {
+    pub id: u64,
+    pub agent: Address,
+    pub action: Permission,
+    /// SHA-256 of the action payload.
+    pub payload_hash: BytesN<32>,
+    pub result: ActionResult,
+    pub timestamp: u64,
+}
+
+#[contractimpl]
+impl AutomationGateway {
+    /// The nonce the agent's next direct action must carry.
+    pub fn get_agent_nonce(env: Env, agent: Address) -> u64 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::AgentNonce(agent))
+            .unwrap_or(0)
+    }
+
+    /// Number of journal entries recorded for `employer`.
+    pub fn get_journal_count(env: Env, employer: Address) -> u64 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::JournalCount(employer))
+            .unwrap_or(0)
+    }
+
+    /// Page through the agent actions taken for `employer`, oldest first.
+    /// `limit` is capped at `MAX_JOURNAL_PAGE`.
+    pub fn get_journal(env: Env, employer: Address, start: u64, limit: u32) -> Vec<JournalEntry> {
+        let count = Self::get_journal_count(env.clone(), employer.clone());
+        let end = start
+            .saturating_add(limit.min(MAX_JOURNAL_PAGE) as u64)
+            .min(count);
+
+        let mut page = Vec::new(&env);
+        let mut id = start;
+        while id < end {
+            if let Some(entry) = env
+                .storage()
+                .persistent()
+                .get(&DataKey::JournalEntry(employer.clone(), id))
+            {
+                page.push_back(entry);
+            }
+            id += 1;
+        }
+        page
+    }
+}
+
+impl AutomationGateway {
+    /// Reject expired actions and consume the agent's next nonce.
+    pub(crate) fn consume_nonce(
+        env: &Env,
+        agent: &Address,
+        nonce: u64,
+        expires_at: Option<u64>,
+    ) -> Result<(), QuipayError> {
+        if let Some(expires_at) = expires_at {
+            require!(
+                env.ledger().timestamp() <= expires_at,
+                QuipayError::ActionExpired
+            );
+        }
+
+        let expected = Self::get_agent_nonce(env.clone(), agent.clone());
+        require!(nonce == expected, QuipayError::InvalidNonce);
+        env.storage()
+            .persistent()
+            .set(&DataKey::AgentNonce(agent.clone()), &(expected + 1));
+        Ok(())
+    }
+
+    /// Hash an action payload for the journal.
+    pub(crate) fn payload_hash<T: IntoVal<Env, Val>>(env: &Env, payload: T) -> BytesN<32> {
+        env.crypto().sha256(&payload.to_xdr(env)).into()
+    }
+
+    /// Append an entry to the employer's journal and return its id.
+    pub(crate) fn record_action(
+        env: &Env,
+        employer: &Address,
+        agent: &Address,
+        action: Permission,
+        payload_hash: BytesN<32>,
+        result: ActionResult,
+    ) -> u64 {
+        let id = Self::get_journal_count(env.clone(), employer.clone());
+        let entry = JournalEntry {
+            id,
+            agent: agent.clone(),
+            action,
+            payload_hash,
+            result,
+            timestamp: env.ledger().timestamp(),
+        };
+        env.storage()
+            .persistent()
+            .set(&DataKey::JournalEntry(employer.clone(), id), &entry);
+        env.storage()
+            .persistent()
+            .set(&DataKey::JournalCount(employer.clone()), &(id + 1));
+        ttl::extend(env, &DataKey::JournalEntry(employer.clone(), id));
+        ttl::extend(env, &DataKey::JournalCount(employer.clone()));
+        id
+    }
+}
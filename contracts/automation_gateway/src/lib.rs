@@ -1,10 +1,26 @@
 #![no_std]
-use quipay_common::{QuipayError, require};
+use events::{
+    ActionExecuted, AgentRegistered, AgentRevoked, PermissionGranted, PermissionRevoked,
+    PermissionsSet, StreamCanceled, StreamCreated,
+};
+use quipay_common::{
+    AdminKeys, AdminStorage, PendingUpgrade, QuipayError, Role, VersionInfo, admin, rbac, require,
+    ttl, upgrade,
+};
 use soroban_sdk::{
-    Address, Bytes, Env, IntoVal, Symbol, Vec, contract, contractimpl, contracttype, symbol_short,
-    vec,
+    Address, Bytes, BytesN, Env, IntoVal, Symbol, Val, Vec, contract, contractimpl, contracttype,
+    token, vec,
 };
 
+pub mod events;
+mod journal;
+mod proposal;
+mod stake;
+mod storage_ttl;
+pub use journal::{ActionResult, JournalEntry};
+pub use proposal::{ActionProposal, ActionProposalStatus, ProposedAction, StreamRequest};
+pub use stake::{AgentBond, StakeConfig};
+
 #[contracttype]
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 #[repr(u32)]
@@ -15,12 +31,15 @@ pub enum Permission {
     CreateStream = 4,
     CancelStream = 5,
     RebalanceTreasury = 6,
+    /// Queue actions for human approval instead of executing them.
+    ProposeActions = 7,
 }
 
 #[contracttype]
 #[derive(Clone, Debug)]
 pub struct Agent {
     pub address: Address,
+    pub employer: Address,
     pub permissions: Vec<Permission>,
     pub registered_at: u64,
 }
@@ -28,110 +47,120 @@ pub struct Agent {
 #[contracttype]
 pub enum DataKey {
     Admin,
-    PendingAdmin, // Two-step admin transfer
-    Agent(Address),
+    PendingAdmin,            // Two-step admin transfer
+    Agent(Address, Address), // (employer, agent)
+    EmployerAgents(Address), // Agents registered by an employer
     PayrollStream,
+    StakeConfig,
+    Bond(Address, Address), // (employer, agent)
+    PermissionMinStake(Permission),
+    Slasher,
+    AgentNonce(Address),
+    JournalCount(Address),      // employer
+    JournalEntry(Address, u64), // (employer, entry id)
+    ActionApprover(Address),    // employer
+    NextActionProposalId,
+    ActionProposal(u64),
+    PendingActions(Address), // employer
 }
 
+const ADMIN_KEYS: AdminKeys<DataKey> = AdminKeys {
+    storage: AdminStorage::Instance,
+    admin: DataKey::Admin,
+    pending: DataKey::PendingAdmin,
+};
+
 #[contract]
+/// A single gateway serves many employers. Each employer registers and
+/// manages its own agents; an agent's permissions are scoped to the employer
+/// that granted them, so an agent can only act for employers that authorised
+/// it. The admin only manages protocol configuration.
 pub struct AutomationGateway;
 
 #[contractimpl]
 impl AutomationGateway {
-    /// Initialize the contract with an admin (employer).
+    /// Initialize the contract with the protocol admin.
     pub fn init(env: Env, admin: Address) -> Result<(), QuipayError> {
         require!(
             !env.storage().instance().has(&DataKey::Admin),
             QuipayError::AlreadyInitialized
         );
         env.storage().instance().set(&DataKey::Admin, &admin);
+        upgrade::set_initial_version(&env, (1, 0, 0));
         Ok(())
     }
 
     /// Replace an agent's permissions.
-    /// Only the admin can call this.
+    /// Only the employer that registered the agent can call this.
     pub fn set_agent_permissions(
         env: Env,
+        employer: Address,
         agent_address: Address,
         permissions: Vec<Permission>,
     ) -> Result<(), QuipayError> {
-        let admin = Self::get_admin(env.clone())?;
-        admin.require_auth();
+        employer.require_auth();
 
-        let mut agent: Agent = env
-            .storage()
-            .instance()
-            .get(&DataKey::Agent(agent_address.clone()))
+        let mut agent = Self::get_agent(env.clone(), employer.clone(), agent_address.clone())
             .ok_or(QuipayError::AgentNotFound)?;
 
         agent.permissions = permissions.clone();
-        env.storage()
-            .instance()
-            .set(&DataKey::Agent(agent_address.clone()), &agent);
-
-        env.events().publish(
-            (
-                symbol_short!("gateway"),
-                symbol_short!("perm_set"),
-                agent_address.clone(),
-                symbol_short!("admin"),
-            ),
-            permissions,
+        Self::save_agent(&env, &agent);
+
+        events::emit(
+            &env,
+            &employer,
+            PermissionsSet {
+                agent: agent_address,
+                employer: employer.clone(),
+                permissions,
+            },
         );
 
         Ok(())
     }
 
     /// Grant a single permission to an agent.
-    /// Only the admin can call this.
+    /// Only the employer that registered the agent can call this.
     pub fn grant_permission(
         env: Env,
+        employer: Address,
         agent_address: Address,
         permission: Permission,
     ) -> Result<(), QuipayError> {
-        let admin = Self::get_admin(env.clone())?;
-        admin.require_auth();
+        employer.require_auth();
 
-        let mut agent: Agent = env
-            .storage()
-            .instance()
-            .get(&DataKey::Agent(agent_address.clone()))
+        let mut agent = Self::get_agent(env.clone(), employer.clone(), agent_address.clone())
             .ok_or(QuipayError::AgentNotFound)?;
 
         if !agent.permissions.contains(permission) {
             agent.permissions.push_back(permission);
-            env.storage()
-                .instance()
-                .set(&DataKey::Agent(agent_address.clone()), &agent);
+            Self::save_agent(&env, &agent);
         }
 
-        env.events().publish(
-            (
-                symbol_short!("gateway"),
-                symbol_short!("perm_add"),
-                agent_address.clone(),
-                symbol_short!("admin"),
-            ),
-            permission,
+        events::emit(
+            &env,
+            &employer,
+            PermissionGranted {
+                agent: agent_address,
+                employer: employer.clone(),
+                permission,
+            },
         );
 
         Ok(())
     }
 
     /// Revoke a single permission from an agent.
-    /// Only the admin can call this.
+    /// Only the employer that registered the agent can call this.
     pub fn revoke_permission(
         env: Env,
+        employer: Address,
         agent_address: Address,
         permission: Permission,
     ) -> Result<(), QuipayError> {
-        let admin = Self::get_admin(env.clone())?;
-        admin.require_auth();
+        employer.require_auth();
 
-        let mut agent: Agent = env
-            .storage()
-            .instance()
-            .get(&DataKey::Agent(agent_address.clone()))
+        let mut agent = Self::get_agent(env.clone(), employer.clone(), agent_address.clone())
             .ok_or(QuipayError::AgentNotFound)?;
 
         let mut new_perms: Vec<Permission> = Vec::new(&env);
@@ -144,180 +173,288 @@ impl AutomationGateway {
             i += 1;
         }
         agent.permissions = new_perms;
-        env.storage()
-            .instance()
-            .set(&DataKey::Agent(agent_address.clone()), &agent);
-
-        env.events().publish(
-            (
-                symbol_short!("gateway"),
-                symbol_short!("perm_rev"),
-                agent_address.clone(),
-                symbol_short!("admin"),
-            ),
-            permission,
+        Self::save_agent(&env, &agent);
+
+        events::emit(
+            &env,
+            &employer,
+            PermissionRevoked {
+                agent: agent_address,
+                employer: employer.clone(),
+                permission,
+            },
         );
 
         Ok(())
     }
 
-    /// Register a new AI agent with specific permissions.
-    /// Only the admin can call this.
+    /// Register an AI agent to act for `employer` with specific permissions.
+    /// Registering an existing agent replaces its permissions.
+    /// Only the employer can call this.
     pub fn register_agent(
         env: Env,
+        employer: Address,
         agent_address: Address,
         permissions: Vec<Permission>,
     ) -> Result<(), QuipayError> {
-        let admin = Self::get_admin(env.clone())?;
-        admin.require_auth();
+        employer.require_auth();
+        Self::require_min_stake(&env, &employer, &agent_address)?;
 
         let agent = Agent {
             address: agent_address.clone(),
+            employer: employer.clone(),
             permissions: permissions.clone(),
             registered_at: env.ledger().timestamp(),
         };
+        Self::save_agent(&env, &agent);
 
-        env.storage()
-            .instance()
-            .set(&DataKey::Agent(agent_address.clone()), &agent);
-
-        env.events().publish(
-            (
-                symbol_short!("gateway"),
-                symbol_short!("agent_reg"),
-                agent_address.clone(),
-                symbol_short!("admin"),
-            ),
-            permissions,
+        let index_key = DataKey::EmployerAgents(employer.clone());
+        let mut agents: Vec<Address> = env
+            .storage()
+            .persistent()
+            .get(&index_key)
+            .unwrap_or_else(|| Vec::new(&env));
+        if !agents.contains(agent_address.clone()) {
+            agents.push_back(agent_address.clone());
+            env.storage().persistent().set(&index_key, &agents);
+        }
+
+        events::emit(
+            &env,
+            &employer,
+            AgentRegistered {
+                agent: agent_address,
+                employer: employer.clone(),
+                permissions,
+            },
         );
 
         Ok(())
     }
 
-    /// Revoke an AI agent's authorization.
-    /// Only the admin can call this.
-    pub fn revoke_agent(env: Env, agent_address: Address) -> Result<(), QuipayError> {
-        let admin = Self::get_admin(env.clone())?;
-        admin.require_auth();
+    /// Revoke an AI agent's authorization to act for `employer`.
+    /// Only the employer can call this.
+    pub fn revoke_agent(
+        env: Env,
+        employer: Address,
+        agent_address: Address,
+    ) -> Result<(), QuipayError> {
+        employer.require_auth();
 
         env.storage()
-            .instance()
-            .remove(&DataKey::Agent(agent_address.clone()));
-
-        env.events().publish(
-            (
-                symbol_short!("gateway"),
-                symbol_short!("agent_rev"),
-                agent_address.clone(),
-                symbol_short!("admin"),
-            ),
-            (),
+            .persistent()
+            .remove(&DataKey::Agent(employer.clone(), agent_address.clone()));
+
+        let index_key = DataKey::EmployerAgents(employer.clone());
+        let agents: Vec<Address> = env
+            .storage()
+            .persistent()
+            .get(&index_key)
+            .unwrap_or_else(|| Vec::new(&env));
+        if let Some(index) = agents.first_index_of(agent_address.clone()) {
+            let mut agents = agents;
+            agents.remove(index);
+            env.storage().persistent().set(&index_key, &agents);
+        }
+        Self::start_bond_unlock(&env, &employer, &agent_address);
+
+        events::emit(
+            &env,
+            &employer,
+            AgentRevoked {
+                agent: agent_address,
+                employer: employer.clone(),
+            },
         );
 
         Ok(())
     }
 
-    /// Check if an agent is authorized to perform a specific action.
-    pub fn is_authorized(env: Env, agent_address: Address, action: Permission) -> bool {
-        let agent_data: Option<Agent> =
-            env.storage().instance().get(&DataKey::Agent(agent_address));
+    /// Get an agent registered by `employer`.
+    pub fn get_agent(env: Env, employer: Address, agent_address: Address) -> Option<Agent> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Agent(employer, agent_address))
+    }
 
-        match agent_data {
-            Some(agent) => agent.permissions.contains(action),
+    /// List the agents registered by `employer`.
+    pub fn get_employer_agents(env: Env, employer: Address) -> Vec<Address> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::EmployerAgents(employer))
+            .unwrap_or_else(|| Vec::new(&env))
+    }
+
+    /// Check if an agent is authorized to perform a specific action for `employer`.
+    /// When staking is enabled the agent's bond must also cover the action.
+    /// An agent holding `ProposeActions` is only authorized to propose.
+    pub fn is_authorized(
+        env: Env,
+        employer: Address,
+        agent_address: Address,
+        action: Permission,
+    ) -> bool {
+        match Self::get_agent(env.clone(), employer.clone(), agent_address.clone()) {
+            Some(agent) => {
+                (action == Permission::ProposeActions
+                    || !agent.permissions.contains(Permission::ProposeActions))
+                    && agent.permissions.contains(action)
+                    && Self::bonded_amount(&env, &employer, &agent_address)
+                        >= Self::required_stake(&env, action)
+            }
             None => false,
         }
     }
 
-    /// Route an automated action.
+    /// Route an automated action on behalf of `employer`.
+    /// `nonce` must equal the agent's next nonce and the call is rejected
+    /// after `expires_at`, so a signed action cannot be replayed.
     /// For now, this is a placeholder that verifies authorization.
     pub fn execute_automation(
         env: Env,
         agent: Address,
+        employer: Address,
         action: Permission,
-        _data: Bytes,
+        data: Bytes,
+        nonce: u64,
+        expires_at: Option<u64>,
     ) -> Result<(), QuipayError> {
         agent.require_auth();
+        Self::consume_nonce(&env, &agent, nonce, expires_at)?;
 
         require!(
-            Self::is_authorized(env.clone(), agent.clone(), action),
+            Self::is_authorized(env.clone(), employer.clone(), agent.clone(), action),
             QuipayError::InsufficientPermissions
         );
 
-        // TODO: Implement actual routing/integration with other contracts
-        env.events().publish(
-            (
-                symbol_short!("gateway"),
-                symbol_short!("executed"),
-                agent.clone(),
-                Symbol::new(&env, "action"),
-            ),
-            _data,
-        );
-
+        Self::route_automation(&env, &agent, &employer, action, data);
         Ok(())
     }
 
     // Helper to get admin
     pub fn get_admin(env: Env) -> Result<Address, QuipayError> {
-        env.storage()
-            .instance()
-            .get(&DataKey::Admin)
-            .ok_or(QuipayError::NotInitialized)
+        admin::get_admin(&env, &ADMIN_KEYS)
     }
 
     /// Get the pending admin address (if any)
     pub fn get_pending_admin(env: Env) -> Option<Address> {
-        env.storage().instance().get(&DataKey::PendingAdmin)
+        admin::get_pending_admin(&env, &ADMIN_KEYS)
     }
 
     /// Propose a new admin (step 1 of two-step transfer)
     pub fn propose_admin(env: Env, new_admin: Address) -> Result<(), QuipayError> {
-        let admin = Self::get_admin(env.clone())?;
-        admin.require_auth();
-
-        env.storage()
-            .instance()
-            .set(&DataKey::PendingAdmin, &new_admin);
-        Ok(())
+        admin::propose_admin(&env, &ADMIN_KEYS, &new_admin)
     }
 
     /// Accept admin role (step 2 of two-step transfer)
     pub fn accept_admin(env: Env) -> Result<(), QuipayError> {
-        let pending_admin: Address = env
-            .storage()
-            .instance()
-            .get(&DataKey::PendingAdmin)
-            .ok_or(QuipayError::NoPendingAdmin)?;
+        admin::accept_admin(&env, &ADMIN_KEYS)?;
+        Ok(())
+    }
 
-        pending_admin.require_auth();
+    /// Transfer admin rights to a new address (backward compatible - atomic version)
+    pub fn transfer_admin(env: Env, new_admin: Address) -> Result<(), QuipayError> {
+        admin::transfer_admin(&env, &ADMIN_KEYS, &new_admin)
+    }
 
-        // Transfer admin rights
-        env.storage()
-            .instance()
-            .set(&DataKey::Admin, &pending_admin);
-        // Clear pending admin
-        env.storage().instance().remove(&DataKey::PendingAdmin);
+    /// Propose an upgrade to `new_version` with a 48-hour timelock.
+    /// Requires the admin or an `Upgrader`.
+    pub fn propose_upgrade(
+        env: Env,
+        caller: Address,
+        new_wasm_hash: BytesN<32>,
+        new_version: (u32, u32, u32),
+    ) -> Result<(), QuipayError> {
+        let admin = Self::get_admin(env.clone())?;
+        rbac::require_role(&env, Role::Upgrader, &caller, &admin)?;
+        upgrade::propose(&env, &caller, new_wasm_hash, new_version)?;
+        Ok(())
+    }
 
+    /// Execute the pending upgrade once its timelock has passed.
+    /// Requires the admin or an `Upgrader`.
+    pub fn execute_upgrade(env: Env, caller: Address) -> Result<(), QuipayError> {
+        let admin = Self::get_admin(env.clone())?;
+        rbac::require_role(&env, Role::Upgrader, &caller, &admin)?;
+        upgrade::execute(&env, &caller)?;
         Ok(())
     }
 
-    /// Transfer admin rights to a new address (backward compatible - atomic version)
-    pub fn transfer_admin(env: Env, new_admin: Address) -> Result<(), QuipayError> {
+    /// Cancel the pending upgrade.
+    /// Requires the admin or an `Upgrader`.
+    pub fn cancel_upgrade(env: Env, caller: Address) -> Result<(), QuipayError> {
         let admin = Self::get_admin(env.clone())?;
-        admin.require_auth();
+        rbac::require_role(&env, Role::Upgrader, &caller, &admin)?;
+        upgrade::cancel(&env, &caller)?;
+        Ok(())
+    }
 
-        // Atomic two-step: propose and accept
-        env.storage()
-            .instance()
-            .set(&DataKey::PendingAdmin, &new_admin);
+    /// Reinstall the WASM replaced by the last upgrade, without a timelock.
+    /// Requires the admin or an `Upgrader`.
+    pub fn rollback_upgrade(env: Env, caller: Address) -> Result<VersionInfo, QuipayError> {
+        let admin = Self::get_admin(env.clone())?;
+        rbac::require_role(&env, Role::Upgrader, &caller, &admin)?;
+        upgrade::rollback(&env, &caller)
+    }
 
-        // Simulate accept by new admin (backward compatibility)
-        env.storage().instance().set(&DataKey::Admin, &new_admin);
-        env.storage().instance().remove(&DataKey::PendingAdmin);
+    /// Run the data migrations of the installed version, once, after an
+    /// upgrade from `from_version`.
+    /// Requires the admin or an `Upgrader`.
+    pub fn migrate(
+        env: Env,
+        caller: Address,
+        from_version: (u32, u32, u32),
+    ) -> Result<VersionInfo, QuipayError> {
+        let admin = Self::get_admin(env.clone())?;
+        rbac::require_role(&env, Role::Upgrader, &caller, &admin)?;
+        let info = upgrade::begin_migration(&env, from_version)?;
+
+        // The action proposal counter moved from instance to persistent storage.
+        let legacy: Option<u64> = env.storage().instance().get(&DataKey::NextActionProposalId);
+        if let Some(next) = legacy {
+            env.storage()
+                .persistent()
+                .set(&DataKey::NextActionProposalId, &next);
+            env.storage()
+                .instance()
+                .remove(&DataKey::NextActionProposalId);
+        }
+        Ok(info)
+    }
+
+    pub fn get_pending_upgrade(env: Env) -> Option<PendingUpgrade> {
+        upgrade::get_pending(&env)
+    }
+
+    pub fn get_version(env: Env) -> VersionInfo {
+        upgrade::installed_version(&env)
+    }
 
+    /// Grant a role to `account` (admin only).
+    pub fn grant_role(env: Env, role: Role, account: Address) -> Result<(), QuipayError> {
+        let admin = Self::get_admin(env.clone())?;
+        admin.require_auth();
+        rbac::grant_role(&env, &admin, role, &account);
         Ok(())
     }
 
+    /// Revoke a role from `account` (admin only).
+    pub fn revoke_role(env: Env, role: Role, account: Address) -> Result<(), QuipayError> {
+        let admin = Self::get_admin(env.clone())?;
+        admin.require_auth();
+        rbac::revoke_role(&env, &admin, role, &account);
+        Ok(())
+    }
+
+    /// Give up a role held by `account`.
+    pub fn renounce_role(env: Env, account: Address, role: Role) {
+        rbac::renounce_role(&env, role, &account);
+    }
+
+    pub fn has_role(env: Env, role: Role, account: Address) -> bool {
+        rbac::has_role(&env, role, &account)
+    }
+
     /// Set the PayrollStream contract address.
     /// Only the admin can call this.
     pub fn set_payroll_stream(env: Env, payroll_stream: Address) -> Result<(), QuipayError> {
@@ -335,7 +472,8 @@ impl AutomationGateway {
     }
 
     /// Create a stream on behalf of an employer through an authorized agent.
-    /// The agent must have CreateStream permission.
+    /// The agent must have CreateStream permission. `nonce` and `expires_at`
+    /// are checked as in `execute_automation`.
     pub fn agent_create_stream(
         env: Env,
         agent: Address,
@@ -346,79 +484,189 @@ impl AutomationGateway {
         cliff_ts: u64,
         start_ts: u64,
         end_ts: u64,
+        nonce: u64,
+        expires_at: Option<u64>,
     ) -> Result<u64, QuipayError> {
         agent.require_auth();
+        Self::consume_nonce(&env, &agent, nonce, expires_at)?;
+
+        require!(
+            Self::is_authorized(
+                env.clone(),
+                employer.clone(),
+                agent.clone(),
+                Permission::CreateStream
+            ),
+            QuipayError::InsufficientPermissions
+        );
+
+        Self::route_create_stream(
+            &env,
+            &agent,
+            &employer,
+            &StreamRequest {
+                worker,
+                token,
+                rate,
+                cliff_ts,
+                start_ts,
+                end_ts,
+            },
+        )
+    }
+
+    /// Cancel a stream on behalf of an employer through an authorized agent.
+    /// The agent must have CancelStream permission. `nonce` and `expires_at`
+    /// are checked as in `execute_automation`.
+    pub fn agent_cancel_stream(
+        env: Env,
+        agent: Address,
+        stream_id: u64,
+        employer: Address,
+        nonce: u64,
+        expires_at: Option<u64>,
+    ) -> Result<(), QuipayError> {
+        agent.require_auth();
+        Self::consume_nonce(&env, &agent, nonce, expires_at)?;
 
         require!(
-            Self::is_authorized(env.clone(), agent.clone(), Permission::CreateStream),
+            Self::is_authorized(
+                env.clone(),
+                employer.clone(),
+                agent.clone(),
+                Permission::CancelStream
+            ),
             QuipayError::InsufficientPermissions
         );
 
+        Self::route_cancel_stream(&env, &agent, &employer, stream_id)
+    }
+}
+
+impl AutomationGateway {
+    fn save_agent(env: &Env, agent: &Agent) {
+        let key = DataKey::Agent(agent.employer.clone(), agent.address.clone());
+        env.storage().persistent().set(&key, agent);
+        ttl::extend(env, &key);
+    }
+
+    /// Journal an automated action and emit it for off-chain executors.
+    fn route_automation(
+        env: &Env,
+        agent: &Address,
+        employer: &Address,
+        action: Permission,
+        data: Bytes,
+    ) {
+        Self::record_action(
+            env,
+            employer,
+            agent,
+            action,
+            env.crypto().sha256(&data).into(),
+            ActionResult::Executed,
+        );
+
+        // TODO: Implement actual routing/integration with other contracts
+        events::emit(
+            env,
+            agent,
+            ActionExecuted {
+                agent: agent.clone(),
+                employer: employer.clone(),
+                action,
+                data,
+            },
+        );
+    }
+
+    /// Create a stream through PayrollStream. Callers check authorization.
+    fn route_create_stream(
+        env: &Env,
+        agent: &Address,
+        employer: &Address,
+        request: &StreamRequest,
+    ) -> Result<u64, QuipayError> {
         let payroll_stream =
             Self::get_payroll_stream(env.clone()).ok_or(QuipayError::NotInitialized)?;
 
         // Invoke create_stream_via_gateway on PayrollStream contract
         let stream_id: u64 = env.invoke_contract(
             &payroll_stream,
-            &Symbol::new(&env, "create_stream_via_gateway"),
+            &Symbol::new(env, "create_stream_via_gateway"),
             vec![
-                &env,
-                employer.into_val(&env),
-                worker.clone().into_val(&env),
-                token.into_val(&env),
-                rate.into_val(&env),
-                cliff_ts.into_val(&env),
-                start_ts.into_val(&env),
-                end_ts.into_val(&env),
+                env,
+                employer.into_val(env),
+                request.worker.into_val(env),
+                request.token.into_val(env),
+                request.rate.into_val(env),
+                request.cliff_ts.into_val(env),
+                request.start_ts.into_val(env),
+                request.end_ts.into_val(env),
+                Option::<BytesN<32>>::None.into_val(env),
             ],
         );
 
-        env.events().publish(
-            (
-                symbol_short!("gateway"),
-                Symbol::new(&env, "stream_created"),
-                agent.clone(),
-                employer.clone(),
-            ),
-            (stream_id, worker, rate, start_ts, end_ts),
+        Self::record_action(
+            env,
+            employer,
+            agent,
+            Permission::CreateStream,
+            Self::payload_hash(env, request.clone()),
+            ActionResult::StreamCreated(stream_id),
+        );
+
+        events::emit(
+            env,
+            agent,
+            StreamCreated {
+                stream_id,
+                agent: agent.clone(),
+                employer: employer.clone(),
+                worker: request.worker.clone(),
+                rate: request.rate,
+                start_ts: request.start_ts,
+                end_ts: request.end_ts,
+            },
         );
 
         Ok(stream_id)
     }
 
-    /// Cancel a stream on behalf of an employer through an authorized agent.
-    /// The agent must have CancelStream permission.
-    pub fn agent_cancel_stream(
-        env: Env,
-        agent: Address,
+    /// Cancel a stream through PayrollStream. Callers check authorization.
+    fn route_cancel_stream(
+        env: &Env,
+        agent: &Address,
+        employer: &Address,
         stream_id: u64,
-        employer: Address,
     ) -> Result<(), QuipayError> {
-        agent.require_auth();
-
-        require!(
-            Self::is_authorized(env.clone(), agent.clone(), Permission::CancelStream),
-            QuipayError::InsufficientPermissions
-        );
-
         let payroll_stream =
             Self::get_payroll_stream(env.clone()).ok_or(QuipayError::NotInitialized)?;
 
         // Invoke cancel_stream_via_gateway on PayrollStream contract
         env.invoke_contract::<()>(
             &payroll_stream,
-            &Symbol::new(&env, "cancel_stream_via_gateway"),
-            vec![&env, stream_id.into_val(&env), employer.into_val(&env)],
+            &Symbol::new(env, "cancel_stream_via_gateway"),
+            vec![env, stream_id.into_val(env), employer.into_val(env)],
         );
 
-        env.events().publish(
-            (
-                symbol_short!("gateway"),
-                Symbol::new(&env, "stream_canceled"),
-                agent.clone(),
-                employer.clone(),
-            ),
-            (stream_id,),
+        Self::record_action(
+            env,
+            employer,
+            agent,
+            Permission::CancelStream,
+            Self::payload_hash(env, stream_id),
+            ActionResult::StreamCanceled(stream_id),
+        );
+
+        events::emit(
+            env,
+            agent,
+            StreamCanceled {
+                stream_id,
+                agent: agent.clone(),
+                employer: employer.clone(),
+            },
         );
 
         Ok(())
@@ -0,0 +1,310 @@
+//! Two-person rule for agent actions.
+//!
+//! An agent holding `Permission::ProposeActions` cannot act directly, even
+//! with other permissions: `is_authorized` denies it every other action, so
+//! it queues a proposed action instead. The employer, or an approver the
+//! employer designated, approves or rejects it within `ACTION_PROPOSAL_TTL`
+//! seconds. Approved actions run through the same routes as
+//! `agent_create_stream`, `agent_cancel_stream` and `execute_automation`, so
+//! they are journaled under the proposing agent.
+
+use super::*;
+use crate::events::{self, ActionApproved, ActionProposed, ActionRejected};
+
+/// Time the approver has to decide on a proposed action (3 days).
+pub(crate) const ACTION_PROPOSAL_TTL: u64 = 3 * 24 * 60 * 60;
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct StreamRequest {
+    pub worker: Address,
+    pub token: Address,
+    pub rate: i128,
+    pub cliff_ts: u64,
+    pub start_ts: u64,
+    pub end_ts: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub enum ProposedAction {
+    CreateStream(StreamRequest),
+    CancelStream(u64),
+    /// Treasury rebalance with an opaque payload, routed like `execute_automation`.
+    Rebalance(Bytes),
+}
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ActionProposalStatus {
+    Pending,
+    Executed,
+    Rejected,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ActionProposal {
+    pub id: u64,
+    pub employer: Address,
+    pub agent: Address,
+    pub action: ProposedAction,
+    pub created_at: u64,
+    pub expires_at: u64,
+    pub status: ActionProposalStatus,
+}
+
+#[contractimpl]
+impl AutomationGateway {
+    /// Designate who, besides the employer, may approve the employer's
+    /// proposed actions. Pass `None` to leave approval to the employer alone.
+    pub fn set_action_approver(
+        env: Env,
+        employer: Address,
+        approver: Option<Address>,
+    ) -> Result<(), QuipayError> {
+        employer.require_auth();
+
+        let key = DataKey::ActionApprover(employer);
+        match approver {
+            Some(approver) => env.storage().persistent().set(&key, &approver),
+            None => env.storage().persistent().remove(&key),
+        }
+        Ok(())
+    }
+
+    pub fn get_action_approver(env: Env, employer: Address) -> Option<Address> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::ActionApprover(employer))
+    }
+
+    /// Queue an action for human approval. The agent needs the
+    /// `ProposeActions` permission from `employer`.
+    pub fn propose_action(
+        env: Env,
+        agent: Address,
+        employer: Address,
+        action: ProposedAction,
+    ) -> Result<u64, QuipayError> {
+        agent.require_auth();
+        require!(
+            Self::is_authorized(
+                env.clone(),
+                employer.clone(),
+                agent.clone(),
+                Permission::ProposeActions
+            ),
+            QuipayError::InsufficientPermissions
+        );
+
+        // The counter lives in persistent storage so the shared instance
+        // entry does not grow with proposal traffic.
+        let id: u64 = env
+            .storage()
+            .persistent()
+            .get(&DataKey::NextActionProposalId)
+            .unwrap_or(1);
+        let next = id.checked_add(1).ok_or(QuipayError::Overflow)?;
+        env.storage()
+            .persistent()
+            .set(&DataKey::NextActionProposalId, &next);
+        ttl::extend(&env, &DataKey::NextActionProposalId);
+
+        let now = env.ledger().timestamp();
+        let proposal = ActionProposal {
+            id,
+            employer: employer.clone(),
+            agent: agent.clone(),
+            action,
+            created_at: now,
+            expires_at: now.saturating_add(ACTION_PROPOSAL_TTL),
+            status: ActionProposalStatus::Pending,
+        };
+        env.storage()
+            .persistent()
+            .set(&DataKey::ActionProposal(id), &proposal);
+
+        // Expired proposals can no longer be approved; drop them so the list
+        // does not grow with proposals nobody acted on.
+        let mut pending = Self::get_pending_actions(env.clone(), employer.clone());
+        pending.push_back(id);
+        env.storage()
+            .persistent()
+            .set(&DataKey::PendingActions(employer.clone()), &pending);
+
+        events::emit(
+            &env,
+            &agent,
+            ActionProposed {
+                proposal_id: id,
+                agent: agent.clone(),
+                employer,
+                action: proposal.action,
+                expires_at: proposal.expires_at,
+            },
+        );
+
+        Ok(id)
+    }
+
+    /// Approve a proposed action and execute it. Only the employer or its
+    /// designated approver can call this, before the proposal expires. The
+    /// proposing agent must still hold `ProposeActions`.
+    pub fn approve_action(
+        env: Env,
+        approver: Address,
+        proposal_id: u64,
+    ) -> Result<(), QuipayError> {
+        approver.require_auth();
+
+        let mut proposal = Self::load_open_action(&env, proposal_id)?;
+        Self::require_action_approver(&env, &proposal.employer, &approver)?;
+        require!(
+            Self::is_authorized(
+                env.clone(),
+                proposal.employer.clone(),
+                proposal.agent.clone(),
+                Permission::ProposeActions
+            ),
+            QuipayError::InsufficientPermissions
+        );
+
+        // Mark executed before acting so the action cannot be replayed.
+        proposal.status = ActionProposalStatus::Executed;
+        env.storage()
+            .persistent()
+            .set(&DataKey::ActionProposal(proposal_id), &proposal);
+        Self::remove_pending_action(&env, &proposal.employer, proposal_id);
+
+        let (agent, employer) = (proposal.agent.clone(), proposal.employer.clone());
+        match proposal.action {
+            ProposedAction::CreateStream(request) => {
+                Self::route_create_stream(&env, &agent, &employer, &request)?;
+            }
+            ProposedAction::CancelStream(stream_id) => {
+                Self::route_cancel_stream(&env, &agent, &employer, stream_id)?;
+            }
+            ProposedAction::Rebalance(data) => {
+                Self::route_automation(
+                    &env,
+                    &agent,
+                    &employer,
+                    Permission::RebalanceTreasury,
+                    data,
+                );
+            }
+        }
+
+        events::emit(
+            &env,
+            &approver,
+            ActionApproved {
+                proposal_id,
+                agent,
+                employer,
+            },
+        );
+        Ok(())
+    }
+
+    /// Reject a proposed action. Only the employer or its designated approver
+    /// can call this.
+    pub fn reject_action(env: Env, approver: Address, proposal_id: u64) -> Result<(), QuipayError> {
+        approver.require_auth();
+
+        let mut proposal = Self::get_action_proposal(env.clone(), proposal_id)
+            .ok_or(QuipayError::ProposalNotFound)?;
+        require!(
+            proposal.status == ActionProposalStatus::Pending,
+            QuipayError::ProposalNotPending
+        );
+        Self::require_action_approver(&env, &proposal.employer, &approver)?;
+
+        proposal.status = ActionProposalStatus::Rejected;
+        env.storage()
+            .persistent()
+            .set(&DataKey::ActionProposal(proposal_id), &proposal);
+        Self::remove_pending_action(&env, &proposal.employer, proposal_id);
+
+        events::emit(
+            &env,
+            &approver,
+            ActionRejected {
+                proposal_id,
+                agent: proposal.agent,
+                employer: proposal.employer,
+            },
+        );
+        Ok(())
+    }
+
+    pub fn get_action_proposal(env: Env, proposal_id: u64) -> Option<ActionProposal> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::ActionProposal(proposal_id))
+    }
+
+    /// Ids of the employer's proposed actions that can still be approved:
+    /// neither executed, rejected nor expired.
+    pub fn get_pending_actions(env: Env, employer: Address) -> Vec<u64> {
+        let stored: Vec<u64> = env
+            .storage()
+            .persistent()
+            .get(&DataKey::PendingActions(employer))
+            .unwrap_or_else(|| Vec::new(&env));
+
+        let now = env.ledger().timestamp();
+        let mut pending = Vec::new(&env);
+        for id in stored.iter() {
+            if let Some(proposal) = Self::get_action_proposal(env.clone(), id)
+                && now < proposal.expires_at
+            {
+                pending.push_back(id);
+            }
+        }
+        pending
+    }
+}
+
+impl AutomationGateway {
+    /// Load a proposed action that can still be approved.
+    fn load_open_action(env: &Env, proposal_id: u64) -> Result<ActionProposal, QuipayError> {
+        let proposal = Self::get_action_proposal(env.clone(), proposal_id)
+            .ok_or(QuipayError::ProposalNotFound)?;
+        require!(
+            proposal.status == ActionProposalStatus::Pending,
+            QuipayError::ProposalNotPending
+        );
+        require!(
+            env.ledger().timestamp() < proposal.expires_at,
+            QuipayError::ProposalExpired
+        );
+        Ok(proposal)
+    }
+
+    fn require_action_approver(
+        env: &Env,
+        employer: &Address,
+        approver: &Address,
+    ) -> Result<(), QuipayError> {
+        require!(
+            approver == employer
+                || Some(approver.clone())
+                    == Self::get_action_approver(env.clone(), employer.clone()),
+            QuipayError::Unauthorized
+        );
+        Ok(())
+    }
+
+    fn remove_pending_action(env: &Env, employer: &Address, proposal_id: u64) {
+        let pending = Self::get_pending_actions(env.clone(), employer.clone());
+        if let Some(index) = pending.first_index_of(proposal_id) {
+            let mut pending = pending;
+            pending.remove(index);
+            env.storage()
+                .persistent()
+                .set(&DataKey::PendingActions(employer.clone()), &pending);
+        }
+    }
+}
@@ -0,0 +1,280 @@
+//! Stake-based trust for agents.
+//!
+//! When the admin configures a stake token, an agent has to lock a bond
+//! before an employer can register it. The bond is held per
+//! `(employer, agent)` pair so misbehaviour towards one employer is paid out
+//! of the bond that employer relied on. The admin, or a configured slasher
+//! contract running a dispute flow, can slash the bond to the employer.
+//!
+//! Revoking the agent starts the unlock cooldown; the bond stays slashable
+//! until the cooldown has elapsed and the agent withdraws it. Individual
+//! permissions can require a higher stake than the registration minimum.
+
+use super::*;
+use crate::events::{self, BondPosted, BondSlashed, BondWithdrawn};
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct StakeConfig {
+    pub token: Address,
+    /// Bond required to register an agent.
+    pub min_stake: i128,
+    /// Seconds a revoked agent's bond stays locked (and slashable).
+    pub unlock_cooldown: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct AgentBond {
+    pub token: Address,
+    pub amount: i128,
+    /// End of the unlock cooldown started by the last revocation.
+    pub unlock_at: u64,
+}
+
+#[contractimpl]
+impl AutomationGateway {
+    /// Require agents to post a bond. Pass `None` to stop requiring one;
+    /// existing bonds stay withdrawable under their own unlock times.
+    /// Only the admin can call this.
+    pub fn set_stake_config(env: Env, config: Option<StakeConfig>) -> Result<(), QuipayError> {
+        let admin = Self::get_admin(env.clone())?;
+        admin.require_auth();
+
+        match config {
+            Some(config) => {
+                require!(config.min_stake >= 0, QuipayError::InvalidAmount);
+                env.storage().instance().set(&DataKey::StakeConfig, &config);
+            }
+            None => env.storage().instance().remove(&DataKey::StakeConfig),
+        }
+        Ok(())
+    }
+
+    pub fn get_stake_config(env: Env) -> Option<StakeConfig> {
+        env.storage().instance().get(&DataKey::StakeConfig)
+    }
+
+    /// Require a minimum bond for agents using `permission`.
+    /// Only the admin can call this.
+    pub fn set_permission_min_stake(
+        env: Env,
+        permission: Permission,
+        min_stake: i128,
+    ) -> Result<(), QuipayError> {
+        let admin = Self::get_admin(env.clone())?;
+        admin.require_auth();
+        require!(min_stake >= 0, QuipayError::InvalidAmount);
+
+        env.storage()
+            .instance()
+            .set(&DataKey::PermissionMinStake(permission), &min_stake);
+        Ok(())
+    }
+
+    pub fn get_permission_min_stake(env: Env, permission: Permission) -> i128 {
+        env.storage()
+            .instance()
+            .get(&DataKey::PermissionMinStake(permission))
+            .unwrap_or(0)
+    }
+
+    /// Set the contract (e.g. a dispute resolver) allowed to slash bonds
+    /// besides the admin. Only the admin can call this.
+    pub fn set_slasher(env: Env, slasher: Option<Address>) -> Result<(), QuipayError> {
+        let admin = Self::get_admin(env.clone())?;
+        admin.require_auth();
+
+        match slasher {
+            Some(addr) => env.storage().instance().set(&DataKey::Slasher, &addr),
+            None => env.storage().instance().remove(&DataKey::Slasher),
+        }
+        Ok(())
+    }
+
+    pub fn get_slasher(env: Env) -> Option<Address> {
+        env.storage().instance().get(&DataKey::Slasher)
+    }
+
+    /// Lock `amount` of the stake token as the agent's bond towards `employer`.
+    pub fn post_bond(
+        env: Env,
+        employer: Address,
+        agent: Address,
+        amount: i128,
+    ) -> Result<(), QuipayError> {
+        agent.require_auth();
+        require!(amount > 0, QuipayError::InvalidAmount);
+
+        let config = Self::get_stake_config(env.clone()).ok_or(QuipayError::NotInitialized)?;
+        let key = DataKey::Bond(employer.clone(), agent.clone());
+        let mut bond = match Self::get_bond(env.clone(), employer.clone(), agent.clone()) {
+            Some(bond) => {
+                require!(bond.token == config.token, QuipayError::InvalidToken);
+                bond
+            }
+            None => AgentBond {
+                token: config.token.clone(),
+                amount: 0,
+                unlock_at: 0,
+            },
+        };
+
+        token::Client::new(&env, &bond.token).transfer(
+            &agent,
+            env.current_contract_address(),
+            &amount,
+        );
+        bond.amount = bond
+            .amount
+            .checked_add(amount)
+            .ok_or(QuipayError::Overflow)?;
+        env.storage().persistent().set(&key, &bond);
+
+        events::emit(
+            &env,
+            &agent,
+            BondPosted {
+                agent: agent.clone(),
+                employer,
+                amount,
+                total: bond.amount,
+            },
+        );
+        Ok(())
+    }
+
+    /// Slash up to `amount` of an agent's bond and pay it to the employer.
+    /// Callable by the admin or the configured slasher. Returns the amount slashed.
+    pub fn slash_bond(
+        env: Env,
+        caller: Address,
+        employer: Address,
+        agent: Address,
+        amount: i128,
+    ) -> Result<i128, QuipayError> {
+        caller.require_auth();
+        require!(
+            caller == Self::get_admin(env.clone())?
+                || Some(caller.clone()) == Self::get_slasher(env.clone()),
+            QuipayError::Unauthorized
+        );
+        require!(amount > 0, QuipayError::InvalidAmount);
+
+        let key = DataKey::Bond(employer.clone(), agent.clone());
+        let mut bond = Self::get_bond(env.clone(), employer.clone(), agent.clone())
+            .ok_or(QuipayError::AgentNotFound)?;
+
+        let slashed = amount.min(bond.amount);
+        if slashed > 0 {
+            token::Client::new(&env, &bond.token).transfer(
+                &env.current_contract_address(),
+                &employer,
+                &slashed,
+            );
+        }
+        bond.amount -= slashed;
+        env.storage().persistent().set(&key, &bond);
+
+        events::emit(
+            &env,
+            &caller,
+            BondSlashed {
+                agent,
+                employer,
+                amount: slashed,
+                remaining: bond.amount,
+            },
+        );
+        Ok(slashed)
+    }
+
+    /// Withdraw the bond. Locked while the agent is registered for `employer`
+    /// and, after revocation, until the unlock cooldown has elapsed.
+    pub fn withdraw_bond(env: Env, employer: Address, agent: Address) -> Result<i128, QuipayError> {
+        agent.require_auth();
+
+        let key = DataKey::Bond(employer.clone(), agent.clone());
+        let bond = Self::get_bond(env.clone(), employer.clone(), agent.clone())
+            .ok_or(QuipayError::AgentNotFound)?;
+        require!(
+            Self::get_agent(env.clone(), employer.clone(), agent.clone()).is_none()
+                && env.ledger().timestamp() >= bond.unlock_at,
+            QuipayError::BondLocked
+        );
+
+        env.storage().persistent().remove(&key);
+        if bond.amount > 0 {
+            token::Client::new(&env, &bond.token).transfer(
+                &env.current_contract_address(),
+                &agent,
+                &bond.amount,
+            );
+        }
+
+        events::emit(
+            &env,
+            &agent,
+            BondWithdrawn {
+                agent: agent.clone(),
+                employer,
+                amount: bond.amount,
+            },
+        );
+        Ok(bond.amount)
+    }
+
+    pub fn get_bond(env: Env, employer: Address, agent: Address) -> Option<AgentBond> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Bond(employer, agent))
+    }
+}
+
+impl AutomationGateway {
+    /// Bond an agent needs for `permission`, or 0 when staking is disabled.
+    pub(crate) fn required_stake(env: &Env, permission: Permission) -> i128 {
+        match Self::get_stake_config(env.clone()) {
+            Some(config) => config
+                .min_stake
+                .max(Self::get_permission_min_stake(env.clone(), permission)),
+            None => 0,
+        }
+    }
+
+    pub(crate) fn bonded_amount(env: &Env, employer: &Address, agent: &Address) -> i128 {
+        Self::get_bond(env.clone(), employer.clone(), agent.clone())
+            .map(|bond| bond.amount)
+            .unwrap_or(0)
+    }
+
+    /// Check the bond covers the registration minimum.
+    pub(crate) fn require_min_stake(
+        env: &Env,
+        employer: &Address,
+        agent: &Address,
+    ) -> Result<(), QuipayError> {
+        let Some(config) = Self::get_stake_config(env.clone()) else {
+            return Ok(());
+        };
+        require!(
+            Self::bonded_amount(env, employer, agent) >= config.min_stake,
+            QuipayError::InsufficientStake
+        );
+        Ok(())
+    }
+
+    /// Start the unlock cooldown of a revoked agent's bond.
+    pub(crate) fn start_bond_unlock(env: &Env, employer: &Address, agent: &Address) {
+        let Some(mut bond) = Self::get_bond(env.clone(), employer.clone(), agent.clone()) else {
+            return;
+        };
+        let cooldown = Self::get_stake_config(env.clone())
+            .map(|config| config.unlock_cooldown)
+            .unwrap_or(0);
+        bond.unlock_at = env.ledger().timestamp().saturating_add(cooldown);
+        env.storage()
+            .persistent()
+            .set(&DataKey::Bond(employer.clone(), agent.clone()), &bond);
+    }
+}
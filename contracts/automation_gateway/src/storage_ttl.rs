@@ -0,0 +1,70 @@
+//! Keeper entry points for the gateway's storage TTL.
+//!
+//! Agent registrations, bonds and journals are only written when an employer
+//! or agent acts, so an idle employer's agents would eventually be archived.
+//! `bump_ttl` extends them for any caller willing to pay the rent. Supported
+//! scopes:
+//!
+//! - `Contract`: instance storage (configuration), the upgrade state and the
+//!   action proposal counter.
+//! - `Account(employer)`: the employer's agents with their bonds and nonces,
+//!   its action approver, pending action proposals, journal count and the
+//!   latest `MAX_JOURNAL_PAGE` journal entries.
+//! - `Account(agent)`: the agent's nonce.
+//!
+//! Either account scope also extends the roles the address holds.
+
+use super::*;
+use journal::MAX_JOURNAL_PAGE;
+use quipay_common::TtlScope;
+
+#[contractimpl]
+impl AutomationGateway {
+    /// Extend every entry of `scope` to the full policy TTL. Permissionless.
+    /// Returns the ledger the entries stay live until.
+    pub fn bump_ttl(env: Env, scope: TtlScope) -> Result<u32, QuipayError> {
+        match &scope {
+            TtlScope::Contract => {
+                ttl::extend_instance(&env);
+                upgrade::extend_ttl(&env);
+                ttl::extend_fully(&env, &DataKey::NextActionProposalId);
+            }
+            TtlScope::Account(account) => {
+                Self::extend_account_ttl(&env, account);
+                rbac::extend_roles_ttl(&env, account);
+            }
+            _ => return Err(QuipayError::UnsupportedTtlScope),
+        }
+        Ok(ttl::record_bump(&env, &scope))
+    }
+
+    /// Ledger `scope`'s entries were last extended to by `bump_ttl`, or
+    /// `None` if no keeper has bumped them.
+    pub fn get_ttl_expiry(env: Env, scope: TtlScope) -> Option<u32> {
+        ttl::expiry(&env, &scope)
+    }
+}
+
+impl AutomationGateway {
+    fn extend_account_ttl(env: &Env, account: &Address) {
+        ttl::extend_fully(env, &DataKey::EmployerAgents(account.clone()));
+        for agent in Self::get_employer_agents(env.clone(), account.clone()).iter() {
+            ttl::extend_fully(env, &DataKey::Agent(account.clone(), agent.clone()));
+            ttl::extend_fully(env, &DataKey::Bond(account.clone(), agent.clone()));
+            ttl::extend_fully(env, &DataKey::AgentNonce(agent));
+        }
+        ttl::extend_fully(env, &DataKey::AgentNonce(account.clone()));
+        ttl::extend_fully(env, &DataKey::ActionApprover(account.clone()));
+
+        ttl::extend_fully(env, &DataKey::PendingActions(account.clone()));
+        for proposal_id in Self::get_pending_actions(env.clone(), account.clone()).iter() {
+            ttl::extend_fully(env, &DataKey::ActionProposal(proposal_id));
+        }
+
+        let count = Self::get_journal_count(env.clone(), account.clone());
+        ttl::extend_fully(env, &DataKey::JournalCount(account.clone()));
+        for id in count.saturating_sub(MAX_JOURNAL_PAGE as u64)..count {
+            ttl::extend_fully(env, &DataKey::JournalEntry(account.clone(), id));
+        }
+    }
+}
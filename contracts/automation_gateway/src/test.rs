@@ -1,12 +1,16 @@
 #![cfg(test)]
 use super::*;
 use quipay_common::QuipayError;
-use soroban_sdk::{Address, Bytes, Env, testutils::Address as _, vec};
+use soroban_sdk::{
+    Address, Bytes, Env, TryFromVal,
+    testutils::{Address as _, Events as _, Ledger},
+    token, vec,
+};
 
 // Dummy PayrollStream contract for testing gateway integration
 mod dummy_payroll_stream {
     use quipay_common::QuipayError;
-    use soroban_sdk::{Address, Env, contract, contractimpl, contracttype};
+    use soroban_sdk::{Address, BytesN, Env, contract, contractimpl, contracttype};
 
     #[contracttype]
     #[derive(Clone)]
@@ -38,6 +42,7 @@ mod dummy_payroll_stream {
             _cliff_ts: u64,
             _start_ts: u64,
             _end_ts: u64,
+            _metadata_hash: Option<BytesN<32>>,
         ) -> Result<u64, QuipayError> {
             // Verify caller is the authorized gateway
             let gateway: Address = env
@@ -86,6 +91,7 @@ fn test_registration_and_auth() {
 
     let admin = Address::generate(&env);
     let agent = Address::generate(&env);
+    let employer = Address::generate(&env);
 
     let contract_id = env.register(AutomationGateway, ());
     let client = AutomationGatewayClient::new(&env, &contract_id);
@@ -93,21 +99,25 @@ fn test_registration_and_auth() {
     client.init(&admin);
 
     // 1. Initial state: not authorized
-    assert!(!client.is_authorized(&agent, &Permission::CreateStream));
+    assert!(!client.is_authorized(&employer, &agent, &Permission::CreateStream));
 
     // 2. Register agent with specific permission
-    client.register_agent(&agent, &vec![&env, Permission::CreateStream]);
-    assert!(client.is_authorized(&agent, &Permission::CreateStream));
-    assert!(!client.is_authorized(&agent, &Permission::RebalanceTreasury));
+    client.register_agent(&employer, &agent, &vec![&env, Permission::CreateStream]);
+    assert!(client.is_authorized(&employer, &agent, &Permission::CreateStream));
+    assert!(!client.is_authorized(&employer, &agent, &Permission::RebalanceTreasury));
 
     // 3. Registering again overwrites permissions
-    client.register_agent(&agent, &vec![&env, Permission::RebalanceTreasury]);
-    assert!(!client.is_authorized(&agent, &Permission::CreateStream));
-    assert!(client.is_authorized(&agent, &Permission::RebalanceTreasury));
+    client.register_agent(
+        &employer,
+        &agent,
+        &vec![&env, Permission::RebalanceTreasury],
+    );
+    assert!(!client.is_authorized(&employer, &agent, &Permission::CreateStream));
+    assert!(client.is_authorized(&employer, &agent, &Permission::RebalanceTreasury));
 
     // 4. Revoke agent
-    client.revoke_agent(&agent);
-    assert!(!client.is_authorized(&agent, &Permission::RebalanceTreasury));
+    client.revoke_agent(&employer, &agent);
+    assert!(!client.is_authorized(&employer, &agent, &Permission::RebalanceTreasury));
 }
 
 #[test]
@@ -130,15 +140,23 @@ fn test_execute_automation_auth() {
 
     let admin = Address::generate(&env);
     let agent = Address::generate(&env);
+    let employer = Address::generate(&env);
 
     let contract_id = env.register(AutomationGateway, ());
     let client = AutomationGatewayClient::new(&env, &contract_id);
 
     client.init(&admin);
-    client.register_agent(&agent, &vec![&env, Permission::CreateStream]);
+    client.register_agent(&employer, &agent, &vec![&env, Permission::CreateStream]);
 
     // Authorized call
-    client.execute_automation(&agent, &Permission::CreateStream, &Bytes::new(&env));
+    client.execute_automation(
+        &agent,
+        &employer,
+        &Permission::CreateStream,
+        &Bytes::new(&env),
+        &0,
+        &None,
+    );
 }
 
 #[test]
@@ -148,16 +166,27 @@ fn test_execute_automation_unauthorized() {
 
     let admin = Address::generate(&env);
     let agent = Address::generate(&env);
+    let employer = Address::generate(&env);
 
     let contract_id = env.register(AutomationGateway, ());
     let client = AutomationGatewayClient::new(&env, &contract_id);
 
     client.init(&admin);
-    client.register_agent(&agent, &vec![&env, Permission::RebalanceTreasury]);
+    client.register_agent(
+        &employer,
+        &agent,
+        &vec![&env, Permission::RebalanceTreasury],
+    );
 
     // Unauthorized action
-    let result =
-        client.try_execute_automation(&agent, &Permission::CreateStream, &Bytes::new(&env));
+    let result = client.try_execute_automation(
+        &agent,
+        &employer,
+        &Permission::CreateStream,
+        &Bytes::new(&env),
+        &0,
+        &None,
+    );
 
     assert_eq!(result, Err(Ok(QuipayError::InsufficientPermissions)));
 }
@@ -188,14 +217,33 @@ fn test_agent_create_stream_authorized() {
     payroll_client.set_gateway(&gateway_id);
 
     // Register agent with CreateStream permission
-    gateway_client.register_agent(&agent, &vec![&env, Permission::CreateStream]);
+    gateway_client.register_agent(&employer, &agent, &vec![&env, Permission::CreateStream]);
 
     // Agent creates stream on behalf of employer
     let stream_id = gateway_client.agent_create_stream(
-        &agent, &employer, &worker, &token, &100i128, &0u64, &10u64, &100u64,
+        &agent, &employer, &worker, &token, &100i128, &0u64, &10u64, &100u64, &0, &None,
     );
 
     assert_eq!(stream_id, 1u64);
+
+    let (contract, topics, data) = env.events().all().last().unwrap();
+    assert_eq!(contract, gateway_id);
+    assert_eq!(
+        topics,
+        quipay_common::events::topics::<events::StreamCreated>(&env, &agent).into_val(&env)
+    );
+    assert_eq!(
+        events::StreamCreated::try_from_val(&env, &data).unwrap(),
+        events::StreamCreated {
+            stream_id,
+            agent: agent.clone(),
+            employer: employer.clone(),
+            worker,
+            rate: 100,
+            start_ts: 10,
+            end_ts: 100,
+        }
+    );
 }
 
 #[test]
@@ -224,11 +272,11 @@ fn test_agent_create_stream_unauthorized() {
     payroll_client.set_gateway(&gateway_id);
 
     // Register agent with ONLY CancelStream permission (not CreateStream)
-    gateway_client.register_agent(&agent, &vec![&env, Permission::CancelStream]);
+    gateway_client.register_agent(&employer, &agent, &vec![&env, Permission::CancelStream]);
 
     // Agent tries to create stream but is unauthorized
     let result = gateway_client.try_agent_create_stream(
-        &agent, &employer, &worker, &token, &100i128, &0u64, &10u64, &100u64,
+        &agent, &employer, &worker, &token, &100i128, &0u64, &10u64, &100u64, &0, &None,
     );
 
     assert_eq!(result, Err(Ok(QuipayError::InsufficientPermissions)));
@@ -261,17 +309,18 @@ fn test_agent_cancel_stream_authorized() {
 
     // Register agent with both CreateStream and CancelStream permissions
     gateway_client.register_agent(
+        &employer,
         &agent,
         &vec![&env, Permission::CreateStream, Permission::CancelStream],
     );
 
     // First, create a stream
     let stream_id = gateway_client.agent_create_stream(
-        &agent, &employer, &worker, &token, &100i128, &0u64, &10u64, &100u64,
+        &agent, &employer, &worker, &token, &100i128, &0u64, &10u64, &100u64, &0, &None,
     );
 
     // Then cancel it
-    gateway_client.agent_cancel_stream(&agent, &stream_id, &employer);
+    gateway_client.agent_cancel_stream(&agent, &stream_id, &employer, &1, &None);
 }
 
 #[test]
@@ -300,15 +349,15 @@ fn test_agent_cancel_stream_unauthorized() {
     payroll_client.set_gateway(&gateway_id);
 
     // Register agent with ONLY CreateStream permission (not CancelStream)
-    gateway_client.register_agent(&agent, &vec![&env, Permission::CreateStream]);
+    gateway_client.register_agent(&employer, &agent, &vec![&env, Permission::CreateStream]);
 
     // First, create a stream
     let stream_id = gateway_client.agent_create_stream(
-        &agent, &employer, &worker, &token, &100i128, &0u64, &10u64, &100u64,
+        &agent, &employer, &worker, &token, &100i128, &0u64, &10u64, &100u64, &0, &None,
     );
 
     // Agent tries to cancel stream but is unauthorized
-    let result = gateway_client.try_agent_cancel_stream(&agent, &stream_id, &employer);
+    let result = gateway_client.try_agent_cancel_stream(&agent, &stream_id, &employer, &1, &None);
 
     assert_eq!(result, Err(Ok(QuipayError::InsufficientPermissions)));
 }
@@ -339,14 +388,14 @@ fn test_revoked_agent_blocked() {
     payroll_client.set_gateway(&gateway_id);
 
     // Register agent with CreateStream permission
-    gateway_client.register_agent(&agent, &vec![&env, Permission::CreateStream]);
+    gateway_client.register_agent(&employer, &agent, &vec![&env, Permission::CreateStream]);
 
     // Revoke the agent
-    gateway_client.revoke_agent(&agent);
+    gateway_client.revoke_agent(&employer, &agent);
 
     // Revoked agent tries to create stream
     let result = gateway_client.try_agent_create_stream(
-        &agent, &employer, &worker, &token, &100i128, &0u64, &10u64, &100u64,
+        &agent, &employer, &worker, &token, &100i128, &0u64, &10u64, &100u64, &0, &None,
     );
 
     assert_eq!(result, Err(Ok(QuipayError::InsufficientPermissions)));
@@ -358,6 +407,7 @@ fn test_create_stream_permission_types() {
     env.mock_all_auths();
 
     let admin = Address::generate(&env);
+    let employer = Address::generate(&env);
     let agent_create = Address::generate(&env);
     let agent_cancel = Address::generate(&env);
     let agent_both = Address::generate(&env);
@@ -368,22 +418,31 @@ fn test_create_stream_permission_types() {
     gateway_client.init(&admin);
 
     // Register agents with different permissions
-    gateway_client.register_agent(&agent_create, &vec![&env, Permission::CreateStream]);
-    gateway_client.register_agent(&agent_cancel, &vec![&env, Permission::CancelStream]);
     gateway_client.register_agent(
+        &employer,
+        &agent_create,
+        &vec![&env, Permission::CreateStream],
+    );
+    gateway_client.register_agent(
+        &employer,
+        &agent_cancel,
+        &vec![&env, Permission::CancelStream],
+    );
+    gateway_client.register_agent(
+        &employer,
         &agent_both,
         &vec![&env, Permission::CreateStream, Permission::CancelStream],
     );
 
     // Verify permissions
-    assert!(gateway_client.is_authorized(&agent_create, &Permission::CreateStream));
-    assert!(!gateway_client.is_authorized(&agent_create, &Permission::CancelStream));
+    assert!(gateway_client.is_authorized(&employer, &agent_create, &Permission::CreateStream));
+    assert!(!gateway_client.is_authorized(&employer, &agent_create, &Permission::CancelStream));
 
-    assert!(!gateway_client.is_authorized(&agent_cancel, &Permission::CreateStream));
-    assert!(gateway_client.is_authorized(&agent_cancel, &Permission::CancelStream));
+    assert!(!gateway_client.is_authorized(&employer, &agent_cancel, &Permission::CreateStream));
+    assert!(gateway_client.is_authorized(&employer, &agent_cancel, &Permission::CancelStream));
 
-    assert!(gateway_client.is_authorized(&agent_both, &Permission::CreateStream));
-    assert!(gateway_client.is_authorized(&agent_both, &Permission::CancelStream));
+    assert!(gateway_client.is_authorized(&employer, &agent_both, &Permission::CreateStream));
+    assert!(gateway_client.is_authorized(&employer, &agent_both, &Permission::CancelStream));
 }
 
 #[test]
@@ -393,24 +452,106 @@ fn test_admin_modify_permissions() {
 
     let admin = Address::generate(&env);
     let agent = Address::generate(&env);
+    let employer = Address::generate(&env);
 
     let contract_id = env.register(AutomationGateway, ());
     let client = AutomationGatewayClient::new(&env, &contract_id);
 
     client.init(&admin);
-    client.register_agent(&agent, &vec![&env, Permission::CreateStream]);
+    client.register_agent(&employer, &agent, &vec![&env, Permission::CreateStream]);
 
-    client.grant_permission(&agent, &Permission::CancelStream);
-    assert!(client.is_authorized(&agent, &Permission::CreateStream));
-    assert!(client.is_authorized(&agent, &Permission::CancelStream));
+    client.grant_permission(&employer, &agent, &Permission::CancelStream);
+    assert!(client.is_authorized(&employer, &agent, &Permission::CreateStream));
+    assert!(client.is_authorized(&employer, &agent, &Permission::CancelStream));
 
-    client.revoke_permission(&agent, &Permission::CreateStream);
-    assert!(!client.is_authorized(&agent, &Permission::CreateStream));
-    assert!(client.is_authorized(&agent, &Permission::CancelStream));
+    client.revoke_permission(&employer, &agent, &Permission::CreateStream);
+    assert!(!client.is_authorized(&employer, &agent, &Permission::CreateStream));
+    assert!(client.is_authorized(&employer, &agent, &Permission::CancelStream));
 
-    client.set_agent_permissions(&agent, &vec![&env, Permission::RebalanceTreasury]);
-    assert!(!client.is_authorized(&agent, &Permission::CancelStream));
-    assert!(client.is_authorized(&agent, &Permission::RebalanceTreasury));
+    client.set_agent_permissions(
+        &employer,
+        &agent,
+        &vec![&env, Permission::RebalanceTreasury],
+    );
+    assert!(!client.is_authorized(&employer, &agent, &Permission::CancelStream));
+    assert!(client.is_authorized(&employer, &agent, &Permission::RebalanceTreasury));
+}
+
+#[test]
+fn test_agents_are_scoped_per_employer() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let agent = Address::generate(&env);
+    let employer_a = Address::generate(&env);
+    let employer_b = Address::generate(&env);
+    let worker = Address::generate(&env);
+    let token = Address::generate(&env);
+
+    let payroll_stream_id = env.register(dummy_payroll_stream::DummyPayrollStream, ());
+    let payroll_client =
+        dummy_payroll_stream::DummyPayrollStreamClient::new(&env, &payroll_stream_id);
+    payroll_client.init();
+
+    let gateway_id = env.register(AutomationGateway, ());
+    let client = AutomationGatewayClient::new(&env, &gateway_id);
+    client.init(&admin);
+    client.set_payroll_stream(&payroll_stream_id);
+    payroll_client.set_gateway(&gateway_id);
+
+    // Only employer A authorises the agent
+    client.register_agent(&employer_a, &agent, &vec![&env, Permission::CreateStream]);
+    assert!(client.is_authorized(&employer_a, &agent, &Permission::CreateStream));
+    assert!(!client.is_authorized(&employer_b, &agent, &Permission::CreateStream));
+
+    client.agent_create_stream(
+        &agent,
+        &employer_a,
+        &worker,
+        &token,
+        &100i128,
+        &0u64,
+        &10u64,
+        &100u64,
+        &0,
+        &None,
+    );
+    let result = client.try_agent_create_stream(
+        &agent,
+        &employer_b,
+        &worker,
+        &token,
+        &100i128,
+        &0u64,
+        &10u64,
+        &100u64,
+        &1,
+        &None,
+    );
+    assert_eq!(result, Err(Ok(QuipayError::InsufficientPermissions)));
+
+    // Employer B cannot modify employer A's agent
+    let result = client.try_grant_permission(&employer_b, &agent, &Permission::CancelStream);
+    assert_eq!(result, Err(Ok(QuipayError::AgentNotFound)));
+
+    // The same agent can hold different permissions per employer
+    client.register_agent(&employer_b, &agent, &vec![&env, Permission::CancelStream]);
+    assert!(!client.is_authorized(&employer_b, &agent, &Permission::CreateStream));
+    assert!(client.is_authorized(&employer_b, &agent, &Permission::CancelStream));
+    assert_eq!(
+        client.get_employer_agents(&employer_a),
+        vec![&env, agent.clone()]
+    );
+
+    // Revoking for one employer leaves the other untouched
+    client.revoke_agent(&employer_b, &agent);
+    assert_eq!(client.get_employer_agents(&employer_b).len(), 0);
+    assert!(client.get_agent(&employer_b, &agent).is_none());
+    assert_eq!(
+        client.get_agent(&employer_a, &agent).unwrap().employer,
+        employer_a
+    );
 }
 
 // ============================================================================
@@ -506,3 +647,607 @@ fn test_propose_admin_overwrites_previous_pending() {
     client.accept_admin();
     assert_eq!(client.get_admin(), new_admin2);
 }
+
+struct StakeSetup<'a> {
+    client: AutomationGatewayClient<'a>,
+    token: token::Client<'a>,
+    employer: Address,
+    agent: Address,
+}
+
+/// Gateway requiring a 1_000 bond with a one-hour unlock cooldown; the agent
+/// holds 5_000 of the stake token.
+fn setup_stake(env: &Env) -> StakeSetup<'_> {
+    env.mock_all_auths();
+    let client = AutomationGatewayClient::new(env, &env.register(AutomationGateway, ()));
+    client.init(&Address::generate(env));
+
+    let token = env
+        .register_stellar_asset_contract_v2(Address::generate(env))
+        .address();
+    client.set_stake_config(&Some(StakeConfig {
+        token: token.clone(),
+        min_stake: 1_000,
+        unlock_cooldown: 3_600,
+    }));
+
+    let agent = Address::generate(env);
+    token::StellarAssetClient::new(env, &token).mint(&agent, &5_000);
+
+    StakeSetup {
+        client,
+        token: token::Client::new(env, &token),
+        employer: Address::generate(env),
+        agent,
+    }
+}
+
+#[test]
+fn test_register_agent_requires_min_stake() {
+    let env = Env::default();
+    let s = setup_stake(&env);
+    let perms = vec![&env, Permission::ExecutePayroll];
+
+    let result = s.client.try_register_agent(&s.employer, &s.agent, &perms);
+    assert_eq!(result, Err(Ok(QuipayError::InsufficientStake)));
+
+    s.client.post_bond(&s.employer, &s.agent, &600);
+    let result = s.client.try_register_agent(&s.employer, &s.agent, &perms);
+    assert_eq!(result, Err(Ok(QuipayError::InsufficientStake)));
+
+    s.client.post_bond(&s.employer, &s.agent, &400);
+    s.client.register_agent(&s.employer, &s.agent, &perms);
+    assert!(
+        s.client
+            .is_authorized(&s.employer, &s.agent, &Permission::ExecutePayroll)
+    );
+    assert_eq!(s.token.balance(&s.client.address), 1_000);
+    assert_eq!(
+        s.client.get_bond(&s.employer, &s.agent).unwrap().amount,
+        1_000
+    );
+}
+
+#[test]
+fn test_permission_min_stake_gates_authorization() {
+    let env = Env::default();
+    let s = setup_stake(&env);
+    s.client
+        .set_permission_min_stake(&Permission::ManageTreasury, &3_000);
+
+    s.client.post_bond(&s.employer, &s.agent, &1_000);
+    s.client.register_agent(
+        &s.employer,
+        &s.agent,
+        &vec![&env, Permission::ExecutePayroll, Permission::ManageTreasury],
+    );
+    assert!(
+        s.client
+            .is_authorized(&s.employer, &s.agent, &Permission::ExecutePayroll)
+    );
+    assert!(
+        !s.client
+            .is_authorized(&s.employer, &s.agent, &Permission::ManageTreasury)
+    );
+
+    s.client.post_bond(&s.employer, &s.agent, &2_000);
+    assert!(
+        s.client
+            .is_authorized(&s.employer, &s.agent, &Permission::ManageTreasury)
+    );
+}
+
+#[test]
+fn test_slash_bond_pays_employer_and_drops_authorization() {
+    let env = Env::default();
+    let s = setup_stake(&env);
+    let slasher = Address::generate(&env);
+    let outsider = Address::generate(&env);
+    s.client.set_slasher(&Some(slasher.clone()));
+
+    s.client.post_bond(&s.employer, &s.agent, &1_500);
+    s.client.register_agent(
+        &s.employer,
+        &s.agent,
+        &vec![&env, Permission::ExecutePayroll],
+    );
+
+    let result = s
+        .client
+        .try_slash_bond(&outsider, &s.employer, &s.agent, &100);
+    assert_eq!(result, Err(Ok(QuipayError::Unauthorized)));
+
+    assert_eq!(
+        s.client.slash_bond(&slasher, &s.employer, &s.agent, &600),
+        600
+    );
+    assert_eq!(s.token.balance(&s.employer), 600);
+    assert_eq!(
+        s.client.get_bond(&s.employer, &s.agent).unwrap().amount,
+        900
+    );
+    assert!(
+        !s.client
+            .is_authorized(&s.employer, &s.agent, &Permission::ExecutePayroll)
+    );
+
+    // Slashing is capped at the remaining bond.
+    assert_eq!(
+        s.client.slash_bond(&slasher, &s.employer, &s.agent, &5_000),
+        900
+    );
+    assert_eq!(s.token.balance(&s.employer), 1_500);
+}
+
+#[test]
+fn test_bond_withdrawal_after_revocation_cooldown() {
+    let env = Env::default();
+    let s = setup_stake(&env);
+
+    s.client.post_bond(&s.employer, &s.agent, &1_000);
+    s.client.register_agent(
+        &s.employer,
+        &s.agent,
+        &vec![&env, Permission::ExecutePayroll],
+    );
+
+    let result = s.client.try_withdraw_bond(&s.employer, &s.agent);
+    assert_eq!(result, Err(Ok(QuipayError::BondLocked)));
+
+    s.client.revoke_agent(&s.employer, &s.agent);
+    let result = s.client.try_withdraw_bond(&s.employer, &s.agent);
+    assert_eq!(result, Err(Ok(QuipayError::BondLocked)));
+
+    // Still slashable while the cooldown runs.
+    s.client
+        .slash_bond(&s.client.get_admin(), &s.employer, &s.agent, &200);
+
+    env.ledger().with_mut(|li| li.timestamp += 3_600);
+    assert_eq!(s.client.withdraw_bond(&s.employer, &s.agent), 800);
+    assert_eq!(s.token.balance(&s.agent), 4_800);
+    assert!(s.client.get_bond(&s.employer, &s.agent).is_none());
+}
+
+#[test]
+fn test_execute_automation_rejects_replayed_and_expired_actions() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = AutomationGatewayClient::new(&env, &env.register(AutomationGateway, ()));
+    let agent = Address::generate(&env);
+    let employer = Address::generate(&env);
+    client.init(&Address::generate(&env));
+    client.register_agent(&employer, &agent, &vec![&env, Permission::ExecutePayroll]);
+
+    let data = Bytes::from_array(&env, &[1, 2, 3]);
+    assert_eq!(client.get_agent_nonce(&agent), 0);
+    client.execute_automation(
+        &agent,
+        &employer,
+        &Permission::ExecutePayroll,
+        &data,
+        &0,
+        &None,
+    );
+    assert_eq!(client.get_agent_nonce(&agent), 1);
+
+    // Replaying the same signed action fails.
+    let result = client.try_execute_automation(
+        &agent,
+        &employer,
+        &Permission::ExecutePayroll,
+        &data,
+        &0,
+        &None,
+    );
+    assert_eq!(result, Err(Ok(QuipayError::InvalidNonce)));
+
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+    let result = client.try_execute_automation(
+        &agent,
+        &employer,
+        &Permission::ExecutePayroll,
+        &data,
+        &1,
+        &Some(999),
+    );
+    assert_eq!(result, Err(Ok(QuipayError::ActionExpired)));
+
+    client.execute_automation(
+        &agent,
+        &employer,
+        &Permission::ExecutePayroll,
+        &data,
+        &1,
+        &Some(1_000),
+    );
+    assert_eq!(client.get_agent_nonce(&agent), 2);
+}
+
+#[test]
+fn test_agent_stream_actions_reject_replayed_and_expired_nonces() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let payroll_stream_id = env.register(dummy_payroll_stream::DummyPayrollStream, ());
+    let payroll_client =
+        dummy_payroll_stream::DummyPayrollStreamClient::new(&env, &payroll_stream_id);
+    payroll_client.init();
+
+    let client = AutomationGatewayClient::new(&env, &env.register(AutomationGateway, ()));
+    client.init(&Address::generate(&env));
+    client.set_payroll_stream(&payroll_stream_id);
+    payroll_client.set_gateway(&client.address);
+
+    let agent = Address::generate(&env);
+    let employer = Address::generate(&env);
+    let worker = Address::generate(&env);
+    let token = Address::generate(&env);
+    client.register_agent(
+        &employer,
+        &agent,
+        &vec![&env, Permission::CreateStream, Permission::CancelStream],
+    );
+
+    let stream_id = client.agent_create_stream(
+        &agent, &employer, &worker, &token, &100, &0, &10, &100, &0, &None,
+    );
+    let result = client.try_agent_create_stream(
+        &agent, &employer, &worker, &token, &100, &0, &10, &100, &0, &None,
+    );
+    assert_eq!(result, Err(Ok(QuipayError::InvalidNonce)));
+
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+    let result = client.try_agent_cancel_stream(&agent, &stream_id, &employer, &1, &Some(999));
+    assert_eq!(result, Err(Ok(QuipayError::ActionExpired)));
+
+    client.agent_cancel_stream(&agent, &stream_id, &employer, &1, &Some(1_000));
+    let result = client.try_agent_cancel_stream(&agent, &stream_id, &employer, &1, &None);
+    assert_eq!(result, Err(Ok(QuipayError::InvalidNonce)));
+    assert_eq!(client.get_agent_nonce(&agent), 2);
+}
+
+#[test]
+fn test_agent_actions_are_journaled_per_employer() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let agent = Address::generate(&env);
+    let employer = Address::generate(&env);
+    let other_employer = Address::generate(&env);
+
+    let payroll_stream_id = env.register(dummy_payroll_stream::DummyPayrollStream, ());
+    let payroll_client =
+        dummy_payroll_stream::DummyPayrollStreamClient::new(&env, &payroll_stream_id);
+    payroll_client.init();
+    let client = AutomationGatewayClient::new(&env, &env.register(AutomationGateway, ()));
+    client.init(&Address::generate(&env));
+    client.set_payroll_stream(&payroll_stream_id);
+    payroll_client.set_gateway(&client.address);
+
+    client.register_agent(
+        &employer,
+        &agent,
+        &vec![
+            &env,
+            Permission::ExecutePayroll,
+            Permission::CreateStream,
+            Permission::CancelStream,
+        ],
+    );
+    client.register_agent(
+        &other_employer,
+        &agent,
+        &vec![&env, Permission::ExecutePayroll],
+    );
+
+    env.ledger().with_mut(|li| li.timestamp = 500);
+    let data = Bytes::from_array(&env, &[7; 4]);
+    client.execute_automation(
+        &agent,
+        &employer,
+        &Permission::ExecutePayroll,
+        &data,
+        &0,
+        &None,
+    );
+    let stream_id = client.agent_create_stream(
+        &agent,
+        &employer,
+        &Address::generate(&env),
+        &Address::generate(&env),
+        &100,
+        &0,
+        &10,
+        &100,
+        &1,
+        &None,
+    );
+    client.agent_cancel_stream(&agent, &stream_id, &employer, &2, &None);
+    client.execute_automation(
+        &agent,
+        &other_employer,
+        &Permission::ExecutePayroll,
+        &data,
+        &3,
+        &None,
+    );
+
+    assert_eq!(client.get_journal_count(&employer), 3);
+    assert_eq!(client.get_journal_count(&other_employer), 1);
+
+    let journal = client.get_journal(&employer, &0, &10);
+    assert_eq!(journal.len(), 3);
+    let first = journal.get(0).unwrap();
+    assert_eq!(first.agent, agent);
+    assert_eq!(first.action, Permission::ExecutePayroll);
+    assert_eq!(first.payload_hash, env.crypto().sha256(&data).to_bytes());
+    assert_eq!(first.result, ActionResult::Executed);
+    assert_eq!(first.timestamp, 500);
+    assert_eq!(
+        journal.get(1).unwrap().result,
+        ActionResult::StreamCreated(stream_id)
+    );
+    assert_eq!(
+        journal.get(2).unwrap().result,
+        ActionResult::StreamCanceled(stream_id)
+    );
+
+    let page = client.get_journal(&employer, &2, &10);
+    assert_eq!(page.len(), 1);
+    assert_eq!(page.get(0).unwrap().id, 2);
+    assert_eq!(client.get_journal(&employer, &3, &10).len(), 0);
+}
+
+struct ProposalSetup<'a> {
+    client: AutomationGatewayClient<'a>,
+    employer: Address,
+    agent: Address,
+}
+
+/// Gateway wired to the dummy PayrollStream with a propose-only agent.
+fn setup_proposals(env: &Env) -> ProposalSetup<'_> {
+    env.mock_all_auths();
+    let payroll_stream_id = env.register(dummy_payroll_stream::DummyPayrollStream, ());
+    let payroll_client =
+        dummy_payroll_stream::DummyPayrollStreamClient::new(env, &payroll_stream_id);
+    payroll_client.init();
+
+    let client = AutomationGatewayClient::new(env, &env.register(AutomationGateway, ()));
+    client.init(&Address::generate(env));
+    client.set_payroll_stream(&payroll_stream_id);
+    payroll_client.set_gateway(&client.address);
+
+    let employer = Address::generate(env);
+    let agent = Address::generate(env);
+    client.register_agent(&employer, &agent, &vec![env, Permission::ProposeActions]);
+
+    ProposalSetup {
+        client,
+        employer,
+        agent,
+    }
+}
+
+fn stream_request(env: &Env) -> StreamRequest {
+    StreamRequest {
+        worker: Address::generate(env),
+        token: Address::generate(env),
+        rate: 100,
+        cliff_ts: 0,
+        start_ts: 10,
+        end_ts: 100,
+    }
+}
+
+#[test]
+fn test_proposed_stream_executes_after_employer_approval() {
+    let env = Env::default();
+    let s = setup_proposals(&env);
+    let request = stream_request(&env);
+
+    // Propose-only agents cannot act directly.
+    let result = s.client.try_agent_create_stream(
+        &s.agent,
+        &s.employer,
+        &request.worker,
+        &request.token,
+        &request.rate,
+        &request.cliff_ts,
+        &request.start_ts,
+        &request.end_ts,
+        &0,
+        &None,
+    );
+    assert_eq!(result, Err(Ok(QuipayError::InsufficientPermissions)));
+
+    let id = s.client.propose_action(
+        &s.agent,
+        &s.employer,
+        &ProposedAction::CreateStream(request.clone()),
+    );
+    assert_eq!(s.client.get_pending_actions(&s.employer), vec![&env, id]);
+    assert_eq!(s.client.get_journal_count(&s.employer), 0);
+
+    s.client.approve_action(&s.employer, &id);
+
+    let proposal = s.client.get_action_proposal(&id).unwrap();
+    assert_eq!(proposal.status, ActionProposalStatus::Executed);
+    assert_eq!(s.client.get_pending_actions(&s.employer).len(), 0);
+
+    let entry = s.client.get_journal(&s.employer, &0, &10).get(0).unwrap();
+    assert_eq!(entry.agent, s.agent);
+    assert_eq!(entry.result, ActionResult::StreamCreated(1));
+
+    let result = s.client.try_approve_action(&s.employer, &id);
+    assert_eq!(result, Err(Ok(QuipayError::ProposalNotPending)));
+}
+
+#[test]
+fn test_designated_approver_and_rejection() {
+    let env = Env::default();
+    let s = setup_proposals(&env);
+    let approver = Address::generate(&env);
+    let outsider = Address::generate(&env);
+    s.client
+        .set_action_approver(&s.employer, &Some(approver.clone()));
+
+    let cancel = s
+        .client
+        .propose_action(&s.agent, &s.employer, &ProposedAction::CancelStream(7));
+    let rebalance = s.client.propose_action(
+        &s.agent,
+        &s.employer,
+        &ProposedAction::Rebalance(Bytes::from_array(&env, &[1])),
+    );
+
+    let result = s.client.try_approve_action(&outsider, &cancel);
+    assert_eq!(result, Err(Ok(QuipayError::Unauthorized)));
+    let result = s.client.try_reject_action(&outsider, &cancel);
+    assert_eq!(result, Err(Ok(QuipayError::Unauthorized)));
+
+    s.client.reject_action(&approver, &cancel);
+    assert_eq!(
+        s.client.get_action_proposal(&cancel).unwrap().status,
+        ActionProposalStatus::Rejected
+    );
+    let result = s.client.try_approve_action(&s.employer, &cancel);
+    assert_eq!(result, Err(Ok(QuipayError::ProposalNotPending)));
+
+    s.client.approve_action(&approver, &rebalance);
+    let entry = s.client.get_journal(&s.employer, &0, &10).get(0).unwrap();
+    assert_eq!(entry.action, Permission::RebalanceTreasury);
+    assert_eq!(entry.result, ActionResult::Executed);
+    assert_eq!(s.client.get_pending_actions(&s.employer).len(), 0);
+}
+
+#[test]
+fn test_proposed_action_expires_and_requires_live_agent() {
+    let env = Env::default();
+    let s = setup_proposals(&env);
+
+    let late = s
+        .client
+        .propose_action(&s.agent, &s.employer, &ProposedAction::CancelStream(1));
+    env.ledger().with_mut(|li| li.timestamp += 3 * 24 * 60 * 60);
+    let result = s.client.try_approve_action(&s.employer, &late);
+    assert_eq!(result, Err(Ok(QuipayError::ProposalExpired)));
+    assert_eq!(s.client.get_pending_actions(&s.employer).len(), 0);
+
+    let id = s.client.propose_action(
+        &s.agent,
+        &s.employer,
+        &ProposedAction::CreateStream(stream_request(&env)),
+    );
+    assert_eq!(id, late + 1);
+
+    // Proposing prunes the expired id from the stored pending list.
+    let stored: Vec<u64> = env.as_contract(&s.client.address, || {
+        assert!(
+            env.storage()
+                .persistent()
+                .has(&DataKey::NextActionProposalId)
+        );
+        env.storage()
+            .persistent()
+            .get(&DataKey::PendingActions(s.employer.clone()))
+            .unwrap()
+    });
+    assert_eq!(stored, vec![&env, id]);
+    s.client.revoke_agent(&s.employer, &s.agent);
+    let result = s.client.try_approve_action(&s.employer, &id);
+    assert_eq!(result, Err(Ok(QuipayError::InsufficientPermissions)));
+
+    // Only agents holding ProposeActions can queue actions.
+    let result =
+        s.client
+            .try_propose_action(&s.agent, &s.employer, &ProposedAction::CancelStream(1));
+    assert_eq!(result, Err(Ok(QuipayError::InsufficientPermissions)));
+}
+
+#[test]
+fn test_propose_only_agent_cannot_use_other_permissions_directly() {
+    let env = Env::default();
+    let s = setup_proposals(&env);
+    let agent = Address::generate(&env);
+    s.client.register_agent(
+        &s.employer,
+        &agent,
+        &vec![
+            &env,
+            Permission::ProposeActions,
+            Permission::CreateStream,
+            Permission::CancelStream,
+        ],
+    );
+
+    assert!(
+        s.client
+            .is_authorized(&s.employer, &agent, &Permission::ProposeActions)
+    );
+    assert!(
+        !s.client
+            .is_authorized(&s.employer, &agent, &Permission::CancelStream)
+    );
+    let result = s
+        .client
+        .try_agent_cancel_stream(&agent, &1, &s.employer, &0, &None);
+    assert_eq!(result, Err(Ok(QuipayError::InsufficientPermissions)));
+    let request = stream_request(&env);
+    let result = s.client.try_agent_create_stream(
+        &agent,
+        &s.employer,
+        &request.worker,
+        &request.token,
+        &request.rate,
+        &request.cliff_ts,
+        &request.start_ts,
+        &request.end_ts,
+        &0,
+        &None,
+    );
+    assert_eq!(result, Err(Ok(QuipayError::InsufficientPermissions)));
+
+    // The same action still runs once the employer approves it.
+    let id = s
+        .client
+        .propose_action(&agent, &s.employer, &ProposedAction::CreateStream(request));
+    s.client.approve_action(&s.employer, &id);
+    assert_eq!(
+        s.client.get_action_proposal(&id).unwrap().status,
+        ActionProposalStatus::Executed
+    );
+}
+
+#[test]
+fn test_bump_ttl_keeps_employer_agents_alive() {
+    use quipay_common::{TtlScope, ttl::TTL_EXTEND_TO};
+    use soroban_sdk::testutils::storage::Persistent as _;
+
+    let env = Env::default();
+    let s = setup_proposals(&env);
+    let id = s
+        .client
+        .propose_action(&s.agent, &s.employer, &ProposedAction::CancelStream(1));
+
+    env.ledger().set_sequence_number(50);
+    let live_until = s.client.bump_ttl(&TtlScope::Account(s.employer.clone()));
+    assert_eq!(live_until, 50 + TTL_EXTEND_TO);
+    assert_eq!(
+        s.client
+            .get_ttl_expiry(&TtlScope::Account(s.employer.clone())),
+        Some(live_until)
+    );
+    for key in [
+        DataKey::Agent(s.employer.clone(), s.agent.clone()),
+        DataKey::EmployerAgents(s.employer.clone()),
+        DataKey::PendingActions(s.employer.clone()),
+        DataKey::ActionProposal(id),
+    ] {
+        let ttl = env.as_contract(&s.client.address, || {
+            env.storage().persistent().get_ttl(&key)
+        });
+        assert_eq!(ttl, TTL_EXTEND_TO);
+    }
+    s.client.bump_ttl(&TtlScope::Contract);
+
+    let result = s.client.try_bump_ttl(&TtlScope::Stream(1));
+    assert_eq!(result, Err(Ok(QuipayError::UnsupportedTtlScope)));
+}
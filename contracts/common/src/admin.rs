@@ -0,0 +1,101 @@
+//! Two-step admin transfer shared by Quipay contracts.
+//!
+//! The admin proposes a successor with `propose_admin` and the successor
+//! takes over with `accept_admin`, so the role is never handed to a key
+//! nobody controls. `transfer_admin` keeps the one-step handover for
+//! existing integrations and clears any pending proposal.
+//!
+//! Unlike the role and upgrade modules, these helpers authenticate the
+//! current or pending admin themselves. Each contract keeps its admin where
+//! it always has, in instance or persistent storage under its own `DataKey`,
+//! and describes that location with an [`AdminKeys`].
+
+use crate::{QuipayError, ttl};
+use soroban_sdk::{Address, Env, IntoVal, Val};
+
+/// Storage type holding a contract's admin entries.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AdminStorage {
+    Instance,
+    Persistent,
+}
+
+/// Where a contract stores its admin and pending admin.
+pub struct AdminKeys<K> {
+    pub storage: AdminStorage,
+    pub admin: K,
+    pub pending: K,
+}
+
+impl<K: IntoVal<Env, Val>> AdminKeys<K> {
+    fn get(&self, env: &Env, key: &K) -> Option<Address> {
+        match self.storage {
+            AdminStorage::Instance => env.storage().instance().get(key),
+            AdminStorage::Persistent => env.storage().persistent().get(key),
+        }
+    }
+
+    fn set(&self, env: &Env, key: &K, value: &Address) {
+        match self.storage {
+            AdminStorage::Instance => env.storage().instance().set(key, value),
+            AdminStorage::Persistent => {
+                env.storage().persistent().set(key, value);
+                ttl::extend(env, key);
+            }
+        }
+    }
+
+    fn remove(&self, env: &Env, key: &K) {
+        match self.storage {
+            AdminStorage::Instance => env.storage().instance().remove(key),
+            AdminStorage::Persistent => env.storage().persistent().remove(key),
+        }
+    }
+}
+
+pub fn get_admin<K: IntoVal<Env, Val>>(
+    env: &Env,
+    keys: &AdminKeys<K>,
+) -> Result<Address, QuipayError> {
+    keys.get(env, &keys.admin)
+        .ok_or(QuipayError::NotInitialized)
+}
+
+pub fn get_pending_admin<K: IntoVal<Env, Val>>(env: &Env, keys: &AdminKeys<K>) -> Option<Address> {
+    keys.get(env, &keys.pending)
+}
+
+/// Propose `new_admin` as the next admin. Requires the current admin.
+pub fn propose_admin<K: IntoVal<Env, Val>>(
+    env: &Env,
+    keys: &AdminKeys<K>,
+    new_admin: &Address,
+) -> Result<(), QuipayError> {
+    get_admin(env, keys)?.require_auth();
+    keys.set(env, &keys.pending, new_admin);
+    Ok(())
+}
+
+/// Make the pending admin the admin. Requires the pending admin.
+pub fn accept_admin<K: IntoVal<Env, Val>>(
+    env: &Env,
+    keys: &AdminKeys<K>,
+) -> Result<Address, QuipayError> {
+    let pending = get_pending_admin(env, keys).ok_or(QuipayError::NoPendingAdmin)?;
+    pending.require_auth();
+    keys.set(env, &keys.admin, &pending);
+    keys.remove(env, &keys.pending);
+    Ok(pending)
+}
+
+/// Hand the admin role to `new_admin` in one step. Requires the current admin.
+pub fn transfer_admin<K: IntoVal<Env, Val>>(
+    env: &Env,
+    keys: &AdminKeys<K>,
+    new_admin: &Address,
+) -> Result<(), QuipayError> {
+    get_admin(env, keys)?.require_auth();
+    keys.set(env, &keys.admin, new_admin);
+    keys.remove(env, &keys.pending);
+    Ok(())
+}
@@ -10,7 +10,11 @@ pub type QuipayResult<T> = Result<T, QuipayError>;
 /// available number.
 ///
 /// See `docs/error-codes.md` for the full table with recovery guidance.
-#[contracterror]
+///
+/// The contract spec format caps error enums at 50 cases, so `QuipayError` is
+/// not exported to contract specs (`export = false`). Clients map codes to
+/// names using the table in `docs/error-codes.md`.
+#[contracterror(export = false)]
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 #[repr(u32)]
 pub enum QuipayError {
@@ -138,6 +142,131 @@ pub enum QuipayError {
     /// No receipt exists for the given receipt ID.
     ReceiptNotFound = 1044,
 
+    // ── Treasury Strategies ───────────────────────────────────────────────────
+
+    /// No yield strategy is configured for the token.
+    StrategyNotConfigured = 1045,
+
+    // ── Top-up Mandates ───────────────────────────────────────────────────────
+
+    /// No top-up mandate exists for the given mandate ID.
+    MandateNotFound = 1046,
+
+    // ── Multi-sig Proposals ───────────────────────────────────────────────────
+
+    /// No proposal exists for the given proposal ID.
+    ProposalNotFound = 1047,
+    /// The proposal was already executed, canceled, rejected or finalized.
+    ProposalNotPending = 1048,
+    /// The proposal's approval window has passed.
+    ProposalExpired = 1049,
+    /// The signer has already approved this proposal.
+    AlreadyApproved = 1050,
+    /// The signer has not approved this proposal.
+    NotApproved = 1051,
+
+    // ── Agent Bonds ───────────────────────────────────────────────────────────
+
+    /// The agent's bond is below the stake required for the action.
+    InsufficientStake = 1052,
+    /// The agent's bond is still locked (agent registered or cooldown running).
+    BondLocked = 1053,
+
+    // ── Agent Actions ─────────────────────────────────────────────────────────
+
+    /// The action nonce does not match the agent's next nonce (replay or gap).
+    InvalidNonce = 1054,
+    /// The action's expiry timestamp has passed.
+    ActionExpired = 1055,
+
+    // ── KYC Attestations ──────────────────────────────────────────────────────
+
+    /// The caller is not an attester approved by the registry admin.
+    AttesterNotApproved = 1056,
+    /// The worker has no valid attestation at the required level.
+    AttestationRequired = 1057,
+    /// No attestation is recorded for the worker.
+    AttestationNotFound = 1058,
+
+    // ── Employment Records ────────────────────────────────────────────────────
+
+    /// No employment record exists for the given worker and record ID.
+    EmploymentNotFound = 1059,
+    /// The worker already has an open employment record with this employer.
+    EmploymentActive = 1060,
+    /// No employer record exists for the given address.
+    EmployerNotFound = 1061,
+
+    // ── Token Allowlist ───────────────────────────────────────────────────────
+
+    /// The deposit would take the treasury balance above the token's cap.
+    DepositCapExceeded = 1062,
+    /// The liability is below the token's minimum liability size.
+    LiabilityBelowMinimum = 1063,
+
+    // ── Upgrades ──────────────────────────────────────────────────────────────
+
+    /// There is no earlier release to roll back to.
+    NoRollbackTarget = 1064,
+    /// The version does not follow the installed or replaced version.
+    VersionMismatch = 1065,
+    /// The migration for the installed version has already run.
+    MigrationAlreadyApplied = 1066,
+
+    // ── Storage TTL ───────────────────────────────────────────────────────────
+
+    /// The contract keeps no entries under the given TTL scope.
+    UnsupportedTtlScope = 1067,
+
+    // ── Disputes ──────────────────────────────────────────────────────────────
+
+    /// The stream is frozen by a dispute until an arbitrator resolves it.
+    StreamDisputed = 1068,
+    /// The stream already has an open dispute.
+    AlreadyDisputed = 1069,
+    /// No dispute has been raised for the stream.
+    NoOpenDispute = 1070,
+    /// The stream's dispute has already been resolved.
+    DisputeAlreadyResolved = 1071,
+
+    // ── DAO Governance ────────────────────────────────────────────────────────
+
+    /// The voter has already voted on this proposal.
+    AlreadyVoted = 1072,
+    /// The proposal's voting window has ended.
+    VotingClosed = 1073,
+    /// The proposal's voting window has not ended yet.
+    VotingStillOpen = 1074,
+    /// The proposal did not pass, so it cannot be executed.
+    ProposalNotPassed = 1075,
+    /// The account holds no governance tokens.
+    NoVotingPower = 1076,
+
+    // ── Upgrade Proposals ─────────────────────────────────────────────────────
+
+    /// An upgrade is already pending; cancel it before proposing another.
+    UpgradeAlreadyPending = 1077,
+    /// No upgrade has been proposed.
+    NoPendingUpgrade = 1078,
+    /// The upgrade timelock has not yet expired.
+    UpgradeTimelockActive = 1079,
+
+    // ── Emergency Drains ──────────────────────────────────────────────────────
+
+    /// A drain is already pending; cancel it before proposing another.
+    DrainAlreadyPending = 1080,
+
+    // ── State Checks ──────────────────────────────────────────────────────────
+
+    /// The operation requires a paused stream.
+    StreamNotPaused = 1081,
+    /// The worker already has the maximum number of open employment records.
+    TooManyOpenEmployments = 1082,
+    /// The employer's KYB status in the registry is not `Verified`.
+    EmployerNotVerified = 1083,
+    /// A protective drain's wage reserves have not been fully tallied.
+    DrainTallyIncomplete = 1084,
+
     // ── Catch-all ─────────────────────────────────────────────────────────────
 
     /// A custom error condition not covered by the above codes.
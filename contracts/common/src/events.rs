@@ -0,0 +1,214 @@
+//! Versioned, typed events shared by Quipay contracts.
+//!
+//! Every event is published through [`emit`] with the same four topics:
+//!
+//! | # | Topic     | Type      | Meaning                                        |
+//! |---|-----------|-----------|------------------------------------------------|
+//! | 0 | namespace | `Symbol`  | Emitting component (`stream`, `vault`, `rbac`) |
+//! | 1 | name      | `Symbol`  | Event name within the namespace                |
+//! | 2 | version   | `u32`     | [`EVENT_SCHEMA_VERSION`]                       |
+//! | 3 | actor     | `Address` | Account that authorised the action             |
+//!
+//! The data is a `#[contracttype]` struct implementing [`QuipayEvent`], so it
+//! decodes as a map keyed by field name. Events about one stream carry its id
+//! in a `stream_id` field. Permissionless calls (keeper bumps, settling a
+//! stream after its grace period) use the emitting contract as the actor.
+//!
+//! Events emitted by the shared modules (roles, upgrades, TTL bumps) are
+//! defined here; each contract defines its own in its `events` module and
+//! names them with [`event_names!`](crate::event_names).
+
+use crate::{Role, TtlScope};
+use soroban_sdk::{Address, BytesN, Env, IntoVal, Symbol, Val, contracttype};
+
+/// Version carried in every event's third topic. Bumped whenever the layout
+/// of any event changes, so indexers can decode old and new events side by
+/// side.
+pub const EVENT_SCHEMA_VERSION: u32 = 1;
+
+/// A typed event payload and the topics it is published under.
+pub trait QuipayEvent: IntoVal<Env, Val> {
+    const NAMESPACE: &'static str;
+    const NAME: &'static str;
+}
+
+/// Implement [`QuipayEvent`] for a list of payload types sharing a namespace.
+///
+/// ```ignore
+/// quipay_common::event_names!("stream" {
+///     StreamCreated => "created",
+///     StreamWithdrawn => "withdrawn",
+/// });
+/// ```
+#[macro_export]
+macro_rules! event_names {
+    ($namespace:literal { $($event:ty => $name:literal),* $(,)? }) => {
+        $(
+            impl $crate::events::QuipayEvent for $event {
+                const NAMESPACE: &'static str = $namespace;
+                const NAME: &'static str = $name;
+            }
+        )*
+    };
+}
+
+/// The topics an `E` event caused by `actor` is published under.
+pub fn topics<E: QuipayEvent>(env: &Env, actor: &Address) -> (Symbol, Symbol, u32, Address) {
+    (
+        Symbol::new(env, E::NAMESPACE),
+        Symbol::new(env, E::NAME),
+        EVENT_SCHEMA_VERSION,
+        actor.clone(),
+    )
+}
+
+/// Publish `event` with the standard topics.
+pub fn emit<E: QuipayEvent>(env: &Env, actor: &Address, event: E) {
+    #[allow(deprecated)]
+    env.events().publish(topics::<E>(env, actor), event);
+}
+
+/// Publish `event` for a permissionless call, with the emitting contract as
+/// the actor.
+pub fn emit_from_contract<E: QuipayEvent>(env: &Env, event: E) {
+    emit(env, &env.current_contract_address(), event);
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RoleGranted {
+    pub role: Role,
+    pub account: Address,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RoleRevoked {
+    pub role: Role,
+    pub account: Address,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RoleRenounced {
+    pub role: Role,
+    pub account: Address,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct UpgradeProposed {
+    pub wasm_hash: BytesN<32>,
+    pub version: (u32, u32, u32),
+    pub execute_after: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct UpgradeCanceled {
+    pub wasm_hash: BytesN<32>,
+    pub version: (u32, u32, u32),
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct UpgradeExecuted {
+    pub wasm_hash: BytesN<32>,
+    pub from_version: (u32, u32, u32),
+    pub to_version: (u32, u32, u32),
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct UpgradeRolledBack {
+    pub wasm_hash: BytesN<32>,
+    pub from_version: (u32, u32, u32),
+    pub to_version: (u32, u32, u32),
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Migrated {
+    pub from_version: (u32, u32, u32),
+    pub to_version: (u32, u32, u32),
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TtlBumped {
+    pub scope: TtlScope,
+    pub live_until: u32,
+}
+
+crate::event_names!("rbac" {
+    RoleGranted => "granted",
+    RoleRevoked => "revoked",
+    RoleRenounced => "renounced",
+});
+
+crate::event_names!("upgrade" {
+    UpgradeProposed => "proposed",
+    UpgradeCanceled => "canceled",
+    UpgradeExecuted => "executed",
+    UpgradeRolledBack => "rolled_back",
+    Migrated => "migrated",
+});
+
+crate::event_names!("ttl" {
+    TtlBumped => "bumped",
+});
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use soroban_sdk::{
+        Map, contract, map, symbol_short,
+        testutils::{Address as _, Events as _},
+        vec,
+    };
+
+    #[contract]
+    struct EventHost;
+
+    #[test]
+    fn test_emit_uses_standard_topics() {
+        let env = Env::default();
+        let host = env.register(EventHost, ());
+        let admin = Address::generate(&env);
+        let account = Address::generate(&env);
+
+        env.as_contract(&host, || {
+            emit(
+                &env,
+                &admin,
+                RoleGranted {
+                    role: Role::Pauser,
+                    account: account.clone(),
+                },
+            );
+        });
+
+        let expected_data: Map<Symbol, Val> = map![
+            &env,
+            (symbol_short!("account"), account.into_val(&env)),
+            (symbol_short!("role"), Role::Pauser.into_val(&env)),
+        ];
+        assert_eq!(
+            env.events().all(),
+            vec![
+                &env,
+                (
+                    host,
+                    (
+                        symbol_short!("rbac"),
+                        symbol_short!("granted"),
+                        EVENT_SCHEMA_VERSION,
+                        admin,
+                    )
+                        .into_val(&env),
+                    expected_data.into_val(&env),
+                ),
+            ]
+        );
+    }
+}
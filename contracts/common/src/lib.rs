@@ -1,5 +1,14 @@
 #![no_std]
 
+pub mod admin;
 pub mod error;
+pub mod events;
+pub mod rbac;
+pub mod ttl;
+pub mod upgrade;
 
+pub use admin::{AdminKeys, AdminStorage};
 pub use error::{QuipayError, QuipayHelpers, QuipayResult};
+pub use rbac::Role;
+pub use ttl::TtlScope;
+pub use upgrade::{PendingUpgrade, VersionInfo};
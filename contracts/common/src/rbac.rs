@@ -0,0 +1,157 @@
+//! Named roles shared by Quipay contracts.
+//!
+//! Each contract keeps its single `Admin`, who implicitly holds every role
+//! and grants or revokes roles through the contract's `grant_role` /
+//! `revoke_role` entry points. A role holder can give up a role with
+//! `renounce_role`. Operations that used to require the admin key check the
+//! matching role instead, so pausing, upgrading, fee changes, dispute
+//! arbitration and blacklisting can be delegated to separate keys.
+//!
+//! Role memberships live in the calling contract's persistent storage under
+//! their own key type, so they never collide with a contract's `DataKey`.
+
+use crate::QuipayError;
+use crate::events::{self, RoleGranted, RoleRenounced, RoleRevoked};
+use soroban_sdk::{Address, Env, contracttype};
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum Role {
+    /// Pause and unpause the protocol or individual streams.
+    Pauser = 1,
+    /// Propose and cancel contract upgrades.
+    Upgrader = 2,
+    /// Change protocol fees.
+    FeeSetter = 3,
+    /// Resolve stream disputes.
+    Arbitrator = 4,
+    /// Blacklist and unblacklist workers.
+    Blacklister = 5,
+}
+
+#[contracttype]
+#[derive(Clone)]
+enum RbacKey {
+    Member(Role, Address),
+}
+
+/// True if `account` was granted `role`. Does not account for the admin's
+/// implicit roles; use [`require_role`] to authorise a caller.
+pub fn has_role(env: &Env, role: Role, account: &Address) -> bool {
+    env.storage()
+        .persistent()
+        .has(&RbacKey::Member(role, account.clone()))
+}
+
+/// Grant `role` to `account`. The contract authenticates `admin` first.
+pub fn grant_role(env: &Env, admin: &Address, role: Role, account: &Address) {
+    env.storage()
+        .persistent()
+        .set(&RbacKey::Member(role, account.clone()), &true);
+    events::emit(
+        env,
+        admin,
+        RoleGranted {
+            role,
+            account: account.clone(),
+        },
+    );
+}
+
+/// Revoke `role` from `account`. The contract authenticates `admin` first.
+pub fn revoke_role(env: &Env, admin: &Address, role: Role, account: &Address) {
+    env.storage()
+        .persistent()
+        .remove(&RbacKey::Member(role, account.clone()));
+    events::emit(
+        env,
+        admin,
+        RoleRevoked {
+            role,
+            account: account.clone(),
+        },
+    );
+}
+
+/// Give up `role`. Requires `account`'s authorisation.
+pub fn renounce_role(env: &Env, role: Role, account: &Address) {
+    account.require_auth();
+    env.storage()
+        .persistent()
+        .remove(&RbacKey::Member(role, account.clone()));
+    events::emit(
+        env,
+        account,
+        RoleRenounced {
+            role,
+            account: account.clone(),
+        },
+    );
+}
+
+/// Extend the TTL of every role `account` holds to the full policy TTL.
+pub fn extend_roles_ttl(env: &Env, account: &Address) {
+    for role in [
+        Role::Pauser,
+        Role::Upgrader,
+        Role::FeeSetter,
+        Role::Arbitrator,
+        Role::Blacklister,
+    ] {
+        crate::ttl::extend_fully(env, &RbacKey::Member(role, account.clone()));
+    }
+}
+
+/// Authenticate `caller` and require it to be `admin` or to hold `role`.
+pub fn require_role(
+    env: &Env,
+    role: Role,
+    caller: &Address,
+    admin: &Address,
+) -> Result<(), QuipayError> {
+    caller.require_auth();
+    if caller == admin || has_role(env, role, caller) {
+        Ok(())
+    } else {
+        Err(QuipayError::InsufficientPermissions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use soroban_sdk::{contract, testutils::Address as _};
+
+    #[contract]
+    struct RbacHost;
+
+    #[test]
+    fn test_grant_revoke_and_renounce() {
+        let env = Env::default();
+        env.mock_all_auths();
+        let host = env.register(RbacHost, ());
+        let admin = Address::generate(&env);
+        let pauser = Address::generate(&env);
+
+        // Each call authorises in its own frame, like separate invocations.
+        let check = |caller: &Address| {
+            env.as_contract(&host, || require_role(&env, Role::Pauser, caller, &admin))
+        };
+        assert_eq!(check(&pauser), Err(QuipayError::InsufficientPermissions));
+        assert!(check(&admin).is_ok());
+
+        env.as_contract(&host, || grant_role(&env, &admin, Role::Pauser, &pauser));
+        assert!(check(&pauser).is_ok());
+        env.as_contract(&host, || {
+            assert!(!has_role(&env, Role::Upgrader, &pauser));
+            revoke_role(&env, &admin, Role::Pauser, &pauser);
+            assert!(!has_role(&env, Role::Pauser, &pauser));
+            grant_role(&env, &admin, Role::Arbitrator, &pauser);
+        });
+        env.as_contract(&host, || {
+            renounce_role(&env, Role::Arbitrator, &pauser);
+            assert!(!has_role(&env, Role::Arbitrator, &pauser));
+        });
+    }
+}
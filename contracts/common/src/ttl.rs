@@ -0,0 +1,147 @@
+//! Storage TTL policy shared by Quipay contracts.
+//!
+//! Persistent and instance entries are archived once their TTL runs out.
+//! Write paths extend the entries they touch with [`extend`], which only
+//! pays rent once fewer than `TTL_THRESHOLD` ledgers remain. Entries nobody
+//! writes to (a stream left untouched for months, a worker's receipts) are
+//! kept alive by keepers through each contract's permissionless
+//! `bump_ttl(scope)` entry point, which extends every entry of a
+//! [`TtlScope`] to the full `TTL_EXTEND_TO`.
+//!
+//! Contracts cannot read an entry's TTL, so a keeper bump records the ledger
+//! the scope's entries live until. `get_ttl_expiry(scope)` reports that
+//! ledger; write-path extensions only push the real expiry further out.
+
+use crate::events::{self, TtlBumped};
+use soroban_sdk::{Address, Env, IntoVal, Val, contracttype};
+
+/// Ledgers closed per day at a five second close time.
+pub const LEDGERS_PER_DAY: u32 = 17_280;
+/// Write paths extend an entry once fewer than this many ledgers remain (30 days).
+pub const TTL_THRESHOLD: u32 = 30 * LEDGERS_PER_DAY;
+/// Ledgers an entry stays live after an extension (120 days).
+pub const TTL_EXTEND_TO: u32 = 120 * LEDGERS_PER_DAY;
+
+/// A group of entries extended together by `bump_ttl`. Each contract
+/// documents which scopes it supports and rejects the others with
+/// `UnsupportedTtlScope`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum TtlScope {
+    /// Instance storage and contract-wide configuration.
+    Contract,
+    Stream(u64),
+    /// Entries kept per employer, worker or agent.
+    Account(Address),
+    /// Entries kept per token.
+    Token(Address),
+    Receipt(u64),
+    Proposal(u64),
+}
+
+#[contracttype]
+#[derive(Clone)]
+enum TtlKey {
+    Expiry(TtlScope),
+}
+
+/// Extend a persistent entry on a write path, if it exists.
+pub fn extend<K>(env: &Env, key: &K)
+where
+    K: IntoVal<Env, Val>,
+{
+    let storage = env.storage().persistent();
+    if storage.has(key) {
+        storage.extend_ttl(key, TTL_THRESHOLD, TTL_EXTEND_TO);
+    }
+}
+
+/// Extend a persistent entry to the full `TTL_EXTEND_TO`. Returns `false`
+/// if the entry does not exist.
+pub fn extend_fully<K>(env: &Env, key: &K) -> bool
+where
+    K: IntoVal<Env, Val>,
+{
+    let storage = env.storage().persistent();
+    if !storage.has(key) {
+        return false;
+    }
+    storage.extend_ttl(key, TTL_EXTEND_TO, TTL_EXTEND_TO);
+    true
+}
+
+/// Extend the contract instance and its code to the full `TTL_EXTEND_TO`.
+pub fn extend_instance(env: &Env) {
+    env.storage()
+        .instance()
+        .extend_ttl(TTL_EXTEND_TO, TTL_EXTEND_TO);
+}
+
+/// Record that `scope`'s entries were just extended fully. Returns the
+/// ledger they stay live until.
+pub fn record_bump(env: &Env, scope: &TtlScope) -> u32 {
+    let live_until = env.ledger().sequence().saturating_add(TTL_EXTEND_TO);
+    let key = TtlKey::Expiry(scope.clone());
+    env.storage().persistent().set(&key, &live_until);
+    extend_fully(env, &key);
+
+    events::emit_from_contract(
+        env,
+        TtlBumped {
+            scope: scope.clone(),
+            live_until,
+        },
+    );
+    live_until
+}
+
+/// The ledger `scope`'s entries were last extended to by `bump_ttl`, or
+/// `None` if no keeper has bumped them.
+pub fn expiry(env: &Env, scope: &TtlScope) -> Option<u32> {
+    env.storage()
+        .persistent()
+        .get(&TtlKey::Expiry(scope.clone()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use soroban_sdk::{
+        contract, symbol_short,
+        testutils::{Ledger, storage::Persistent as _},
+    };
+
+    #[contract]
+    struct TtlHost;
+
+    #[test]
+    fn test_extend_fully_and_record_bump() {
+        let env = Env::default();
+        let host = env.register(TtlHost, ());
+        let key = symbol_short!("entry");
+
+        env.as_contract(&host, || {
+            assert!(!extend_fully(&env, &key));
+            env.storage().persistent().set(&key, &1u32);
+
+            // Below the threshold a write-path extension tops the entry up.
+            extend(&env, &key);
+            assert_eq!(env.storage().persistent().get_ttl(&key), TTL_EXTEND_TO);
+
+            env.ledger().set_sequence_number(1_000);
+            extend(&env, &key);
+            assert_eq!(
+                env.storage().persistent().get_ttl(&key),
+                TTL_EXTEND_TO - 1_000
+            );
+            assert!(extend_fully(&env, &key));
+            assert_eq!(env.storage().persistent().get_ttl(&key), TTL_EXTEND_TO);
+
+            let scope = TtlScope::Stream(7);
+            assert_eq!(expiry(&env, &scope), None);
+            assert_eq!(record_bump(&env, &scope), 1_000 + TTL_EXTEND_TO);
+            assert_eq!(expiry(&env, &scope), Some(1_000 + TTL_EXTEND_TO));
+            assert_eq!(expiry(&env, &TtlScope::Stream(8)), None);
+        });
+    }
+}
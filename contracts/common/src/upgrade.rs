@@ -0,0 +1,371 @@
+//! Timelocked upgrades shared by Quipay contracts.
+//!
+//! A contract proposes a new WASM hash together with the version it
+//! installs, executes it once `UPGRADE_TIMELOCK` has passed, and can roll
+//! back to the WASM it replaced. After an upgrade the new code runs its data
+//! migrations through the contract's `migrate(from_version)` entry point,
+//! which calls [`begin_migration`] so each migration runs exactly once.
+//!
+//! Contracts authenticate the caller before calling into this module. The
+//! upgrade state lives in the calling contract's persistent storage under its
+//! own key type, so it never collides with a contract's `DataKey`.
+
+use crate::QuipayError;
+use crate::events::{
+    self, Migrated, UpgradeCanceled, UpgradeExecuted, UpgradeProposed, UpgradeRolledBack,
+};
+use soroban_sdk::{Address, BytesN, Env, Executable, contracttype};
+
+/// Delay between proposing and executing an upgrade (48 hours).
+pub const UPGRADE_TIMELOCK: u64 = 48 * 60 * 60;
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct VersionInfo {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+    pub upgraded_at: u64,
+}
+
+impl VersionInfo {
+    pub fn triple(&self) -> (u32, u32, u32) {
+        (self.major, self.minor, self.patch)
+    }
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct PendingUpgrade {
+    pub wasm_hash: BytesN<32>,
+    pub version: (u32, u32, u32),
+    pub execute_after: u64,
+    pub proposed_at: u64,
+    pub proposed_by: Address,
+}
+
+/// The release an upgrade replaced, kept for `rollback`.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct PreviousRelease {
+    /// `None` if the contract was not running WASM code.
+    pub wasm_hash: Option<BytesN<32>>,
+    pub version: VersionInfo,
+}
+
+#[contracttype]
+#[derive(Clone)]
+enum UpgradeKey {
+    Version,
+    Pending,
+    Previous,
+    Migrated,
+}
+
+/// Record the version of a freshly initialised contract.
+pub fn set_initial_version(env: &Env, version: (u32, u32, u32)) {
+    let (major, minor, patch) = version;
+    let info = VersionInfo {
+        major,
+        minor,
+        patch,
+        upgraded_at: env.ledger().timestamp(),
+    };
+    env.storage().persistent().set(&UpgradeKey::Version, &info);
+}
+
+/// Record a version the contract tracked before it used this module.
+/// Does nothing once the module tracks a version.
+pub fn adopt_version(env: &Env, version: &VersionInfo) {
+    if get_version(env).is_none() {
+        env.storage()
+            .persistent()
+            .set(&UpgradeKey::Version, version);
+    }
+}
+
+/// The installed version, or `None` if the contract predates the manager.
+pub fn get_version(env: &Env) -> Option<VersionInfo> {
+    env.storage().persistent().get(&UpgradeKey::Version)
+}
+
+pub fn get_pending(env: &Env) -> Option<PendingUpgrade> {
+    env.storage().persistent().get(&UpgradeKey::Pending)
+}
+
+pub fn get_previous(env: &Env) -> Option<PreviousRelease> {
+    env.storage().persistent().get(&UpgradeKey::Previous)
+}
+
+/// Propose installing `wasm_hash` as `version` after `UPGRADE_TIMELOCK`.
+/// `version` must be newer than the installed version.
+pub fn propose(
+    env: &Env,
+    proposer: &Address,
+    wasm_hash: BytesN<32>,
+    version: (u32, u32, u32),
+) -> Result<PendingUpgrade, QuipayError> {
+    if env.storage().persistent().has(&UpgradeKey::Pending) {
+        return Err(QuipayError::UpgradeAlreadyPending);
+    }
+    if let Some(installed) = get_version(env)
+        && version <= installed.triple()
+    {
+        return Err(QuipayError::VersionMismatch);
+    }
+    let now = env.ledger().timestamp();
+    let pending = PendingUpgrade {
+        wasm_hash,
+        version,
+        execute_after: now.saturating_add(UPGRADE_TIMELOCK),
+        proposed_at: now,
+        proposed_by: proposer.clone(),
+    };
+    env.storage()
+        .persistent()
+        .set(&UpgradeKey::Pending, &pending);
+
+    events::emit(
+        env,
+        proposer,
+        UpgradeProposed {
+            wasm_hash: pending.wasm_hash.clone(),
+            version: pending.version,
+            execute_after: pending.execute_after,
+        },
+    );
+    Ok(pending)
+}
+
+/// Drop the pending upgrade.
+pub fn cancel(env: &Env, caller: &Address) -> Result<PendingUpgrade, QuipayError> {
+    let pending = get_pending(env).ok_or(QuipayError::NoPendingUpgrade)?;
+    env.storage().persistent().remove(&UpgradeKey::Pending);
+
+    events::emit(
+        env,
+        caller,
+        UpgradeCanceled {
+            wasm_hash: pending.wasm_hash.clone(),
+            version: pending.version,
+        },
+    );
+    Ok(pending)
+}
+
+/// Install the pending upgrade once its timelock has passed, keeping the
+/// replaced release for `rollback`.
+pub fn execute(env: &Env, caller: &Address) -> Result<VersionInfo, QuipayError> {
+    let pending = get_pending(env).ok_or(QuipayError::NoPendingUpgrade)?;
+    let now = env.ledger().timestamp();
+    if now < pending.execute_after {
+        return Err(QuipayError::UpgradeTimelockActive);
+    }
+
+    let storage = env.storage().persistent();
+    let previous = PreviousRelease {
+        wasm_hash: current_wasm(env),
+        version: installed_version(env),
+    };
+    let (major, minor, patch) = pending.version;
+    let version = VersionInfo {
+        major,
+        minor,
+        patch,
+        upgraded_at: now,
+    };
+    storage.set(&UpgradeKey::Previous, &previous);
+    storage.set(&UpgradeKey::Version, &version);
+    storage.remove(&UpgradeKey::Pending);
+
+    env.deployer()
+        .update_current_contract_wasm(pending.wasm_hash.clone());
+
+    events::emit(
+        env,
+        caller,
+        UpgradeExecuted {
+            wasm_hash: pending.wasm_hash,
+            from_version: previous.version.triple(),
+            to_version: version.triple(),
+        },
+    );
+    Ok(version)
+}
+
+/// Reinstall the release replaced by the last upgrade. Takes effect
+/// immediately and clears any pending upgrade. Data migrations already run
+/// by the newer release are not reverted.
+pub fn rollback(env: &Env, caller: &Address) -> Result<VersionInfo, QuipayError> {
+    let previous = get_previous(env).ok_or(QuipayError::NoRollbackTarget)?;
+    let wasm_hash = previous.wasm_hash.ok_or(QuipayError::NoRollbackTarget)?;
+    let from = installed_version(env);
+
+    let storage = env.storage().persistent();
+    storage.set(&UpgradeKey::Version, &previous.version);
+    storage.remove(&UpgradeKey::Previous);
+    storage.remove(&UpgradeKey::Pending);
+
+    env.deployer()
+        .update_current_contract_wasm(wasm_hash.clone());
+
+    events::emit(
+        env,
+        caller,
+        UpgradeRolledBack {
+            wasm_hash,
+            from_version: from.triple(),
+            to_version: previous.version.triple(),
+        },
+    );
+    Ok(previous.version)
+}
+
+/// Guard a contract's `migrate(from_version)` entry point.
+///
+/// `from_version` must be the version the last upgrade replaced, and each
+/// installed version migrates only once. Returns the installed version for
+/// the contract to run its migration steps against.
+pub fn begin_migration(
+    env: &Env,
+    from_version: (u32, u32, u32),
+) -> Result<VersionInfo, QuipayError> {
+    let previous = get_previous(env).ok_or(QuipayError::VersionMismatch)?;
+    if previous.version.triple() != from_version {
+        return Err(QuipayError::VersionMismatch);
+    }
+
+    let version = installed_version(env);
+    let migrated: Option<(u32, u32, u32)> = env.storage().persistent().get(&UpgradeKey::Migrated);
+    if migrated == Some(version.triple()) {
+        return Err(QuipayError::MigrationAlreadyApplied);
+    }
+    env.storage()
+        .persistent()
+        .set(&UpgradeKey::Migrated, &version.triple());
+
+    events::emit_from_contract(
+        env,
+        Migrated {
+            from_version,
+            to_version: version.triple(),
+        },
+    );
+    Ok(version)
+}
+
+/// Extend the TTL of the upgrade state to the full policy TTL.
+pub fn extend_ttl(env: &Env) {
+    for key in [
+        UpgradeKey::Version,
+        UpgradeKey::Pending,
+        UpgradeKey::Previous,
+        UpgradeKey::Migrated,
+    ] {
+        crate::ttl::extend_fully(env, &key);
+    }
+}
+
+/// The WASM hash the contract is currently running.
+fn current_wasm(env: &Env) -> Option<BytesN<32>> {
+    match env.current_contract_address().executable() {
+        Some(Executable::Wasm(hash)) => Some(hash),
+        _ => None,
+    }
+}
+
+/// The installed version. Contracts deployed before the manager carry no
+/// version; they count as 1.0.0.
+pub fn installed_version(env: &Env) -> VersionInfo {
+    get_version(env).unwrap_or(VersionInfo {
+        major: 1,
+        minor: 0,
+        patch: 0,
+        upgraded_at: 0,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use soroban_sdk::{
+        contract,
+        testutils::{Address as _, Ledger},
+    };
+
+    #[contract]
+    struct UpgradeHost;
+
+    #[test]
+    fn test_propose_cancel_and_migration_guards() {
+        let env = Env::default();
+        let host = env.register(UpgradeHost, ());
+        let proposer = Address::generate(&env);
+        let hash = BytesN::from_array(&env, &[7u8; 32]);
+
+        env.as_contract(&host, || {
+            set_initial_version(&env, (1, 0, 0));
+            assert_eq!(
+                propose(&env, &proposer, hash.clone(), (1, 0, 0)),
+                Err(QuipayError::VersionMismatch)
+            );
+            let pending = propose(&env, &proposer, hash.clone(), (1, 1, 0)).unwrap();
+            assert_eq!(pending.execute_after, UPGRADE_TIMELOCK);
+            assert_eq!(
+                propose(&env, &proposer, hash.clone(), (1, 2, 0)),
+                Err(QuipayError::UpgradeAlreadyPending)
+            );
+            assert_eq!(
+                execute(&env, &proposer),
+                Err(QuipayError::UpgradeTimelockActive)
+            );
+            assert_eq!(cancel(&env, &proposer), Ok(pending));
+            assert_eq!(cancel(&env, &proposer), Err(QuipayError::NoPendingUpgrade));
+            assert_eq!(
+                rollback(&env, &proposer),
+                Err(QuipayError::NoRollbackTarget)
+            );
+
+            // Never upgraded: there is nothing to migrate from.
+            assert_eq!(
+                begin_migration(&env, (1, 0, 0)),
+                Err(QuipayError::VersionMismatch)
+            );
+
+            // Simulate an executed 1.0.0 -> 1.1.0 upgrade without swapping WASM.
+            env.ledger().set_timestamp(100);
+            env.storage().persistent().set(
+                &UpgradeKey::Previous,
+                &PreviousRelease {
+                    wasm_hash: None,
+                    version: get_version(&env).unwrap(),
+                },
+            );
+            env.storage().persistent().set(
+                &UpgradeKey::Version,
+                &VersionInfo {
+                    major: 1,
+                    minor: 1,
+                    patch: 0,
+                    upgraded_at: 100,
+                },
+            );
+            assert_eq!(
+                rollback(&env, &proposer),
+                Err(QuipayError::NoRollbackTarget)
+            );
+            assert_eq!(
+                begin_migration(&env, (0, 9, 0)),
+                Err(QuipayError::VersionMismatch)
+            );
+            assert_eq!(
+                begin_migration(&env, (1, 0, 0)).unwrap().triple(),
+                (1, 1, 0)
+            );
+            assert_eq!(
+                begin_migration(&env, (1, 0, 0)),
+                Err(QuipayError::MigrationAlreadyApplied)
+            );
+        });
+    }
+}
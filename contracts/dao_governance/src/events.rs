@@ -0,0 +1,47 @@
+//! Events published by DaoGovernance, under the `dao` namespace.
+//!
+//! Topics follow `quipay_common::events`. Finalizing a proposal is
+//! permissionless, so that event names the governance contract as the actor.
+
+use crate::ProposalStatus;
+use soroban_sdk::{String, contracttype};
+
+pub(crate) use quipay_common::events::{emit, emit_from_contract};
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ProposalCreated {
+    pub proposal_id: u64,
+    pub title: String,
+    pub voting_ends_at: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VoteCast {
+    pub proposal_id: u64,
+    pub support: bool,
+    pub weight: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ProposalFinalized {
+    pub proposal_id: u64,
+    pub status: ProposalStatus,
+}
+
+/// A passed proposal executed; `stream_id` is the stream it created.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ProposalExecuted {
+    pub proposal_id: u64,
+    pub stream_id: u64,
+}
+
+quipay_common::event_names!("dao" {
+    ProposalCreated => "proposal_created",
+    VoteCast => "vote_cast",
+    ProposalFinalized => "proposal_finalized",
+    ProposalExecuted => "proposal_executed",
+});
@@ -15,12 +15,16 @@
 //!   Proposal(u64), VoteCast(u64, Address)
 
 #![no_std]
-use quipay_common::{QuipayError, require};
+use quipay_common::{
+    PendingUpgrade, QuipayError, Role, TtlScope, VersionInfo, rbac, require, ttl, upgrade,
+};
 use soroban_sdk::{
-    Address, BytesN, Env, IntoVal, Symbol, Vec, contract, contractimpl, contracttype,
-    symbol_short, token,
+    Address, BytesN, Env, IntoVal, Symbol, Vec, contract, contractimpl, contracttype, token,
 };
 
+pub mod events;
+use events::{ProposalCreated, ProposalExecuted, ProposalFinalized, VoteCast};
+
 // ─── Storage keys ─────────────────────────────────────────────────────────────
 
 #[contracttype]
@@ -91,16 +95,6 @@ const DEFAULT_QUORUM_BPS: u32 = 1000; // 10%
 const DEFAULT_APPROVAL_BPS: u32 = 5001; // >50%
 const BPS_DENOMINATOR: i128 = 10_000;
 
-// Storage TTL (in ledgers)
-const STORAGE_TTL_THRESHOLD: u32 = 500_000;
-const STORAGE_TTL_EXTEND: u32 = 1_000_000;
-
-// Event symbols
-const PROPOSAL_CREATED: Symbol = symbol_short!("prop_new");
-const VOTE_CAST: Symbol = symbol_short!("voted");
-const PROPOSAL_EXECUTED: Symbol = symbol_short!("prop_exec");
-const PROPOSAL_FINALIZED: Symbol = symbol_short!("prop_fin");
-
 #[contract]
 pub struct DaoGovernance;
 
@@ -137,6 +131,7 @@ impl DaoGovernance {
         env.storage()
             .instance()
             .set(&DataKey::NextProposalId, &1u64);
+        upgrade::set_initial_version(&env, (1, 0, 0));
         Ok(())
     }
 
@@ -193,6 +188,97 @@ impl DaoGovernance {
             .ok_or(QuipayError::NotInitialized)
     }
 
+    // ─── Upgrades ─────────────────────────────────────────────────────────────
+
+    /// Propose an upgrade to `new_version` with a 48-hour timelock.
+    /// Requires the admin or an `Upgrader`.
+    pub fn propose_upgrade(
+        env: Env,
+        caller: Address,
+        new_wasm_hash: BytesN<32>,
+        new_version: (u32, u32, u32),
+    ) -> Result<(), QuipayError> {
+        let admin = Self::get_admin(env.clone())?;
+        rbac::require_role(&env, Role::Upgrader, &caller, &admin)?;
+        upgrade::propose(&env, &caller, new_wasm_hash, new_version)?;
+        Ok(())
+    }
+
+    /// Execute the pending upgrade once its timelock has passed.
+    /// Requires the admin or an `Upgrader`.
+    pub fn execute_upgrade(env: Env, caller: Address) -> Result<(), QuipayError> {
+        let admin = Self::get_admin(env.clone())?;
+        rbac::require_role(&env, Role::Upgrader, &caller, &admin)?;
+        upgrade::execute(&env, &caller)?;
+        Ok(())
+    }
+
+    /// Cancel the pending upgrade.
+    /// Requires the admin or an `Upgrader`.
+    pub fn cancel_upgrade(env: Env, caller: Address) -> Result<(), QuipayError> {
+        let admin = Self::get_admin(env.clone())?;
+        rbac::require_role(&env, Role::Upgrader, &caller, &admin)?;
+        upgrade::cancel(&env, &caller)?;
+        Ok(())
+    }
+
+    /// Reinstall the WASM replaced by the last upgrade, without a timelock.
+    /// Requires the admin or an `Upgrader`.
+    pub fn rollback_upgrade(env: Env, caller: Address) -> Result<VersionInfo, QuipayError> {
+        let admin = Self::get_admin(env.clone())?;
+        rbac::require_role(&env, Role::Upgrader, &caller, &admin)?;
+        upgrade::rollback(&env, &caller)
+    }
+
+    /// Run the data migrations of the installed version, once, after an
+    /// upgrade from `from_version`.
+    /// Requires the admin or an `Upgrader`.
+    pub fn migrate(
+        env: Env,
+        caller: Address,
+        from_version: (u32, u32, u32),
+    ) -> Result<VersionInfo, QuipayError> {
+        let admin = Self::get_admin(env.clone())?;
+        rbac::require_role(&env, Role::Upgrader, &caller, &admin)?;
+        // No storage layout changes to migrate yet.
+        upgrade::begin_migration(&env, from_version)
+    }
+
+    pub fn get_pending_upgrade(env: Env) -> Option<PendingUpgrade> {
+        upgrade::get_pending(&env)
+    }
+
+    pub fn get_version(env: Env) -> VersionInfo {
+        upgrade::installed_version(&env)
+    }
+
+    /// Grant a role to `account` (admin only).
+    pub fn grant_role(env: Env, role: Role, account: Address) -> Result<(), QuipayError> {
+        let admin = Self::get_admin(env.clone())?;
+        admin.require_auth();
+        rbac::grant_role(&env, &admin, role, &account);
+        Ok(())
+    }
+
+    /// Revoke a role from `account` (admin only).
+    pub fn revoke_role(env: Env, role: Role, account: Address) -> Result<(), QuipayError> {
+        let admin = Self::get_admin(env.clone())?;
+        admin.require_auth();
+        rbac::revoke_role(&env, &admin, role, &account);
+        Ok(())
+    }
+
+    /// Give up a role held by `account`.
+    pub fn renounce_role(env: Env, account: Address, role: Role) {
+        rbac::renounce_role(&env, role, &account);
+    }
+
+    pub fn has_role(env: Env, role: Role, account: Address) -> bool {
+        rbac::has_role(&env, role, &account)
+    }
+
+    // ─── Views ────────────────────────────────────────────────────────────────
+
     pub fn get_config(env: Env) -> (u64, u32, u32) {
         let voting_period: u64 = env
             .storage()
@@ -232,7 +318,7 @@ impl DaoGovernance {
             .get(&DataKey::GovernanceToken)
             .ok_or(QuipayError::NotInitialized)?;
         let balance = token::Client::new(&env, &gov_token).balance(&proposer);
-        require!(balance > 0, QuipayError::InsufficientPermissions);
+        require!(balance > 0, QuipayError::NoVotingPower);
 
         // Validate stream params
         require!(
@@ -289,17 +375,20 @@ impl DaoGovernance {
         env.storage()
             .persistent()
             .set(&DataKey::Proposal(proposal_id), &proposal);
-        env.storage()
-            .persistent()
-            .extend_ttl(&DataKey::Proposal(proposal_id), STORAGE_TTL_THRESHOLD, STORAGE_TTL_EXTEND);
+        ttl::extend(&env, &DataKey::Proposal(proposal_id));
 
         env.storage()
             .instance()
             .set(&DataKey::NextProposalId, &(proposal_id + 1));
 
-        env.events().publish(
-            (PROPOSAL_CREATED, proposer, proposal_id),
-            title,
+        events::emit(
+            &env,
+            &proposer,
+            ProposalCreated {
+                proposal_id,
+                title,
+                voting_ends_at: proposal.voting_ends_at,
+            },
         );
 
         Ok(proposal_id)
@@ -319,21 +408,21 @@ impl DaoGovernance {
             .storage()
             .persistent()
             .get(&DataKey::Proposal(proposal_id))
-            .ok_or(QuipayError::StreamNotFound)?;
+            .ok_or(QuipayError::ProposalNotFound)?;
 
         require!(
             proposal.status == ProposalStatus::Active,
-            QuipayError::StreamClosed
+            QuipayError::ProposalNotPending
         );
 
         let now = env.ledger().timestamp();
-        require!(now <= proposal.voting_ends_at, QuipayError::StreamExpired);
+        require!(now <= proposal.voting_ends_at, QuipayError::VotingClosed);
 
         // Prevent double voting
         let vote_key = DataKey::VoteCast(proposal_id, voter.clone());
         require!(
             !env.storage().persistent().has(&vote_key),
-            QuipayError::AlreadySigner
+            QuipayError::AlreadyVoted
         );
 
         // Weight = token balance
@@ -343,7 +432,7 @@ impl DaoGovernance {
             .get(&DataKey::GovernanceToken)
             .ok_or(QuipayError::NotInitialized)?;
         let weight = token::Client::new(&env, &gov_token).balance(&voter);
-        require!(weight > 0, QuipayError::InsufficientPermissions);
+        require!(weight > 0, QuipayError::NoVotingPower);
 
         if support {
             proposal.votes_for = proposal
@@ -360,18 +449,21 @@ impl DaoGovernance {
         env.storage()
             .persistent()
             .set(&DataKey::Proposal(proposal_id), &proposal);
-        env.storage()
-            .persistent()
-            .extend_ttl(&DataKey::Proposal(proposal_id), STORAGE_TTL_THRESHOLD, STORAGE_TTL_EXTEND);
+        ttl::extend(&env, &DataKey::Proposal(proposal_id));
 
         // Record vote to prevent double-voting
         env.storage().persistent().set(&vote_key, &support);
-        env.storage()
-            .persistent()
-            .extend_ttl(&vote_key, STORAGE_TTL_THRESHOLD, STORAGE_TTL_EXTEND);
-
-        env.events()
-            .publish((VOTE_CAST, voter, proposal_id), (support, weight));
+        ttl::extend(&env, &vote_key);
+
+        events::emit(
+            &env,
+            &voter,
+            VoteCast {
+                proposal_id,
+                support,
+                weight,
+            },
+        );
 
         Ok(())
     }
@@ -384,15 +476,15 @@ impl DaoGovernance {
             .storage()
             .persistent()
             .get(&DataKey::Proposal(proposal_id))
-            .ok_or(QuipayError::StreamNotFound)?;
+            .ok_or(QuipayError::ProposalNotFound)?;
 
         require!(
             proposal.status == ProposalStatus::Active,
-            QuipayError::StreamClosed
+            QuipayError::ProposalNotPending
         );
 
         let now = env.ledger().timestamp();
-        require!(now > proposal.voting_ends_at, QuipayError::GracePeriodActive);
+        require!(now > proposal.voting_ends_at, QuipayError::VotingStillOpen);
 
         let status = Self::compute_status(&env, &proposal);
         proposal.status = status;
@@ -400,12 +492,15 @@ impl DaoGovernance {
         env.storage()
             .persistent()
             .set(&DataKey::Proposal(proposal_id), &proposal);
-        env.storage()
-            .persistent()
-            .extend_ttl(&DataKey::Proposal(proposal_id), STORAGE_TTL_THRESHOLD, STORAGE_TTL_EXTEND);
-
-        env.events()
-            .publish((PROPOSAL_FINALIZED, proposal_id), status as u32);
+        ttl::extend(&env, &DataKey::Proposal(proposal_id));
+
+        events::emit_from_contract(
+            &env,
+            ProposalFinalized {
+                proposal_id,
+                status,
+            },
+        );
 
         Ok(status)
     }
@@ -423,7 +518,7 @@ impl DaoGovernance {
             .storage()
             .persistent()
             .get(&DataKey::Proposal(proposal_id))
-            .ok_or(QuipayError::StreamNotFound)?;
+            .ok_or(QuipayError::ProposalNotFound)?;
 
         // Auto-finalize if still Active and voting window closed
         if proposal.status == ProposalStatus::Active {
@@ -435,7 +530,7 @@ impl DaoGovernance {
 
         require!(
             proposal.status == ProposalStatus::Passed,
-            QuipayError::InsufficientPermissions
+            QuipayError::ProposalNotPassed
         );
 
         let payroll_stream: Address = env
@@ -470,13 +565,15 @@ impl DaoGovernance {
         env.storage()
             .persistent()
             .set(&DataKey::Proposal(proposal_id), &proposal);
-        env.storage()
-            .persistent()
-            .extend_ttl(&DataKey::Proposal(proposal_id), STORAGE_TTL_THRESHOLD, STORAGE_TTL_EXTEND);
-
-        env.events().publish(
-            (PROPOSAL_EXECUTED, executor, proposal_id),
-            stream_id,
+        ttl::extend(&env, &DataKey::Proposal(proposal_id));
+
+        events::emit(
+            &env,
+            &executor,
+            ProposalExecuted {
+                proposal_id,
+                stream_id,
+            },
         );
 
         Ok(stream_id)
@@ -503,6 +600,33 @@ impl DaoGovernance {
             .unwrap_or(1)
     }
 
+    // ─── Storage TTL ──────────────────────────────────────────────────────────
+
+    /// Extend every entry of `scope` to the full policy TTL. Permissionless.
+    /// Supports `Contract` (instance storage and upgrade state) and
+    /// `Proposal(id)`. Returns the ledger the entries stay live until.
+    pub fn bump_ttl(env: Env, scope: TtlScope) -> Result<u32, QuipayError> {
+        match &scope {
+            TtlScope::Contract => {
+                ttl::extend_instance(&env);
+                upgrade::extend_ttl(&env);
+            }
+            TtlScope::Proposal(proposal_id) => {
+                if !ttl::extend_fully(&env, &DataKey::Proposal(*proposal_id)) {
+                    return Err(QuipayError::ProposalNotFound);
+                }
+            }
+            _ => return Err(QuipayError::UnsupportedTtlScope),
+        }
+        Ok(ttl::record_bump(&env, &scope))
+    }
+
+    /// Ledger `scope`'s entries were last extended to by `bump_ttl`, or
+    /// `None` if no keeper has bumped them.
+    pub fn get_ttl_expiry(env: Env, scope: TtlScope) -> Option<u32> {
+        ttl::expiry(&env, &scope)
+    }
+
     // ─── Helpers ──────────────────────────────────────────────────────────────
 
     fn require_admin(env: &Env) -> Result<(), QuipayError> {
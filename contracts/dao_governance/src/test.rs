@@ -1,9 +1,9 @@
 #![cfg(test)]
 use super::*;
 use soroban_sdk::{
-    testutils::{Address as _, Ledger},
+    testutils::{Address as _, Events as _, Ledger},
     token::{Client as TokenClient, StellarAssetClient},
-    Env, String,
+    Env, String, TryFromVal,
 };
 
 fn setup_env() -> (Env, Address, Address, Address, Address) {
@@ -90,6 +90,21 @@ fn test_vote_for() {
 
     client.vote(&voter, &proposal_id, &true);
 
+    let (contract, topics, data) = env.events().all().last().unwrap();
+    assert_eq!(contract, contract_id);
+    assert_eq!(
+        topics,
+        quipay_common::events::topics::<events::VoteCast>(&env, &voter).into_val(&env)
+    );
+    assert_eq!(
+        events::VoteCast::try_from_val(&env, &data).unwrap(),
+        events::VoteCast {
+            proposal_id,
+            support: true,
+            weight: 500_000,
+        }
+    );
+
     let proposal = client.get_proposal(&proposal_id).unwrap();
     assert_eq!(proposal.votes_for, 500_000_i128);
     assert_eq!(proposal.votes_against, 0);
@@ -114,7 +129,7 @@ fn test_double_vote_rejected() {
     client.vote(&voter, &proposal_id, &true);
     // Second vote should fail
     let result = client.try_vote(&voter, &proposal_id, &true);
-    assert!(result.is_err());
+    assert_eq!(result, Err(Ok(QuipayError::AlreadyVoted)));
 }
 
 #[test]
@@ -198,7 +213,7 @@ fn test_cannot_vote_after_window() {
     });
 
     let result = client.try_vote(&voter, &proposal_id, &true);
-    assert!(result.is_err());
+    assert_eq!(result, Err(Ok(QuipayError::VotingClosed)));
 }
 
 #[test]
@@ -210,3 +225,67 @@ fn test_get_config() {
     assert_eq!(quorum_bps, 1000);
     assert_eq!(approval_bps, 5001);
 }
+
+#[test]
+fn test_bump_ttl_keeps_proposal_alive() {
+    use quipay_common::ttl::TTL_EXTEND_TO;
+    use soroban_sdk::testutils::storage::Persistent as _;
+
+    let (env, contract_id, admin, _gov_token, _payroll_stream) = setup_env();
+    let client = DaoGovernanceClient::new(&env, &contract_id);
+    let params = make_stream_params(&env, &admin);
+    let proposal_id = client.create_proposal(
+        &admin,
+        &String::from_str(&env, "Pay Alice"),
+        &String::from_str(&env, "Stream 100 XLM/s to Alice"),
+        &params,
+    );
+
+    env.ledger().set_sequence_number(30);
+    let live_until = client.bump_ttl(&TtlScope::Proposal(proposal_id));
+    assert_eq!(live_until, 30 + TTL_EXTEND_TO);
+    assert_eq!(
+        client.get_ttl_expiry(&TtlScope::Proposal(proposal_id)),
+        Some(live_until)
+    );
+    let ttl = env.as_contract(&contract_id, || {
+        env.storage()
+            .persistent()
+            .get_ttl(&DataKey::Proposal(proposal_id))
+    });
+    assert_eq!(ttl, TTL_EXTEND_TO);
+    client.bump_ttl(&TtlScope::Contract);
+
+    let result = client.try_bump_ttl(&TtlScope::Proposal(proposal_id + 1));
+    assert_eq!(result, Err(Ok(QuipayError::ProposalNotFound)));
+    let result = client.try_bump_ttl(&TtlScope::Stream(1));
+    assert_eq!(result, Err(Ok(QuipayError::UnsupportedTtlScope)));
+}
+
+#[test]
+fn test_upgrader_role_gates_upgrades() {
+    let (env, contract_id, admin, _gov_token, _payroll_stream) = setup_env();
+    let client = DaoGovernanceClient::new(&env, &contract_id);
+    let upgrader = Address::generate(&env);
+    let wasm_hash = BytesN::from_array(&env, &[1; 32]);
+
+    assert_eq!(
+        client.try_propose_upgrade(&upgrader, &wasm_hash, &(1, 1, 0)),
+        Err(Ok(QuipayError::InsufficientPermissions))
+    );
+    client.grant_role(&Role::Upgrader, &upgrader);
+    client.propose_upgrade(&upgrader, &wasm_hash, &(1, 1, 0));
+    assert_eq!(client.get_pending_upgrade().unwrap().proposed_by, upgrader);
+
+    client.renounce_role(&upgrader, &Role::Upgrader);
+    assert!(!client.has_role(&Role::Upgrader, &upgrader));
+    assert_eq!(
+        client.try_cancel_upgrade(&upgrader),
+        Err(Ok(QuipayError::InsufficientPermissions))
+    );
+    assert_eq!(
+        client.try_migrate(&upgrader, &(1, 0, 0)),
+        Err(Ok(QuipayError::InsufficientPermissions))
+    );
+    client.cancel_upgrade(&admin);
+}
@@ -0,0 +1,25 @@
+//! Events published by PayrollReceipt, under the `receipt` namespace.
+//!
+//! Topics follow `quipay_common::events`; the minter (PayrollStream) is the
+//! actor.
+
+use crate::ClosureReason;
+use soroban_sdk::{Address, contracttype};
+
+pub(crate) use quipay_common::events::emit;
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReceiptMinted {
+    pub receipt_id: u64,
+    pub stream_id: u64,
+    pub employer: Address,
+    pub worker: Address,
+    pub token: Address,
+    pub total_paid: i128,
+    pub reason: ClosureReason,
+}
+
+quipay_common::event_names!("receipt" {
+    ReceiptMinted => "minted",
+});
@@ -1,10 +1,16 @@
 #![no_std]
 
-use quipay_common::{QuipayError, require};
+use quipay_common::{
+    AdminKeys, AdminStorage, PendingUpgrade, QuipayError, Role, TtlScope, VersionInfo, admin,
+    rbac, require, ttl, upgrade,
+};
 use soroban_sdk::{
-    Address, Env, contract, contractimpl, contracttype, symbol_short,
+    Address, BytesN, Env, contract, contractimpl, contracttype,
 };
 
+pub mod events;
+use events::ReceiptMinted;
+
 #[cfg(test)]
 mod test;
 
@@ -21,6 +27,12 @@ pub enum DataKey {
     WorkerReceipts(Address),
 }
 
+const ADMIN_KEYS: AdminKeys<DataKey> = AdminKeys {
+    storage: AdminStorage::Instance,
+    admin: DataKey::Admin,
+    pending: DataKey::PendingAdmin,
+};
+
 // ── Data types ────────────────────────────────────────────────────────────────
 
 /// How the stream ended.
@@ -67,6 +79,7 @@ impl PayrollReceiptContract {
         env.storage().instance().set(&DataKey::Admin, &admin);
         env.storage().instance().set(&DataKey::Minter, &minter);
         env.storage().instance().set(&DataKey::NextReceiptId, &1u64);
+        upgrade::set_initial_version(&env, (1, 0, 0));
         Ok(())
     }
 
@@ -77,23 +90,101 @@ impl PayrollReceiptContract {
     }
 
     pub fn propose_admin(env: Env, new_admin: Address) -> Result<(), QuipayError> {
-        Self::require_admin(&env)?;
-        env.storage().instance().set(&DataKey::PendingAdmin, &new_admin);
-        Ok(())
+        admin::propose_admin(&env, &ADMIN_KEYS, &new_admin)
     }
 
     pub fn accept_admin(env: Env) -> Result<(), QuipayError> {
-        let pending: Address = env
-            .storage()
-            .instance()
-            .get(&DataKey::PendingAdmin)
-            .ok_or(QuipayError::NoPendingAdmin)?;
-        pending.require_auth();
-        env.storage().instance().set(&DataKey::Admin, &pending);
-        env.storage().instance().remove(&DataKey::PendingAdmin);
+        admin::accept_admin(&env, &ADMIN_KEYS)?;
         Ok(())
     }
 
+    /// Propose an upgrade to `new_version` with a 48-hour timelock.
+    /// Requires the admin or an `Upgrader`.
+    pub fn propose_upgrade(
+        env: Env,
+        caller: Address,
+        new_wasm_hash: BytesN<32>,
+        new_version: (u32, u32, u32),
+    ) -> Result<(), QuipayError> {
+        let admin = Self::get_admin(env.clone())?;
+        rbac::require_role(&env, Role::Upgrader, &caller, &admin)?;
+        upgrade::propose(&env, &caller, new_wasm_hash, new_version)?;
+        Ok(())
+    }
+
+    /// Execute the pending upgrade once its timelock has passed.
+    /// Requires the admin or an `Upgrader`.
+    pub fn execute_upgrade(env: Env, caller: Address) -> Result<(), QuipayError> {
+        let admin = Self::get_admin(env.clone())?;
+        rbac::require_role(&env, Role::Upgrader, &caller, &admin)?;
+        upgrade::execute(&env, &caller)?;
+        Ok(())
+    }
+
+    /// Cancel the pending upgrade.
+    /// Requires the admin or an `Upgrader`.
+    pub fn cancel_upgrade(env: Env, caller: Address) -> Result<(), QuipayError> {
+        let admin = Self::get_admin(env.clone())?;
+        rbac::require_role(&env, Role::Upgrader, &caller, &admin)?;
+        upgrade::cancel(&env, &caller)?;
+        Ok(())
+    }
+
+    /// Reinstall the WASM replaced by the last upgrade, without a timelock.
+    /// Requires the admin or an `Upgrader`.
+    pub fn rollback_upgrade(env: Env, caller: Address) -> Result<VersionInfo, QuipayError> {
+        let admin = Self::get_admin(env.clone())?;
+        rbac::require_role(&env, Role::Upgrader, &caller, &admin)?;
+        upgrade::rollback(&env, &caller)
+    }
+
+    /// Run the data migrations of the installed version, once, after an
+    /// upgrade from `from_version`.
+    /// Requires the admin or an `Upgrader`.
+    pub fn migrate(
+        env: Env,
+        caller: Address,
+        from_version: (u32, u32, u32),
+    ) -> Result<VersionInfo, QuipayError> {
+        let admin = Self::get_admin(env.clone())?;
+        rbac::require_role(&env, Role::Upgrader, &caller, &admin)?;
+        // No storage layout changes to migrate yet.
+        upgrade::begin_migration(&env, from_version)
+    }
+
+    pub fn get_pending_upgrade(env: Env) -> Option<PendingUpgrade> {
+        upgrade::get_pending(&env)
+    }
+
+    pub fn get_version(env: Env) -> VersionInfo {
+        upgrade::installed_version(&env)
+    }
+
+    /// Grant a role to `account` (admin only).
+    pub fn grant_role(env: Env, role: Role, account: Address) -> Result<(), QuipayError> {
+        let admin = Self::get_admin(env.clone())?;
+        admin.require_auth();
+        rbac::grant_role(&env, &admin, role, &account);
+        Ok(())
+    }
+
+    /// Revoke a role from `account` (admin only).
+    pub fn revoke_role(env: Env, role: Role, account: Address) -> Result<(), QuipayError> {
+        let admin = Self::get_admin(env.clone())?;
+        admin.require_auth();
+        rbac::revoke_role(&env, &admin, role, &account);
+        Ok(())
+    }
+
+    /// Give up a role held by `account`.
+    pub fn renounce_role(env: Env, account: Address, role: Role) {
+        rbac::renounce_role(&env, role, &account);
+    }
+
+    pub fn has_role(env: Env, role: Role, account: Address) -> bool {
+        rbac::has_role(&env, role, &account)
+    }
+
     /// Mint a receipt for a completed or cancelled stream.
     /// Only the authorised minter (PayrollStream) may call this.
     /// Receipts are non-transferable: once written they are immutable.
@@ -145,12 +236,23 @@ impl PayrollReceiptContract {
             .unwrap_or_else(|| soroban_sdk::Vec::new(&env));
         ids.push_back(receipt_id);
         env.storage().persistent().set(&index_key, &ids);
+        ttl::extend(&env, &DataKey::Receipt(receipt_id));
+        ttl::extend(&env, &index_key);
 
         env.storage().instance().set(&DataKey::NextReceiptId, &(receipt_id + 1));
 
-        env.events().publish(
-            (symbol_short!("receipt"), symbol_short!("minted"), worker, employer),
-            (receipt_id, stream_id, token, total_paid, reason),
+        events::emit(
+            &env,
+            &minter,
+            ReceiptMinted {
+                receipt_id,
+                stream_id,
+                employer,
+                worker,
+                token,
+                total_paid,
+                reason,
+            },
         );
 
         Ok(receipt_id)
@@ -192,20 +294,53 @@ impl PayrollReceiptContract {
         page
     }
 
+    /// Extend every entry of `scope` to the full policy TTL. Permissionless.
+    /// Returns the ledger the entries stay live until.
+    ///
+    /// Scopes: `Contract` (instance storage and upgrade state), `Receipt(id)`
+    /// and `Account(account)` (a worker's receipt list and its receipts, and
+    /// any roles the account holds).
+    pub fn bump_ttl(env: Env, scope: TtlScope) -> Result<u32, QuipayError> {
+        match &scope {
+            TtlScope::Contract => {
+                ttl::extend_instance(&env);
+                upgrade::extend_ttl(&env);
+            }
+            TtlScope::Receipt(receipt_id) => {
+                if !ttl::extend_fully(&env, &DataKey::Receipt(*receipt_id)) {
+                    return Err(QuipayError::ReceiptNotFound);
+                }
+            }
+            TtlScope::Account(worker) => {
+                let index_key = DataKey::WorkerReceipts(worker.clone());
+                let ids: soroban_sdk::Vec<u64> = env
+                    .storage()
+                    .persistent()
+                    .get(&index_key)
+                    .unwrap_or_else(|| soroban_sdk::Vec::new(&env));
+                ttl::extend_fully(&env, &index_key);
+                for receipt_id in ids.iter() {
+                    ttl::extend_fully(&env, &DataKey::Receipt(receipt_id));
+                }
+                rbac::extend_roles_ttl(&env, worker);
+            }
+            _ => return Err(QuipayError::UnsupportedTtlScope),
+        }
+        Ok(ttl::record_bump(&env, &scope))
+    }
+
+    /// Ledger `scope`'s entries were last extended to by `bump_ttl`, or
+    /// `None` if no keeper has bumped them.
+    pub fn get_ttl_expiry(env: Env, scope: TtlScope) -> Option<u32> {
+        ttl::expiry(&env, &scope)
+    }
+
     pub fn get_admin(env: Env) -> Result<Address, QuipayError> {
-        env.storage()
-            .instance()
-            .get(&DataKey::Admin)
-            .ok_or(QuipayError::NotInitialized)
+        admin::get_admin(&env, &ADMIN_KEYS)
     }
 
     fn require_admin(env: &Env) -> Result<(), QuipayError> {
-        let admin: Address = env
-            .storage()
-            .instance()
-            .get(&DataKey::Admin)
-            .ok_or(QuipayError::NotInitialized)?;
-        admin.require_auth();
+        admin::get_admin(env, &ADMIN_KEYS)?.require_auth();
         Ok(())
     }
 }
@@ -1,7 +1,11 @@
 #![cfg(test)]
 
-use soroban_sdk::{Address, Env, testutils::Address as _};
+use soroban_sdk::{
+    Address, Env, IntoVal, TryFromVal,
+    testutils::{Address as _, Events as _},
+};
 
+use crate::events::ReceiptMinted;
 use crate::{ClosureReason, PayrollReceiptContract, PayrollReceiptContractClient};
 
 fn setup(env: &Env) -> (Address, Address, PayrollReceiptContractClient) {
@@ -17,7 +21,7 @@ fn setup(env: &Env) -> (Address, Address, PayrollReceiptContractClient) {
 #[test]
 fn test_mint_and_get_receipt() {
     let env = Env::default();
-    let (_admin, _minter, client) = setup(&env);
+    let (_admin, minter, client) = setup(&env);
 
     let employer = Address::generate(&env);
     let worker = Address::generate(&env);
@@ -30,6 +34,22 @@ fn test_mint_and_get_receipt() {
     );
     assert_eq!(receipt_id, 1u64);
 
+    let (contract, topics, data) = env.events().all().last().unwrap();
+    assert_eq!(contract, client.address);
+    assert_eq!(topics, quipay_common::events::topics::<ReceiptMinted>(&env, &minter).into_val(&env));
+    assert_eq!(
+        ReceiptMinted::try_from_val(&env, &data).unwrap(),
+        ReceiptMinted {
+            receipt_id,
+            stream_id: 1,
+            employer: employer.clone(),
+            worker: worker.clone(),
+            token: token.clone(),
+            total_paid: 1_000_000,
+            reason: ClosureReason::Completed,
+        }
+    );
+
     let receipt = client.get_receipt(&receipt_id);
     assert_eq!(receipt.stream_id, 1u64);
     assert_eq!(receipt.employer, employer);
@@ -69,3 +89,59 @@ fn test_receipt_ids_increment() {
     assert_eq!(id1, 1u64);
     assert_eq!(id2, 2u64);
 }
+
+#[test]
+fn test_bump_ttl_keeps_worker_receipts_readable() {
+    use quipay_common::{QuipayError, TtlScope, ttl::TTL_EXTEND_TO};
+    use soroban_sdk::testutils::{Ledger as _, storage::Persistent as _};
+
+    let env = Env::default();
+    let (_admin, _minter, client) = setup(&env);
+
+    let employer = Address::generate(&env);
+    let worker = Address::generate(&env);
+    let token = Address::generate(&env);
+    let id = client.mint(&1u64, &employer, &worker, &token, &100i128, &0u64, &100u64, &100u64, &ClosureReason::Completed);
+
+    let live_until = client.bump_ttl(&TtlScope::Account(worker.clone()));
+    assert_eq!(client.get_ttl_expiry(&TtlScope::Account(worker.clone())), Some(live_until));
+    env.as_contract(&client.address, || {
+        assert_eq!(env.storage().persistent().get_ttl(&crate::DataKey::Receipt(id)), TTL_EXTEND_TO);
+    });
+    client.bump_ttl(&TtlScope::Contract);
+    assert_eq!(client.try_bump_ttl(&TtlScope::Receipt(id + 1)), Err(Ok(QuipayError::ReceiptNotFound)));
+    assert_eq!(client.try_bump_ttl(&TtlScope::Stream(1)), Err(Ok(QuipayError::UnsupportedTtlScope)));
+
+    // Past the TTL the receipt is archived and restored on access.
+    env.ledger().with_mut(|li| li.sequence_number += TTL_EXTEND_TO + 1);
+    assert_eq!(client.get_receipt(&id).total_paid, 100);
+    assert_eq!(client.get_worker_receipts(&worker, &0u32, &10u32).len(), 1);
+}
+
+#[test]
+fn test_upgrader_role_gates_upgrades() {
+    let env = Env::default();
+    let (admin, _minter, client) = setup(&env);
+    let upgrader = Address::generate(&env);
+    let wasm_hash = soroban_sdk::BytesN::from_array(&env, &[1; 32]);
+
+    assert_eq!(
+        client.try_propose_upgrade(&upgrader, &wasm_hash, &(1, 1, 0)),
+        Err(Ok(quipay_common::QuipayError::InsufficientPermissions))
+    );
+    client.grant_role(&quipay_common::Role::Upgrader, &upgrader);
+    assert!(client.has_role(&quipay_common::Role::Upgrader, &upgrader));
+    client.propose_upgrade(&upgrader, &wasm_hash, &(1, 1, 0));
+    client.cancel_upgrade(&admin);
+    // Never upgraded, so there is nothing to migrate from.
+    assert_eq!(
+        client.try_migrate(&upgrader, &(1, 0, 0)),
+        Err(Ok(quipay_common::QuipayError::VersionMismatch))
+    );
+
+    client.revoke_role(&quipay_common::Role::Upgrader, &upgrader);
+    assert_eq!(
+        client.try_propose_upgrade(&upgrader, &wasm_hash, &(1, 1, 0)),
+        Err(Ok(quipay_common::QuipayError::InsufficientPermissions))
+    );
+}
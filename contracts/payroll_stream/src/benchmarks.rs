@@ -21,6 +21,15 @@ mod bench_vault {
     #[contractimpl]
     impl DummyVault {
         pub fn add_liability(_env: Env, _token: Address, _amount: i128) {}
+        pub fn charge_protocol_fee(
+            _env: Env,
+            _stream_id: u64,
+            _employer: Address,
+            _token: Address,
+            _volume: i128,
+        ) -> i128 {
+            0
+        }
     }
 }
 
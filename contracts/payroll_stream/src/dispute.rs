@@ -8,12 +8,14 @@
 //!   - Uses `StreamStatus::Disputed` as the freeze mechanism, consistent with
 //!     how `StreamStatus::Paused` and `StreamStatus::PendingCancel` work
 //!   - Returns `Result<_, QuipayError>` everywhere
-//!   - Event topics follow the existing (Symbol, Symbol, stream_id, actor) pattern
-//!   - Token transfers go through existing `call_vault_payout` / `call_vault_remove_liability`
+//!   - Events are typed payloads from `crate::events`, published with the shared topics
+//!   - Worker payouts go through `pay_or_hold_worker`; employer refunds go through
+//!     `call_vault_refund_stream_liability`, which is fee-free and insolvency-aware
 
-use quipay_common::QuipayError;
-use soroban_sdk::{Address, BytesN, Env, Symbol, contracttype};
+use quipay_common::{QuipayError, Role, ttl};
+use soroban_sdk::{Address, BytesN, Env, contracttype};
 
+use crate::events::{self, DisputeRaised, DisputeResolved};
 use crate::{DataKey, DisputeOutcome, PayrollStream, Stream, StreamKey, StreamStatus};
 
 // ─── Types ────────────────────────────────────────────────────────────────────
@@ -49,14 +51,14 @@ pub struct Dispute {
 ///
 /// Callable by the stream's employer or worker. Immediately sets
 /// `stream.status = StreamStatus::Disputed`, which causes all withdrawal
-/// paths (`withdraw`, `batch_withdraw`, `batch_claim`) to return
-/// `QuipayError::StreamNotActive` until the dispute is resolved.
+/// paths to refuse the stream (`withdraw` fails with
+/// `QuipayError::StreamDisputed`) until the dispute is resolved.
 ///
 /// # Errors
 /// - `QuipayError::StreamNotFound` — stream does not exist.
 /// - `QuipayError::Unauthorized`   — caller is neither employer nor worker.
 /// - `QuipayError::StreamClosed`   — stream is already Canceled or Completed.
-/// - `QuipayError::Custom`         — stream already has an open (unresolved) dispute.
+/// - `QuipayError::AlreadyDisputed` — stream already has an open (unresolved) dispute.
 pub fn raise_dispute(
     env: &Env,
     stream_id: u64,
@@ -86,7 +88,7 @@ pub fn raise_dispute(
         // Already disputed — reject duplicate open dispute
         StreamStatus::Disputed => {
             if has_open_dispute(env, stream_id) {
-                return Err(QuipayError::Custom); // AlreadyDisputed
+                return Err(QuipayError::AlreadyDisputed);
             }
             // Previous dispute is resolved — allow a fresh one to be raised
         }
@@ -108,18 +110,21 @@ pub fn raise_dispute(
         },
     );
 
+    ttl::extend(env, &DataKey::Dispute(stream_id));
+
     // Freeze stream by transitioning to Disputed status
     stream.status = StreamStatus::Disputed;
     env.storage().persistent().set(&key, &stream);
 
-    env.events().publish(
-        (
-            Symbol::new(env, "stream"),
-            Symbol::new(env, "dispute_raised"),
+    events::emit(
+        env,
+        caller,
+        DisputeRaised {
             stream_id,
-            caller.clone(),
-        ),
-        (stream.worker.clone(), stream.employer.clone(), reason_hash),
+            employer: stream.employer.clone(),
+            worker: stream.worker.clone(),
+            reason_hash,
+        },
     );
 
     Ok(())
@@ -129,14 +134,16 @@ pub fn raise_dispute(
 
 /// Resolve an open dispute and apply the chosen outcome.
 ///
-/// Only the contract admin may call this. Applies one of three outcomes,
-/// performs vault transfers inline, and updates stream status.
+/// Only the contract admin or an `Arbitrator` may call this. Applies one of three outcomes,
+/// performs vault transfers inline, and updates stream status. A blacklisted worker's share
+/// is held for `claim_held_payout`, as on cancellation.
 ///
 /// # Errors
 /// - `QuipayError::NotInitialized` — contract not set up.
-/// - `QuipayError::Unauthorized`   — caller is not the admin.
+/// - `QuipayError::InsufficientPermissions` — caller is neither the admin nor an `Arbitrator`.
 /// - `QuipayError::StreamNotFound` — stream does not exist.
-/// - `QuipayError::Custom`         — no dispute exists, or already resolved.
+/// - `QuipayError::NoOpenDispute` — no dispute was raised for the stream.
+/// - `QuipayError::DisputeAlreadyResolved` — the dispute is already resolved.
 /// - `QuipayError::Overflow`       — arithmetic overflow during payout split.
 pub fn resolve_dispute(
     env: &Env,
@@ -144,27 +151,17 @@ pub fn resolve_dispute(
     arbitrator: &Address,
     outcome: DisputeOutcome,
 ) -> Result<(), QuipayError> {
-    arbitrator.require_auth();
-
-    // Auth: only admin can resolve
-    let admin: Address = env
-        .storage()
-        .instance()
-        .get(&DataKey::Admin)
-        .ok_or(QuipayError::NotInitialized)?;
-    if *arbitrator != admin {
-        return Err(QuipayError::Unauthorized);
-    }
+    PayrollStream::require_role(env, Role::Arbitrator, arbitrator)?;
 
     // Load and validate dispute
     let mut dispute: Dispute = env
         .storage()
         .persistent()
         .get(&DataKey::Dispute(stream_id))
-        .ok_or(QuipayError::Custom)?; // NoOpenDispute
+        .ok_or(QuipayError::NoOpenDispute)?;
 
     if dispute.resolved {
-        return Err(QuipayError::Custom); // DisputeAlreadyResolved
+        return Err(QuipayError::DisputeAlreadyResolved);
     }
 
     let key = StreamKey::Stream(stream_id);
@@ -198,24 +195,23 @@ pub fn resolve_dispute(
                 .ok_or(QuipayError::Overflow)?;
 
             if remaining > 0 {
-                // Remove liability from vault then pay employer
-                PayrollStream::call_vault_remove_liability(
-                    &env,
-                    &vault,
-                    stream.token.clone(),
-                    remaining,
-                );
-                PayrollStream::call_vault_payout(
-                    &env,
+                PayrollStream::call_vault_refund_stream_liability(
+                    env,
                     &vault,
+                    stream_id,
                     stream.employer.clone(),
                     stream.token.clone(),
                     remaining,
                 );
             }
 
-            stream.status = StreamStatus::Canceled;
-            stream.closed_at = now;
+            PayrollStream::close_stream_internal(
+                &env,
+                stream_id,
+                &mut stream,
+                now,
+                StreamStatus::Canceled,
+            );
         }
 
         // ── CancelWithPartialPayout: earned → worker, remainder → employer ───
@@ -239,13 +235,14 @@ pub fn resolve_dispute(
                 .ok_or(QuipayError::Overflow)?;
 
             if worker_payout > 0 {
-                PayrollStream::call_vault_payout(
+                PayrollStream::pay_or_hold_worker(
                     env,
+                    arbitrator,
                     &vault,
-                    stream.worker.clone(),
-                    stream.token.clone(),
+                    stream_id,
+                    &stream,
                     worker_payout,
-                );
+                )?;
                 stream.withdrawn_amount = stream
                     .withdrawn_amount
                     .checked_add(worker_payout)
@@ -254,24 +251,24 @@ pub fn resolve_dispute(
             }
 
             if employer_refund > 0 {
-                // Remaining liability that isn't paid to worker — remove then refund
-                PayrollStream::call_vault_remove_liability(
-                    env,
-                    &vault,
-                    stream.token.clone(),
-                    employer_refund,
-                );
-                PayrollStream::call_vault_payout(
+                // Refunds are not payouts: no protocol fee is charged.
+                PayrollStream::call_vault_refund_stream_liability(
                     env,
                     &vault,
+                    stream_id,
                     stream.employer.clone(),
                     stream.token.clone(),
                     employer_refund,
                 );
             }
 
-            stream.status = StreamStatus::Canceled;
-            stream.closed_at = now;
+            PayrollStream::close_stream_internal(
+                env,
+                stream_id,
+                &mut stream,
+                now,
+                StreamStatus::Canceled,
+            );
         }
     }
 
@@ -283,15 +280,19 @@ pub fn resolve_dispute(
         .set(&DataKey::Dispute(stream_id), &dispute);
 
     env.storage().persistent().set(&key, &stream);
+    if stream.status == StreamStatus::Canceled {
+        PayrollStream::registry_stream_closed(env, &stream.employer, &stream.worker);
+    }
 
-    env.events().publish(
-        (
-            Symbol::new(env, "stream"),
-            Symbol::new(env, "dispute_resolved"),
+    events::emit(
+        env,
+        arbitrator,
+        DisputeResolved {
             stream_id,
-            arbitrator.clone(),
-        ),
-        (stream.worker.clone(), stream.employer.clone(), outcome),
+            employer: stream.employer.clone(),
+            worker: stream.worker.clone(),
+            outcome,
+        },
     );
 
     Ok(())
@@ -0,0 +1,79 @@
+#![cfg(test)]
+use super::*;
+use crate::test::setup;
+use soroban_sdk::{BytesN, testutils::Ledger as _};
+
+#[test]
+fn test_dispute_freezes_withdrawals() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, employer, worker, token, _admin) = setup(&env);
+
+    env.ledger().with_mut(|li| li.timestamp = 0);
+    let stream_id =
+        client.create_stream(&employer, &worker, &token, &1, &0, &0, &100, &None, &None);
+
+    client.raise_dispute(&stream_id, &worker, &BytesN::from_array(&env, &[1; 32]));
+    assert!(client.has_open_dispute(&stream_id));
+
+    env.ledger().with_mut(|li| li.timestamp = 10);
+    let result = client.try_withdraw(&stream_id, &worker);
+    assert_eq!(result, Err(Ok(QuipayError::StreamDisputed)));
+}
+
+#[test]
+fn test_duplicate_dispute_rejected() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, employer, worker, token, _admin) = setup(&env);
+
+    env.ledger().with_mut(|li| li.timestamp = 0);
+    let stream_id =
+        client.create_stream(&employer, &worker, &token, &1, &0, &0, &100, &None, &None);
+    let reason = BytesN::from_array(&env, &[1; 32]);
+
+    client.raise_dispute(&stream_id, &worker, &reason);
+    let result = client.try_raise_dispute(&stream_id, &employer, &reason);
+    assert_eq!(result, Err(Ok(QuipayError::AlreadyDisputed)));
+}
+
+#[test]
+fn test_resolve_dispute_errors() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, employer, worker, token, admin) = setup(&env);
+
+    env.ledger().with_mut(|li| li.timestamp = 0);
+    let stream_id =
+        client.create_stream(&employer, &worker, &token, &1, &0, &0, &100, &None, &None);
+
+    // Nothing to resolve before a dispute is raised
+    let result = client.try_resolve_dispute(&stream_id, &admin, &DisputeOutcome::Resume);
+    assert_eq!(result, Err(Ok(QuipayError::NoOpenDispute)));
+
+    client.raise_dispute(&stream_id, &employer, &BytesN::from_array(&env, &[2; 32]));
+    client.resolve_dispute(&stream_id, &admin, &DisputeOutcome::Resume);
+    assert_eq!(
+        client.get_stream(&stream_id).unwrap().status,
+        StreamStatus::Active
+    );
+
+    let result = client.try_resolve_dispute(&stream_id, &admin, &DisputeOutcome::Resume);
+    assert_eq!(result, Err(Ok(QuipayError::DisputeAlreadyResolved)));
+}
+
+#[test]
+fn test_resume_requires_paused_stream() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, employer, worker, token, admin) = setup(&env);
+
+    env.ledger().with_mut(|li| li.timestamp = 0);
+    let stream_id =
+        client.create_stream(&employer, &worker, &token, &1, &0, &0, &100, &None, &None);
+
+    let result = client.try_resume_stream(&stream_id, &employer);
+    assert_eq!(result, Err(Ok(QuipayError::StreamNotPaused)));
+    let result = client.try_admin_resume_stream(&admin, &stream_id);
+    assert_eq!(result, Err(Ok(QuipayError::StreamNotPaused)));
+}
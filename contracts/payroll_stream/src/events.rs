@@ -0,0 +1,149 @@
+//! Events published by PayrollStream, all under the `stream` namespace.
+//!
+//! Topics follow `quipay_common::events`: namespace, name, schema version and
+//! the actor that authorised the call. Stream creations and cancellations
+//! routed through governance or the automation gateway name that contract as
+//! the actor.
+
+use crate::{DisputeOutcome, StreamClaimResult};
+use soroban_sdk::{Address, BytesN, Vec, contracttype};
+
+pub(crate) use quipay_common::events::{emit, emit_from_contract};
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StreamCreated {
+    pub stream_id: u64,
+    pub employer: Address,
+    pub worker: Address,
+    pub token: Address,
+    pub rate: i128,
+    pub start_ts: u64,
+    pub end_ts: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StreamWithdrawn {
+    pub stream_id: u64,
+    pub worker: Address,
+    pub token: Address,
+    /// Amount the vault paid; while it is insolvent the shortfall becomes
+    /// an IOU and is paid as `iou_settled` later.
+    pub amount: i128,
+}
+
+/// A closing payout kept in the vault because the worker is blacklisted.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PayoutHeld {
+    pub stream_id: u64,
+    pub worker: Address,
+    pub token: Address,
+    pub amount: i128,
+}
+
+/// One `batch_claim`, after a `StreamWithdrawn` per stream it paid from.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct BatchClaimed {
+    pub worker: Address,
+    pub total_claimed: i128,
+    pub streams: Vec<StreamClaimResult>,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StreamTransferred {
+    pub stream_id: u64,
+    pub employer: Address,
+    pub old_worker: Address,
+    pub new_worker: Address,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CancelScheduled {
+    pub stream_id: u64,
+    pub employer: Address,
+    pub worker: Address,
+    pub effective_at: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StreamCanceled {
+    pub stream_id: u64,
+    pub employer: Address,
+    pub worker: Address,
+    pub token: Address,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StreamExtended {
+    pub stream_id: u64,
+    pub additional_amount: i128,
+    pub old_end_ts: u64,
+    pub new_end_ts: u64,
+    pub rate: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StreamPaused {
+    pub stream_id: u64,
+    pub paused_at: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StreamResumed {
+    pub stream_id: u64,
+    pub resumed_at: u64,
+    pub paused_duration: u64,
+    pub total_paused_duration: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DisputeRaised {
+    pub stream_id: u64,
+    pub employer: Address,
+    pub worker: Address,
+    pub reason_hash: BytesN<32>,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DisputeResolved {
+    pub stream_id: u64,
+    pub employer: Address,
+    pub worker: Address,
+    pub outcome: DisputeOutcome,
+}
+
+/// An account's legacy stream list moved into the chunked indexes.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct IndexMigrated {
+    pub account: Address,
+    pub as_employer: bool,
+    pub streams: u32,
+}
+
+quipay_common::event_names!("stream" {
+    StreamCreated => "created",
+    StreamWithdrawn => "withdrawn",
+    BatchClaimed => "batch_claimed",
+    StreamTransferred => "transferred",
+    CancelScheduled => "cancel_scheduled",
+    StreamCanceled => "canceled",
+    StreamExtended => "extended",
+    StreamPaused => "paused",
+    StreamResumed => "resumed",
+    DisputeRaised => "dispute_raised",
+    DisputeResolved => "dispute_resolved",
+    PayoutHeld => "payout_held",
+    IndexMigrated => "index_migrated",
+});
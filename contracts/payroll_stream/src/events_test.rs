@@ -0,0 +1,121 @@
+//! Exact topic and payload shapes of the stream events.
+
+use super::*;
+use crate::test::setup;
+use events::{StreamCanceled, StreamCreated, StreamWithdrawn};
+use quipay_common::events::{EVENT_SCHEMA_VERSION, QuipayEvent, topics};
+use soroban_sdk::{
+    Address, Env, IntoVal, Symbol, TryFromVal, Val,
+    testutils::{Address as _, Events as _, Ledger as _},
+};
+
+/// The last event `client` published in the last invocation, decoded as `E`.
+fn last_event<E>(env: &Env, client: &PayrollStreamClient) -> (soroban_sdk::Vec<Val>, E)
+where
+    E: QuipayEvent + TryFromVal<Env, Val>,
+{
+    let (_, topics, data) = env
+        .events()
+        .all()
+        .iter()
+        .filter(|(contract, _, _)| *contract == client.address)
+        .last()
+        .expect("no stream event");
+    let event = E::try_from_val(env, &data).unwrap_or_else(|_| panic!("not a {}", E::NAME));
+    (topics, event)
+}
+
+#[test]
+fn test_stream_lifecycle_event_shapes() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, employer, worker, token, _) = setup(&env);
+    client.set_cancellation_grace_period(&0);
+
+    let stream_id = client.create_stream(
+        &employer, &worker, &token, &1, &0u64, &0u64, &100u64, &None, &None,
+    );
+    let (topics, created) = last_event::<StreamCreated>(&env, &client);
+    assert_eq!(
+        topics,
+        (
+            Symbol::new(&env, "stream"),
+            Symbol::new(&env, "created"),
+            EVENT_SCHEMA_VERSION,
+            employer.clone(),
+        )
+            .into_val(&env)
+    );
+    assert_eq!(
+        created,
+        StreamCreated {
+            stream_id,
+            employer: employer.clone(),
+            worker: worker.clone(),
+            token: token.clone(),
+            rate: 1,
+            start_ts: 0,
+            end_ts: 100,
+        }
+    );
+
+    env.ledger().with_mut(|li| li.timestamp = 40);
+    client.withdraw(&stream_id, &worker);
+    let (topics, withdrawn) = last_event::<StreamWithdrawn>(&env, &client);
+    assert_eq!(topics, topics_for::<StreamWithdrawn>(&env, &worker));
+    assert_eq!(
+        withdrawn,
+        StreamWithdrawn {
+            stream_id,
+            worker: worker.clone(),
+            token: token.clone(),
+            amount: 40,
+        }
+    );
+
+    client.cancel_stream(&stream_id, &employer, &None);
+    let (topics, canceled) = last_event::<StreamCanceled>(&env, &client);
+    assert_eq!(topics, topics_for::<StreamCanceled>(&env, &employer));
+    assert_eq!(
+        canceled,
+        StreamCanceled {
+            stream_id,
+            employer,
+            worker,
+            token,
+        }
+    );
+}
+
+#[test]
+fn test_gateway_created_stream_names_gateway_as_actor() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let (client, employer, worker, token, _) = setup(&env);
+    let gateway = Address::generate(&env);
+    client.set_gateway(&gateway);
+
+    let stream_id = client
+        .create_stream_via_gateway(&employer, &worker, &token, &1, &0u64, &0u64, &100u64, &None);
+    let (topics, created) = last_event::<StreamCreated>(&env, &client);
+    assert_eq!(topics, topics_for::<StreamCreated>(&env, &gateway));
+    assert_eq!(created.stream_id, stream_id);
+    assert_eq!(created.employer, employer);
+
+    // Exactly one creation event is published per stream.
+    let creations = env
+        .events()
+        .all()
+        .iter()
+        .filter(|(contract, topics, _)| {
+            *contract == client.address
+                && topics.get(1).map(|t| Symbol::try_from_val(&env, &t).ok())
+                    == Some(Some(Symbol::new(&env, "created")))
+        })
+        .count();
+    assert_eq!(creations, 1);
+}
+
+fn topics_for<E: QuipayEvent>(env: &Env, actor: &Address) -> soroban_sdk::Vec<Val> {
+    topics::<E>(env, actor).into_val(env)
+}
@@ -8,7 +8,8 @@ use super::*;
 use payroll_vault::{PayrollVault, PayrollVaultClient};
 use soroban_sdk::{
     Address, Env,
-    testutils::{Address as _, Ledger as _},
+    TryFromVal,
+    testutils::{Address as _, Events as _, Ledger as _},
     token,
 };
 
@@ -40,6 +41,7 @@ fn setup_integration(
     let stream_client = PayrollStreamClient::new(env, &stream_id);
 
     vault_client.initialize(&admin);
+    vault_client.set_token_allowlist_enforced(&false);
     stream_client.init(&admin);
     stream_client.set_min_stream_duration(&0u64);
 
@@ -320,3 +322,284 @@ fn test_integration_full_stream_lifecycle_create_withdraw_extend_full_withdraw_c
     assert_eq!(token_client.balance(&worker1), balance_before_cancel_1);
     assert_eq!(token_client.balance(&worker2), balance_before_cancel_2);
 }
+
+#[test]
+fn test_integration_insolvent_vault_pays_pro_rata_and_settles_ious_on_deposit() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+
+    let (stream_client, vault_client, _admin, employer, worker1, token_id, depositor) =
+        setup_integration(&env);
+    let token_client = token::Client::new(&env, &token_id);
+    let token_admin_client = token::StellarAssetClient::new(&env, &token_id);
+    let worker2 = Address::generate(&env);
+
+    env.ledger().with_mut(|li| li.timestamp = 0);
+    let stream_id_1 = stream_client.create_stream(
+        &employer, &worker1, &token_id, &50, &0u64, &0u64, &100u64, &None, &None,
+    );
+    let stream_id_2 = stream_client.create_stream(
+        &employer, &worker2, &token_id, &50, &0u64, &0u64, &100u64, &None, &None,
+    );
+    assert_eq!(vault_client.get_total_liability(&token_id), 10_000);
+
+    // Simulate losing half the backing funds (e.g. a clawback).
+    env.as_contract(&vault_client.address, || {
+        env.storage().persistent().set(
+            &payroll_vault::StateKey::TreasuryBalance(token_id.clone()),
+            &5_000i128,
+        );
+    });
+    assert!(vault_client.is_insolvent(&token_id));
+
+    // Both workers are owed 5_000; each receives a pro-rata share instead of the
+    // first withdrawer draining the vault.
+    env.ledger().with_mut(|li| li.timestamp = 100);
+    let paid_1 = stream_client.withdraw(&stream_id_1, &worker1);
+    let (_, _, data) = env
+        .events()
+        .all()
+        .iter()
+        .filter(|(contract, _, _)| *contract == stream_client.address)
+        .last()
+        .unwrap();
+    let withdrawn = events::StreamWithdrawn::try_from_val(&env, &data).unwrap();
+    assert_eq!(withdrawn.amount, 2_500);
+    let paid_2 = stream_client.withdraw(&stream_id_2, &worker2);
+    assert_eq!(paid_1, 2_500);
+    assert_eq!(paid_2, 2_500);
+    assert_eq!(stream_client.get_stream_iou(&stream_id_1), 2_500);
+    assert_eq!(stream_client.get_stream_iou(&stream_id_2), 2_500);
+
+    // Streams are fully credited; the shortfall lives on as vault IOUs.
+    let stream1 = stream_client.get_stream(&stream_id_1).unwrap();
+    assert_eq!(stream1.withdrawn_amount, 5_000);
+    assert_eq!(stream1.status, StreamStatus::Completed);
+    assert_eq!(vault_client.get_total_liability(&token_id), 5_000);
+
+    // A fresh deposit settles the IOUs in creation order.
+    token_admin_client.mint(&depositor, &5_000);
+    vault_client.deposit(&depositor, &token_id, &5_000);
+    assert_eq!(token_client.balance(&worker1), 5_000);
+    assert_eq!(token_client.balance(&worker2), 5_000);
+    assert_eq!(stream_client.get_stream_iou(&stream_id_1), 0);
+    assert_eq!(stream_client.get_stream_iou(&stream_id_2), 0);
+    assert_eq!(vault_client.get_total_liability(&token_id), 0);
+}
+
+#[test]
+fn test_integration_top_up_mandate_pulls_runway_shortfall() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+
+    let (stream_client, vault_client, _admin, employer, worker, token_id, _depositor) =
+        setup_integration(&env);
+    let token_client = token::Client::new(&env, &token_id);
+    let token_admin_client = token::StellarAssetClient::new(&env, &token_id);
+
+    // The 10_000 deposit funds a 200s stream paying 50/s.
+    env.ledger().with_mut(|li| li.timestamp = 0);
+    let stream_id = stream_client.create_stream(
+        &employer, &worker, &token_id, &50, &0u64, &0u64, &200u64, &None, &None,
+    );
+    assert_eq!(
+        stream_client.get_employer_obligations(&employer, &token_id, &50u64),
+        (10_000, 0, 2_500)
+    );
+
+    // Keep 50s of payroll plus 500 funded beyond what workers can claim.
+    token_admin_client.mint(&employer, &10_000);
+    token_client.approve(&employer, &vault_client.address, &10_000, &1_000);
+    let mandate_id = vault_client.create_top_up_mandate(
+        &employer, &employer, &token_id, &50u64, &500, &10_000, &(7 * 24 * 60 * 60),
+    );
+    let keeper = Address::generate(&env);
+    assert_eq!(vault_client.execute_top_up(&keeper, &mandate_id), 0);
+
+    // With 20s left the stream's funding no longer covers the window.
+    env.ledger().with_mut(|li| li.timestamp = 180);
+    stream_client.withdraw(&stream_id, &worker);
+    assert_eq!(vault_client.get_top_up_due(&mandate_id), 2_000);
+    assert_eq!(vault_client.execute_top_up(&keeper, &mandate_id), 2_000);
+    assert_eq!(vault_client.get_treasury_balance(&token_id), 3_000);
+    assert_eq!(token_client.balance(&employer), 8_000);
+    assert_eq!(vault_client.execute_top_up(&keeper, &mandate_id), 0);
+}
+
+#[test]
+fn test_integration_gateway_agent_cancels_only_for_authorising_employer() {
+    use automation_gateway::{AutomationGateway, AutomationGatewayClient, Permission};
+
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+
+    let (stream_client, _vault_client, admin, employer, worker, token_id, _depositor) =
+        setup_integration(&env);
+    stream_client.set_cancellation_grace_period(&0u64);
+
+    let gateway = AutomationGatewayClient::new(&env, &env.register(AutomationGateway, ()));
+    gateway.init(&admin);
+    gateway.set_payroll_stream(&stream_client.address);
+    stream_client.set_gateway(&gateway.address);
+
+    let agent = Address::generate(&env);
+    gateway.register_agent(
+        &employer,
+        &agent,
+        &soroban_sdk::vec![&env, Permission::CancelStream],
+    );
+
+    env.ledger().with_mut(|li| li.timestamp = 0);
+    let other_employer = Address::generate(&env);
+    let own_stream = stream_client.create_stream(
+        &employer, &worker, &token_id, &10, &0u64, &0u64, &100u64, &None, &None,
+    );
+    let other_stream = stream_client.create_stream(
+        &other_employer, &worker, &token_id, &10, &0u64, &0u64, &100u64, &None, &None,
+    );
+
+    // The agent was not authorised by the other employer.
+    let result = gateway.try_agent_cancel_stream(&agent, &other_stream, &other_employer, &0, &None);
+    assert_eq!(result, Err(Ok(QuipayError::InsufficientPermissions)));
+
+    // Agents cannot skip the gateway's nonce and journal by calling the
+    // stream contract directly, whichever gateway they name.
+    let result =
+        stream_client.try_cancel_stream(&own_stream, &agent, &Some(gateway.address.clone()));
+    assert_eq!(result, Err(Ok(QuipayError::Unauthorized)));
+
+    gateway.agent_cancel_stream(&agent, &own_stream, &employer, &0, &None);
+    assert_eq!(
+        stream_client.get_stream(&own_stream).unwrap().status,
+        StreamStatus::Canceled
+    );
+    assert_eq!(gateway.get_agent_nonce(&agent), 1);
+}
+
+#[test]
+fn test_integration_protocol_fees_on_creation_and_payouts() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+
+    let (stream_client, vault_client, admin, employer, worker, token_id, _depositor) =
+        setup_integration(&env);
+    let token_read = token::Client::new(&env, &token_id);
+
+    // Creation basis: 1% of the stream volume leaves the treasury.
+    env.ledger().with_mut(|li| li.timestamp = 0);
+    vault_client.set_protocol_fee(&admin, &100, &payroll_vault::FeeBasis::Creation);
+    stream_client.create_stream(
+        &employer, &worker, &token_id, &10, &0u64, &0u64, &100u64, &None, &None,
+    );
+    assert_eq!(vault_client.get_accrued_fees(&token_id), 10);
+    assert_eq!(vault_client.get_treasury_balance(&token_id), 9_990);
+
+    // Payout basis: 2% of each payout, including batch claims.
+    vault_client.set_protocol_fee(&admin, &200, &payroll_vault::FeeBasis::Payout);
+    let a = stream_client.create_stream(
+        &employer, &worker, &token_id, &10, &0u64, &0u64, &100u64, &None, &None,
+    );
+    stream_client.create_stream(
+        &employer, &worker, &token_id, &10, &0u64, &0u64, &100u64, &None, &None,
+    );
+
+    env.ledger().with_mut(|li| li.timestamp = 50);
+    stream_client.withdraw(&a, &worker);
+    assert_eq!(token_read.balance(&worker), 490);
+
+    env.ledger().with_mut(|li| li.timestamp = 100);
+    stream_client.batch_claim(&worker);
+    // Stream 1 pays 1_000 fee-free, streams 2 and 3 pay 500 and 1_000 less 2%.
+    assert_eq!(token_read.balance(&worker), 490 + 1_000 + 490 + 980);
+    assert_eq!(vault_client.get_accrued_fees(&token_id), 10 + 10 + 10 + 20);
+    assert_eq!(vault_client.get_total_liability(&token_id), 0);
+}
+
+#[test]
+fn test_integration_dispute_refunds_are_fee_free() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+
+    let (stream_client, vault_client, admin, employer, worker, token_id, _depositor) =
+        setup_integration(&env);
+    let token_read = token::Client::new(&env, &token_id);
+    let reason = soroban_sdk::BytesN::from_array(&env, &[1; 32]);
+
+    env.ledger().with_mut(|li| li.timestamp = 0);
+    vault_client.set_protocol_fee(&admin, &200, &payroll_vault::FeeBasis::Payout);
+    let partial = stream_client.create_stream(
+        &employer, &worker, &token_id, &10, &0u64, &0u64, &100u64, &None, &None,
+    );
+    let refund = stream_client.create_stream(
+        &employer, &worker, &token_id, &10, &0u64, &0u64, &100u64, &None, &None,
+    );
+
+    env.ledger().with_mut(|li| li.timestamp = 30);
+    stream_client.raise_dispute(&partial, &worker, &reason);
+    stream_client.resolve_dispute(&partial, &admin, &DisputeOutcome::CancelWithPartialPayout);
+    // The worker's 300 pays the 2% fee; the employer's 700 comes back whole.
+    assert_eq!(token_read.balance(&worker), 294);
+    assert_eq!(token_read.balance(&employer), 700);
+    assert_eq!(vault_client.get_accrued_fees(&token_id), 6);
+    assert_eq!(vault_client.get_total_liability(&token_id), 1_000);
+
+    stream_client.raise_dispute(&refund, &employer, &reason);
+    stream_client.resolve_dispute(&refund, &admin, &DisputeOutcome::CancelWithRefund);
+    assert_eq!(token_read.balance(&employer), 1_700);
+    assert_eq!(vault_client.get_accrued_fees(&token_id), 6);
+    assert_eq!(vault_client.get_total_liability(&token_id), 0);
+    assert_eq!(vault_client.get_treasury_balance(&token_id), 8_000);
+}
+
+#[test]
+fn test_integration_protective_drain_keeps_vested_wages() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+
+    let (stream_client, vault_client, _admin, employer, worker, token_id, depositor) =
+        setup_integration(&env);
+    let token_read = token::Client::new(&env, &token_id);
+    token::StellarAssetClient::new(&env, &token_id).mint(&depositor, &190_000);
+    vault_client.deposit(&depositor, &token_id, &190_000);
+
+    env.ledger().with_mut(|li| li.timestamp = 0);
+    let long = stream_client.create_stream(
+        &employer, &worker, &token_id, &1, &0u64, &0u64, &172_800u64, &None, &None,
+    );
+    let other_worker = Address::generate(&env);
+    let short = stream_client.create_stream(
+        &employer, &other_worker, &token_id, &1, &0u64, &0u64, &10_000u64, &None, &None,
+    );
+
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+    stream_client.withdraw(&long, &worker);
+    let recipient = Address::generate(&env);
+    let targets = soroban_sdk::vec![
+        &env,
+        payroll_vault::DrainTarget { token: token_id.clone(), amount: 0 },
+    ];
+    vault_client.propose_partial_drain(&recipient, &targets, &true);
+
+    // Wages vested by the end of the timelock are tallied one stream at a time.
+    let execute_after = vault_client.get_pending_drain().unwrap().execute_after;
+    env.ledger().with_mut(|li| li.timestamp = execute_after);
+    assert!(!vault_client.tally_drain_reserves(&Some(1)));
+    assert!(vault_client.tally_drain_reserves(&Some(1)));
+    let owed_long = execute_after as i128 - 1_000;
+    assert_eq!(
+        vault_client.get_pending_drain().unwrap().reserves.get(token_id.clone()),
+        Some(owed_long + 10_000)
+    );
+
+    vault_client.execute_emergency_drain();
+    assert_eq!(token_read.balance(&recipient), 199_000 - owed_long - 10_000);
+    assert_eq!(vault_client.get_total_liability(&token_id), owed_long + 10_000);
+
+    // Workers still collect everything they had earned.
+    stream_client.withdraw(&long, &worker);
+    stream_client.withdraw(&short, &other_worker);
+    assert_eq!(token_read.balance(&worker), 1_000 + owed_long);
+    assert_eq!(token_read.balance(&other_worker), 10_000);
+    assert_eq!(vault_client.get_total_liability(&token_id), 0);
+    assert_eq!(vault_client.get_treasury_balance(&token_id), 0);
+}
@@ -1,8 +1,16 @@
 #![no_std]
 use core::convert::TryFrom;
-use quipay_common::{QuipayError, require};
+use events::{
+    BatchClaimed, CancelScheduled, StreamCanceled, StreamCreated, StreamTransferred,
+    StreamWithdrawn,
+};
+use quipay_common::{
+    AdminKeys, AdminStorage, QuipayError, Role, admin, rbac, require, ttl, upgrade,
+};
+pub use quipay_common::{PendingUpgrade, VersionInfo};
+pub use registry::EmployerAction;
 use soroban_sdk::{
-    Address, BytesN, Env, IntoVal, Symbol, Vec, contract, contractimpl, contracttype,
+    Address, BytesN, Env, IntoVal, Map, Symbol, Vec, contract, contractimpl, contracttype,
 };
 
 
@@ -25,7 +33,6 @@ pub enum DataKey {
     Vault,
     Gateway,
     DaoGovernance,           // Authorized DAO governance contract for gated stream creation
-    PendingUpgrade,          // (wasm_hash, execute_after_timestamp)
     EarlyCancelFeeBps,       // Basis points for early cancellation fee (max 1000 = 10%)
     WithdrawalCooldown,      // Minimum seconds a worker must wait between withdrawals
     LastWithdrawal(Address), // Timestamp of last successful withdrawal per worker
@@ -36,16 +43,16 @@ pub enum DataKey {
     EmployerStreamLimit(Address), // Per-employer maximum active stream override
     MinStreamDuration,       // Configurable minimum stream duration in seconds
     Receipt,                 // PayrollReceipt contract address (optional)
+    Registry,                // WorkforceRegistry contract address (optional)
+    MinKycLevel,             // Minimum registry attestation level for new streams
+    RequireVerifiedEmployer, // Whether new streams need a KYB-verified employer
 }
 
-#[contracttype]
-#[derive(Clone, Debug, PartialEq)]
-pub struct PendingUpgrade {
-    pub wasm_hash: soroban_sdk::BytesN<32>,
-    pub execute_after: u64,
-    pub proposed_at: u64,
-    pub proposed_by: Address,
-}
+const ADMIN_KEYS: AdminKeys<DataKey> = AdminKeys {
+    storage: AdminStorage::Instance,
+    admin: DataKey::Admin,
+    pending: DataKey::PendingAdmin,
+};
 
 #[contracttype]
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
@@ -77,8 +84,14 @@ pub enum DisputeOutcome {
 #[derive(Clone)]
 pub enum StreamKey {
     Stream(u64),
-    EmployerStreams(Address),
-    WorkerStreams(Address),
+    EmployerStreams(Address), // Legacy single-vector index, see `stream_index`
+    WorkerStreams(Address),   // Legacy single-vector index, see `stream_index`
+    EmployerStreamsCursor(Address), // Ids of the legacy list already migrated
+    WorkerStreamsCursor(Address),   // Ids of the legacy list already migrated
+    IndexHead(stream_index::StreamIndex),
+    IndexChunk(stream_index::StreamIndex, u32),
+    StreamSlots(u64),
+    HeldPayout(u64), // Worker's payout held while they are blacklisted
 }
 
 #[contracttype]
@@ -143,6 +156,20 @@ pub struct StreamParams {
     pub speed_curve: MaybeSpeedCurve,
 }
 
+/// Stream parameters for `batch_create_preferred_streams`; the token
+/// is the worker's preferred token from the registry.
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct PreferredStreamParams {
+    pub worker: Address,
+    pub rate: i128,
+    pub cliff_ts: u64,
+    pub start_ts: u64,
+    pub end_ts: u64,
+    pub metadata_hash: Option<BytesN<32>>,
+    pub speed_curve: MaybeSpeedCurve,
+}
+
 #[contracttype]
 #[derive(Clone, Debug)]
 pub struct WithdrawResult {
@@ -211,25 +238,9 @@ const DEFAULT_MAX_STREAMS_PER_EMPLOYER: u32 = 500;
 
 const DEFAULT_MIN_STREAM_DURATION: u64 = 3600;
 
-// Storage entries (persistent) are automatically archived after their TTL runs out
-// unless we explicitly extend TTL. Long-running streams can be left untouched for
-// longer than the default TTL, so we bump TTL on each mutation path.
-//
-// These values are expressed in ledgers (Soroban storage TTL units).
-const STORAGE_TTL_THRESHOLD_LEDGER: u32 = 1_000_000;
-const STORAGE_TTL_EXTEND_TO_LEDGER: u32 = 1_000_000;
-
-// 48 hours in seconds for timelock
-const TIMELOCK_DURATION: u64 = 48 * 60 * 60;
-
 // Maximum early cancellation fee: 1000 basis points = 10%
 const MAX_EARLY_CANCEL_FEE_BPS: u32 = 1000;
 
-// Event symbols for timelock
-const UPGRADE_PROPOSED: soroban_sdk::Symbol = soroban_sdk::symbol_short!("up_prop");
-const UPGRADE_EXECUTED: soroban_sdk::Symbol = soroban_sdk::symbol_short!("up_exec");
-const UPGRADE_CANCELED: soroban_sdk::Symbol = soroban_sdk::symbol_short!("up_cancel");
-
 #[contract]
 pub struct PayrollStream;
 
@@ -246,16 +257,13 @@ impl PayrollStream {
         env.storage()
             .instance()
             .set(&DataKey::RetentionSecs, &DEFAULT_RETENTION_SECS);
+        upgrade::set_initial_version(&env, (1, 0, 0));
         Ok(())
     }
 
-    pub fn set_paused(env: Env, paused: bool) -> Result<(), QuipayError> {
-        let admin: Address = env
-            .storage()
-            .instance()
-            .get(&DataKey::Admin)
-            .ok_or(QuipayError::NotInitialized)?;
-        admin.require_auth();
+    /// Pause or unpause the protocol. Requires the admin or a `Pauser`.
+    pub fn set_paused(env: Env, caller: Address, paused: bool) -> Result<(), QuipayError> {
+        Self::require_role(&env, Role::Pauser, &caller)?;
         env.storage().instance().set(&DataKey::Paused, &paused);
         Ok(())
     }
@@ -281,14 +289,9 @@ impl PayrollStream {
     }
 
     /// Set early cancellation fee as basis points (max 1000 = 10%)
-    /// Only admin can call this function
-    pub fn set_early_cancel_fee(env: Env, fee_bps: u32) -> Result<(), QuipayError> {
-        let admin: Address = env
-            .storage()
-            .instance()
-            .get(&DataKey::Admin)
-            .ok_or(QuipayError::NotInitialized)?;
-        admin.require_auth();
+    /// Requires the admin or a `FeeSetter`.
+    pub fn set_early_cancel_fee(env: Env, caller: Address, fee_bps: u32) -> Result<(), QuipayError> {
+        Self::require_role(&env, Role::FeeSetter, &caller)?;
 
         if fee_bps > MAX_EARLY_CANCEL_FEE_BPS {
             return Err(QuipayError::FeeTooHigh);
@@ -491,56 +494,51 @@ impl PayrollStream {
     }
 
     pub fn get_admin(env: Env) -> Result<Address, QuipayError> {
-        env.storage()
-            .instance()
-            .get(&DataKey::Admin)
-            .ok_or(QuipayError::NotInitialized)
+        admin::get_admin(&env, &ADMIN_KEYS)
     }
 
     pub fn get_pending_admin(env: Env) -> Option<Address> {
-        env.storage().instance().get(&DataKey::PendingAdmin)
+        admin::get_pending_admin(&env, &ADMIN_KEYS)
     }
 
     pub fn propose_admin(env: Env, new_admin: Address) -> Result<(), QuipayError> {
-        let admin: Address = env
-            .storage()
-            .instance()
-            .get(&DataKey::Admin)
-            .ok_or(QuipayError::NotInitialized)?;
-        admin.require_auth();
-
-        env.storage()
-            .instance()
-            .set(&DataKey::PendingAdmin, &new_admin);
-        Ok(())
+        admin::propose_admin(&env, &ADMIN_KEYS, &new_admin)
     }
 
     pub fn accept_admin(env: Env) -> Result<(), QuipayError> {
-        let pending: Address = env
-            .storage()
-            .instance()
-            .get(&DataKey::PendingAdmin)
-            .ok_or(QuipayError::NoPendingAdmin)?;
-        pending.require_auth();
-
-        env.storage().instance().set(&DataKey::Admin, &pending);
-        env.storage().instance().remove(&DataKey::PendingAdmin);
+        admin::accept_admin(&env, &ADMIN_KEYS)?;
         Ok(())
     }
 
     pub fn transfer_admin(env: Env, new_admin: Address) -> Result<(), QuipayError> {
-        let admin: Address = env
-            .storage()
-            .instance()
-            .get(&DataKey::Admin)
-            .ok_or(QuipayError::NotInitialized)?;
+        admin::transfer_admin(&env, &ADMIN_KEYS, &new_admin)
+    }
+
+    /// Grant a role to `account` (admin only).
+    pub fn grant_role(env: Env, role: Role, account: Address) -> Result<(), QuipayError> {
+        let admin = Self::get_admin(env.clone())?;
         admin.require_auth();
+        rbac::grant_role(&env, &admin, role, &account);
+        Ok(())
+    }
 
-        env.storage().instance().set(&DataKey::Admin, &new_admin);
-        env.storage().instance().remove(&DataKey::PendingAdmin);
+    /// Revoke a role from `account` (admin only).
+    pub fn revoke_role(env: Env, role: Role, account: Address) -> Result<(), QuipayError> {
+        let admin = Self::get_admin(env.clone())?;
+        admin.require_auth();
+        rbac::revoke_role(&env, &admin, role, &account);
         Ok(())
     }
 
+    /// Give up a role held by `account`.
+    pub fn renounce_role(env: Env, account: Address, role: Role) {
+        rbac::renounce_role(&env, role, &account);
+    }
+
+    pub fn has_role(env: Env, role: Role, account: Address) -> bool {
+        rbac::has_role(&env, role, &account)
+    }
+
     /// Create a new payroll stream.
     ///
     /// ### Time Granularity
@@ -565,6 +563,7 @@ impl PayrollStream {
         // Call the internal create stream logic
         let stream_id = Self::create_stream_internal(
             env.clone(),
+            &employer,
             employer.clone(),
             worker.clone(),
             token.clone(),
@@ -576,15 +575,42 @@ impl PayrollStream {
             speed_curve,
         )?;
 
-        env.events().publish(
-            (
-                Symbol::new(&env, "stream"),
-                Symbol::new(&env, "created"),
-                worker,
-                employer,
-            ),
-            (stream_id, token, rate, start_ts, end_ts),
-        );
+        Ok(stream_id)
+    }
+
+    /// Create a stream on behalf of `employer`. `delegate` must be one of the
+    /// employer's registry admins or a payroll manager whose role allows
+    /// creating streams.
+    pub fn create_stream_as_delegate(
+        env: Env,
+        delegate: Address,
+        employer: Address,
+        worker: Address,
+        token: Address,
+        rate: i128,
+        cliff_ts: u64,
+        start_ts: u64,
+        end_ts: u64,
+        metadata_hash: Option<BytesN<32>>,
+        speed_curve: Option<stream_curve::SpeedCurve>,
+    ) -> Result<u64, QuipayError> {
+        Self::require_not_paused(&env)?;
+        delegate.require_auth();
+        Self::require_employer_delegate(&env, &employer, &delegate, EmployerAction::CreateStream)?;
+
+        let stream_id = Self::create_stream_internal(
+            env.clone(),
+            &delegate,
+            employer.clone(),
+            worker.clone(),
+            token.clone(),
+            rate,
+            cliff_ts,
+            start_ts,
+            end_ts,
+            metadata_hash,
+            speed_curve,
+        )?;
 
         Ok(stream_id)
     }
@@ -622,6 +648,7 @@ impl PayrollStream {
 
             let stream_id = Self::create_stream_internal(
                 env.clone(),
+                &authorized_employer,
                 param.employer.clone(),
                 param.worker.clone(),
                 param.token.clone(),
@@ -636,22 +663,6 @@ impl PayrollStream {
                 },
             )?;
 
-            env.events().publish(
-                (
-                    Symbol::new(&env, "stream"),
-                    Symbol::new(&env, "created"),
-                    param.worker,
-                    param.employer,
-                ),
-                (
-                    stream_id,
-                    param.token,
-                    param.rate,
-                    param.start_ts,
-                    param.end_ts,
-                ),
-            );
-
             let stream_id = u32::try_from(stream_id).map_err(|_| QuipayError::Overflow)?;
             stream_ids.push_back(stream_id);
             index += 1;
@@ -685,8 +696,9 @@ impl PayrollStream {
         }
 
         if stream.status == StreamStatus::Disputed {
-            return Err(QuipayError::StreamNotFound);
+            return Err(QuipayError::StreamDisputed);
         }
+        Self::require_not_blacklisted(&env, &worker)?;
 
         let now = env.ledger().timestamp();
 
@@ -724,9 +736,12 @@ impl PayrollStream {
             .get(&DataKey::Vault)
             .ok_or(QuipayError::NotInitialized)?;
 
-        Self::call_vault_payout(
+        // While the vault is insolvent only a pro-rata share is transferred; the
+        // shortfall is held by the vault as an IOU for this stream.
+        let paid = Self::call_vault_stream_payout(
             &env,
             &vault,
+            stream_id,
             worker.clone(),
             stream.token.clone(),
             available,
@@ -739,7 +754,7 @@ impl PayrollStream {
         stream.last_withdrawal_ts = now;
 
         if stream.withdrawn_amount >= stream.total_amount {
-            Self::close_stream_internal(&mut stream, now, StreamStatus::Completed);
+            Self::close_stream_internal(&env, stream_id, &mut stream, now, StreamStatus::Completed);
         }
 
         env.storage().persistent().set(&key, &stream);
@@ -749,22 +764,24 @@ impl PayrollStream {
             .persistent()
             .set(&DataKey::LastWithdrawal(worker.clone()), &now);
 
-        env.events().publish(
-            (
-                Symbol::new(&env, "stream"),
-                Symbol::new(&env, "withdrawn"),
+        events::emit(
+            &env,
+            &worker,
+            StreamWithdrawn {
                 stream_id,
-                worker.clone(),
-            ),
-            (available, stream.token.clone()),
+                worker: worker.clone(),
+                token: stream.token.clone(),
+                amount: paid,
+            },
         );
 
         // Mint receipt if stream just completed
         if stream.status == StreamStatus::Completed {
             Self::try_mint_receipt(&env, &stream, stream_id, 0u32); // 0 = Completed
+            Self::registry_stream_closed(&env, &stream.employer, &worker);
         }
 
-        Ok(available)
+        Ok(paid)
     }
 
     /// NOTE: This function is atomic. If any single payout fails, the entire batch reverts.
@@ -776,6 +793,7 @@ impl PayrollStream {
     ) -> Result<Vec<WithdrawResult>, QuipayError> {
         Self::require_not_paused(&env)?;
         caller.require_auth();
+        Self::require_not_blacklisted(&env, &caller)?;
 
         let now = env.ledger().timestamp();
         let vault: Address = env
@@ -882,9 +900,10 @@ impl PayrollStream {
                     let mut stream = candidate.stream;
                     let available = candidate.amount;
 
-                    Self::call_vault_payout(
+                    let paid = Self::call_vault_stream_payout(
                         &env,
                         &vault,
+                        candidate.stream_id,
                         caller.clone(),
                         stream.token.clone(),
                         available,
@@ -897,7 +916,13 @@ impl PayrollStream {
                     stream.last_withdrawal_ts = now;
 
                     if stream.withdrawn_amount >= stream.total_amount {
-                        Self::close_stream_internal(&mut stream, now, StreamStatus::Completed);
+                        Self::close_stream_internal(
+                            &env,
+                            candidate.stream_id,
+                            &mut stream,
+                            now,
+                            StreamStatus::Completed,
+                        );
                     }
 
                     env.storage().persistent().set(&key, &stream);
@@ -909,24 +934,26 @@ impl PayrollStream {
                         .persistent()
                         .set(&DataKey::LastWithdrawal(caller.clone()), &now);
 
-                    env.events().publish(
-                        (
-                            Symbol::new(&env, "stream"),
-                            Symbol::new(&env, "withdrawn"),
-                            candidate.stream_id,
-                            caller.clone(),
-                        ),
-                        (available, stream.token.clone()),
+                    events::emit(
+                        &env,
+                        &caller,
+                        StreamWithdrawn {
+                            stream_id: candidate.stream_id,
+                            worker: caller.clone(),
+                            token: stream.token.clone(),
+                            amount: paid,
+                        },
                     );
 
                     // Mint receipt if stream just completed
                     if stream.status == StreamStatus::Completed {
                         Self::try_mint_receipt(&env, &stream, candidate.stream_id, 0u32);
+                        Self::registry_stream_closed(&env, &stream.employer, &caller);
                     }
 
                     WithdrawResult {
                         stream_id: candidate.stream_id,
-                        amount: available,
+                        amount: paid,
                         success: true,
                     }
                 }
@@ -964,6 +991,7 @@ impl PayrollStream {
     pub fn batch_claim(env: Env, worker: Address) -> Result<BatchClaimResult, QuipayError> {
         Self::require_not_paused(&env)?;
         worker.require_auth();
+        Self::require_not_blacklisted(&env, &worker)?;
 
         let now = env.ledger().timestamp();
 
@@ -990,20 +1018,19 @@ impl PayrollStream {
             .get(&DataKey::Vault)
             .ok_or(QuipayError::NotInitialized)?;
 
-        // Load all stream IDs for this worker from the persistent index.
-        let all_ids: Vec<u64> = env
-            .storage()
-            .persistent()
-            .get(&StreamKey::WorkerStreams(worker.clone()))
-            .unwrap_or_else(|| Vec::new(&env));
+        // Load the worker's active stream IDs from the persistent index.
+        Self::ensure_index_migrated(&env, &worker);
+        let all_ids = Self::index_ids(
+            &env,
+            &stream_index::StreamIndex::WorkerActive(worker.clone()),
+        );
 
         // ── Phase 1: scan streams, collect candidates ─────────────────────
         // ── Phase 1: scan streams, collect candidates ─────────────────────
-        // Per-token totals are tracked with parallel vecs (token_keys / token_amounts)
-        // to stay no_std compatible without needing Map.
+        // Distinct tokens are tracked in a Vec to stay no_std compatible
+        // without needing Map.
         let mut stream_results: Vec<StreamClaimResult> = Vec::new(&env);
         let mut token_keys: Vec<Address> = Vec::new(&env);
-        let mut token_amounts: Vec<i128> = Vec::new(&env);
 
         // Mutable snapshots we'll write back in phase 2.
         // store as (stream_id, updated_stream) pairs; we reuse the same ordering.
@@ -1056,26 +1083,17 @@ impl PayrollStream {
                 .ok_or(QuipayError::Overflow)?;
             stream.last_withdrawal_ts = now;
             if stream.withdrawn_amount >= stream.total_amount {
-                Self::close_stream_internal(&mut stream, now, StreamStatus::Completed);
+                Self::close_stream_internal(
+                    &env,
+                    stream_id,
+                    &mut stream,
+                    now,
+                    StreamStatus::Completed,
+                );
             }
 
-            // Accumulate per-token totals.
-            let mut found_token = false;
-            let mut tidx: u32 = 0;
-            while tidx < token_keys.len() {
-                if let (Some(k), Some(v)) = (token_keys.get(tidx), token_amounts.get(tidx)) {
-                    if k == stream.token {
-                        let new_total = v.checked_add(available).ok_or(QuipayError::Overflow)?;
-                        token_amounts.set(tidx, new_total);
-                        found_token = true;
-                        break;
-                    }
-                }
-                tidx += 1;
-            }
-            if !found_token {
+            if !token_keys.contains(&stream.token) {
                 token_keys.push_back(stream.token.clone());
-                token_amounts.push_back(available);
             }
 
             total_claimed = total_claimed
@@ -1104,10 +1122,51 @@ impl PayrollStream {
         }
 
         // ── Phase 2: issue one vault payout per token ─────────────────────
+        // An insolvent token is paid out per stream instead, so that each
+        // pro-rata shortfall is recorded against the stream it belongs to.
         let mut tidx: u32 = 0;
         while tidx < token_keys.len() {
-            if let (Some(token), Some(amount)) = (token_keys.get(tidx), token_amounts.get(tidx)) {
-                Self::call_vault_payout(&env, &vault, worker.clone(), token, amount);
+            if let Some(token) = token_keys.get(tidx) {
+                if Self::call_vault_is_insolvent(&env, &vault, &token) {
+                    let mut sidx: u32 = 0;
+                    while sidx < payable_stream_ids.len() {
+                        if let (Some(stream_id), Some(stream), Some(stream_amount)) = (
+                            payable_stream_ids.get(sidx),
+                            payable_streams.get(sidx),
+                            payable_amounts.get(sidx),
+                        ) {
+                            if stream.token == token {
+                                Self::call_vault_stream_payout(
+                                    &env,
+                                    &vault,
+                                    stream_id,
+                                    worker.clone(),
+                                    token.clone(),
+                                    stream_amount,
+                                );
+                            }
+                        }
+                        sidx += 1;
+                    }
+                } else {
+                    let mut ids: Vec<u64> = Vec::new(&env);
+                    let mut amounts: Vec<i128> = Vec::new(&env);
+                    let mut sidx: u32 = 0;
+                    while sidx < payable_stream_ids.len() {
+                        if let (Some(stream_id), Some(stream), Some(stream_amount)) = (
+                            payable_stream_ids.get(sidx),
+                            payable_streams.get(sidx),
+                            payable_amounts.get(sidx),
+                        ) {
+                            if stream.token == token {
+                                ids.push_back(stream_id);
+                                amounts.push_back(stream_amount);
+                            }
+                        }
+                        sidx += 1;
+                    }
+                    Self::call_vault_payout(&env, &vault, worker.clone(), token, ids, amounts);
+                }
             }
             tidx += 1;
         }
@@ -1123,16 +1182,20 @@ impl PayrollStream {
                 let key = StreamKey::Stream(stream_id);
                 env.storage().persistent().set(&key, &stream);
                 Self::bump_stream_storage_ttl(&env, stream_id, &worker);
+                if stream.status == StreamStatus::Completed {
+                    Self::registry_stream_closed(&env, &stream.employer, &worker);
+                }
 
                 // Per-stream withdrawn event (mirrors single withdraw — indexers see it).
-                env.events().publish(
-                    (
-                        Symbol::new(&env, "stream"),
-                        Symbol::new(&env, "withdrawn"),
+                events::emit(
+                    &env,
+                    &worker,
+                    StreamWithdrawn {
                         stream_id,
-                        worker.clone(),
-                    ),
-                    (amount, stream.token.clone()),
+                        worker: worker.clone(),
+                        token: stream.token.clone(),
+                        amount,
+                    },
                 );
             }
             sidx += 1;
@@ -1144,13 +1207,14 @@ impl PayrollStream {
             .set(&DataKey::LastWithdrawal(worker.clone()), &now);
 
         // ── Single BatchClaimed event with full breakdown ──────────────────
-        env.events().publish(
-            (
-                Symbol::new(&env, "stream"),
-                Symbol::new(&env, "batch_claimed"),
-                worker.clone(),
-            ),
-            (total_claimed, stream_results.clone()),
+        events::emit(
+            &env,
+            &worker,
+            BatchClaimed {
+                worker: worker.clone(),
+                total_claimed,
+                streams: stream_results.clone(),
+            },
         );
 
         Ok(BatchClaimResult {
@@ -1190,18 +1254,10 @@ impl PayrollStream {
         if old_recipient == new_recipient {
             return Ok(());
         }
+        Self::require_eligible_worker(&env, &new_recipient)?;
 
         // Update worker indices: remove from old, add to new
-        Self::remove_from_index(&env, StreamKey::WorkerStreams(old_recipient.clone()), stream_id);
-
-        let wrk_key = StreamKey::WorkerStreams(new_recipient.clone());
-        let mut wrk_ids: Vec<u64> = env
-            .storage()
-            .persistent()
-            .get(&wrk_key)
-            .unwrap_or_else(|| Vec::new(&env));
-        wrk_ids.push_back(stream_id);
-        env.storage().persistent().set(&wrk_key, &wrk_ids);
+        Self::index_stream_transferred(&env, stream_id, &old_recipient, &new_recipient);
 
         // Update the stream recipient
         stream.worker = new_recipient.clone();
@@ -1210,18 +1266,26 @@ impl PayrollStream {
         // Ensure the new worker's index and the stream state have their TTL extended
         Self::bump_stream_storage_ttl(&env, stream_id, &new_recipient);
 
-        env.events().publish(
-            (
-                Symbol::new(&env, "stream"),
-                Symbol::new(&env, "transferred"),
+        Self::registry_stream_opened(&env, &employer, &new_recipient);
+        Self::registry_stream_closed(&env, &employer, &old_recipient);
+
+        events::emit(
+            &env,
+            &employer,
+            StreamTransferred {
                 stream_id,
-            ),
-            (old_recipient, new_recipient),
+                employer: employer.clone(),
+                old_worker: old_recipient,
+                new_worker: new_recipient,
+            },
         );
 
         Ok(())
     }
 
+    /// Cancel a stream as its employer or a registry delegate of it. Agents
+    /// use the gateway's `agent_cancel_stream` instead; passing a `gateway`
+    /// is rejected.
     pub fn cancel_stream(
         env: Env,
         stream_id: u64,
@@ -1239,27 +1303,18 @@ impl PayrollStream {
             .ok_or(QuipayError::StreamNotFound)?;
 
         if stream.employer != caller {
-            let gateway_addr = gateway.ok_or(QuipayError::Unauthorized)?;
-            let admin: Address = env.invoke_contract(
-                &gateway_addr,
-                &soroban_sdk::Symbol::new(&env, "get_admin"),
-                soroban_sdk::vec![&env],
-            );
-            if admin != stream.employer {
-                return Err(QuipayError::Unauthorized);
-            }
-            let is_auth: bool = env.invoke_contract(
-                &gateway_addr,
-                &soroban_sdk::Symbol::new(&env, "is_authorized"),
-                soroban_sdk::vec![
-                    &env,
-                    caller.clone().into_val(&env),
-                    1u32.into_val(&env), // Permission::ExecutePayroll
-                ],
-            );
-            if !is_auth {
+            // Agents cancel through the gateway's `agent_cancel_stream`, which
+            // checks their permission, consumes a nonce and journals the action.
+            if gateway.is_some() {
                 return Err(QuipayError::Unauthorized);
             }
+            // Otherwise the caller must be a registry delegate of the employer.
+            Self::require_employer_delegate(
+                &env,
+                &stream.employer,
+                &caller,
+                EmployerAction::CancelStream,
+            )?;
         }
 
         if Self::is_closed(&stream) {
@@ -1280,7 +1335,7 @@ impl PayrollStream {
 
         // If the grace period has already elapsed, finalize the cancellation now.
         if stream.cancel_effective_at > 0 && now >= stream.cancel_effective_at {
-            return Self::finalize_cancel(&env, stream_id, &key, &mut stream, now);
+            return Self::finalize_cancel(&env, &caller, stream_id, &key, &mut stream, now);
         }
 
         // ── First call: set the grace period ────────────────────────────────
@@ -1292,21 +1347,22 @@ impl PayrollStream {
 
         if grace == 0 {
             // Grace period disabled — cancel immediately.
-            return Self::finalize_cancel(&env, stream_id, &key, &mut stream, now);
+            return Self::finalize_cancel(&env, &caller, stream_id, &key, &mut stream, now);
         }
 
         stream.cancel_effective_at = now.saturating_add(grace);
         stream.status = StreamStatus::PendingCancel;
         env.storage().persistent().set(&key, &stream);
 
-        env.events().publish(
-            (
-                soroban_sdk::Symbol::new(&env, "stream"),
-                soroban_sdk::Symbol::new(&env, "cancel_scheduled"),
+        events::emit(
+            &env,
+            &caller,
+            CancelScheduled {
                 stream_id,
-                caller.clone(),
-            ),
-            (stream.worker.clone(), stream.cancel_effective_at),
+                employer: stream.employer.clone(),
+                worker: stream.worker.clone(),
+                effective_at: stream.cancel_effective_at,
+            },
         );
 
         Ok(())
@@ -1366,14 +1422,17 @@ impl PayrollStream {
                     } else if stream.status == StreamStatus::PendingCancel {
                         // Grace period already running — finalize if elapsed, else idempotent.
                         if stream.cancel_effective_at > 0 && now >= stream.cancel_effective_at {
-                            Self::finalize_cancel(&env, stream_id, &key, &mut stream, now)
-                                .is_ok()
+                            Self::finalize_cancel(
+                                &env, &employer, stream_id, &key, &mut stream, now,
+                            )
+                            .is_ok()
                         } else {
                             true
                         }
                     } else if grace == 0 {
                         // Grace period disabled — cancel immediately.
-                        Self::finalize_cancel(&env, stream_id, &key, &mut stream, now).is_ok()
+                        Self::finalize_cancel(&env, &employer, stream_id, &key, &mut stream, now)
+                            .is_ok()
                     } else {
                         // Schedule cancellation with grace period.
                         stream.cancel_effective_at = now.saturating_add(grace);
@@ -1381,14 +1440,15 @@ impl PayrollStream {
                         env.storage().persistent().set(&key, &stream);
                         Self::bump_stream_storage_ttl(&env, stream_id, &stream.worker);
 
-                        env.events().publish(
-                            (
-                                Symbol::new(&env, "stream"),
-                                Symbol::new(&env, "cancel_scheduled"),
+                        events::emit(
+                            &env,
+                            &employer,
+                            CancelScheduled {
                                 stream_id,
-                                employer.clone(),
-                            ),
-                            (stream.worker.clone(), stream.cancel_effective_at),
+                                employer: stream.employer.clone(),
+                                worker: stream.worker.clone(),
+                                effective_at: stream.cancel_effective_at,
+                            },
                         );
                         true
                     }
@@ -1426,12 +1486,13 @@ impl PayrollStream {
         }
 
         let now = env.ledger().timestamp();
-        Self::finalize_cancel(&env, stream_id, &key, &mut stream, now)
+        Self::finalize_cancel(&env, &admin, stream_id, &key, &mut stream, now)
     }
 
     /// Internal helper: pay accrued amount, remove remaining liability, and mark stream Canceled.
     fn finalize_cancel(
         env: &Env,
+        actor: &Address,
         stream_id: u64,
         key: &StreamKey,
         stream: &mut Stream,
@@ -1454,13 +1515,7 @@ impl PayrollStream {
             .ok_or(QuipayError::NotInitialized)?;
 
         if owed > 0 {
-            Self::call_vault_payout(
-                env,
-                &vault,
-                stream.worker.clone(),
-                stream.token.clone(),
-                owed,
-            );
+            Self::pay_or_hold_worker(env, actor, &vault, stream_id, stream, owed)?;
             stream.withdrawn_amount = stream
                 .withdrawn_amount
                 .checked_add(owed)
@@ -1476,38 +1531,34 @@ impl PayrollStream {
         let cancel_fee = Self::calculate_early_cancel_fee(env, remaining_liability);
 
         if remaining_liability > 0 {
-            Self::call_vault_remove_liability(
-                env,
-                &vault,
-                stream.token.clone(),
-                remaining_liability,
-            );
+            // The cancel fee is paid out of the remaining liability; the rest
+            // is released back to the employer.
+            let released = remaining_liability - cancel_fee;
+            if released > 0 {
+                Self::call_vault_remove_liability(env, &vault, stream.token.clone(), released);
+            }
 
             if cancel_fee > 0 {
-                Self::call_vault_payout(
-                    env,
-                    &vault,
-                    stream.worker.clone(),
-                    stream.token.clone(),
-                    cancel_fee,
-                );
+                Self::pay_or_hold_worker(env, actor, &vault, stream_id, stream, cancel_fee)?;
             }
         }
 
-        Self::close_stream_internal(stream, now, StreamStatus::Canceled);
+        Self::close_stream_internal(env, stream_id, stream, now, StreamStatus::Canceled);
         env.storage().persistent().set(key, stream);
 
-        env.events().publish(
-            (
-                soroban_sdk::Symbol::new(env, "stream"),
-                soroban_sdk::Symbol::new(env, "canceled"),
+        events::emit(
+            env,
+            actor,
+            StreamCanceled {
                 stream_id,
-                stream.employer.clone(),
-            ),
-            (stream.worker.clone(), stream.token.clone()),
+                employer: stream.employer.clone(),
+                worker: stream.worker.clone(),
+                token: stream.token.clone(),
+            },
         );
 
         Self::try_mint_receipt(env, stream, stream_id, 1u32); // 1 = Cancelled
+        Self::registry_stream_closed(env, &stream.employer, &stream.worker);
 
         stream.status = StreamStatus::Canceled;
         stream.closed_at = now;
@@ -1559,6 +1610,7 @@ impl PayrollStream {
         // Call the internal create stream logic
         Self::create_stream_internal(
             env,
+            &gateway,
             employer,
             worker,
             token,
@@ -1614,6 +1666,7 @@ impl PayrollStream {
 
         let stream_id = Self::create_stream_internal(
             env.clone(),
+            &dao,
             employer.clone(),
             worker.clone(),
             token.clone(),
@@ -1625,16 +1678,6 @@ impl PayrollStream {
             core::option::Option::<stream_curve::SpeedCurve>::None,
         )?;
 
-        env.events().publish(
-            (
-                Symbol::new(&env, "stream"),
-                Symbol::new(&env, "created_via_governance"),
-                worker,
-                employer,
-            ),
-            (stream_id, token, rate, start_ts, end_ts),
-        );
-
         Ok(stream_id)
     }
 
@@ -1678,7 +1721,7 @@ impl PayrollStream {
 
         // If the grace period has elapsed, finalize now.
         if stream.cancel_effective_at > 0 && now >= stream.cancel_effective_at {
-            return Self::finalize_cancel(&env, stream_id, &key, &mut stream, now);
+            return Self::finalize_cancel(&env, &gateway, stream_id, &key, &mut stream, now);
         }
 
         let grace: u64 = env
@@ -1688,20 +1731,21 @@ impl PayrollStream {
             .unwrap_or(DEFAULT_CANCELLATION_GRACE_PERIOD);
 
         if grace == 0 {
-            return Self::finalize_cancel(&env, stream_id, &key, &mut stream, now);
+            return Self::finalize_cancel(&env, &gateway, stream_id, &key, &mut stream, now);
         }
 
         stream.cancel_effective_at = now.saturating_add(grace);
         env.storage().persistent().set(&key, &stream);
 
-        env.events().publish(
-            (
-                Symbol::new(&env, "stream"),
-                Symbol::new(&env, "cancel_scheduled_via_gateway"),
+        events::emit(
+            &env,
+            &gateway,
+            CancelScheduled {
                 stream_id,
-                employer.clone(),
-            ),
-            (stream.worker.clone(), stream.cancel_effective_at),
+                employer: stream.employer.clone(),
+                worker: stream.worker.clone(),
+                effective_at: stream.cancel_effective_at,
+            },
         );
 
         Ok(())
@@ -1710,6 +1754,7 @@ impl PayrollStream {
     // Internal helper for creating streams (used by both create_stream and create_stream_via_gateway)
     fn create_stream_internal(
         env: Env,
+        actor: &Address,
         employer: Address,
         worker: Address,
         token: Address,
@@ -1736,30 +1781,7 @@ impl PayrollStream {
         }
 
         let limit = Self::get_employer_stream_limit(env.clone(), employer.clone());
-        let emp_key = StreamKey::EmployerStreams(employer.clone());
-        let emp_ids: Vec<u64> = env
-            .storage()
-            .persistent()
-            .get(&emp_key)
-            .unwrap_or_else(|| Vec::new(&env));
-
-        let mut active_count = 0u32;
-        let mut i = 0u32;
-        while i < emp_ids.len() {
-            if let Some(id) = emp_ids.get(i) {
-                if let Some(s) = env
-                    .storage()
-                    .persistent()
-                    .get::<StreamKey, Stream>(&StreamKey::Stream(id))
-                {
-                    if !Self::is_closed(&s) {
-                        active_count += 1;
-                    }
-                }
-            }
-            i += 1;
-        }
-
+        let active_count = Self::get_active_stream_count(env.clone(), employer.clone());
         if active_count >= limit {
             return Err(QuipayError::StreamLimitReached);
         }
@@ -1778,6 +1800,9 @@ impl PayrollStream {
             return Err(QuipayError::StartTimeInPast);
         }
 
+        Self::require_eligible_worker(&env, &worker)?;
+        Self::require_verified_employer(&env, &employer)?;
+
         let duration = end_ts - start_ts;
         let total_amount = rate
             .checked_mul(i128::from(duration as i64))
@@ -1824,6 +1849,8 @@ impl PayrollStream {
             .instance()
             .set(&DataKey::NextStreamId, &next_id);
 
+        Self::call_vault_charge_fee(&env, &vault, stream_id, &employer, &token, total_amount);
+
         let stream = Stream {
             employer: employer.clone(),
             worker: worker.clone(),
@@ -1849,35 +1876,25 @@ impl PayrollStream {
             .persistent()
             .set(&StreamKey::Stream(stream_id), &stream);
 
-        let emp_key = StreamKey::EmployerStreams(employer.clone());
-        let mut emp_ids: Vec<u64> = env
-            .storage()
-            .persistent()
-            .get(&emp_key)
-            .unwrap_or_else(|| Vec::new(&env));
-        emp_ids.push_back(stream_id);
-        env.storage().persistent().set(&emp_key, &emp_ids);
-
-        let wrk_key = StreamKey::WorkerStreams(worker.clone());
-        let mut wrk_ids: Vec<u64> = env
-            .storage()
-            .persistent()
-            .get(&wrk_key)
-            .unwrap_or_else(|| Vec::new(&env));
-        wrk_ids.push_back(stream_id);
-        env.storage().persistent().set(&wrk_key, &wrk_ids);
+        Self::index_stream_created(&env, stream_id, &employer, &worker);
 
         // Keep the new stream state and its worker index entry alive.
         Self::bump_stream_storage_ttl(&env, stream_id, &worker);
 
-        env.events().publish(
-            (
-                Symbol::new(&env, "stream"),
-                Symbol::new(&env, "created_via_gateway"),
-                worker.clone(),
-                employer.clone(),
-            ),
-            (stream_id, token, rate, start_ts, end_ts),
+        Self::registry_stream_opened(&env, &employer, &worker);
+
+        events::emit(
+            &env,
+            actor,
+            StreamCreated {
+                stream_id,
+                employer: employer.clone(),
+                worker: worker.clone(),
+                token,
+                rate,
+                start_ts,
+                end_ts,
+            },
         );
 
         Ok(stream_id)
@@ -1903,18 +1920,11 @@ impl PayrollStream {
     pub fn get_withdrawable(env: Env, stream_id: u64) -> Option<i128> {
         let key = StreamKey::Stream(stream_id);
         let stream: Stream = env.storage().persistent().get(&key)?;
-
-        if Self::is_closed(&stream) {
-            return Some(0);
-        }
-
-        let now = env.ledger().timestamp();
-        let vested = Self::vested_amount(&stream, now);
-        Some(vested.checked_sub(stream.withdrawn_amount).unwrap_or(0))
+        Some(Self::withdrawable_amount(&stream, env.ledger().timestamp()))
     }
 
     /// Pure view: returns claimable amount without mutating state.
-    /// Claimable = min(streamed_amount - withdrawn_amount, vault_available_balance).
+    /// Claimable = min(streamed_amount - withdrawn_amount, vault_treasury_balance).
     pub fn get_claimable(env: Env, stream_id: u64) -> Option<i128> {
         let key = StreamKey::Stream(stream_id);
         let stream: Stream = env.storage().persistent().get(&key)?;
@@ -1934,7 +1944,7 @@ impl PayrollStream {
         use soroban_sdk::{IntoVal, Symbol, vec};
         let vault_balance: i128 = env.invoke_contract(
             &vault,
-            &Symbol::new(&env, "get_balance"),
+            &Symbol::new(&env, "get_treasury_balance"),
             vec![&env, stream.token.clone().into_val(&env)],
         );
         if vault_balance <= 0 {
@@ -1944,6 +1954,20 @@ impl PayrollStream {
         Some(core::cmp::min(streamed_claimable, vault_balance))
     }
 
+    /// Outstanding IOU held by the vault for this stream: the part of past
+    /// payouts that could not be paid while the vault was insolvent.
+    pub fn get_stream_iou(env: Env, stream_id: u64) -> i128 {
+        let Some(vault): Option<Address> = env.storage().instance().get(&DataKey::Vault) else {
+            return 0;
+        };
+        use soroban_sdk::{IntoVal, Symbol, vec};
+        env.invoke_contract(
+            &vault,
+            &Symbol::new(&env, "get_stream_iou"),
+            vec![&env, stream_id.into_val(&env)],
+        )
+    }
+
     /// Check if a stream is currently solvent (vault has enough funds to cover remaining liability)
     pub fn is_stream_solvent(env: Env, stream_id: u64) -> Option<bool> {
         let key = StreamKey::Stream(stream_id);
@@ -2007,10 +2031,11 @@ impl PayrollStream {
 
         use soroban_sdk::{IntoVal, Symbol, vec};
 
-        // Get vault balance and liability for this token
+        // Get vault balance and liability for this token. Both come from the
+        // vault's books; `reconcile` keeps them in line with the ledger.
         let vault_balance: i128 = env.invoke_contract(
             &vault,
-            &Symbol::new(&env, "get_balance"),
+            &Symbol::new(&env, "get_treasury_balance"),
             vec![&env, stream.token.clone().into_val(&env)],
         );
 
@@ -2050,67 +2075,70 @@ impl PayrollStream {
         })
     }
 
+    /// Stream IDs of `employer`: active streams first, then closed ones.
+    /// Streams still waiting in a legacy list are left out until
+    /// `migrate_stream_index` has moved them.
     pub fn get_streams_by_employer(
         env: Env,
         employer: Address,
         offset: Option<u32>,
         limit: Option<u32>,
     ) -> Vec<u64> {
-        let ids: Vec<u64> = env
-            .storage()
-            .persistent()
-            .get(&StreamKey::EmployerStreams(employer))
-            .unwrap_or_else(|| Vec::new(&env));
-
-        Self::paginate(&env, ids, offset, limit)
+        Self::ensure_index_migrated(&env, &employer);
+        Self::paginate(
+            &env,
+            &stream_index::StreamIndex::EmployerActive(employer.clone()),
+            &stream_index::StreamIndex::EmployerClosed(employer),
+            offset,
+            limit,
+        )
     }
 
+    /// Stream IDs of `worker`: active streams first, then closed ones.
+    /// Streams still waiting in a legacy list are left out until
+    /// `migrate_stream_index` has moved them.
     pub fn get_streams_by_worker(
         env: Env,
         worker: Address,
         offset: Option<u32>,
         limit: Option<u32>,
     ) -> Vec<u64> {
-        let ids: Vec<u64> = env
-            .storage()
-            .persistent()
-            .get(&StreamKey::WorkerStreams(worker))
-            .unwrap_or_else(|| Vec::new(&env));
-
-        Self::paginate(&env, ids, offset, limit)
+        Self::ensure_index_migrated(&env, &worker);
+        Self::paginate(
+            &env,
+            &stream_index::StreamIndex::WorkerActive(worker.clone()),
+            &stream_index::StreamIndex::WorkerClosed(worker),
+            offset,
+            limit,
+        )
     }
 
-    /// Paginate a list of stream IDs with bounds checking.
+    /// Paginate the IDs of an active index followed by its closed index.
     ///
     /// ### DoS Protection
     /// The `limit` parameter is capped at `MAX_PAGE_SIZE` (1000) to prevent
     /// performance issues from excessively large page requests.
     ///
     /// ### Parameters
-    /// - `ids`: Full list of stream IDs to paginate
     /// - `offset`: Starting index (default: 0)
     /// - `limit`: Maximum items to return (default: all, capped at MAX_PAGE_SIZE)
-    ///
-    /// ### Returns
-    /// A subset of `ids` from `offset` to `offset + min(limit, MAX_PAGE_SIZE)`
-    fn paginate(env: &Env, ids: Vec<u64>, offset: Option<u32>, limit: Option<u32>) -> Vec<u64> {
+    fn paginate(
+        env: &Env,
+        active: &stream_index::StreamIndex,
+        closed: &stream_index::StreamIndex,
+        offset: Option<u32>,
+        limit: Option<u32>,
+    ) -> Vec<u64> {
         let offset = offset.unwrap_or(0);
-        let ids_len = ids.len();
         // Cap limit at MAX_PAGE_SIZE to prevent DoS
-        let requested_limit = limit.unwrap_or(ids_len);
-        let limit = requested_limit.min(MAX_PAGE_SIZE).min(ids_len);
-
-        let mut result = Vec::new(env);
-        if offset >= ids_len {
-            return result;
-        }
-
-        let end = (offset + limit).min(ids_len);
+        let limit = limit.unwrap_or(MAX_PAGE_SIZE).min(MAX_PAGE_SIZE);
 
-        for i in offset..end {
-            if let Some(id) = ids.get(i) {
-                result.push_back(id);
-            }
+        let active_len = Self::index_head(env, active).len;
+        let mut result = Self::index_page(env, active, offset, limit);
+        let remaining = limit - result.len();
+        if remaining > 0 {
+            let closed_offset = offset.saturating_sub(active_len);
+            result.append(&Self::index_page(env, closed, closed_offset, remaining));
         }
         result
     }
@@ -2136,121 +2164,71 @@ impl PayrollStream {
             return Err(QuipayError::RetentionNotMet);
         }
 
-        Self::remove_from_index(&env, StreamKey::EmployerStreams(stream.employer), stream_id);
-        Self::remove_from_index(&env, StreamKey::WorkerStreams(stream.worker), stream_id);
+        Self::index_stream_removed(&env, stream_id, &stream);
 
         env.storage().persistent().remove(&key);
         Ok(())
     }
 
-    /// Propose an upgrade with a 48-hour timelock
-    /// Only admin can call this function
+    /// Propose an upgrade to `new_version` with a 48-hour timelock
+    /// Requires the admin or an `Upgrader`.
     pub fn propose_upgrade(
         env: Env,
+        caller: Address,
         new_wasm_hash: soroban_sdk::BytesN<32>,
+        new_version: (u32, u32, u32),
     ) -> Result<(), QuipayError> {
-        let admin: Address = env
-            .storage()
-            .instance()
-            .get(&DataKey::Admin)
-            .ok_or(QuipayError::NotInitialized)?;
-        admin.require_auth();
-
-        let now = env.ledger().timestamp();
-        let execute_after = now.saturating_add(TIMELOCK_DURATION);
-
-        // Check if there's already a pending upgrade
-        if env.storage().instance().has(&DataKey::PendingUpgrade) {
-            return Err(QuipayError::Custom);
-        }
-
-        let pending_upgrade = PendingUpgrade {
-            wasm_hash: new_wasm_hash.clone(),
-            execute_after,
-            proposed_at: now,
-            proposed_by: admin.clone(),
-        };
-
-        env.storage()
-            .instance()
-            .set(&DataKey::PendingUpgrade, &pending_upgrade);
-
-        // Emit upgrade proposed event
-        #[allow(deprecated)]
-        env.events()
-            .publish((UPGRADE_PROPOSED, admin), (new_wasm_hash, execute_after));
-
+        Self::require_role(&env, Role::Upgrader, &caller)?;
+        upgrade::propose(&env, &caller, new_wasm_hash, new_version)?;
         Ok(())
     }
 
     /// Execute a proposed upgrade after timelock period
-    /// Only admin can call this function
-    pub fn execute_upgrade(env: Env) -> Result<(), QuipayError> {
-        let admin: Address = env
-            .storage()
-            .instance()
-            .get(&DataKey::Admin)
-            .ok_or(QuipayError::NotInitialized)?;
-        admin.require_auth();
-
-        let pending_upgrade: PendingUpgrade = env
-            .storage()
-            .instance()
-            .get(&DataKey::PendingUpgrade)
-            .ok_or(QuipayError::Custom)?;
-
-        let now = env.ledger().timestamp();
-        if now < pending_upgrade.execute_after {
-            return Err(QuipayError::Custom);
-        }
-
-        // Perform the upgrade
-        env.deployer()
-            .update_current_contract_wasm(pending_upgrade.wasm_hash.clone());
-
-        // Clear pending upgrade
-        env.storage().instance().remove(&DataKey::PendingUpgrade);
-
-        // Emit upgrade executed event
-        #[allow(deprecated)]
-        env.events()
-            .publish((UPGRADE_EXECUTED, admin), (pending_upgrade.wasm_hash, now));
-
+    /// Requires the admin or an `Upgrader`.
+    pub fn execute_upgrade(env: Env, caller: Address) -> Result<(), QuipayError> {
+        Self::require_role(&env, Role::Upgrader, &caller)?;
+        upgrade::execute(&env, &caller)?;
         Ok(())
     }
 
     /// Cancel a pending upgrade
-    /// Only admin can call this function
-    pub fn cancel_upgrade(env: Env) -> Result<(), QuipayError> {
-        let admin: Address = env
-            .storage()
-            .instance()
-            .get(&DataKey::Admin)
-            .ok_or(QuipayError::NotInitialized)?;
-        admin.require_auth();
-
-        let pending_upgrade: PendingUpgrade = env
-            .storage()
-            .instance()
-            .get(&DataKey::PendingUpgrade)
-            .ok_or(QuipayError::Custom)?;
-
-        // Clear pending upgrade
-        env.storage().instance().remove(&DataKey::PendingUpgrade);
+    /// Requires the admin or an `Upgrader`.
+    pub fn cancel_upgrade(env: Env, caller: Address) -> Result<(), QuipayError> {
+        Self::require_role(&env, Role::Upgrader, &caller)?;
+        upgrade::cancel(&env, &caller)?;
+        Ok(())
+    }
 
-        // Emit upgrade canceled event
-        #[allow(deprecated)]
-        env.events().publish(
-            (UPGRADE_CANCELED, admin),
-            (pending_upgrade.wasm_hash, pending_upgrade.execute_after),
-        );
+    /// Reinstall the WASM replaced by the last upgrade, without a timelock
+    /// Requires the admin or an `Upgrader`.
+    pub fn rollback_upgrade(env: Env, caller: Address) -> Result<VersionInfo, QuipayError> {
+        Self::require_role(&env, Role::Upgrader, &caller)?;
+        upgrade::rollback(&env, &caller)
+    }
 
-        Ok(())
+    /// Run the data migrations of the installed version, once, after an
+    /// upgrade from `from_version`
+    /// Requires the admin or an `Upgrader`.
+    pub fn migrate(
+        env: Env,
+        caller: Address,
+        from_version: (u32, u32, u32),
+    ) -> Result<VersionInfo, QuipayError> {
+        Self::require_role(&env, Role::Upgrader, &caller)?;
+        // Stream indexes move to the chunked layout per account on first
+        // use, or through `migrate_stream_index`.
+        upgrade::begin_migration(&env, from_version)
     }
 
     /// Get the current pending upgrade (if any)
     pub fn get_pending_upgrade(env: Env) -> Option<PendingUpgrade> {
-        env.storage().instance().get(&DataKey::PendingUpgrade)
+        upgrade::get_pending(&env)
+    }
+
+    /// Get the installed version. Streams deployed before version tracking
+    /// report 1.0.0.
+    pub fn get_version(env: Env) -> VersionInfo {
+        upgrade::installed_version(&env)
     }
 
     /// Get the current early cancellation fee in basis points
@@ -2273,50 +2251,40 @@ impl PayrollStream {
         Ok(())
     }
 
+    /// Authenticate `caller` as the admin or a holder of `role`.
+    pub(crate) fn require_role(env: &Env, role: Role, caller: &Address) -> Result<(), QuipayError> {
+        let admin = Self::get_admin(env.clone())?;
+        rbac::require_role(env, role, caller, &admin)
+    }
+
     fn is_closed(stream: &Stream) -> bool {
         stream.status == StreamStatus::Canceled || stream.status == StreamStatus::Completed
     }
 
+    /// Extend a stream's entries on a mutation path, following the shared
+    /// TTL policy. Idle streams are kept alive by keepers through `bump_ttl`.
     fn bump_stream_storage_ttl(env: &Env, stream_id: u64, worker: &Address) {
-        let stream_key = StreamKey::Stream(stream_id);
-        env.storage().persistent().extend_ttl(
-            &stream_key,
-            STORAGE_TTL_THRESHOLD_LEDGER,
-            STORAGE_TTL_EXTEND_TO_LEDGER,
-        );
-
-        let worker_key = StreamKey::WorkerStreams(worker.clone());
-        env.storage().persistent().extend_ttl(
-            &worker_key,
-            STORAGE_TTL_THRESHOLD_LEDGER,
-            STORAGE_TTL_EXTEND_TO_LEDGER,
+        ttl::extend(env, &StreamKey::Stream(stream_id));
+        ttl::extend(env, &StreamKey::StreamSlots(stream_id));
+        ttl::extend(
+            env,
+            &StreamKey::IndexHead(stream_index::StreamIndex::WorkerActive(worker.clone())),
         );
+        ttl::extend(env, &DataKey::LastWithdrawal(worker.clone()));
     }
 
-    fn close_stream_internal(stream: &mut Stream, now: u64, status: StreamStatus) {
+    fn close_stream_internal(
+        env: &Env,
+        stream_id: u64,
+        stream: &mut Stream,
+        now: u64,
+        status: StreamStatus,
+    ) {
+        let was_closed = Self::is_closed(stream);
         stream.status = status;
         stream.closed_at = now;
-    }
-
-    fn remove_from_index(env: &Env, key: StreamKey, stream_id: u64) {
-        let ids: Vec<u64> = match env.storage().persistent().get(&key) {
-            Some(v) => v,
-            None => return,
-        };
-        let mut new_ids: Vec<u64> = Vec::new(env);
-        let mut i = 0u32;
-        while i < ids.len() {
-            if let Some(id) = ids.get(i) {
-                if id != stream_id {
-                    new_ids.push_back(id);
-                }
-            }
-            i += 1;
-        }
-        if new_ids.len() == 0 {
-            env.storage().persistent().remove(&key);
-        } else {
-            env.storage().persistent().set(&key, &new_ids);
+        if !was_closed {
+            Self::index_stream_closed(env, stream_id, stream);
         }
     }
 
@@ -2324,6 +2292,16 @@ impl PayrollStream {
         Self::vested_amount_at(stream, now)
     }
 
+    /// Vested but not yet withdrawn amount of `stream`; zero once closed.
+    pub(crate) fn withdrawable_amount(stream: &Stream, now: u64) -> i128 {
+        if Self::is_closed(stream) {
+            return 0;
+        }
+        Self::vested_amount(stream, now)
+            .checked_sub(stream.withdrawn_amount)
+            .unwrap_or(0)
+    }
+
     /// Calculate early cancellation fee based on remaining amount
     fn calculate_early_cancel_fee(env: &Env, remaining_amount: i128) -> i128 {
         let fee_bps: u32 = env
@@ -2343,27 +2321,114 @@ impl PayrollStream {
             .unwrap_or(0)
     }
 
-    /// Invoke `payout_liability` on the vault contract.
+    /// Invoke `payout_streams_liability` on the vault contract: one transfer
+    /// covering several streams' claims on `token`.
     pub(crate) fn call_vault_payout(
         env: &Env,
         vault: &Address,
         worker: Address,
         token: Address,
-        amount: i128,
+        stream_ids: Vec<u64>,
+        amounts: Vec<i128>,
     ) {
         use soroban_sdk::{IntoVal, Symbol, vec};
-        env.invoke_contract::<()>(
+        env.invoke_contract::<i128>(
             vault,
-            &Symbol::new(env, "payout_liability"),
+            &Symbol::new(env, "payout_streams_liability"),
             vec![
                 env,
                 worker.into_val(env),
                 token.into_val(env),
-                amount.into_val(env),
+                stream_ids.into_val(env),
+                amounts.into_val(env),
             ],
         );
     }
 
+    /// Invoke `charge_protocol_fee` on the vault contract for `volume` of
+    /// new liability on `stream_id`.
+    pub(crate) fn call_vault_charge_fee(
+        env: &Env,
+        vault: &Address,
+        stream_id: u64,
+        employer: &Address,
+        token: &Address,
+        volume: i128,
+    ) {
+        use soroban_sdk::{IntoVal, Symbol, vec};
+        env.invoke_contract::<i128>(
+            vault,
+            &Symbol::new(env, "charge_protocol_fee"),
+            vec![
+                env,
+                stream_id.into_val(env),
+                employer.clone().into_val(env),
+                token.clone().into_val(env),
+                volume.into_val(env),
+            ],
+        );
+    }
+
+    /// Invoke `payout_stream_liability` on the vault contract.
+    /// Returns the amount actually transferred, which is a pro-rata share of
+    /// `amount` while the vault is insolvent for `token`.
+    pub(crate) fn call_vault_stream_payout(
+        env: &Env,
+        vault: &Address,
+        stream_id: u64,
+        to: Address,
+        token: Address,
+        amount: i128,
+    ) -> i128 {
+        use soroban_sdk::{IntoVal, Symbol, vec};
+        env.invoke_contract::<i128>(
+            vault,
+            &Symbol::new(env, "payout_stream_liability"),
+            vec![
+                env,
+                stream_id.into_val(env),
+                to.into_val(env),
+                token.into_val(env),
+                amount.into_val(env),
+            ],
+        )
+    }
+
+    /// Invoke `is_insolvent` on the vault contract.
+    pub(crate) fn call_vault_is_insolvent(env: &Env, vault: &Address, token: &Address) -> bool {
+        use soroban_sdk::{IntoVal, Symbol, vec};
+        env.invoke_contract::<bool>(
+            vault,
+            &Symbol::new(env, "is_insolvent"),
+            vec![env, token.clone().into_val(env)],
+        )
+    }
+
+    /// Invoke `refund_stream_liability` on the vault contract: refund `amount`
+    /// of the stream's liability to `to` without a protocol fee. Returns the
+    /// amount the vault paid, which is a pro-rata share while it is insolvent.
+    pub(crate) fn call_vault_refund_stream_liability(
+        env: &Env,
+        vault: &Address,
+        stream_id: u64,
+        to: Address,
+        token: Address,
+        amount: i128,
+    ) -> i128 {
+        use soroban_sdk::{IntoVal, Symbol, vec};
+        env.invoke_contract::<i128>(
+            vault,
+            &Symbol::new(env, "refund_stream_liability"),
+            vec![
+                env,
+                stream_id.into_val(env),
+                to.into_val(env),
+                token.into_val(env),
+                amount.into_val(env),
+            ],
+        )
+    }
+
     /// Invoke `remove_liability` on the vault contract.
     pub(crate) fn call_vault_remove_liability(
         env: &Env,
@@ -2494,16 +2559,26 @@ impl PayrollStream {
 }
 
 mod dispute;
+pub mod events;
 mod extension_test;
 mod pause_test;
+mod registry;
+mod runway;
 mod stream_extension;
+mod stream_index;
 mod stream_pause;
+mod stream_query;
+mod stream_ttl;
 
 mod stream_curve;
 mod test;
 
+#[cfg(test)]
+mod dispute_test;
 #[cfg(test)]
 mod duration_test;
+#[cfg(test)]
+mod events_test;
 
 #[cfg(test)]
 mod batch_cancel_test;
@@ -2517,6 +2592,21 @@ mod cancel_grace_test;
 #[cfg(test)]
 mod integration_test;
 
+#[cfg(test)]
+mod registry_test;
+
+#[cfg(test)]
+mod runway_test;
+
+#[cfg(test)]
+mod stream_index_test;
+
+#[cfg(test)]
+mod stream_query_test;
+
+#[cfg(test)]
+mod stream_ttl_test;
+
 #[cfg(test)]
 mod proptest;
 
@@ -1,13 +1,15 @@
 #![cfg(test)]
 use super::*;
 use crate::test::setup;
-use soroban_sdk::{TryFromVal, testutils::Address as _, testutils::Events, testutils::Ledger as _};
+use soroban_sdk::{
+    IntoVal, TryFromVal, testutils::Address as _, testutils::Events, testutils::Ledger as _,
+};
 
 #[test]
 fn test_pause_and_resume_stream_vesting() {
     let env = Env::default();
     env.mock_all_auths();
-    let (client, employer, worker, token, _admin) = setup(&env);
+    let (client, employer, worker, token, admin) = setup(&env);
 
     env.ledger().with_mut(|li| {
         li.timestamp = 0;
@@ -51,7 +53,7 @@ fn test_pause_and_resume_stream_vesting() {
 fn test_pause_stream_wrong_auth() {
     let env = Env::default();
     env.mock_all_auths();
-    let (client, employer, worker, token, _admin) = setup(&env);
+    let (client, employer, worker, token, admin) = setup(&env);
     let malicious = Address::generate(&env);
 
     env.ledger().with_mut(|li| li.timestamp = 0);
@@ -73,13 +75,13 @@ fn test_admin_pause_and_resume_stream() {
     let stream_id = client.create_stream(&employer, &worker, &token, &1, &0, &0, &100, &None, &None);
 
     // Admin pauses
-    client.admin_pause_stream(&stream_id);
+    client.admin_pause_stream(&admin, &stream_id);
     let stream = client.get_stream(&stream_id).unwrap();
     assert_eq!(stream.status, StreamStatus::Paused);
 
     // Admin resumes
     env.ledger().with_mut(|li| li.timestamp = 10);
-    client.admin_resume_stream(&stream_id);
+    client.admin_resume_stream(&admin, &stream_id);
     let stream = client.get_stream(&stream_id).unwrap();
     assert_eq!(stream.status, StreamStatus::Active);
     assert_eq!(stream.total_paused_duration, 10);
@@ -89,7 +91,7 @@ fn test_admin_pause_and_resume_stream() {
 fn test_withdraw_from_paused_stream() {
     let env = Env::default();
     env.mock_all_auths();
-    let (client, employer, worker, token, _admin) = setup(&env);
+    let (client, employer, worker, token, admin) = setup(&env);
 
     env.ledger().with_mut(|li| li.timestamp = 0);
     let stream_id = client.create_stream(&employer, &worker, &token, &1, &0, &0, &100, &None, &None);
@@ -120,7 +122,7 @@ fn test_withdraw_from_paused_stream() {
 fn test_cliff_ts_equals_start_ts() {
     let env = Env::default();
     env.mock_all_auths();
-    let (client, employer, worker, token, _admin) = setup(&env);
+    let (client, employer, worker, token, admin) = setup(&env);
 
     env.ledger().with_mut(|li| li.timestamp = 0);
 
@@ -141,7 +143,7 @@ fn test_cliff_ts_equals_start_ts() {
 fn test_resume_event_fields() {
     let env = Env::default();
     env.mock_all_auths();
-    let (client, employer, worker, token, _admin) = setup(&env);
+    let (client, employer, worker, token, admin) = setup(&env);
 
     env.ledger().with_mut(|li| li.timestamp = 0);
     let stream_id =
@@ -156,28 +158,33 @@ fn test_resume_event_fields() {
     client.resume_stream(&stream_id, &employer);
 
     let events = env.events().all();
-    let (_, _, value) = events
-        .iter()
-        .find(|(_, topics, _)| {
-            Symbol::try_from_val(&env, &topics.get(0).unwrap()).unwrap()
-                == Symbol::new(&env, "stream")
-                && Symbol::try_from_val(&env, &topics.get(1).unwrap()).unwrap()
-                    == Symbol::new(&env, "resumed")
-        })
-        .expect("Resume event not found");
-
-    // The value should be (now, paused_duration, total_paused_duration)
-    // now = 25, paused_duration = 15, total_paused_duration = 15
-    let expected_val: (u64, u64, u64) = (25, 15, 15);
-    let val: (u64, u64, u64) = TryFromVal::try_from_val(&env, &value).unwrap();
-    assert_eq!(val, expected_val);
+    let (_, topics, value) = events.last().unwrap();
+    assert_eq!(
+        topics,
+        (
+            Symbol::new(&env, "stream"),
+            Symbol::new(&env, "resumed"),
+            quipay_common::events::EVENT_SCHEMA_VERSION,
+            employer.clone(),
+        )
+            .into_val(&env)
+    );
+
+    // paused_duration = 15, total_paused_duration = 15
+    let expected = events::StreamResumed {
+        stream_id,
+        resumed_at: 25,
+        paused_duration: 15,
+        total_paused_duration: 15,
+    };
+    assert_eq!(events::StreamResumed::try_from_val(&env, &value).unwrap(), expected);
 }
 
 #[test]
 fn test_admin_resume_event_fields() {
     let env = Env::default();
     env.mock_all_auths();
-    let (client, employer, worker, token, _admin) = setup(&env);
+    let (client, employer, worker, token, admin) = setup(&env);
 
     env.ledger().with_mut(|li| li.timestamp = 0);
     let stream_id =
@@ -185,34 +192,34 @@ fn test_admin_resume_event_fields() {
 
     // Admin Pause at t=10
     env.ledger().with_mut(|li| li.timestamp = 10);
-    client.admin_pause_stream(&stream_id);
+    client.admin_pause_stream(&admin, &stream_id);
 
     // Admin Resume at t=35 (Pause duration = 25)
     env.ledger().with_mut(|li| li.timestamp = 35);
-    client.admin_resume_stream(&stream_id);
+    client.admin_resume_stream(&admin, &stream_id);
 
     let events = env.events().all();
-    let (_, _, value) = events
-        .iter()
-        .find(|(_, topics, _)| {
-            Symbol::try_from_val(&env, &topics.get(0).unwrap()).unwrap()
-                == Symbol::new(&env, "stream")
-                && Symbol::try_from_val(&env, &topics.get(1).unwrap()).unwrap()
-                    == Symbol::new(&env, "resumed")
-        })
-        .expect("Resume event not found");
-
-    // now = 35, pause_duration = 25, total_paused_duration = 25
-    let expected_val: (u64, u64, u64) = (35, 25, 25);
-    let val: (u64, u64, u64) = TryFromVal::try_from_val(&env, &value).unwrap();
-    assert_eq!(val, expected_val);
+    let (_, topics, value) = events.last().unwrap();
+    assert_eq!(
+        topics,
+        quipay_common::events::topics::<events::StreamResumed>(&env, &admin).into_val(&env)
+    );
+
+    // pause_duration = 25, total_paused_duration = 25
+    let expected = events::StreamResumed {
+        stream_id,
+        resumed_at: 35,
+        paused_duration: 25,
+        total_paused_duration: 25,
+    };
+    assert_eq!(events::StreamResumed::try_from_val(&env, &value).unwrap(), expected);
 }
 
 #[test]
 fn test_is_stream_paused() {
     let env = Env::default();
     env.mock_all_auths();
-    let (client, employer, worker, token, _admin) = setup(&env);
+    let (client, employer, worker, token, admin) = setup(&env);
 
     env.ledger().with_mut(|li| li.timestamp = 0);
     let stream_id =
@@ -246,7 +253,7 @@ fn test_is_stream_paused_not_found() {
 fn test_get_stream_paused_at() {
     let env = Env::default();
     env.mock_all_auths();
-    let (client, employer, worker, token, _admin) = setup(&env);
+    let (client, employer, worker, token, admin) = setup(&env);
 
     env.ledger().with_mut(|li| li.timestamp = 0);
     let stream_id =
@@ -16,8 +16,46 @@ mod dummy_vault {
             true
         }
         pub fn add_liability(_env: Env, _token: Address, _amount: i128) {}
+        pub fn charge_protocol_fee(
+            _env: Env,
+            _stream_id: u64,
+            _employer: Address,
+            _token: Address,
+            _volume: i128,
+        ) -> i128 {
+            0
+        }
         pub fn remove_liability(_env: Env, _token: Address, _amount: i128) {}
-        pub fn payout_liability(_env: Env, _to: Address, _token: Address, _amount: i128) {}
+        pub fn refund_stream_liability(
+            _env: Env,
+            _stream_id: u64,
+            _to: Address,
+            _token: Address,
+            amount: i128,
+        ) -> i128 {
+            amount
+        }
+        pub fn payout_streams_liability(
+            _env: Env,
+            _to: Address,
+            _token: Address,
+            _stream_ids: soroban_sdk::Vec<u64>,
+            amounts: soroban_sdk::Vec<i128>,
+        ) -> i128 {
+            amounts.iter().sum()
+        }
+        pub fn payout_stream_liability(
+            _env: Env,
+            _stream_id: u64,
+            _to: Address,
+            _token: Address,
+            amount: i128,
+        ) -> i128 {
+            amount
+        }
+        pub fn is_insolvent(_env: Env, _token: Address) -> bool {
+            false
+        }
     }
 }
 
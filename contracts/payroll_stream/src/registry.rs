@@ -0,0 +1,454 @@
+//! Optional WorkforceRegistry enforcement.
+//!
+//! When a registry is configured, streams can only be created for (or
+//! transferred to) registered workers that are not blacklisted, and payouts
+//! to a blacklisted worker are blocked until the blacklist entry is lifted.
+//! Closing a stream never waits on the worker's standing: whatever it owes a
+//! blacklisted worker is held in the vault as liability, and the worker
+//! claims it with `claim_held_payout` once cleared.
+//! The admin can also require a minimum KYC attestation level from the
+//! registry for new streams.
+//!
+//! Streams can also be created in the worker's registered preferred token,
+//! provided the vault allowlists it.
+//!
+//! The registry's employer records also let delegates (employer admins and
+//! payroll managers) create, cancel, pause and resume streams for a
+//! KYB-verified employer, as allowed by their role. The admin can further
+//! require every new stream's employer to be KYB-verified.
+//!
+//! The registry's active-worker index is kept in sync through its
+//! `sync_stream_active` hook, which requires this contract to be set as the
+//! registry's stream contract. A worker is marked active for an employer when
+//! a stream between them opens, and inactive once the last one closes.
+
+use super::*;
+
+/// WorkforceRegistry's `EmployerAction`, passed to its `can_act_for`. The
+/// discriminants must match the registry's.
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum EmployerAction {
+    CreateStream = 1,
+    CancelStream = 2,
+    PauseStream = 3,
+}
+
+#[soroban_sdk::contractimpl]
+impl PayrollStream {
+    /// Set the WorkforceRegistry consulted on stream creation and payouts.
+    /// Only admin can call this. Pass `None` to disable enforcement.
+    pub fn set_registry(env: Env, registry: Option<Address>) -> Result<(), QuipayError> {
+        let admin: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::Admin)
+            .ok_or(QuipayError::NotInitialized)?;
+        admin.require_auth();
+        match registry {
+            Some(addr) => env.storage().instance().set(&DataKey::Registry, &addr),
+            None => env.storage().instance().remove(&DataKey::Registry),
+        }
+        Ok(())
+    }
+
+    pub fn get_registry(env: Env) -> Option<Address> {
+        env.storage().instance().get(&DataKey::Registry)
+    }
+
+    /// Require workers of new streams to hold a valid registry attestation of
+    /// at least `level`. Only admin can call this. 0 disables the check.
+    pub fn set_min_kyc_level(env: Env, level: u32) -> Result<(), QuipayError> {
+        let admin: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::Admin)
+            .ok_or(QuipayError::NotInitialized)?;
+        admin.require_auth();
+        env.storage().instance().set(&DataKey::MinKycLevel, &level);
+        Ok(())
+    }
+
+    pub fn get_min_kyc_level(env: Env) -> u32 {
+        env.storage()
+            .instance()
+            .get(&DataKey::MinKycLevel)
+            .unwrap_or(0)
+    }
+
+    /// Require new streams' employers to be KYB-verified in the registry.
+    /// Only admin can call this.
+    pub fn set_require_verified_employer(env: Env, required: bool) -> Result<(), QuipayError> {
+        let admin: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::Admin)
+            .ok_or(QuipayError::NotInitialized)?;
+        admin.require_auth();
+        env.storage()
+            .instance()
+            .set(&DataKey::RequireVerifiedEmployer, &required);
+        Ok(())
+    }
+
+    pub fn requires_verified_employer(env: Env) -> bool {
+        env.storage()
+            .instance()
+            .get(&DataKey::RequireVerifiedEmployer)
+            .unwrap_or(false)
+    }
+
+    /// Create a stream in the worker's preferred token from the registry.
+    /// Fails with `InvalidToken` if the vault does not allowlist that token.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_preferred_stream(
+        env: Env,
+        employer: Address,
+        worker: Address,
+        rate: i128,
+        cliff_ts: u64,
+        start_ts: u64,
+        end_ts: u64,
+        metadata_hash: Option<BytesN<32>>,
+        speed_curve: Option<stream_curve::SpeedCurve>,
+    ) -> Result<u64, QuipayError> {
+        Self::require_not_paused(&env)?;
+        employer.require_auth();
+
+        let params = PreferredStreamParams {
+            worker,
+            rate,
+            cliff_ts,
+            start_ts,
+            end_ts,
+            metadata_hash,
+            speed_curve: match speed_curve {
+                Some(curve) => MaybeSpeedCurve::Some(curve),
+                None => MaybeSpeedCurve::None,
+            },
+        };
+        Self::create_preferred_stream_internal(&env, &employer, params)
+    }
+
+    /// Onboard a team: create one stream per entry, each in that worker's
+    /// preferred token. All-or-nothing; any failure aborts the batch.
+    pub fn batch_create_preferred_streams(
+        env: Env,
+        employer: Address,
+        params: Vec<PreferredStreamParams>,
+    ) -> Result<Vec<u64>, QuipayError> {
+        Self::require_not_paused(&env)?;
+        employer.require_auth();
+        require!(
+            params.len() <= MAX_BATCH_CREATE_STREAMS,
+            QuipayError::BatchTooLarge
+        );
+
+        let mut stream_ids = Vec::new(&env);
+        for param in params.iter() {
+            stream_ids.push_back(Self::create_preferred_stream_internal(
+                &env, &employer, param,
+            )?);
+        }
+        Ok(stream_ids)
+    }
+
+    /// Pay out what a stream held for its worker while they were blacklisted.
+    /// Returns the amount paid, which is a pro-rata share while the vault is
+    /// insolvent (the rest becomes an IOU); zero when nothing is held.
+    pub fn claim_held_payout(
+        env: Env,
+        stream_id: u64,
+        worker: Address,
+    ) -> Result<i128, QuipayError> {
+        Self::require_not_paused(&env)?;
+        worker.require_auth();
+
+        let stream: Stream = env
+            .storage()
+            .persistent()
+            .get(&StreamKey::Stream(stream_id))
+            .ok_or(QuipayError::StreamNotFound)?;
+        require!(stream.worker == worker, QuipayError::NotWorker);
+        Self::require_not_blacklisted(&env, &worker)?;
+
+        let key = StreamKey::HeldPayout(stream_id);
+        let held: i128 = env.storage().persistent().get(&key).unwrap_or(0);
+        if held == 0 {
+            return Ok(0);
+        }
+        env.storage().persistent().remove(&key);
+
+        let vault: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::Vault)
+            .ok_or(QuipayError::NotInitialized)?;
+        let paid = Self::call_vault_stream_payout(
+            &env,
+            &vault,
+            stream_id,
+            worker.clone(),
+            stream.token.clone(),
+            held,
+        );
+
+        events::emit(
+            &env,
+            &worker,
+            StreamWithdrawn {
+                stream_id,
+                worker: worker.clone(),
+                token: stream.token,
+                amount: paid,
+            },
+        );
+        Ok(paid)
+    }
+
+    /// Amount held for a stream's blacklisted worker.
+    pub fn get_held_payout(env: Env, stream_id: u64) -> i128 {
+        env.storage()
+            .persistent()
+            .get(&StreamKey::HeldPayout(stream_id))
+            .unwrap_or(0)
+    }
+}
+
+impl PayrollStream {
+    /// Require `worker` to be registered, not blacklisted and attested at
+    /// the configured minimum KYC level.
+    pub(crate) fn require_eligible_worker(env: &Env, worker: &Address) -> Result<(), QuipayError> {
+        let Some(registry) = Self::get_registry(env.clone()) else {
+            return Ok(());
+        };
+        let registered: bool =
+            Self::call_registry(env, &registry, "is_registered", worker.into_val(env));
+        require!(registered, QuipayError::WorkerNotFound);
+        Self::require_not_blacklisted(env, worker)?;
+
+        let min_level = Self::get_min_kyc_level(env.clone());
+        if min_level > 0 {
+            let attested: bool = env.invoke_contract(
+                &registry,
+                &Symbol::new(env, "has_valid_attestation"),
+                soroban_sdk::vec![env, worker.into_val(env), min_level.into_val(env)],
+            );
+            require!(attested, QuipayError::AttestationRequired);
+        }
+        Ok(())
+    }
+
+    /// Require `employer` to be KYB-verified when the admin demands it.
+    pub(crate) fn require_verified_employer(
+        env: &Env,
+        employer: &Address,
+    ) -> Result<(), QuipayError> {
+        if !Self::requires_verified_employer(env.clone()) {
+            return Ok(());
+        }
+        let registry = Self::get_registry(env.clone()).ok_or(QuipayError::NotInitialized)?;
+        let verified: bool = Self::call_registry(
+            env,
+            &registry,
+            "is_employer_verified",
+            employer.into_val(env),
+        );
+        require!(verified, QuipayError::EmployerNotVerified);
+        Ok(())
+    }
+
+    /// Require `caller` to be allowed to perform `action` for `employer`.
+    /// Without a registry only the employer itself is allowed.
+    pub(crate) fn require_employer_delegate(
+        env: &Env,
+        employer: &Address,
+        caller: &Address,
+        action: EmployerAction,
+    ) -> Result<(), QuipayError> {
+        if caller == employer {
+            return Ok(());
+        }
+        let registry = Self::get_registry(env.clone()).ok_or(QuipayError::Unauthorized)?;
+        let allowed: bool = env.invoke_contract(
+            &registry,
+            &Symbol::new(env, "can_act_for"),
+            soroban_sdk::vec![
+                env,
+                employer.into_val(env),
+                caller.into_val(env),
+                action.into_val(env),
+            ],
+        );
+        require!(allowed, QuipayError::Unauthorized);
+        Ok(())
+    }
+
+    /// The worker's preferred token, which the vault must allowlist.
+    fn preferred_token(env: &Env, worker: &Address) -> Result<Address, QuipayError> {
+        let registry = Self::get_registry(env.clone()).ok_or(QuipayError::NotInitialized)?;
+        let token: Option<Address> = env.invoke_contract(
+            &registry,
+            &Symbol::new(env, "get_preferred_token"),
+            soroban_sdk::vec![env, worker.into_val(env)],
+        );
+        let token = token.ok_or(QuipayError::WorkerNotFound)?;
+
+        let vault: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::Vault)
+            .ok_or(QuipayError::NotInitialized)?;
+        let allowed: bool = env.invoke_contract(
+            &vault,
+            &Symbol::new(env, "is_token_allowed"),
+            soroban_sdk::vec![env, token.into_val(env)],
+        );
+        require!(allowed, QuipayError::InvalidToken);
+        Ok(token)
+    }
+
+    fn create_preferred_stream_internal(
+        env: &Env,
+        employer: &Address,
+        params: PreferredStreamParams,
+    ) -> Result<u64, QuipayError> {
+        let token = Self::preferred_token(env, &params.worker)?;
+        let stream_id = Self::create_stream_internal(
+            env.clone(),
+            employer,
+            employer.clone(),
+            params.worker.clone(),
+            token.clone(),
+            params.rate,
+            params.cliff_ts,
+            params.start_ts,
+            params.end_ts,
+            params.metadata_hash,
+            match params.speed_curve {
+                MaybeSpeedCurve::Some(curve) => Some(curve),
+                MaybeSpeedCurve::None => None,
+            },
+        )?;
+        Ok(stream_id)
+    }
+
+    /// Block payouts to a blacklisted worker.
+    pub(crate) fn require_not_blacklisted(env: &Env, worker: &Address) -> Result<(), QuipayError> {
+        require!(
+            !Self::is_blacklisted(env, worker),
+            QuipayError::AddressBlacklisted
+        );
+        Ok(())
+    }
+
+    fn is_blacklisted(env: &Env, worker: &Address) -> bool {
+        let Some(registry) = Self::get_registry(env.clone()) else {
+            return false;
+        };
+        Self::call_registry(env, &registry, "is_blacklisted", worker.into_val(env))
+    }
+
+    /// Pay `amount` of a closing stream's claim to its worker, or hold it
+    /// while the worker is blacklisted. A held amount stays in the vault as
+    /// liability until `claim_held_payout`.
+    pub(crate) fn pay_or_hold_worker(
+        env: &Env,
+        actor: &Address,
+        vault: &Address,
+        stream_id: u64,
+        stream: &Stream,
+        amount: i128,
+    ) -> Result<(), QuipayError> {
+        if !Self::is_blacklisted(env, &stream.worker) {
+            Self::call_vault_stream_payout(
+                env,
+                vault,
+                stream_id,
+                stream.worker.clone(),
+                stream.token.clone(),
+                amount,
+            );
+            return Ok(());
+        }
+
+        let key = StreamKey::HeldPayout(stream_id);
+        let held: i128 = env.storage().persistent().get(&key).unwrap_or(0);
+        let held = held.checked_add(amount).ok_or(QuipayError::Overflow)?;
+        env.storage().persistent().set(&key, &held);
+        ttl::extend(env, &key);
+
+        events::emit(
+            env,
+            actor,
+            events::PayoutHeld {
+                stream_id,
+                worker: stream.worker.clone(),
+                token: stream.token.clone(),
+                amount,
+            },
+        );
+        Ok(())
+    }
+
+    /// Mark `worker` as active for `employer` after a stream between them opened.
+    pub(crate) fn registry_stream_opened(env: &Env, employer: &Address, worker: &Address) {
+        if let Some(registry) = Self::get_registry(env.clone()) {
+            Self::sync_registry(env, &registry, employer, worker, true);
+        }
+    }
+
+    /// Mark `worker` as inactive for `employer` once no open stream between
+    /// them remains. Call after the closed stream has been persisted.
+    pub(crate) fn registry_stream_closed(env: &Env, employer: &Address, worker: &Address) {
+        let Some(registry) = Self::get_registry(env.clone()) else {
+            return;
+        };
+
+        Self::ensure_index_migrated(env, worker);
+        let ids = Self::index_ids(
+            env,
+            &stream_index::StreamIndex::WorkerActive(worker.clone()),
+        );
+        for id in ids.iter() {
+            let open = env
+                .storage()
+                .persistent()
+                .get::<StreamKey, Stream>(&StreamKey::Stream(id))
+                .is_some_and(|stream| stream.employer == *employer && !Self::is_closed(&stream));
+            if open {
+                return;
+            }
+        }
+
+        Self::sync_registry(env, &registry, employer, worker, false);
+    }
+
+    fn sync_registry(
+        env: &Env,
+        registry: &Address,
+        employer: &Address,
+        worker: &Address,
+        active: bool,
+    ) {
+        env.invoke_contract::<()>(
+            registry,
+            &Symbol::new(env, "sync_stream_active"),
+            soroban_sdk::vec![
+                env,
+                employer.into_val(env),
+                worker.into_val(env),
+                active.into_val(env),
+            ],
+        );
+    }
+
+    fn call_registry(env: &Env, registry: &Address, func: &str, account: soroban_sdk::Val) -> bool {
+        env.invoke_contract(
+            registry,
+            &Symbol::new(env, func),
+            soroban_sdk::vec![env, account],
+        )
+    }
+}
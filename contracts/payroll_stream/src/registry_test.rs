@@ -0,0 +1,504 @@
+//! Tests for WorkforceRegistry enforcement in PayrollStream.
+
+use super::*;
+use payroll_vault::{PayrollVault, PayrollVaultClient};
+use soroban_sdk::{
+    Address, Env, String,
+    testutils::{Address as _, Ledger as _},
+    token,
+};
+use workforce_registry::{WorkforceRegistryContract, WorkforceRegistryContractClient};
+
+struct Setup<'a> {
+    stream: PayrollStreamClient<'a>,
+    vault: PayrollVaultClient<'a>,
+    registry: WorkforceRegistryContractClient<'a>,
+    employer: Address,
+    worker: Address,
+    token: Address,
+}
+
+fn setup(env: &Env) -> Setup<'_> {
+    env.mock_all_auths_allowing_non_root_auth();
+    env.ledger().with_mut(|li| li.timestamp = 0);
+
+    let admin = Address::generate(env);
+    let token = env
+        .register_stellar_asset_contract_v2(Address::generate(env))
+        .address();
+
+    let vault = PayrollVaultClient::new(env, &env.register(PayrollVault, ()));
+    let stream = PayrollStreamClient::new(env, &env.register(PayrollStream, ()));
+    let registry =
+        WorkforceRegistryContractClient::new(env, &env.register(WorkforceRegistryContract, ()));
+
+    vault.initialize(&admin);
+    vault.set_token_allowlist_enforced(&false);
+    stream.init(&admin);
+    stream.set_min_stream_duration(&0u64);
+    stream.set_withdrawal_cooldown(&0u64);
+    stream.set_cancellation_grace_period(&0u64);
+    vault.set_authorized_contract(&stream.address);
+    stream.set_vault(&vault.address);
+
+    registry.initialize(&admin);
+    registry.set_stream_contract(&Some(stream.address.clone()));
+    stream.set_registry(&Some(registry.address.clone()));
+
+    let depositor = Address::generate(env);
+    token::StellarAssetClient::new(env, &token).mint(&depositor, &10_000);
+    vault.deposit(&depositor, &token, &10_000);
+
+    let worker = Address::generate(env);
+    register(env, &registry, &worker, &token);
+
+    Setup {
+        stream,
+        vault,
+        registry,
+        employer: Address::generate(env),
+        worker,
+        token,
+    }
+}
+
+fn register(
+    env: &Env,
+    registry: &WorkforceRegistryContractClient,
+    worker: &Address,
+    token: &Address,
+) {
+    registry.register_worker(worker, token, &String::from_str(env, "QmProfile"));
+}
+
+/// Mark the setup's employer KYB-verified through an approved verifier.
+fn verify_employer(env: &Env, s: &Setup) {
+    let verifier = Address::generate(env);
+    s.registry.set_kyb_verifier(&verifier, &true);
+    s.registry.set_kyb_status(
+        &verifier,
+        &s.employer,
+        &workforce_registry::KybStatus::Verified,
+    );
+}
+
+fn create(s: &Setup, worker: &Address, end: u64) -> Result<u64, QuipayError> {
+    match s.stream.try_create_stream(
+        &s.employer,
+        worker,
+        &s.token,
+        &1,
+        &0u64,
+        &0u64,
+        &end,
+        &None,
+        &None,
+    ) {
+        Ok(Ok(id)) => Ok(id),
+        Err(Ok(err)) => Err(err),
+        _ => panic!("unexpected create_stream result"),
+    }
+}
+
+fn listed() -> payroll_vault::TokenConfig {
+    payroll_vault::TokenConfig {
+        enabled: true,
+        deposit_cap: 0,
+        min_liability: 0,
+    }
+}
+
+fn active_workers(s: &Setup) -> u32 {
+    s.registry
+        .get_workers_by_employer(&s.employer, &0, &10)
+        .len()
+}
+
+#[test]
+fn test_create_requires_registered_worker() {
+    let env = Env::default();
+    let s = setup(&env);
+
+    let unregistered = Address::generate(&env);
+    assert_eq!(
+        create(&s, &unregistered, 100),
+        Err(QuipayError::WorkerNotFound)
+    );
+
+    s.registry
+        .set_blacklisted(&s.registry.get_admin(), &s.worker, &true);
+    assert_eq!(
+        create(&s, &s.worker, 100),
+        Err(QuipayError::AddressBlacklisted)
+    );
+
+    s.registry
+        .set_blacklisted(&s.registry.get_admin(), &s.worker, &false);
+    assert!(create(&s, &s.worker, 100).is_ok());
+
+    // Without a registry, any address can be paid.
+    s.stream.set_registry(&None);
+    assert!(create(&s, &unregistered, 100).is_ok());
+}
+
+#[test]
+fn test_active_index_follows_stream_lifecycle() {
+    let env = Env::default();
+    let s = setup(&env);
+
+    let short = create(&s, &s.worker, 100).unwrap();
+    let long = create(&s, &s.worker, 1_000).unwrap();
+    assert_eq!(active_workers(&s), 1);
+
+    // Completing one stream keeps the worker active while another is open.
+    env.ledger().with_mut(|li| li.timestamp = 100);
+    s.stream.withdraw(&short, &s.worker);
+    assert_eq!(
+        s.stream.get_stream(&short).unwrap().status,
+        StreamStatus::Completed
+    );
+    assert_eq!(active_workers(&s), 1);
+
+    s.stream.cancel_stream(&long, &s.employer, &None);
+    assert_eq!(active_workers(&s), 0);
+}
+
+#[test]
+fn test_blacklisted_worker_cannot_be_paid() {
+    let env = Env::default();
+    let s = setup(&env);
+
+    let id = create(&s, &s.worker, 100).unwrap();
+    env.ledger().with_mut(|li| li.timestamp = 50);
+    s.registry
+        .set_blacklisted(&s.registry.get_admin(), &s.worker, &true);
+
+    assert_eq!(
+        s.stream.try_withdraw(&id, &s.worker),
+        Err(Ok(QuipayError::AddressBlacklisted))
+    );
+    assert_eq!(
+        s.stream.try_batch_claim(&s.worker),
+        Err(Ok(QuipayError::AddressBlacklisted))
+    );
+
+    s.registry
+        .set_blacklisted(&s.registry.get_admin(), &s.worker, &false);
+    assert_eq!(s.stream.withdraw(&id, &s.worker), 50);
+}
+
+#[test]
+fn test_cancel_holds_blacklisted_workers_pay() {
+    let env = Env::default();
+    let s = setup(&env);
+    let token = token::Client::new(&env, &s.token);
+
+    let id = create(&s, &s.worker, 100).unwrap();
+    env.ledger().with_mut(|li| li.timestamp = 50);
+    s.registry
+        .set_blacklisted(&s.registry.get_admin(), &s.worker, &true);
+
+    // The employer can still cancel: the unvested 50 is released and the
+    // vested 50 stays reserved for the worker.
+    s.stream.cancel_stream(&id, &s.employer, &None);
+    assert_eq!(
+        s.stream.get_stream(&id).unwrap().status,
+        StreamStatus::Canceled
+    );
+    assert_eq!(s.stream.get_held_payout(&id), 50);
+    assert_eq!(s.vault.get_total_liability(&s.token), 50);
+    assert_eq!(token.balance(&s.worker), 0);
+
+    assert_eq!(
+        s.stream.try_claim_held_payout(&id, &s.worker),
+        Err(Ok(QuipayError::AddressBlacklisted))
+    );
+
+    s.registry
+        .set_blacklisted(&s.registry.get_admin(), &s.worker, &false);
+    assert_eq!(s.stream.claim_held_payout(&id, &s.worker), 50);
+    assert_eq!(token.balance(&s.worker), 50);
+    assert_eq!(s.stream.get_held_payout(&id), 0);
+    assert_eq!(s.vault.get_total_liability(&s.token), 0);
+    assert_eq!(s.stream.claim_held_payout(&id, &s.worker), 0);
+}
+
+#[test]
+fn test_held_payout_claim_reports_pro_rata_share_when_insolvent() {
+    let env = Env::default();
+    let s = setup(&env);
+
+    let id = create(&s, &s.worker, 100).unwrap();
+    env.ledger().with_mut(|li| li.timestamp = 50);
+    s.registry
+        .set_blacklisted(&s.registry.get_admin(), &s.worker, &true);
+    s.stream.cancel_stream(&id, &s.employer, &None);
+    s.registry
+        .set_blacklisted(&s.registry.get_admin(), &s.worker, &false);
+
+    // Half the backing of the held 50 is lost.
+    env.as_contract(&s.vault.address, || {
+        env.storage().persistent().set(
+            &payroll_vault::StateKey::TreasuryBalance(s.token.clone()),
+            &25i128,
+        );
+    });
+
+    assert_eq!(s.stream.claim_held_payout(&id, &s.worker), 25);
+    assert_eq!(token::Client::new(&env, &s.token).balance(&s.worker), 25);
+    assert_eq!(s.stream.get_stream_iou(&id), 25);
+    assert_eq!(s.stream.get_held_payout(&id), 0);
+}
+
+#[test]
+fn test_dispute_payout_held_for_blacklisted_worker() {
+    let env = Env::default();
+    let s = setup(&env);
+    let admin = Address::generate(&env);
+    s.stream.grant_role(&Role::Arbitrator, &admin);
+
+    let id = create(&s, &s.worker, 100).unwrap();
+    env.ledger().with_mut(|li| li.timestamp = 100);
+    s.stream
+        .raise_dispute(&id, &s.employer, &BytesN::from_array(&env, &[7; 32]));
+    s.registry
+        .set_blacklisted(&s.registry.get_admin(), &s.worker, &true);
+
+    s.stream
+        .resolve_dispute(&id, &admin, &DisputeOutcome::CancelWithPartialPayout);
+    assert_eq!(s.stream.get_held_payout(&id), 100);
+    assert_eq!(s.vault.get_total_liability(&s.token), 100);
+    assert_eq!(token::Client::new(&env, &s.token).balance(&s.worker), 0);
+}
+
+#[test]
+fn test_transfer_moves_active_status() {
+    let env = Env::default();
+    let s = setup(&env);
+    let id = create(&s, &s.worker, 100).unwrap();
+
+    let unregistered = Address::generate(&env);
+    assert_eq!(
+        s.stream
+            .try_transfer_stream(&id, &unregistered, &s.employer),
+        Err(Ok(QuipayError::WorkerNotFound))
+    );
+
+    let new_worker = Address::generate(&env);
+    register(&env, &s.registry, &new_worker, &s.token);
+    s.stream.transfer_stream(&id, &new_worker, &s.employer);
+
+    let active = s.registry.get_workers_by_employer(&s.employer, &0, &10);
+    assert_eq!(active.len(), 1);
+    assert_eq!(active.get(0).unwrap().wallet, new_worker);
+}
+
+#[test]
+fn test_create_requires_min_kyc_level() {
+    let env = Env::default();
+    let s = setup(&env);
+    let attester = Address::generate(&env);
+    s.registry.set_attester(&attester, &true);
+    s.stream.set_min_kyc_level(&2);
+
+    assert_eq!(
+        create(&s, &s.worker, 100),
+        Err(QuipayError::AttestationRequired)
+    );
+
+    let provider_ref = soroban_sdk::BytesN::from_array(&env, &[9; 32]);
+    let jurisdiction = String::from_str(&env, "DE");
+    s.registry
+        .attest(&attester, &s.worker, &1, &jurisdiction, &500, &provider_ref);
+    assert_eq!(
+        create(&s, &s.worker, 100),
+        Err(QuipayError::AttestationRequired)
+    );
+
+    s.registry
+        .attest(&attester, &s.worker, &2, &jurisdiction, &500, &provider_ref);
+    assert!(create(&s, &s.worker, 100).is_ok());
+
+    // Expired attestations no longer qualify.
+    env.ledger().with_mut(|li| li.timestamp = 500);
+    let result = s.stream.try_create_stream(
+        &s.employer,
+        &s.worker,
+        &s.token,
+        &1,
+        &500u64,
+        &500u64,
+        &1_000u64,
+        &None,
+        &None,
+    );
+    assert_eq!(result, Err(Ok(QuipayError::AttestationRequired)));
+}
+
+#[test]
+fn test_employer_delegates_act_by_role() {
+    let env = Env::default();
+    let s = setup(&env);
+    let name_hash = soroban_sdk::BytesN::from_array(&env, &[1; 32]);
+    s.registry.register_employer(&s.employer, &name_hash);
+
+    let hr = Address::generate(&env);
+    let finance = Address::generate(&env);
+    let viewer = Address::generate(&env);
+    s.registry.set_manager(
+        &s.employer,
+        &s.employer,
+        &hr,
+        &workforce_registry::EmployerRole::Hr,
+    );
+    s.registry.set_manager(
+        &s.employer,
+        &s.employer,
+        &finance,
+        &workforce_registry::EmployerRole::Finance,
+    );
+    s.registry.set_manager(
+        &s.employer,
+        &s.employer,
+        &viewer,
+        &workforce_registry::EmployerRole::Viewer,
+    );
+
+    let create_as = |delegate: &Address| {
+        s.stream.try_create_stream_as_delegate(
+            delegate,
+            &s.employer,
+            &s.worker,
+            &s.token,
+            &1,
+            &0u64,
+            &0u64,
+            &100u64,
+            &None,
+            &None,
+        )
+    };
+    // Delegates only act once the employer is KYB-verified.
+    assert_eq!(create_as(&hr), Err(Ok(QuipayError::Unauthorized)));
+    verify_employer(&env, &s);
+
+    assert_eq!(create_as(&viewer), Err(Ok(QuipayError::Unauthorized)));
+    let id = create_as(&hr).unwrap().unwrap();
+    assert_eq!(s.stream.get_stream(&id).unwrap().employer, s.employer);
+
+    // Finance may pause but not cancel; HR may cancel but not pause.
+    assert_eq!(
+        s.stream.try_pause_stream(&id, &hr),
+        Err(Ok(QuipayError::Unauthorized))
+    );
+    s.stream.pause_stream(&id, &finance);
+    s.stream.resume_stream(&id, &finance);
+    assert_eq!(
+        s.stream.try_cancel_stream(&id, &finance, &None),
+        Err(Ok(QuipayError::Unauthorized))
+    );
+    s.stream.cancel_stream(&id, &hr, &None);
+    assert_eq!(
+        s.stream.get_stream(&id).unwrap().status,
+        StreamStatus::Canceled
+    );
+
+    // Removed managers lose access; without a registry only the employer can act.
+    s.registry.remove_manager(&s.employer, &s.employer, &hr);
+    assert_eq!(create_as(&hr), Err(Ok(QuipayError::Unauthorized)));
+    s.stream.set_registry(&None);
+    assert_eq!(create_as(&finance), Err(Ok(QuipayError::Unauthorized)));
+}
+
+#[test]
+fn test_create_can_require_verified_employer() {
+    let env = Env::default();
+    let s = setup(&env);
+    assert!(!s.stream.requires_verified_employer());
+    s.stream.set_require_verified_employer(&true);
+
+    assert_eq!(
+        create(&s, &s.worker, 100),
+        Err(QuipayError::EmployerNotVerified)
+    );
+    s.registry.register_employer(
+        &s.employer,
+        &soroban_sdk::BytesN::from_array(&env, &[1; 32]),
+    );
+    assert_eq!(
+        create(&s, &s.worker, 100),
+        Err(QuipayError::EmployerNotVerified)
+    );
+    verify_employer(&env, &s);
+    assert!(create(&s, &s.worker, 100).is_ok());
+
+    s.stream.set_registry(&None);
+    assert_eq!(create(&s, &s.worker, 100), Err(QuipayError::NotInitialized));
+}
+
+#[test]
+fn test_create_in_preferred_token() {
+    let env = Env::default();
+    let s = setup(&env);
+
+    let create = |worker: &Address| {
+        s.stream
+            .try_create_preferred_stream(&s.employer, worker, &1, &0, &0, &100, &None, &None)
+    };
+    assert_eq!(create(&s.worker), Err(Ok(QuipayError::InvalidToken)));
+    assert_eq!(
+        create(&Address::generate(&env)),
+        Err(Ok(QuipayError::WorkerNotFound))
+    );
+
+    s.vault.set_token_config(&s.token, &listed());
+    let id = create(&s.worker).unwrap().unwrap();
+    assert_eq!(s.stream.get_stream(&id).unwrap().token, s.token);
+
+    s.stream.set_registry(&None);
+    assert_eq!(create(&s.worker), Err(Ok(QuipayError::NotInitialized)));
+}
+
+#[test]
+fn test_batch_create_in_preferred_tokens() {
+    let env = Env::default();
+    let s = setup(&env);
+    s.vault.set_token_config(&s.token, &listed());
+
+    let colleague = Address::generate(&env);
+    register(&env, &s.registry, &colleague, &s.token);
+    let params = |worker: &Address| PreferredStreamParams {
+        worker: worker.clone(),
+        rate: 1,
+        cliff_ts: 0,
+        start_ts: 0,
+        end_ts: 100,
+        metadata_hash: None,
+        speed_curve: MaybeSpeedCurve::None,
+    };
+
+    let ids = s.stream.batch_create_preferred_streams(
+        &s.employer,
+        &soroban_sdk::vec![&env, params(&s.worker), params(&colleague)],
+    );
+    assert_eq!(ids.len(), 2);
+    assert_eq!(
+        s.stream.get_stream(&ids.get(1).unwrap()).unwrap().worker,
+        colleague
+    );
+
+    // A worker preferring a token the vault does not list aborts the batch.
+    let contractor = Address::generate(&env);
+    register(&env, &s.registry, &contractor, &Address::generate(&env));
+    let result = s.stream.try_batch_create_preferred_streams(
+        &s.employer,
+        &soroban_sdk::vec![&env, params(&s.worker), params(&contractor)],
+    );
+    assert_eq!(result, Err(Ok(QuipayError::InvalidToken)));
+    assert_eq!(
+        s.stream
+            .get_streams_by_employer(&s.employer, &None, &None)
+            .len(),
+        2
+    );
+}
@@ -0,0 +1,234 @@
+//! Employer-level runway forecasting.
+//!
+//! `get_stream_health` looks at one stream in isolation. The forecast here
+//! aggregates every open stream of an employer per token and projects how the
+//! amount workers will be able to claim grows over time, using the same
+//! `vested_amount_at` logic as withdrawals (curves, cliffs, pauses and pending
+//! cancellations are all honoured).
+//!
+//! The balance a forecast is compared against is the share of the vault's
+//! tracked treasury that is not reserved for other employers' streams:
+//! `treasury_balance - (total_liability - employer_outstanding)`. No future
+//! deposits are assumed.
+
+use super::*;
+
+/// Maximum number of points in a forecast time series.
+const MAX_RUNWAY_POINTS: u32 = 100;
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// One sample of a runway forecast.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct RunwayPoint {
+    pub timestamp: u64,
+    /// Amount the employer's workers will be able to claim by `timestamp`
+    /// (vested but not yet withdrawn).
+    pub projected_liability: i128,
+}
+
+/// Runway forecast for one token across all of an employer's open streams.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct TokenRunway {
+    pub token: Address,
+    /// Vault funds available to cover this employer's streams.
+    pub balance: i128,
+    /// Remaining (unwithdrawn) amount across the employer's open streams.
+    pub outstanding: i128,
+    /// Amount that vests over the next 24 hours at current rates.
+    pub daily_burn: i128,
+    pub points: Vec<RunwayPoint>,
+    /// First timestamp at which claimable amounts exceed `balance`, if ever.
+    pub insolvent_at: Option<u64>,
+}
+
+#[soroban_sdk::contractimpl]
+impl PayrollStream {
+    /// Forecast runway for every token an employer streams.
+    ///
+    /// Returns one `TokenRunway` per token with `points + 1` evenly spaced
+    /// samples covering `[now, now + horizon_secs]`.
+    pub fn get_employer_runway(
+        env: Env,
+        employer: Address,
+        horizon_secs: u64,
+        points: u32,
+    ) -> Result<Vec<TokenRunway>, QuipayError> {
+        if points == 0 {
+            return Err(QuipayError::InvalidAmount);
+        }
+        if points > MAX_RUNWAY_POINTS {
+            return Err(QuipayError::BatchTooLarge);
+        }
+        let vault: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::Vault)
+            .ok_or(QuipayError::NotInitialized)?;
+
+        let streams = Self::open_streams_by_token(&env, &employer);
+        let now = env.ledger().timestamp();
+        let mut out = Vec::new(&env);
+
+        for (token, token_streams) in streams.iter() {
+            let mut outstanding: i128 = 0;
+            let mut last_end = now;
+            for stream in token_streams.iter() {
+                outstanding = outstanding
+                    .saturating_add(stream.total_amount.saturating_sub(stream.withdrawn_amount));
+                last_end = last_end.max(Self::projected_end(&stream));
+            }
+
+            let treasury = Self::call_vault_view(&env, &vault, "get_treasury_balance", &token);
+            let liability = Self::call_vault_view(&env, &vault, "get_total_liability", &token);
+            let reserved_for_others = liability.saturating_sub(outstanding).max(0);
+            let balance = treasury.saturating_sub(reserved_for_others).max(0);
+
+            let claimable_now = Self::claimable_at(&token_streams, now);
+            let daily_burn =
+                Self::claimable_at(&token_streams, now.saturating_add(SECONDS_PER_DAY))
+                    .saturating_sub(claimable_now);
+
+            let mut series = Vec::new(&env);
+            let mut i: u32 = 0;
+            while i <= points {
+                let offset = (horizon_secs as u128 * i as u128 / points as u128) as u64;
+                let ts = now.saturating_add(offset);
+                series.push_back(RunwayPoint {
+                    timestamp: ts,
+                    projected_liability: Self::claimable_at(&token_streams, ts),
+                });
+                i += 1;
+            }
+
+            let insolvent_at = Self::first_insolvent_ts(&token_streams, balance, now, last_end);
+
+            out.push_back(TokenRunway {
+                token,
+                balance,
+                outstanding,
+                daily_burn,
+                points: series,
+                insolvent_at,
+            });
+        }
+
+        Ok(out)
+    }
+
+    /// Obligations of `employer` in `token` as `(outstanding, claimable, burn)`:
+    /// the unwithdrawn total of its open streams, the part workers can claim
+    /// now, and what its running streams pay out over `horizon_secs` at their
+    /// current rates, whether or not they end within the horizon.
+    ///
+    /// Purely stream-side (no vault calls), so the vault can query it when
+    /// sizing top-ups without re-entering itself.
+    pub fn get_employer_obligations(
+        env: Env,
+        employer: Address,
+        token: Address,
+        horizon_secs: u64,
+    ) -> (i128, i128, i128) {
+        let streams = Self::open_streams_by_token(&env, &employer);
+        let Some(token_streams) = streams.get(token) else {
+            return (0, 0, 0);
+        };
+
+        let now = env.ledger().timestamp();
+        let mut outstanding: i128 = 0;
+        let mut rate: i128 = 0;
+        for stream in token_streams.iter() {
+            outstanding = outstanding
+                .saturating_add(stream.total_amount.saturating_sub(stream.withdrawn_amount));
+            if stream.status == StreamStatus::Active && Self::projected_end(&stream) > now {
+                rate = rate.saturating_add(stream.rate);
+            }
+        }
+        let burn = rate.saturating_mul(horizon_secs as i128);
+        (outstanding, Self::claimable_at(&token_streams, now), burn)
+    }
+}
+
+impl PayrollStream {
+    /// Group the employer's open streams by token.
+    fn open_streams_by_token(env: &Env, employer: &Address) -> Map<Address, Vec<Stream>> {
+        Self::ensure_index_migrated(env, employer);
+        let ids = Self::index_ids(
+            env,
+            &stream_index::StreamIndex::EmployerActive(employer.clone()),
+        );
+
+        let mut by_token: Map<Address, Vec<Stream>> = Map::new(env);
+        for id in ids.iter() {
+            let Some(stream) = env
+                .storage()
+                .persistent()
+                .get::<StreamKey, Stream>(&StreamKey::Stream(id))
+            else {
+                continue;
+            };
+            if Self::is_closed(&stream) {
+                continue;
+            }
+            let mut list = by_token
+                .get(stream.token.clone())
+                .unwrap_or_else(|| Vec::new(env));
+            list.push_back(stream.clone());
+            by_token.set(stream.token, list);
+        }
+        by_token
+    }
+
+    /// Sum of vested-but-unwithdrawn amounts at `ts`.
+    fn claimable_at(streams: &Vec<Stream>, ts: u64) -> i128 {
+        let mut total: i128 = 0;
+        for stream in streams.iter() {
+            let vested = Self::vested_amount_at(&stream, ts);
+            total = total.saturating_add(vested.saturating_sub(stream.withdrawn_amount).max(0));
+        }
+        total
+    }
+
+    /// Timestamp after which a stream accrues nothing more.
+    fn projected_end(stream: &Stream) -> u64 {
+        let end = stream.end_ts.saturating_add(stream.total_paused_duration);
+        if stream.cancel_effective_at > 0 {
+            end.min(stream.cancel_effective_at)
+        } else {
+            end
+        }
+    }
+
+    /// Binary search for the first timestamp at which claims exceed `balance`.
+    /// Claimable amounts are monotonic in time, so the search is exact.
+    fn first_insolvent_ts(streams: &Vec<Stream>, balance: i128, now: u64, end: u64) -> Option<u64> {
+        if Self::claimable_at(streams, now) > balance {
+            return Some(now);
+        }
+        if Self::claimable_at(streams, end) <= balance {
+            return None;
+        }
+
+        // Invariant: solvent at `lo`, insolvent at `hi`.
+        let mut lo = now;
+        let mut hi = end;
+        while hi - lo > 1 {
+            let mid = lo + (hi - lo) / 2;
+            if Self::claimable_at(streams, mid) > balance {
+                hi = mid;
+            } else {
+                lo = mid;
+            }
+        }
+        Some(hi)
+    }
+
+    fn call_vault_view(env: &Env, vault: &Address, func: &str, token: &Address) -> i128 {
+        env.invoke_contract(
+            vault,
+            &Symbol::new(env, func),
+            soroban_sdk::vec![env, token.clone().into_val(env)],
+        )
+    }
+}
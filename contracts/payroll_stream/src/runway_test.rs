@@ -0,0 +1,188 @@
+//! Tests for the employer-level runway forecast.
+
+use super::*;
+use payroll_vault::{PayrollVault, PayrollVaultClient};
+use soroban_sdk::{
+    Address, Env,
+    testutils::{Address as _, Ledger as _},
+    token,
+};
+
+fn setup(
+    env: &Env,
+    deposit: i128,
+) -> (
+    PayrollStreamClient<'_>,
+    PayrollVaultClient<'_>,
+    Address,
+    Address,
+) {
+    let admin = Address::generate(env);
+    let employer = Address::generate(env);
+    let depositor = Address::generate(env);
+
+    let token_admin = Address::generate(env);
+    let token_id = env
+        .register_stellar_asset_contract_v2(token_admin)
+        .address();
+
+    let vault_id = env.register(PayrollVault, ());
+    let stream_id = env.register(PayrollStream, ());
+    let vault_client = PayrollVaultClient::new(env, &vault_id);
+    let stream_client = PayrollStreamClient::new(env, &stream_id);
+
+    vault_client.initialize(&admin);
+    vault_client.set_token_allowlist_enforced(&false);
+    stream_client.init(&admin);
+    stream_client.set_min_stream_duration(&0u64);
+    stream_client.set_withdrawal_cooldown(&0u64);
+    vault_client.set_authorized_contract(&stream_id);
+    stream_client.set_vault(&vault_id);
+
+    token::StellarAssetClient::new(env, &token_id).mint(&depositor, &deposit);
+    vault_client.deposit(&depositor, &token_id, &deposit);
+
+    (stream_client, vault_client, employer, token_id)
+}
+
+fn create(
+    client: &PayrollStreamClient,
+    employer: &Address,
+    token: &Address,
+    rate: i128,
+    cliff: u64,
+    end: u64,
+) -> u64 {
+    let worker = Address::generate(&client.env);
+    client.create_stream(
+        employer, &worker, token, &rate, &cliff, &0u64, &end, &None, &None,
+    )
+}
+
+#[test]
+fn test_runway_aggregates_streams_per_token() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    env.ledger().with_mut(|li| li.timestamp = 0);
+
+    let (client, _vault, employer, token) = setup(&env, 10_000);
+    create(&client, &employer, &token, 1, 0, 1_000);
+    create(&client, &employer, &token, 2, 0, 1_000);
+
+    let forecast = client.get_employer_runway(&employer, &1_000, &4);
+    assert_eq!(forecast.len(), 1);
+    let runway = forecast.get(0).unwrap();
+    assert_eq!(runway.token, token);
+    assert_eq!(runway.outstanding, 3_000);
+    assert_eq!(runway.balance, 10_000);
+    // 3 units/sec, capped at the streams' end after 1_000s.
+    assert_eq!(runway.daily_burn, 3_000);
+    assert_eq!(runway.insolvent_at, None);
+
+    assert_eq!(runway.points.len(), 5);
+    let expected = [
+        (0, 0),
+        (250, 750),
+        (500, 1_500),
+        (750, 2_250),
+        (1_000, 3_000),
+    ];
+    for (i, (ts, liability)) in expected.iter().enumerate() {
+        let point = runway.points.get(i as u32).unwrap();
+        assert_eq!(point.timestamp, *ts);
+        assert_eq!(point.projected_liability, *liability);
+    }
+}
+
+#[test]
+fn test_runway_reports_insolvency_timestamp() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    env.ledger().with_mut(|li| li.timestamp = 0);
+
+    let (client, vault, employer, token) = setup(&env, 2_000);
+    create(&client, &employer, &token, 1, 0, 1_000);
+    create(&client, &employer, &token, 1, 0, 1_000);
+
+    // Half of the treasury is lost; claims pass 1_000 just after t = 500.
+    env.as_contract(&vault.address, || {
+        env.storage().persistent().set(
+            &payroll_vault::StateKey::TreasuryBalance(token.clone()),
+            &1_000i128,
+        );
+    });
+
+    let runway = client
+        .get_employer_runway(&employer, &1_000, &10)
+        .get(0)
+        .unwrap();
+    assert_eq!(runway.balance, 1_000);
+    assert_eq!(runway.insolvent_at, Some(501));
+}
+
+#[test]
+fn test_runway_excludes_other_employers_liability() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    env.ledger().with_mut(|li| li.timestamp = 0);
+
+    let (client, _vault, employer, token) = setup(&env, 10_000);
+    let other_employer = Address::generate(&env);
+    create(&client, &other_employer, &token, 5, 0, 1_000);
+    create(&client, &employer, &token, 2, 0, 1_000);
+
+    let runway = client
+        .get_employer_runway(&employer, &1_000, &1)
+        .get(0)
+        .unwrap();
+    // 10_000 treasury minus the 5_000 reserved for the other employer.
+    assert_eq!(runway.balance, 5_000);
+    assert_eq!(runway.outstanding, 2_000);
+}
+
+#[test]
+fn test_runway_honours_cliff_pause_and_pending_cancel() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    env.ledger().with_mut(|li| li.timestamp = 0);
+
+    let (client, _vault, employer, token) = setup(&env, 10_000);
+    client.set_cancellation_grace_period(&100u64);
+
+    create(&client, &employer, &token, 1, 500, 1_000);
+    let paused_stream = create(&client, &employer, &token, 1, 0, 1_000);
+    let cancelled_stream = create(&client, &employer, &token, 1, 0, 1_000);
+
+    env.ledger().with_mut(|li| li.timestamp = 100);
+    client.pause_stream(&paused_stream, &employer);
+    client.cancel_stream(&cancelled_stream, &employer, &None);
+
+    let runway = client
+        .get_employer_runway(&employer, &900, &2)
+        .get(0)
+        .unwrap();
+
+    // t=100: cliff not reached (0), paused at 100 (100), cancelling (100).
+    assert_eq!(runway.points.get(0).unwrap().projected_liability, 200);
+    // t=550: cliff passed (550), paused stays at 100, cancel capped at 200.
+    assert_eq!(runway.points.get(1).unwrap().projected_liability, 850);
+    // t=1000: cliff stream finished (1_000).
+    assert_eq!(runway.points.get(2).unwrap().projected_liability, 1_300);
+}
+
+#[test]
+fn test_runway_rejects_invalid_point_count() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+
+    let (client, _vault, employer, _token) = setup(&env, 1_000);
+    assert_eq!(
+        client.try_get_employer_runway(&employer, &1_000, &0),
+        Err(Ok(QuipayError::InvalidAmount))
+    );
+    assert_eq!(
+        client.try_get_employer_runway(&employer, &1_000, &101),
+        Err(Ok(QuipayError::BatchTooLarge))
+    );
+    assert_eq!(client.get_employer_runway(&employer, &1_000, &1).len(), 0);
+}
@@ -1,4 +1,5 @@
 use super::*;
+use events::StreamExtended;
 
 #[soroban_sdk::contractimpl]
 impl PayrollStream {
@@ -89,6 +90,14 @@ impl PayrollStream {
                     additional_amount.into_val(&env),
                 ],
             );
+            Self::call_vault_charge_fee(
+                &env,
+                &vault,
+                stream_id,
+                &stream.employer,
+                &stream.token,
+                additional_amount,
+            );
 
             // Update stream total amount
             stream.total_amount = stream
@@ -121,14 +130,16 @@ impl PayrollStream {
         Self::bump_stream_storage_ttl(&env, stream_id, &stream.worker);
 
         // Emit extension event
-        env.events().publish(
-            (
-                Symbol::new(&env, "stream"),
-                Symbol::new(&env, "extended"),
+        events::emit(
+            &env,
+            &stream.employer,
+            StreamExtended {
                 stream_id,
-                stream.employer.clone(),
-            ),
-            (additional_amount, old_end_ts, new_end_time, stream.rate),
+                additional_amount,
+                old_end_ts,
+                new_end_ts: new_end_time,
+                rate: stream.rate,
+            },
         );
 
         Ok(())
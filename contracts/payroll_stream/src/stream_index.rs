@@ -0,0 +1,403 @@
+//! Chunked stream indexes.
+//!
+//! Every employer and worker has an index of active streams and one of
+//! closed streams. An index is split into chunks of at most
+//! `INDEX_CHUNK_SIZE` ids, each its own ledger entry, so creating, closing
+//! or transferring a stream rewrites one small chunk instead of the whole
+//! list. The head entry keeps the index length, which for an employer's
+//! active index is the active-stream counter checked against the stream
+//! limit. `StreamSlots(stream_id)` records which chunk holds the stream in
+//! its employer and worker index, so a stream is found without a scan.
+//!
+//! Streams created before this layout sit in the single-vector
+//! `EmployerStreams`/`WorkerStreams` entries. An account's legacy list is
+//! moved into the chunked indexes `LEGACY_MIGRATION_BATCH` ids at a time,
+//! resuming from a cursor kept next to the list: one batch each time the
+//! account's streams are touched, or eagerly with `migrate_stream_index`.
+//! A legacy stream that is closed, transferred or removed before its batch
+//! runs is placed by that operation, and the batch skips it.
+//!
+//! Until an account's legacy lists are gone its indexes are partial: the
+//! active-stream count adds the ids still waiting, so the stream limit holds,
+//! and paged views never report `complete`.
+
+use super::*;
+use events::IndexMigrated;
+
+/// Maximum number of stream ids held by one index chunk.
+pub const INDEX_CHUNK_SIZE: u32 = 64;
+/// Maximum number of legacy stream ids migrated per account list and call.
+pub const LEGACY_MIGRATION_BATCH: u32 = 50;
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum StreamIndex {
+    EmployerActive(Address),
+    EmployerClosed(Address),
+    WorkerActive(Address),
+    WorkerClosed(Address),
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct IndexHead {
+    /// Number of stream ids in the index.
+    pub len: u32,
+    /// Lowest chunk that may still hold ids.
+    pub first_chunk: u32,
+    /// One past the highest chunk ever allocated.
+    pub next_chunk: u32,
+}
+
+/// The chunk holding a stream in its employer and worker index.
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct StreamSlots {
+    pub employer_chunk: u32,
+    pub worker_chunk: u32,
+}
+
+#[soroban_sdk::contractimpl]
+impl PayrollStream {
+    /// Move the next batch of `account`'s legacy employer and worker stream
+    /// lists into the chunked indexes. Permissionless; returns the number of
+    /// legacy ids processed, so call again until it returns 0.
+    pub fn migrate_stream_index(env: Env, account: Address) -> u32 {
+        Self::migrate_legacy_index(&env, &account, true)
+            + Self::migrate_legacy_index(&env, &account, false)
+    }
+
+    /// Number of streams of `employer` that are not closed. While the
+    /// employer's legacy list is being migrated, every id still waiting in it
+    /// is counted as active.
+    pub fn get_active_stream_count(env: Env, employer: Address) -> u32 {
+        Self::ensure_index_migrated(&env, &employer);
+        Self::index_head(&env, &StreamIndex::EmployerActive(employer.clone()))
+            .len
+            .saturating_add(Self::legacy_ids_pending(&env, &employer, true))
+    }
+}
+
+impl PayrollStream {
+    /// Add a new stream to its employer's and worker's active indexes.
+    pub(crate) fn index_stream_created(
+        env: &Env,
+        stream_id: u64,
+        employer: &Address,
+        worker: &Address,
+    ) {
+        Self::ensure_index_migrated(env, employer);
+        Self::ensure_index_migrated(env, worker);
+        let slots = StreamSlots {
+            employer_chunk: Self::index_insert(
+                env,
+                &StreamIndex::EmployerActive(employer.clone()),
+                stream_id,
+            ),
+            worker_chunk: Self::index_insert(
+                env,
+                &StreamIndex::WorkerActive(worker.clone()),
+                stream_id,
+            ),
+        };
+        Self::set_stream_slots(env, stream_id, &slots);
+    }
+
+    /// Move a stream that just closed into the closed indexes.
+    pub(crate) fn index_stream_closed(env: &Env, stream_id: u64, stream: &Stream) {
+        Self::ensure_index_migrated(env, &stream.employer);
+        Self::ensure_index_migrated(env, &stream.worker);
+        let slots = Self::stream_slots(env, stream_id);
+        Self::index_remove(
+            env,
+            &StreamIndex::EmployerActive(stream.employer.clone()),
+            slots.employer_chunk,
+            stream_id,
+        );
+        Self::index_remove(
+            env,
+            &StreamIndex::WorkerActive(stream.worker.clone()),
+            slots.worker_chunk,
+            stream_id,
+        );
+        let slots = StreamSlots {
+            employer_chunk: Self::index_insert(
+                env,
+                &StreamIndex::EmployerClosed(stream.employer.clone()),
+                stream_id,
+            ),
+            worker_chunk: Self::index_insert(
+                env,
+                &StreamIndex::WorkerClosed(stream.worker.clone()),
+                stream_id,
+            ),
+        };
+        Self::set_stream_slots(env, stream_id, &slots);
+    }
+
+    /// Move an active stream from `old_worker`'s index to its new worker's.
+    pub(crate) fn index_stream_transferred(
+        env: &Env,
+        stream_id: u64,
+        old_worker: &Address,
+        new_worker: &Address,
+    ) {
+        Self::ensure_index_migrated(env, old_worker);
+        Self::ensure_index_migrated(env, new_worker);
+        let mut slots = Self::stream_slots(env, stream_id);
+        Self::index_remove(
+            env,
+            &StreamIndex::WorkerActive(old_worker.clone()),
+            slots.worker_chunk,
+            stream_id,
+        );
+        slots.worker_chunk = Self::index_insert(
+            env,
+            &StreamIndex::WorkerActive(new_worker.clone()),
+            stream_id,
+        );
+        Self::set_stream_slots(env, stream_id, &slots);
+    }
+
+    /// Drop a closed stream from the closed indexes.
+    pub(crate) fn index_stream_removed(env: &Env, stream_id: u64, stream: &Stream) {
+        Self::ensure_index_migrated(env, &stream.employer);
+        Self::ensure_index_migrated(env, &stream.worker);
+        let slots = Self::stream_slots(env, stream_id);
+        Self::index_remove(
+            env,
+            &StreamIndex::EmployerClosed(stream.employer.clone()),
+            slots.employer_chunk,
+            stream_id,
+        );
+        Self::index_remove(
+            env,
+            &StreamIndex::WorkerClosed(stream.worker.clone()),
+            slots.worker_chunk,
+            stream_id,
+        );
+        env.storage()
+            .persistent()
+            .remove(&StreamKey::StreamSlots(stream_id));
+    }
+
+    /// All ids in `index`, oldest chunk first.
+    pub(crate) fn index_ids(env: &Env, index: &StreamIndex) -> Vec<u64> {
+        Self::index_page(env, index, 0, u32::MAX)
+    }
+
+    /// Up to `limit` ids of `index`, skipping the first `offset`.
+    pub(crate) fn index_page(env: &Env, index: &StreamIndex, offset: u32, limit: u32) -> Vec<u64> {
+        let head = Self::index_head(env, index);
+        let mut ids = Vec::new(env);
+        let mut skip = offset;
+        let mut chunk = head.first_chunk;
+        while chunk < head.next_chunk && ids.len() < limit {
+            let chunk_ids = Self::index_chunk(env, index, chunk);
+            chunk += 1;
+            if skip >= chunk_ids.len() {
+                skip -= chunk_ids.len();
+                continue;
+            }
+            for id in chunk_ids.iter().skip(skip as usize) {
+                if ids.len() >= limit {
+                    break;
+                }
+                ids.push_back(id);
+            }
+            skip = 0;
+        }
+        ids
+    }
+
+    pub(crate) fn index_head(env: &Env, index: &StreamIndex) -> IndexHead {
+        env.storage()
+            .persistent()
+            .get(&StreamKey::IndexHead(index.clone()))
+            .unwrap_or_default()
+    }
+
+    /// Move the next batch of `account`'s legacy lists into the chunked
+    /// indexes, if any remain.
+    pub(crate) fn ensure_index_migrated(env: &Env, account: &Address) {
+        Self::migrate_legacy_index(env, account, true);
+        Self::migrate_legacy_index(env, account, false);
+    }
+
+    /// Whether either legacy list of `account` still waits to be migrated.
+    pub(crate) fn has_legacy_index(env: &Env, account: &Address) -> bool {
+        let storage = env.storage().persistent();
+        storage.has(&StreamKey::EmployerStreams(account.clone()))
+            || storage.has(&StreamKey::WorkerStreams(account.clone()))
+    }
+
+    /// Number of ids of `account`'s legacy employer or worker list that have
+    /// not been migrated yet.
+    fn legacy_ids_pending(env: &Env, account: &Address, as_employer: bool) -> u32 {
+        let (legacy_key, cursor_key) = Self::legacy_keys(account, as_employer);
+        let storage = env.storage().persistent();
+        let Some(ids) = storage.get::<StreamKey, Vec<u64>>(&legacy_key) else {
+            return 0;
+        };
+        let start: u32 = storage.get(&cursor_key).unwrap_or(0);
+        ids.len().saturating_sub(start)
+    }
+
+    fn legacy_keys(account: &Address, as_employer: bool) -> (StreamKey, StreamKey) {
+        if as_employer {
+            (
+                StreamKey::EmployerStreams(account.clone()),
+                StreamKey::EmployerStreamsCursor(account.clone()),
+            )
+        } else {
+            (
+                StreamKey::WorkerStreams(account.clone()),
+                StreamKey::WorkerStreamsCursor(account.clone()),
+            )
+        }
+    }
+
+    /// Migrate up to `LEGACY_MIGRATION_BATCH` ids of a legacy list from its
+    /// cursor. Returns the number of ids processed.
+    fn migrate_legacy_index(env: &Env, account: &Address, as_employer: bool) -> u32 {
+        let (legacy_key, cursor_key) = Self::legacy_keys(account, as_employer);
+        let storage = env.storage().persistent();
+        let Some(ids) = storage.get::<StreamKey, Vec<u64>>(&legacy_key) else {
+            return 0;
+        };
+        let start: u32 = storage.get(&cursor_key).unwrap_or(0);
+        let end = start.saturating_add(LEGACY_MIGRATION_BATCH).min(ids.len());
+
+        for pos in start..end {
+            let stream_id = ids.get_unchecked(pos);
+            let Some(stream) = storage.get::<StreamKey, Stream>(&StreamKey::Stream(stream_id))
+            else {
+                continue;
+            };
+            let owner = if as_employer {
+                &stream.employer
+            } else {
+                &stream.worker
+            };
+            // Transferred to another worker since the list was written.
+            if owner != account {
+                continue;
+            }
+            let closed = Self::is_closed(&stream);
+            let index = match (as_employer, closed) {
+                (true, false) => StreamIndex::EmployerActive(account.clone()),
+                (true, true) => StreamIndex::EmployerClosed(account.clone()),
+                (false, false) => StreamIndex::WorkerActive(account.clone()),
+                (false, true) => StreamIndex::WorkerClosed(account.clone()),
+            };
+            let mut slots = Self::stream_slots(env, stream_id);
+            let slot = if as_employer {
+                &mut slots.employer_chunk
+            } else {
+                &mut slots.worker_chunk
+            };
+            // Already placed by an operation on the stream.
+            if Self::index_chunk(env, &index, *slot).contains(stream_id) {
+                continue;
+            }
+            *slot = Self::index_insert(env, &index, stream_id);
+            Self::set_stream_slots(env, stream_id, &slots);
+        }
+
+        if end >= ids.len() {
+            storage.remove(&legacy_key);
+            storage.remove(&cursor_key);
+        } else {
+            storage.set(&cursor_key, &end);
+            ttl::extend(env, &cursor_key);
+        }
+
+        events::emit_from_contract(
+            env,
+            IndexMigrated {
+                account: account.clone(),
+                as_employer,
+                streams: end - start,
+            },
+        );
+        end - start
+    }
+
+    /// Append `stream_id` to `index`. Returns the chunk it was written to.
+    fn index_insert(env: &Env, index: &StreamIndex, stream_id: u64) -> u32 {
+        let mut head = Self::index_head(env, index);
+        let mut chunk = head.next_chunk.saturating_sub(1);
+        let mut ids = if head.next_chunk == 0 {
+            Vec::new(env)
+        } else {
+            Self::index_chunk(env, index, chunk)
+        };
+        if head.next_chunk == 0 || ids.len() >= INDEX_CHUNK_SIZE {
+            chunk = head.next_chunk;
+            head.next_chunk += 1;
+            ids = Vec::new(env);
+        }
+        ids.push_back(stream_id);
+        head.len += 1;
+
+        env.storage()
+            .persistent()
+            .set(&StreamKey::IndexChunk(index.clone(), chunk), &ids);
+        env.storage()
+            .persistent()
+            .set(&StreamKey::IndexHead(index.clone()), &head);
+        chunk
+    }
+
+    /// Remove `stream_id` from `chunk` of `index`, if it is there.
+    fn index_remove(env: &Env, index: &StreamIndex, chunk: u32, stream_id: u64) {
+        let mut ids = Self::index_chunk(env, index, chunk);
+        let Some(pos) = ids.first_index_of(stream_id) else {
+            return;
+        };
+        ids.remove(pos);
+
+        let mut head = Self::index_head(env, index);
+        head.len = head.len.saturating_sub(1);
+        let chunk_key = StreamKey::IndexChunk(index.clone(), chunk);
+        let storage = env.storage().persistent();
+        if !ids.is_empty() {
+            storage.set(&chunk_key, &ids);
+        } else {
+            storage.remove(&chunk_key);
+            // Skip drained chunks at the front so reads start at live ids.
+            while head.first_chunk < head.next_chunk
+                && !storage.has(&StreamKey::IndexChunk(index.clone(), head.first_chunk))
+            {
+                head.first_chunk += 1;
+            }
+        }
+
+        let head_key = StreamKey::IndexHead(index.clone());
+        if head.len == 0 {
+            storage.remove(&head_key);
+        } else {
+            storage.set(&head_key, &head);
+        }
+    }
+
+    pub(crate) fn index_chunk(env: &Env, index: &StreamIndex, chunk: u32) -> Vec<u64> {
+        env.storage()
+            .persistent()
+            .get(&StreamKey::IndexChunk(index.clone(), chunk))
+            .unwrap_or_else(|| Vec::new(env))
+    }
+
+    fn stream_slots(env: &Env, stream_id: u64) -> StreamSlots {
+        env.storage()
+            .persistent()
+            .get(&StreamKey::StreamSlots(stream_id))
+            .unwrap_or_default()
+    }
+
+    fn set_stream_slots(env: &Env, stream_id: u64, slots: &StreamSlots) {
+        env.storage()
+            .persistent()
+            .set(&StreamKey::StreamSlots(stream_id), slots);
+    }
+}
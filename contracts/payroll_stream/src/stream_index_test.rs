@@ -0,0 +1,323 @@
+//! Tests for the chunked employer and worker stream indexes.
+
+use super::*;
+use payroll_vault::{PayrollVault, PayrollVaultClient};
+use soroban_sdk::{
+    Address, Env,
+    testutils::{Address as _, Ledger as _},
+    token,
+};
+use stream_index::{INDEX_CHUNK_SIZE, IndexHead, StreamIndex};
+use stream_query::StreamFilter;
+
+fn setup(env: &Env) -> (PayrollStreamClient<'_>, Address, Address) {
+    let admin = Address::generate(env);
+    let employer = Address::generate(env);
+
+    let token_id = env
+        .register_stellar_asset_contract_v2(Address::generate(env))
+        .address();
+
+    let vault_id = env.register(PayrollVault, ());
+    let stream_id = env.register(PayrollStream, ());
+    let vault_client = PayrollVaultClient::new(env, &vault_id);
+    let stream_client = PayrollStreamClient::new(env, &stream_id);
+
+    vault_client.initialize(&admin);
+    vault_client.set_token_allowlist_enforced(&false);
+    stream_client.init(&admin);
+    stream_client.set_min_stream_duration(&0u64);
+    stream_client.set_cancellation_grace_period(&0u64);
+    stream_client.set_retention_secs(&0u64);
+    vault_client.set_authorized_contract(&stream_id);
+    stream_client.set_vault(&vault_id);
+
+    token::StellarAssetClient::new(env, &token_id).mint(&employer, &100_000);
+    vault_client.deposit(&employer, &token_id, &100_000);
+
+    (stream_client, employer, token_id)
+}
+
+fn create(
+    client: &PayrollStreamClient,
+    employer: &Address,
+    worker: &Address,
+    token: &Address,
+) -> u64 {
+    client.create_stream(
+        employer, worker, token, &1, &0u64, &0u64, &100u64, &None, &None,
+    )
+}
+
+fn head(env: &Env, client: &PayrollStreamClient, index: StreamIndex) -> IndexHead {
+    env.as_contract(&client.address, || PayrollStream::index_head(env, &index))
+}
+
+#[test]
+fn test_indexes_split_into_chunks_and_track_active_streams() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    env.ledger().with_mut(|li| li.timestamp = 0);
+    let (client, employer, token) = setup(&env);
+    let worker = Address::generate(&env);
+
+    let count = INDEX_CHUNK_SIZE + 6;
+    for _ in 0..count {
+        create(&client, &employer, &worker, &token);
+    }
+    assert_eq!(client.get_active_stream_count(&employer), count);
+    assert_eq!(
+        head(&env, &client, StreamIndex::WorkerActive(worker.clone())),
+        IndexHead {
+            len: count,
+            first_chunk: 0,
+            next_chunk: 2,
+        }
+    );
+
+    // Closing every stream of the first chunk moves them to the closed index.
+    for id in 1..=u64::from(INDEX_CHUNK_SIZE) {
+        client.cancel_stream(&id, &employer, &None);
+    }
+    assert_eq!(client.get_active_stream_count(&employer), 6);
+    assert_eq!(
+        head(&env, &client, StreamIndex::EmployerActive(employer.clone())),
+        IndexHead {
+            len: 6,
+            first_chunk: 1,
+            next_chunk: 2,
+        }
+    );
+    assert_eq!(
+        head(&env, &client, StreamIndex::EmployerClosed(employer.clone())).len,
+        INDEX_CHUNK_SIZE
+    );
+
+    // Active streams are listed first, then closed ones.
+    let page = client.get_streams_by_employer(&employer, &Some(4), &Some(4));
+    assert_eq!(page, soroban_sdk::vec![&env, 69u64, 70, 1, 2]);
+    assert_eq!(
+        client.get_streams_by_worker(&worker, &None, &None).len(),
+        count
+    );
+
+    client.cleanup_stream(&1);
+    let closed = head(&env, &client, StreamIndex::WorkerClosed(worker.clone()));
+    assert_eq!(closed.len, INDEX_CHUNK_SIZE - 1);
+    assert_eq!(
+        client.get_streams_by_worker(&worker, &Some(6), &Some(1)),
+        soroban_sdk::vec![&env, 2u64]
+    );
+}
+
+#[test]
+fn test_transfer_moves_stream_between_worker_indexes() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    env.ledger().with_mut(|li| li.timestamp = 0);
+    let (client, employer, token) = setup(&env);
+    let worker = Address::generate(&env);
+    let new_worker = Address::generate(&env);
+
+    let id = create(&client, &employer, &worker, &token);
+    client.transfer_stream(&id, &new_worker, &employer);
+    assert!(
+        client
+            .get_streams_by_worker(&worker, &None, &None)
+            .is_empty()
+    );
+
+    // Closing after a transfer finds the stream in the new worker's index.
+    client.cancel_stream(&id, &employer, &None);
+    assert_eq!(
+        head(&env, &client, StreamIndex::WorkerActive(new_worker.clone())).len,
+        0
+    );
+    assert_eq!(
+        client.get_streams_by_worker(&new_worker, &None, &None),
+        soroban_sdk::vec![&env, id]
+    );
+}
+
+#[test]
+fn test_legacy_indexes_migrate_on_first_use() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    env.ledger().with_mut(|li| li.timestamp = 0);
+    let (client, employer, token) = setup(&env);
+    let worker = Address::generate(&env);
+    let other_worker = Address::generate(&env);
+
+    let id1 = create(&client, &employer, &worker, &token);
+    let id2 = create(&client, &employer, &worker, &token);
+    let id3 = create(&client, &employer, &other_worker, &token);
+    client.cancel_stream(&id2, &employer, &None);
+
+    // Rewrite the indexes into the single-vector layout of older releases.
+    env.as_contract(&client.address, || {
+        let storage = env.storage().persistent();
+        for index in [
+            StreamIndex::EmployerActive(employer.clone()),
+            StreamIndex::EmployerClosed(employer.clone()),
+            StreamIndex::WorkerActive(worker.clone()),
+            StreamIndex::WorkerClosed(worker.clone()),
+            StreamIndex::WorkerActive(other_worker.clone()),
+        ] {
+            storage.remove(&StreamKey::IndexHead(index.clone()));
+            storage.remove(&StreamKey::IndexChunk(index, 0));
+        }
+        for id in [id1, id2, id3] {
+            storage.remove(&StreamKey::StreamSlots(id));
+        }
+        storage.set(
+            &StreamKey::EmployerStreams(employer.clone()),
+            &soroban_sdk::vec![&env, id1, id2, id3],
+        );
+        storage.set(
+            &StreamKey::WorkerStreams(worker.clone()),
+            &soroban_sdk::vec![&env, id1, id2],
+        );
+        storage.set(
+            &StreamKey::WorkerStreams(other_worker.clone()),
+            &soroban_sdk::vec![&env, id3],
+        );
+    });
+
+    assert_eq!(client.migrate_stream_index(&worker), 2);
+    assert_eq!(client.migrate_stream_index(&worker), 0);
+    // The employer's list migrates on first use.
+    assert_eq!(client.get_active_stream_count(&employer), 2);
+    assert_eq!(
+        client.get_streams_by_employer(&employer, &None, &None),
+        soroban_sdk::vec![&env, id1, id3, id2]
+    );
+
+    // Streams closed after the migration move between the new indexes.
+    client.cancel_stream(&id3, &employer, &None);
+    assert_eq!(client.get_active_stream_count(&employer), 1);
+    assert_eq!(
+        client.get_streams_by_worker(&other_worker, &None, &None),
+        soroban_sdk::vec![&env, id3]
+    );
+    env.as_contract(&client.address, || {
+        assert!(
+            !env.storage()
+                .persistent()
+                .has(&StreamKey::WorkerStreams(other_worker.clone()))
+        );
+    });
+}
+
+#[test]
+fn test_legacy_index_migrates_in_batches() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    env.ledger().with_mut(|li| li.timestamp = 0);
+    let (client, employer, token) = setup(&env);
+    let worker = Address::generate(&env);
+
+    let a = create(&client, &employer, &worker, &token);
+    let b = create(&client, &employer, &worker, &token);
+
+    // A legacy employer list spanning three batches, with ids of streams
+    // that no longer exist ahead of the live ones.
+    env.as_contract(&client.address, || {
+        let storage = env.storage().persistent();
+        for index in [
+            StreamIndex::EmployerActive(employer.clone()),
+            StreamIndex::WorkerActive(worker.clone()),
+        ] {
+            storage.remove(&StreamKey::IndexHead(index.clone()));
+            storage.remove(&StreamKey::IndexChunk(index, 0));
+        }
+        for id in [a, b] {
+            storage.remove(&StreamKey::StreamSlots(id));
+        }
+        let mut ids = soroban_sdk::Vec::new(&env);
+        for pos in 0..110u64 {
+            ids.push_back(match pos {
+                105 => a,
+                108 => b,
+                _ => 1_000 + pos,
+            });
+        }
+        storage.set(&StreamKey::EmployerStreams(employer.clone()), &ids);
+        storage.set(
+            &StreamKey::WorkerStreams(worker.clone()),
+            &soroban_sdk::vec![&env, a, b],
+        );
+    });
+
+    assert_eq!(client.migrate_stream_index(&employer), 50);
+    assert_eq!(client.migrate_stream_index(&worker), 2);
+
+    // Cancelling runs the second batch, then places `a` ahead of its own.
+    client.cancel_stream(&a, &employer, &None);
+    assert_eq!(client.migrate_stream_index(&employer), 10);
+    assert_eq!(client.migrate_stream_index(&employer), 0);
+
+    assert_eq!(client.get_active_stream_count(&employer), 1);
+    assert_eq!(
+        client.get_streams_by_employer(&employer, &None, &None),
+        soroban_sdk::vec![&env, b, a]
+    );
+    env.as_contract(&client.address, || {
+        let storage = env.storage().persistent();
+        assert!(!storage.has(&StreamKey::EmployerStreams(employer.clone())));
+        assert!(!storage.has(&StreamKey::EmployerStreamsCursor(employer.clone())));
+    });
+}
+
+#[test]
+fn test_partial_legacy_index_counts_pending_ids_and_stays_incomplete() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    env.ledger().with_mut(|li| li.timestamp = 0);
+    let (client, employer, token) = setup(&env);
+    let worker = Address::generate(&env);
+    let a = create(&client, &employer, &worker, &token);
+
+    // A legacy employer list of 170 ids, of which only `a` still exists.
+    env.as_contract(&client.address, || {
+        let storage = env.storage().persistent();
+        let index = StreamIndex::EmployerActive(employer.clone());
+        storage.remove(&StreamKey::IndexHead(index.clone()));
+        storage.remove(&StreamKey::IndexChunk(index, 0));
+        let mut ids = soroban_sdk::Vec::new(&env);
+        for pos in 0..169u64 {
+            ids.push_back(1_000 + pos);
+        }
+        ids.push_back(a);
+        storage.set(&StreamKey::EmployerStreams(employer.clone()), &ids);
+    });
+
+    // The first batch leaves 120 ids waiting, all counted against the limit.
+    assert_eq!(client.get_active_stream_count(&employer), 120);
+    client.set_employer_stream_limit(&employer, &50);
+    assert_eq!(
+        client.try_create_stream(
+            &employer, &worker, &token, &1, &0u64, &0u64, &100u64, &None, &None,
+        ),
+        Err(Ok(QuipayError::StreamLimitReached))
+    );
+
+    let filter = StreamFilter {
+        statuses: soroban_sdk::Vec::new(&env),
+        token: None,
+        from_ts: None,
+        to_ts: None,
+    };
+    assert!(
+        !client
+            .query_employer_streams(&employer, &filter, &None, &None)
+            .complete
+    );
+    assert!(!client.get_employer_token_totals(&employer, &None).complete);
+
+    client.migrate_stream_index(&employer);
+    assert_eq!(client.get_active_stream_count(&employer), 1);
+    let page = client.query_employer_streams(&employer, &filter, &None, &None);
+    assert!(page.complete);
+    assert_eq!(page.streams.len(), 1);
+    assert!(client.get_employer_token_totals(&employer, &None).complete);
+}
@@ -1,10 +1,13 @@
 use super::*;
+use events::{StreamPaused, StreamResumed};
 
 #[soroban_sdk::contractimpl]
 impl PayrollStream {
-    pub fn pause_stream(env: Env, stream_id: u64, employer: Address) -> Result<(), QuipayError> {
+    /// Pause a stream. `caller` is the employer or a registry delegate
+    /// allowed to pause its streams.
+    pub fn pause_stream(env: Env, stream_id: u64, caller: Address) -> Result<(), QuipayError> {
         Self::require_not_paused(&env)?;
-        employer.require_auth();
+        caller.require_auth();
 
         let key = StreamKey::Stream(stream_id);
         let mut stream: Stream = env
@@ -13,9 +16,12 @@ impl PayrollStream {
             .get(&key)
             .ok_or(QuipayError::StreamNotFound)?;
 
-        if stream.employer != employer {
-            return Err(QuipayError::Unauthorized);
-        }
+        Self::require_employer_delegate(
+            &env,
+            &stream.employer,
+            &caller,
+            EmployerAction::PauseStream,
+        )?;
 
         if stream.status != StreamStatus::Active {
             return Err(QuipayError::StreamClosed);
@@ -28,22 +34,23 @@ impl PayrollStream {
         env.storage().persistent().set(&key, &stream);
         Self::bump_stream_storage_ttl(&env, stream_id, &stream.worker);
 
-        env.events().publish(
-            (
-                Symbol::new(&env, "stream"),
-                Symbol::new(&env, "paused"),
+        events::emit(
+            &env,
+            &caller,
+            StreamPaused {
                 stream_id,
-                employer,
-            ),
-            (now,),
+                paused_at: now,
+            },
         );
 
         Ok(())
     }
 
-    pub fn resume_stream(env: Env, stream_id: u64, employer: Address) -> Result<(), QuipayError> {
+    /// Resume a paused stream. `caller` is the employer or a registry
+    /// delegate allowed to pause its streams.
+    pub fn resume_stream(env: Env, stream_id: u64, caller: Address) -> Result<(), QuipayError> {
         Self::require_not_paused(&env)?;
-        employer.require_auth();
+        caller.require_auth();
 
         let key = StreamKey::Stream(stream_id);
         let mut stream: Stream = env
@@ -52,12 +59,15 @@ impl PayrollStream {
             .get(&key)
             .ok_or(QuipayError::StreamNotFound)?;
 
-        if stream.employer != employer {
-            return Err(QuipayError::Unauthorized);
-        }
+        Self::require_employer_delegate(
+            &env,
+            &stream.employer,
+            &caller,
+            EmployerAction::PauseStream,
+        )?;
 
         if stream.status != StreamStatus::Paused {
-            return Err(QuipayError::Custom); // Should be Active or something else
+            return Err(QuipayError::StreamNotPaused);
         }
 
         let now = env.ledger().timestamp();
@@ -70,22 +80,27 @@ impl PayrollStream {
         env.storage().persistent().set(&key, &stream);
         Self::bump_stream_storage_ttl(&env, stream_id, &stream.worker);
 
-        env.events().publish(
-            (
-                Symbol::new(&env, "stream"),
-                Symbol::new(&env, "resumed"),
+        events::emit(
+            &env,
+            &caller,
+            StreamResumed {
                 stream_id,
-                employer,
-            ),
-            (now, paused_duration, stream.total_paused_duration),
+                resumed_at: now,
+                paused_duration,
+                total_paused_duration: stream.total_paused_duration,
+            },
         );
 
         Ok(())
     }
 
-    pub fn admin_pause_stream(env: Env, stream_id: u64) -> Result<(), QuipayError> {
-        let admin = Self::get_admin(env.clone())?;
-        admin.require_auth();
+    /// Pause any stream. Requires the admin or a `Pauser`.
+    pub fn admin_pause_stream(
+        env: Env,
+        caller: Address,
+        stream_id: u64,
+    ) -> Result<(), QuipayError> {
+        Self::require_role(&env, Role::Pauser, &caller)?;
 
         let key = StreamKey::Stream(stream_id);
         let mut stream: Stream = env
@@ -105,14 +120,13 @@ impl PayrollStream {
         env.storage().persistent().set(&key, &stream);
         Self::bump_stream_storage_ttl(&env, stream_id, &stream.worker);
 
-        env.events().publish(
-            (
-                Symbol::new(&env, "stream"),
-                Symbol::new(&env, "paused"),
+        events::emit(
+            &env,
+            &caller,
+            StreamPaused {
                 stream_id,
-                admin,
-            ),
-            (now,),
+                paused_at: now,
+            },
         );
 
         Ok(())
@@ -146,9 +160,13 @@ impl PayrollStream {
         }
     }
 
-    pub fn admin_resume_stream(env: Env, stream_id: u64) -> Result<(), QuipayError> {
-        let admin = Self::get_admin(env.clone())?;
-        admin.require_auth();
+    /// Resume any stream. Requires the admin or a `Pauser`.
+    pub fn admin_resume_stream(
+        env: Env,
+        caller: Address,
+        stream_id: u64,
+    ) -> Result<(), QuipayError> {
+        Self::require_role(&env, Role::Pauser, &caller)?;
 
         let key = StreamKey::Stream(stream_id);
         let mut stream: Stream = env
@@ -158,7 +176,7 @@ impl PayrollStream {
             .ok_or(QuipayError::StreamNotFound)?;
 
         if stream.status != StreamStatus::Paused {
-            return Err(QuipayError::Custom);
+            return Err(QuipayError::StreamNotPaused);
         }
 
         let now = env.ledger().timestamp();
@@ -171,14 +189,15 @@ impl PayrollStream {
         env.storage().persistent().set(&key, &stream);
         Self::bump_stream_storage_ttl(&env, stream_id, &stream.worker);
 
-        env.events().publish(
-            (
-                Symbol::new(&env, "stream"),
-                Symbol::new(&env, "resumed"),
+        events::emit(
+            &env,
+            &caller,
+            StreamResumed {
                 stream_id,
-                admin,
-            ),
-            (now, paused_duration, stream.total_paused_duration),
+                resumed_at: now,
+                paused_duration,
+                total_paused_duration: stream.total_paused_duration,
+            },
         );
 
         Ok(())
@@ -0,0 +1,480 @@
+//! Batched stream views.
+//!
+//! `query_employer_streams` and `query_worker_streams` return full `Stream`
+//! records together with their withdrawable amount, filtered by status, token
+//! and schedule. Results walk the account's active index and then its closed
+//! index, resuming from a `StreamCursor` that points at a chunk and position
+//! in the chunked indexes. Each call reads at most `MAX_QUERY_SCAN` stream
+//! ids, so a page can come back short while `complete` is still `false`.
+//!
+//! A cursor is a position, not a snapshot: streams created, closed or removed
+//! between calls may be skipped or returned twice. Neither view reports
+//! `complete` while the account still has a legacy stream list; run
+//! `migrate_stream_index` until it returns 0 and start over.
+//!
+//! The token totals views aggregate every stream of an account per token.
+//! They walk the same indexes under the same scan budget, carrying the
+//! running totals in a `TokenTotalsPage` that is passed back until
+//! `complete` is `true`.
+//!
+//! `get_vested_unwithdrawn` walks every stream by id and reports the wages
+//! workers have earned but not withdrawn, per token. PayrollVault tallies it
+//! before a protective drain so those wages stay in the vault.
+
+use super::*;
+use stream_index::StreamIndex;
+
+/// Maximum number of streams returned by one query page.
+const MAX_QUERY_PAGE_SIZE: u32 = 50;
+/// Maximum number of stream ids read by one query call.
+const MAX_QUERY_SCAN: u32 = 200;
+
+/// Which streams a query returns. Unset fields match every stream.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct StreamFilter {
+    /// Statuses to include; empty matches every status.
+    pub statuses: Vec<StreamStatus>,
+    pub token: Option<Address>,
+    /// Skip streams whose schedule ends before this timestamp.
+    pub from_ts: Option<u64>,
+    /// Skip streams whose schedule starts after this timestamp.
+    pub to_ts: Option<u64>,
+}
+
+/// Position of a query in an account's indexes.
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct StreamCursor {
+    /// `false` while walking the active index, `true` for the closed index.
+    pub closed: bool,
+    pub chunk: u32,
+    pub position: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct StreamView {
+    pub stream_id: u64,
+    pub stream: Stream,
+    /// Vested but not yet withdrawn amount; zero once the stream is closed.
+    pub withdrawable: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct StreamPage {
+    pub streams: Vec<StreamView>,
+    /// Pass back to fetch the next page.
+    pub cursor: StreamCursor,
+    /// `true` once both indexes have been read to the end.
+    pub complete: bool,
+}
+
+/// Per-token totals over an account's streams.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct TokenTotals {
+    pub token: Address,
+    /// Unwithdrawn amount of open streams, which the vault holds as liability.
+    pub active_liability: i128,
+    /// Amount withdrawn over all streams, open and closed.
+    pub withdrawn: i128,
+    /// Amount of open streams that has not vested yet.
+    pub remaining: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct TokenTotalsPage {
+    /// Totals over the streams read so far.
+    pub totals: Vec<TokenTotals>,
+    /// Pass the page back to continue from here.
+    pub cursor: StreamCursor,
+    /// `true` once both indexes have been read to the end.
+    pub complete: bool,
+}
+
+#[soroban_sdk::contractimpl]
+impl PayrollStream {
+    /// A page of `employer`'s streams matching `filter`, active streams first.
+    /// Start with `cursor = None`; `limit` defaults to 50 and is clamped to 1..=50.
+    pub fn query_employer_streams(
+        env: Env,
+        employer: Address,
+        filter: StreamFilter,
+        cursor: Option<StreamCursor>,
+        limit: Option<u32>,
+    ) -> StreamPage {
+        Self::ensure_index_migrated(&env, &employer);
+        let mut page = Self::query_streams(
+            &env,
+            &StreamIndex::EmployerActive(employer.clone()),
+            &StreamIndex::EmployerClosed(employer.clone()),
+            &filter,
+            cursor.unwrap_or_default(),
+            limit,
+        );
+        page.complete &= !Self::has_legacy_index(&env, &employer);
+        page
+    }
+
+    /// A page of `worker`'s streams matching `filter`, active streams first.
+    /// Start with `cursor = None`; `limit` defaults to 50 and is clamped to 1..=50.
+    pub fn query_worker_streams(
+        env: Env,
+        worker: Address,
+        filter: StreamFilter,
+        cursor: Option<StreamCursor>,
+        limit: Option<u32>,
+    ) -> StreamPage {
+        Self::ensure_index_migrated(&env, &worker);
+        let mut page = Self::query_streams(
+            &env,
+            &StreamIndex::WorkerActive(worker.clone()),
+            &StreamIndex::WorkerClosed(worker.clone()),
+            &filter,
+            cursor.unwrap_or_default(),
+            limit,
+        );
+        page.complete &= !Self::has_legacy_index(&env, &worker);
+        page
+    }
+
+    /// Per-token totals over every stream of `employer`. Start with
+    /// `page = None` and pass the returned page back until it is complete;
+    /// the complete page holds the totals over every stream.
+    pub fn get_employer_token_totals(
+        env: Env,
+        employer: Address,
+        page: Option<TokenTotalsPage>,
+    ) -> TokenTotalsPage {
+        Self::ensure_index_migrated(&env, &employer);
+        let mut page = Self::token_totals(
+            &env,
+            &StreamIndex::EmployerActive(employer.clone()),
+            &StreamIndex::EmployerClosed(employer.clone()),
+            page,
+        );
+        page.complete &= !Self::has_legacy_index(&env, &employer);
+        page
+    }
+
+    /// Per-token totals over every stream of `worker`, paged like
+    /// `get_employer_token_totals`.
+    pub fn get_worker_token_totals(
+        env: Env,
+        worker: Address,
+        page: Option<TokenTotalsPage>,
+    ) -> TokenTotalsPage {
+        Self::ensure_index_migrated(&env, &worker);
+        let mut page = Self::token_totals(
+            &env,
+            &StreamIndex::WorkerActive(worker.clone()),
+            &StreamIndex::WorkerClosed(worker.clone()),
+            page,
+        );
+        page.complete &= !Self::has_legacy_index(&env, &worker);
+        page
+    }
+
+    /// Wages vested by `as_of` but not yet withdrawn, per token, over the
+    /// streams from id `start_id`, plus payouts held for blacklisted workers.
+    /// Reads at most `limit` streams, capped at 200. Returns the totals and
+    /// the id to resume from, `None` once every stream has been read.
+    pub fn get_vested_unwithdrawn(
+        env: Env,
+        as_of: u64,
+        start_id: u64,
+        limit: Option<u32>,
+    ) -> (Map<Address, i128>, Option<u64>) {
+        let next_stream_id: u64 = env
+            .storage()
+            .instance()
+            .get(&DataKey::NextStreamId)
+            .unwrap_or(1);
+        let scan = limit.unwrap_or(MAX_QUERY_SCAN).clamp(1, MAX_QUERY_SCAN) as u64;
+        let start_id = start_id.max(1);
+        let end_id = start_id.saturating_add(scan).min(next_stream_id);
+
+        let mut totals: Map<Address, i128> = Map::new(&env);
+        for stream_id in start_id..end_id {
+            let Some(stream) = env
+                .storage()
+                .persistent()
+                .get::<StreamKey, Stream>(&StreamKey::Stream(stream_id))
+            else {
+                continue;
+            };
+            let unwithdrawn = stream.total_amount.saturating_sub(stream.withdrawn_amount);
+            let owed = Self::withdrawable_amount(&stream, as_of).clamp(0, unwithdrawn.max(0))
+                + Self::get_held_payout(env.clone(), stream_id);
+            if owed > 0 {
+                let total = totals.get(stream.token.clone()).unwrap_or(0);
+                totals.set(stream.token, total.saturating_add(owed));
+            }
+        }
+
+        let next = if end_id < next_stream_id {
+            Some(end_id)
+        } else {
+            None
+        };
+        (totals, next)
+    }
+}
+
+impl PayrollStream {
+    fn query_streams(
+        env: &Env,
+        active: &StreamIndex,
+        closed: &StreamIndex,
+        filter: &StreamFilter,
+        mut cursor: StreamCursor,
+        limit: Option<u32>,
+    ) -> StreamPage {
+        let limit = limit
+            .unwrap_or(MAX_QUERY_PAGE_SIZE)
+            .clamp(1, MAX_QUERY_PAGE_SIZE);
+        let now = env.ledger().timestamp();
+        let (wants_active, wants_closed) = Self::wanted_indexes(filter);
+        let mut streams = Vec::new(env);
+        let mut budget = MAX_QUERY_SCAN;
+
+        let mut complete = true;
+        if !cursor.closed {
+            if wants_active
+                && !Self::scan_index(
+                    env,
+                    active,
+                    filter,
+                    now,
+                    limit,
+                    &mut cursor,
+                    &mut budget,
+                    &mut streams,
+                )
+            {
+                complete = false;
+            } else {
+                cursor = StreamCursor {
+                    closed: true,
+                    ..StreamCursor::default()
+                };
+            }
+        }
+        if complete && wants_closed {
+            complete = Self::scan_index(
+                env,
+                closed,
+                filter,
+                now,
+                limit,
+                &mut cursor,
+                &mut budget,
+                &mut streams,
+            );
+        }
+
+        StreamPage {
+            streams,
+            cursor,
+            complete,
+        }
+    }
+
+    /// Whether `filter` can match streams in the active and closed indexes.
+    fn wanted_indexes(filter: &StreamFilter) -> (bool, bool) {
+        if filter.statuses.is_empty() {
+            return (true, true);
+        }
+        let mut active = false;
+        let mut closed = false;
+        for status in filter.statuses.iter() {
+            match status {
+                StreamStatus::Canceled | StreamStatus::Completed => closed = true,
+                _ => active = true,
+            }
+        }
+        (active, closed)
+    }
+
+    /// Read `index` from `cursor` into `out` until the page is full or the
+    /// scan budget runs out. Returns `true` if the index was read to the end.
+    #[allow(clippy::too_many_arguments)]
+    fn scan_index(
+        env: &Env,
+        index: &StreamIndex,
+        filter: &StreamFilter,
+        now: u64,
+        limit: u32,
+        cursor: &mut StreamCursor,
+        budget: &mut u32,
+        out: &mut Vec<StreamView>,
+    ) -> bool {
+        let head = Self::index_head(env, index);
+        if cursor.chunk < head.first_chunk {
+            cursor.chunk = head.first_chunk;
+            cursor.position = 0;
+        }
+        while cursor.chunk < head.next_chunk {
+            let ids = Self::index_chunk(env, index, cursor.chunk);
+            while cursor.position < ids.len() {
+                if out.len() >= limit || *budget == 0 {
+                    return false;
+                }
+                let stream_id = ids.get_unchecked(cursor.position);
+                cursor.position += 1;
+                *budget -= 1;
+
+                let Some(stream) = env
+                    .storage()
+                    .persistent()
+                    .get::<StreamKey, Stream>(&StreamKey::Stream(stream_id))
+                else {
+                    continue;
+                };
+                if Self::matches_filter(&stream, filter) {
+                    out.push_back(StreamView {
+                        stream_id,
+                        withdrawable: Self::withdrawable_amount(&stream, now),
+                        stream,
+                    });
+                }
+            }
+            cursor.chunk += 1;
+            cursor.position = 0;
+        }
+        true
+    }
+
+    fn matches_filter(stream: &Stream, filter: &StreamFilter) -> bool {
+        (filter.statuses.is_empty() || filter.statuses.contains(stream.status))
+            && filter
+                .token
+                .as_ref()
+                .is_none_or(|token| stream.token == *token)
+            && filter
+                .from_ts
+                .is_none_or(|from_ts| stream.end_ts >= from_ts)
+            && filter.to_ts.is_none_or(|to_ts| stream.start_ts <= to_ts)
+    }
+
+    fn token_totals(
+        env: &Env,
+        active: &StreamIndex,
+        closed: &StreamIndex,
+        page: Option<TokenTotalsPage>,
+    ) -> TokenTotalsPage {
+        let page = page.unwrap_or_else(|| TokenTotalsPage {
+            totals: Vec::new(env),
+            cursor: StreamCursor::default(),
+            complete: false,
+        });
+        if page.complete {
+            return page;
+        }
+
+        let now = env.ledger().timestamp();
+        let mut totals: Map<Address, TokenTotals> = Map::new(env);
+        for entry in page.totals.iter() {
+            totals.set(entry.token.clone(), entry);
+        }
+        let mut cursor = page.cursor;
+        let mut budget = MAX_QUERY_SCAN;
+
+        let mut complete = true;
+        if !cursor.closed {
+            if Self::scan_totals(
+                env,
+                active,
+                true,
+                now,
+                &mut cursor,
+                &mut budget,
+                &mut totals,
+            ) {
+                cursor = StreamCursor {
+                    closed: true,
+                    ..StreamCursor::default()
+                };
+            } else {
+                complete = false;
+            }
+        }
+        if complete {
+            complete = Self::scan_totals(
+                env,
+                closed,
+                false,
+                now,
+                &mut cursor,
+                &mut budget,
+                &mut totals,
+            );
+        }
+
+        TokenTotalsPage {
+            totals: totals.values(),
+            cursor,
+            complete,
+        }
+    }
+
+    /// Add the streams of `index` from `cursor` to `totals` until the scan
+    /// budget runs out. Returns `true` if the index was read to the end.
+    fn scan_totals(
+        env: &Env,
+        index: &StreamIndex,
+        open: bool,
+        now: u64,
+        cursor: &mut StreamCursor,
+        budget: &mut u32,
+        totals: &mut Map<Address, TokenTotals>,
+    ) -> bool {
+        let head = Self::index_head(env, index);
+        if cursor.chunk < head.first_chunk {
+            cursor.chunk = head.first_chunk;
+            cursor.position = 0;
+        }
+        while cursor.chunk < head.next_chunk {
+            let ids = Self::index_chunk(env, index, cursor.chunk);
+            while cursor.position < ids.len() {
+                if *budget == 0 {
+                    return false;
+                }
+                let stream_id = ids.get_unchecked(cursor.position);
+                cursor.position += 1;
+                *budget -= 1;
+
+                let Some(stream) = env
+                    .storage()
+                    .persistent()
+                    .get::<StreamKey, Stream>(&StreamKey::Stream(stream_id))
+                else {
+                    continue;
+                };
+                let mut entry = totals.get(stream.token.clone()).unwrap_or(TokenTotals {
+                    token: stream.token.clone(),
+                    active_liability: 0,
+                    withdrawn: 0,
+                    remaining: 0,
+                });
+                entry.withdrawn = entry.withdrawn.saturating_add(stream.withdrawn_amount);
+                if open && !Self::is_closed(&stream) {
+                    entry.active_liability = entry.active_liability.saturating_add(
+                        stream.total_amount.saturating_sub(stream.withdrawn_amount),
+                    );
+                    let vested = Self::vested_amount(&stream, now);
+                    entry.remaining = entry
+                        .remaining
+                        .saturating_add(stream.total_amount.saturating_sub(vested).max(0));
+                }
+                totals.set(stream.token, entry);
+            }
+            cursor.chunk += 1;
+            cursor.position = 0;
+        }
+        true
+    }
+}
@@ -0,0 +1,365 @@
+//! Tests for the batched stream query and token totals views.
+
+use super::*;
+use payroll_vault::{PayrollVault, PayrollVaultClient};
+use soroban_sdk::{
+    Address, Env,
+    testutils::{Address as _, Ledger as _},
+    token,
+};
+use stream_index::{IndexHead, StreamIndex};
+use stream_query::{StreamCursor, StreamFilter, TokenTotals};
+
+struct Setup<'a> {
+    client: PayrollStreamClient<'a>,
+    employer: Address,
+    worker: Address,
+    token: Address,
+    other_token: Address,
+}
+
+fn setup(env: &Env) -> Setup<'_> {
+    let admin = Address::generate(env);
+    let employer = Address::generate(env);
+
+    let vault_id = env.register(PayrollVault, ());
+    let stream_id = env.register(PayrollStream, ());
+    let vault_client = PayrollVaultClient::new(env, &vault_id);
+    let client = PayrollStreamClient::new(env, &stream_id);
+
+    vault_client.initialize(&admin);
+    vault_client.set_token_allowlist_enforced(&false);
+    client.init(&admin);
+    client.set_min_stream_duration(&0u64);
+    client.set_cancellation_grace_period(&0u64);
+    client.set_withdrawal_cooldown(&0u64);
+    vault_client.set_authorized_contract(&stream_id);
+    client.set_vault(&vault_id);
+
+    let mut tokens = [0u8; 2].map(|_| {
+        let token = env
+            .register_stellar_asset_contract_v2(Address::generate(env))
+            .address();
+        token::StellarAssetClient::new(env, &token).mint(&employer, &10_000);
+        vault_client.deposit(&employer, &token, &10_000);
+        token
+    });
+    tokens.sort();
+    let [token, other_token] = tokens;
+
+    Setup {
+        client,
+        employer,
+        worker: Address::generate(env),
+        token,
+        other_token,
+    }
+}
+
+fn no_filter(env: &Env) -> StreamFilter {
+    StreamFilter {
+        statuses: Vec::new(env),
+        token: None,
+        from_ts: None,
+        to_ts: None,
+    }
+}
+
+/// Stream ids returned by a query, in order.
+fn ids(env: &Env, page: &stream_query::StreamPage) -> Vec<u64> {
+    let mut ids = Vec::new(env);
+    for view in page.streams.iter() {
+        ids.push_back(view.stream_id);
+    }
+    ids
+}
+
+#[test]
+fn test_query_returns_records_with_withdrawable_and_pages_by_cursor() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    env.ledger().with_mut(|li| li.timestamp = 0);
+    let s = setup(&env);
+    let c = &s.client;
+
+    let a = c.create_stream(
+        &s.employer,
+        &s.worker,
+        &s.token,
+        &1,
+        &0u64,
+        &0u64,
+        &100u64,
+        &None,
+        &None,
+    );
+    let b = c.create_stream(
+        &s.employer,
+        &s.worker,
+        &s.token,
+        &1,
+        &0u64,
+        &200u64,
+        &300u64,
+        &None,
+        &None,
+    );
+    let d = c.create_stream(
+        &s.employer,
+        &s.worker,
+        &s.other_token,
+        &1,
+        &0u64,
+        &0u64,
+        &100u64,
+        &None,
+        &None,
+    );
+    env.ledger().with_mut(|li| li.timestamp = 50);
+    c.cancel_stream(&d, &s.employer, &None);
+
+    let page = c.query_employer_streams(&s.employer, &no_filter(&env), &None, &Some(2));
+    assert_eq!(ids(&env, &page), soroban_sdk::vec![&env, a, b]);
+    assert!(!page.complete);
+    let first = page.streams.get_unchecked(0);
+    assert_eq!(first.stream.token, s.token);
+    assert_eq!(first.stream.total_amount, 100);
+    assert_eq!(first.withdrawable, 50);
+    assert_eq!(page.streams.get_unchecked(1).withdrawable, 0);
+
+    let page =
+        c.query_employer_streams(&s.employer, &no_filter(&env), &Some(page.cursor), &Some(2));
+    assert_eq!(ids(&env, &page), soroban_sdk::vec![&env, d]);
+    assert!(page.complete);
+    assert_eq!(page.streams.get_unchecked(0).withdrawable, 0);
+    assert_eq!(
+        page.cursor,
+        StreamCursor {
+            closed: true,
+            chunk: 1,
+            position: 0,
+        }
+    );
+
+    let worker_page = c.query_worker_streams(&s.worker, &no_filter(&env), &None, &None);
+    assert_eq!(ids(&env, &worker_page), soroban_sdk::vec![&env, a, b, d]);
+    assert!(worker_page.complete);
+
+    // A zero limit still makes progress.
+    let page = c.query_worker_streams(&s.worker, &no_filter(&env), &None, &Some(0));
+    assert_eq!(ids(&env, &page), soroban_sdk::vec![&env, a]);
+}
+
+#[test]
+fn test_query_filters_by_status_token_and_time_range() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    env.ledger().with_mut(|li| li.timestamp = 0);
+    let s = setup(&env);
+    let c = &s.client;
+
+    let a = c.create_stream(
+        &s.employer,
+        &s.worker,
+        &s.token,
+        &1,
+        &0u64,
+        &0u64,
+        &100u64,
+        &None,
+        &None,
+    );
+    let b = c.create_stream(
+        &s.employer,
+        &s.worker,
+        &s.token,
+        &1,
+        &0u64,
+        &200u64,
+        &300u64,
+        &None,
+        &None,
+    );
+    let d = c.create_stream(
+        &s.employer,
+        &s.worker,
+        &s.other_token,
+        &1,
+        &0u64,
+        &0u64,
+        &100u64,
+        &None,
+        &None,
+    );
+    c.cancel_stream(&d, &s.employer, &None);
+
+    let query = |filter: StreamFilter| {
+        let page = c.query_employer_streams(&s.employer, &filter, &None, &None);
+        assert!(page.complete);
+        ids(&env, &page)
+    };
+
+    let mut filter = no_filter(&env);
+    filter.statuses = soroban_sdk::vec![&env, StreamStatus::Canceled];
+    assert_eq!(query(filter), soroban_sdk::vec![&env, d]);
+
+    let mut filter = no_filter(&env);
+    filter.statuses = soroban_sdk::vec![&env, StreamStatus::Active];
+    assert_eq!(query(filter), soroban_sdk::vec![&env, a, b]);
+
+    let mut filter = no_filter(&env);
+    filter.token = Some(s.other_token.clone());
+    assert_eq!(query(filter), soroban_sdk::vec![&env, d]);
+
+    let mut filter = no_filter(&env);
+    filter.from_ts = Some(150);
+    assert_eq!(query(filter), soroban_sdk::vec![&env, b]);
+
+    let mut filter = no_filter(&env);
+    filter.to_ts = Some(100);
+    filter.token = Some(s.token.clone());
+    assert_eq!(query(filter), soroban_sdk::vec![&env, a]);
+}
+
+#[test]
+fn test_token_totals_split_liability_withdrawn_and_remaining() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    env.ledger().with_mut(|li| li.timestamp = 0);
+    let s = setup(&env);
+    let c = &s.client;
+
+    let a = c.create_stream(
+        &s.employer,
+        &s.worker,
+        &s.token,
+        &1,
+        &0u64,
+        &0u64,
+        &100u64,
+        &None,
+        &None,
+    );
+    c.create_stream(
+        &s.employer,
+        &s.worker,
+        &s.token,
+        &1,
+        &0u64,
+        &200u64,
+        &300u64,
+        &None,
+        &None,
+    );
+    let d = c.create_stream(
+        &s.employer,
+        &s.worker,
+        &s.other_token,
+        &1,
+        &0u64,
+        &0u64,
+        &100u64,
+        &None,
+        &None,
+    );
+
+    env.ledger().with_mut(|li| li.timestamp = 50);
+    c.withdraw(&a, &s.worker);
+    env.ledger().with_mut(|li| li.timestamp = 60);
+    c.cancel_stream(&d, &s.employer, &None);
+
+    let totals = soroban_sdk::vec![
+        &env,
+        TokenTotals {
+            token: s.token.clone(),
+            active_liability: 150,
+            withdrawn: 50,
+            remaining: 140,
+        },
+        TokenTotals {
+            token: s.other_token.clone(),
+            active_liability: 0,
+            withdrawn: c.get_stream(&d).unwrap().withdrawn_amount,
+            remaining: 0,
+        },
+    ];
+    let page = c.get_employer_token_totals(&s.employer, &None);
+    assert!(page.complete);
+    assert_eq!(page.totals, totals);
+    assert_eq!(c.get_worker_token_totals(&s.worker, &None).totals, totals);
+    assert!(
+        c.get_worker_token_totals(&Address::generate(&env), &None)
+            .totals
+            .is_empty()
+    );
+    // A complete page is returned as is.
+    assert_eq!(
+        c.get_employer_token_totals(&s.employer, &Some(page.clone())),
+        page
+    );
+}
+
+#[test]
+fn test_token_totals_page_through_large_indexes() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    env.ledger().with_mut(|li| li.timestamp = 0);
+    let s = setup(&env);
+    let c = &s.client;
+
+    let a = c.create_stream(
+        &s.employer,
+        &s.worker,
+        &s.token,
+        &1,
+        &0u64,
+        &0u64,
+        &100u64,
+        &None,
+        &None,
+    );
+
+    // Put 256 ids of removed streams ahead of `a` in the employer's index.
+    env.as_contract(&c.address, || {
+        let storage = env.storage().persistent();
+        let index = StreamIndex::EmployerActive(s.employer.clone());
+        for chunk in 0..4u32 {
+            let mut ids = Vec::new(&env);
+            for pos in 0..64u64 {
+                ids.push_back(1_000 + u64::from(chunk) * 64 + pos);
+            }
+            storage.set(&StreamKey::IndexChunk(index.clone(), chunk), &ids);
+        }
+        storage.set(
+            &StreamKey::IndexChunk(index.clone(), 4),
+            &soroban_sdk::vec![&env, a],
+        );
+        storage.set(
+            &StreamKey::IndexHead(index),
+            &IndexHead {
+                len: 257,
+                first_chunk: 0,
+                next_chunk: 5,
+            },
+        );
+    });
+
+    // One call reads at most 200 ids.
+    let page = c.get_employer_token_totals(&s.employer, &None);
+    assert!(!page.complete);
+    assert!(page.totals.is_empty());
+    let page = c.get_employer_token_totals(&s.employer, &Some(page));
+    assert!(page.complete);
+    assert_eq!(
+        page.totals,
+        soroban_sdk::vec![
+            &env,
+            TokenTotals {
+                token: s.token.clone(),
+                active_liability: 100,
+                withdrawn: 0,
+                remaining: 100,
+            },
+        ]
+    );
+}
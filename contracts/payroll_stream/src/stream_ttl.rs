@@ -0,0 +1,125 @@
+//! Keeper entry points for the stream contract's storage TTL.
+//!
+//! Mutation paths extend the entries they touch (`bump_stream_storage_ttl`),
+//! but a stream nobody withdraws from, or an employer that stops creating
+//! streams, would eventually be archived. `bump_ttl` extends those entries
+//! for any caller willing to pay the rent. Supported scopes:
+//!
+//! - `Contract`: instance storage (configuration) and the upgrade state.
+//! - `Stream(id)`: the stream record, its index slots and its dispute.
+//! - `Account(address)`: the heads of the account's employer and worker
+//!   indexes, legacy index lists, last withdrawal timestamp and granted roles.
+//!
+//! An account's index chunks grow with its stream count, so they are not
+//! part of the `Account` scope: `bump_index_ttl` extends them a range of
+//! chunks at a time. `get_expiring_streams` pages through the employer's
+//! active index the same way.
+
+use super::*;
+use quipay_common::TtlScope;
+use stream_index::StreamIndex;
+
+/// Maximum number of index chunks extended by one `bump_index_ttl` call.
+const MAX_BUMP_CHUNKS: u32 = 50;
+/// Maximum number of index chunks read by one `get_expiring_streams` call.
+const MAX_EXPIRING_CHUNKS: u32 = 3;
+
+#[soroban_sdk::contractimpl]
+impl PayrollStream {
+    /// Extend every entry of `scope` to the full policy TTL. Permissionless.
+    /// Returns the ledger the entries stay live until.
+    pub fn bump_ttl(env: Env, scope: TtlScope) -> Result<u32, QuipayError> {
+        match &scope {
+            TtlScope::Contract => {
+                ttl::extend_instance(&env);
+                upgrade::extend_ttl(&env);
+            }
+            TtlScope::Stream(stream_id) => {
+                if !ttl::extend_fully(&env, &StreamKey::Stream(*stream_id)) {
+                    return Err(QuipayError::StreamNotFound);
+                }
+                ttl::extend_fully(&env, &StreamKey::StreamSlots(*stream_id));
+                ttl::extend_fully(&env, &DataKey::Dispute(*stream_id));
+                ttl::extend_fully(&env, &StreamKey::HeldPayout(*stream_id));
+            }
+            TtlScope::Account(account) => Self::extend_account_ttl(&env, account),
+            _ => return Err(QuipayError::UnsupportedTtlScope),
+        }
+        Ok(ttl::record_bump(&env, &scope))
+    }
+
+    /// Ledger `scope`'s entries were last extended to by `bump_ttl`, or
+    /// `None` if no keeper has bumped them.
+    ///
+    /// This only reflects the last keeper bump, not the entries' real TTL:
+    /// write paths may have extended them further since, and entries
+    /// created after the bump are not covered by it.
+    pub fn get_ttl_expiry(env: Env, scope: TtlScope) -> Option<u32> {
+        ttl::expiry(&env, &scope)
+    }
+
+    /// Extend the chunks of `index` from `start_chunk` on to the full policy
+    /// TTL. Permissionless. Extends at most 50 chunks and returns the chunk
+    /// to resume from, `None` once the index has been read to the end.
+    pub fn bump_index_ttl(env: Env, index: StreamIndex, start_chunk: u32) -> Option<u32> {
+        let head = Self::index_head(&env, &index);
+        let start_chunk = start_chunk.max(head.first_chunk);
+        let end_chunk = start_chunk
+            .saturating_add(MAX_BUMP_CHUNKS)
+            .min(head.next_chunk);
+        for chunk in start_chunk..end_chunk {
+            ttl::extend_fully(&env, &StreamKey::IndexChunk(index.clone(), chunk));
+        }
+        (end_chunk < head.next_chunk).then_some(end_chunk)
+    }
+
+    /// Active streams of `employer` that no keeper has bumped, or whose
+    /// last keeper bump (see `get_ttl_expiry`) expires within
+    /// `within_ledgers`, in the index chunks from `start_chunk` on. Reads at
+    /// most 3 chunks and returns the ids and the chunk to resume from,
+    /// `None` once the index has been read to the end.
+    pub fn get_expiring_streams(
+        env: Env,
+        employer: Address,
+        within_ledgers: u32,
+        start_chunk: u32,
+    ) -> (Vec<u64>, Option<u32>) {
+        Self::ensure_index_migrated(&env, &employer);
+        let horizon = env.ledger().sequence().saturating_add(within_ledgers);
+        let index = StreamIndex::EmployerActive(employer);
+        let head = Self::index_head(&env, &index);
+        let start_chunk = start_chunk.max(head.first_chunk);
+        let end_chunk = start_chunk
+            .saturating_add(MAX_EXPIRING_CHUNKS)
+            .min(head.next_chunk);
+        let mut expiring = Vec::new(&env);
+        for chunk in start_chunk..end_chunk {
+            for stream_id in Self::index_chunk(&env, &index, chunk).iter() {
+                match ttl::expiry(&env, &TtlScope::Stream(stream_id)) {
+                    Some(live_until) if live_until > horizon => {}
+                    _ => expiring.push_back(stream_id),
+                }
+            }
+        }
+        (expiring, (end_chunk < head.next_chunk).then_some(end_chunk))
+    }
+}
+
+impl PayrollStream {
+    fn extend_account_ttl(env: &Env, account: &Address) {
+        for index in [
+            StreamIndex::EmployerActive(account.clone()),
+            StreamIndex::EmployerClosed(account.clone()),
+            StreamIndex::WorkerActive(account.clone()),
+            StreamIndex::WorkerClosed(account.clone()),
+        ] {
+            ttl::extend_fully(env, &StreamKey::IndexHead(index));
+        }
+        ttl::extend_fully(env, &StreamKey::EmployerStreams(account.clone()));
+        ttl::extend_fully(env, &StreamKey::WorkerStreams(account.clone()));
+        ttl::extend_fully(env, &StreamKey::EmployerStreamsCursor(account.clone()));
+        ttl::extend_fully(env, &StreamKey::WorkerStreamsCursor(account.clone()));
+        ttl::extend_fully(env, &DataKey::LastWithdrawal(account.clone()));
+        rbac::extend_roles_ttl(env, account);
+    }
+}
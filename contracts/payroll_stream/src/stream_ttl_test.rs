@@ -0,0 +1,206 @@
+//! Tests for the keeper TTL entry points.
+
+use super::*;
+use payroll_vault::{PayrollVault, PayrollVaultClient};
+use quipay_common::{
+    TtlScope,
+    ttl::{TTL_EXTEND_TO, TTL_THRESHOLD},
+};
+use soroban_sdk::{
+    Address, Env,
+    testutils::{Address as _, Ledger as _, storage::Persistent as _},
+    token,
+};
+use stream_index::{INDEX_CHUNK_SIZE, StreamIndex};
+
+fn setup(env: &Env) -> (PayrollStreamClient<'_>, Address, Address, u64) {
+    let admin = Address::generate(env);
+    let employer = Address::generate(env);
+    let worker = Address::generate(env);
+
+    let token_id = env
+        .register_stellar_asset_contract_v2(Address::generate(env))
+        .address();
+
+    let vault_id = env.register(PayrollVault, ());
+    let stream_id = env.register(PayrollStream, ());
+    let vault_client = PayrollVaultClient::new(env, &vault_id);
+    let client = PayrollStreamClient::new(env, &stream_id);
+
+    vault_client.initialize(&admin);
+    vault_client.set_token_allowlist_enforced(&false);
+    client.init(&admin);
+    client.set_min_stream_duration(&0u64);
+    client.set_withdrawal_cooldown(&0u64);
+    vault_client.set_authorized_contract(&stream_id);
+    client.set_vault(&vault_id);
+
+    token::StellarAssetClient::new(env, &token_id).mint(&employer, &10_000);
+    vault_client.deposit(&employer, &token_id, &10_000);
+
+    let id = client.create_stream(
+        &employer, &worker, &token_id, &1, &0u64, &0u64, &1_000u64, &None, &None,
+    );
+    (client, employer, worker, id)
+}
+
+fn persistent_ttl<K>(env: &Env, client: &PayrollStreamClient, key: &K) -> u32
+where
+    K: soroban_sdk::IntoVal<Env, soroban_sdk::Val>,
+{
+    env.as_contract(&client.address, || env.storage().persistent().get_ttl(key))
+}
+
+#[test]
+fn test_bump_ttl_extends_scopes_and_reports_expiry() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    let (client, employer, worker, id) = setup(&env);
+    env.ledger().set_sequence_number(100);
+
+    assert_eq!(client.get_ttl_expiry(&TtlScope::Stream(id)), None);
+    assert_eq!(
+        client.get_expiring_streams(&employer, &0, &0),
+        (soroban_sdk::vec![&env, id], None)
+    );
+
+    let live_until = client.bump_ttl(&TtlScope::Stream(id));
+    assert_eq!(live_until, 100 + TTL_EXTEND_TO);
+    assert_eq!(
+        client.get_ttl_expiry(&TtlScope::Stream(id)),
+        Some(live_until)
+    );
+    assert_eq!(
+        persistent_ttl(&env, &client, &StreamKey::Stream(id)),
+        TTL_EXTEND_TO
+    );
+    assert_eq!(
+        persistent_ttl(&env, &client, &StreamKey::StreamSlots(id)),
+        TTL_EXTEND_TO
+    );
+
+    client.bump_ttl(&TtlScope::Account(employer.clone()));
+    client.bump_ttl(&TtlScope::Account(worker.clone()));
+    client.bump_ttl(&TtlScope::Contract);
+    let index = StreamIndex::WorkerActive(worker.clone());
+    assert_eq!(client.bump_index_ttl(&index, &0), None);
+    let head = StreamKey::IndexHead(StreamIndex::EmployerActive(employer.clone()));
+    let chunk = StreamKey::IndexChunk(index, 0);
+    assert_eq!(persistent_ttl(&env, &client, &head), TTL_EXTEND_TO);
+    assert_eq!(persistent_ttl(&env, &client, &chunk), TTL_EXTEND_TO);
+    assert_eq!(
+        client.get_ttl_expiry(&TtlScope::Account(worker.clone())),
+        Some(live_until)
+    );
+
+    assert!(
+        client
+            .get_expiring_streams(&employer, &TTL_THRESHOLD, &0)
+            .0
+            .is_empty()
+    );
+    assert_eq!(
+        client.get_expiring_streams(&employer, &(TTL_EXTEND_TO + 1), &0),
+        (soroban_sdk::vec![&env, id], None)
+    );
+}
+
+#[test]
+fn test_bump_ttl_rejects_unknown_entries() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    let (client, _, _, id) = setup(&env);
+
+    assert_eq!(
+        client.try_bump_ttl(&TtlScope::Stream(id + 1)),
+        Err(Ok(QuipayError::StreamNotFound))
+    );
+    assert_eq!(
+        client.try_bump_ttl(&TtlScope::Receipt(id)),
+        Err(Ok(QuipayError::UnsupportedTtlScope))
+    );
+}
+
+#[test]
+fn test_bumped_stream_outlives_default_ttl_and_restores_after_archival() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    let (client, employer, worker, id) = setup(&env);
+    let min_ttl = env.ledger().get().min_persistent_entry_ttl;
+
+    client.bump_ttl(&TtlScope::Stream(id));
+    client.bump_ttl(&TtlScope::Account(worker.clone()));
+
+    // Well past the default TTL the bumped entries are still live.
+    env.ledger().with_mut(|li| {
+        li.sequence_number += min_ttl * 10;
+        li.timestamp = 100;
+    });
+    assert!(persistent_ttl(&env, &client, &StreamKey::Stream(id)) > 0);
+    assert_eq!(client.withdraw(&id, &worker), 100);
+
+    // Past the full TTL the entries are archived; reading them restores them.
+    env.ledger().with_mut(|li| {
+        li.sequence_number += TTL_EXTEND_TO + 1;
+        li.timestamp = 200;
+    });
+    assert_eq!(client.get_stream(&id).unwrap().withdrawn_amount, 100);
+    assert_eq!(
+        client.get_streams_by_employer(&employer, &None, &None),
+        soroban_sdk::vec![&env, id]
+    );
+    assert_eq!(client.withdraw(&id, &worker), 100);
+    assert_eq!(
+        client.get_expiring_streams(&employer, &0, &0),
+        (soroban_sdk::vec![&env, id], None)
+    );
+}
+
+#[test]
+fn test_expiring_streams_span_index_chunks() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    let (client, employer, worker, id) = setup(&env);
+    let token = client.get_stream(&id).unwrap().token;
+
+    let mut expected = soroban_sdk::Vec::new(&env);
+    for _ in 0..INDEX_CHUNK_SIZE + 6 {
+        expected.push_back(client.create_stream(
+            &employer, &worker, &token, &1, &0u64, &0u64, &10u64, &None, &None,
+        ));
+    }
+    client.bump_ttl(&TtlScope::Stream(id));
+
+    assert_eq!(
+        client.get_expiring_streams(&employer, &0, &0),
+        (expected, None)
+    );
+}
+
+#[test]
+fn test_index_ttl_and_expiring_streams_page_by_chunk() {
+    let env = Env::default();
+    env.mock_all_auths_allowing_non_root_auth();
+    let (client, employer, worker, id) = setup(&env);
+    let token = client.get_stream(&id).unwrap().token;
+
+    // Five chunks: the first holds `id` and 63 more, the last holds 1.
+    for _ in 0..4 * INDEX_CHUNK_SIZE {
+        client.create_stream(
+            &employer, &worker, &token, &1, &0u64, &0u64, &10u64, &None, &None,
+        );
+    }
+
+    let (page, next) = client.get_expiring_streams(&employer, &0, &0);
+    assert_eq!(page.len(), 3 * INDEX_CHUNK_SIZE);
+    assert_eq!(page.get(0), Some(id));
+    assert_eq!(next, Some(3));
+    let (page, next) = client.get_expiring_streams(&employer, &0, &3);
+    assert_eq!(page.len(), INDEX_CHUNK_SIZE + 1);
+    assert_eq!(next, None);
+
+    let index = StreamIndex::EmployerActive(employer.clone());
+    assert_eq!(client.bump_index_ttl(&index, &0), None);
+    let last = StreamKey::IndexChunk(index, 4);
+    assert_eq!(persistent_ttl(&env, &client, &last), TTL_EXTEND_TO);
+}
@@ -17,9 +17,47 @@ mod dummy_vault {
             true
         }
         pub fn add_liability(_env: Env, _token: Address, _amount: i128) {}
+        pub fn charge_protocol_fee(
+            _env: Env,
+            _stream_id: u64,
+            _employer: Address,
+            _token: Address,
+            _volume: i128,
+        ) -> i128 {
+            0
+        }
         pub fn remove_liability(_env: Env, _token: Address, _amount: i128) {}
-        pub fn payout_liability(_env: Env, _to: Address, _token: Address, _amount: i128) {}
-        pub fn get_balance(_env: Env, _token: Address) -> i128 {
+        pub fn refund_stream_liability(
+            _env: Env,
+            _stream_id: u64,
+            _to: Address,
+            _token: Address,
+            amount: i128,
+        ) -> i128 {
+            amount
+        }
+        pub fn payout_streams_liability(
+            _env: Env,
+            _to: Address,
+            _token: Address,
+            _stream_ids: soroban_sdk::Vec<u64>,
+            amounts: soroban_sdk::Vec<i128>,
+        ) -> i128 {
+            amounts.iter().sum()
+        }
+        pub fn payout_stream_liability(
+            _env: Env,
+            _stream_id: u64,
+            _to: Address,
+            _token: Address,
+            amount: i128,
+        ) -> i128 {
+            amount
+        }
+        pub fn is_insolvent(_env: Env, _token: Address) -> bool {
+            false
+        }
+        pub fn get_treasury_balance(_env: Env, _token: Address) -> i128 {
             1_000_000
         }
         pub fn get_liability(_env: Env, _token: Address) -> i128 {
@@ -53,11 +91,49 @@ mod selective_rejecting_payout_vault {
             true
         }
         pub fn add_liability(_env: Env, _token: Address, _amount: i128) {}
+        pub fn charge_protocol_fee(
+            _env: Env,
+            _stream_id: u64,
+            _employer: Address,
+            _token: Address,
+            _volume: i128,
+        ) -> i128 {
+            0
+        }
         pub fn remove_liability(_env: Env, _token: Address, _amount: i128) {}
-        pub fn payout_liability(_env: Env, _to: Address, _token: Address, amount: i128) {
+        pub fn refund_stream_liability(
+            _env: Env,
+            _stream_id: u64,
+            _to: Address,
+            _token: Address,
+            amount: i128,
+        ) -> i128 {
+            amount
+        }
+        pub fn payout_streams_liability(
+            _env: Env,
+            _to: Address,
+            _token: Address,
+            _stream_ids: soroban_sdk::Vec<u64>,
+            amounts: soroban_sdk::Vec<i128>,
+        ) -> i128 {
+            let amount: i128 = amounts.iter().sum();
+            if amount >= 1000 {
+                panic!("vault rejected payout");
+            }
+            amount
+        }
+        pub fn payout_stream_liability(
+            _env: Env,
+            _stream_id: u64,
+            _to: Address,
+            _token: Address,
+            amount: i128,
+        ) -> i128 {
             if amount >= 1000 {
                 panic!("vault rejected payout");
             }
+            amount
         }
     }
 }
@@ -73,8 +149,46 @@ mod insolvent_vault {
             false
         }
         pub fn add_liability(_env: Env, _token: Address, _amount: i128) {}
+        pub fn charge_protocol_fee(
+            _env: Env,
+            _stream_id: u64,
+            _employer: Address,
+            _token: Address,
+            _volume: i128,
+        ) -> i128 {
+            0
+        }
         pub fn remove_liability(_env: Env, _token: Address, _amount: i128) {}
-        pub fn payout_liability(_env: Env, _to: Address, _token: Address, _amount: i128) {}
+        pub fn refund_stream_liability(
+            _env: Env,
+            _stream_id: u64,
+            _to: Address,
+            _token: Address,
+            amount: i128,
+        ) -> i128 {
+            amount
+        }
+        pub fn payout_streams_liability(
+            _env: Env,
+            _to: Address,
+            _token: Address,
+            _stream_ids: soroban_sdk::Vec<u64>,
+            amounts: soroban_sdk::Vec<i128>,
+        ) -> i128 {
+            amounts.iter().sum()
+        }
+        pub fn payout_stream_liability(
+            _env: Env,
+            _stream_id: u64,
+            _to: Address,
+            _token: Address,
+            amount: i128,
+        ) -> i128 {
+            amount
+        }
+        pub fn is_insolvent(_env: Env, _token: Address) -> bool {
+            false
+        }
     }
 }
 
@@ -143,7 +257,7 @@ fn test_pause_mechanism() {
         &employer, &worker, &token, &100, &0u64, &0u64, &10u64, &None, &None
     );
 
-    client.set_paused(&true);
+    client.set_paused(&admin, &true);
     assert!(client.is_paused());
 }
 
@@ -163,7 +277,7 @@ fn test_create_stream_paused() {
     client.init(&admin);
     client.set_min_stream_duration(&0u64);
     client.set_vault(&vault_id);
-    client.set_paused(&true);
+    client.set_paused(&admin, &true);
 
     env.ledger().with_mut(|li| {
         li.timestamp = 0;
@@ -185,7 +299,7 @@ fn test_withdraw_paused() {
 
     client.init(&admin);
     client.set_min_stream_duration(&0u64);
-    client.set_paused(&true);
+    client.set_paused(&admin, &true);
     let result = client.try_withdraw(&1u64, &worker);
 
     assert!(result.is_err());
@@ -202,7 +316,7 @@ fn test_cancel_stream_paused() {
 
     client.init(&admin);
     client.set_min_stream_duration(&0u64);
-    client.set_paused(&true);
+    client.set_paused(&admin, &true);
     let result = client.try_cancel_stream(&1u64, &employer, &None);
 
     assert!(result.is_err());
@@ -224,10 +338,10 @@ fn test_unpause_resumes_operations() {
     client.init(&admin);
     client.set_min_stream_duration(&0u64);
     client.set_vault(&vault_id);
-    client.set_paused(&true);
+    client.set_paused(&admin, &true);
     assert!(client.is_paused());
 
-    client.set_paused(&false);
+    client.set_paused(&admin, &false);
     assert!(!client.is_paused());
 
     env.ledger().with_mut(|li| {
@@ -250,23 +364,65 @@ fn test_upgrade_functions_exempt_from_pause() {
     client.init(&admin);
     client.set_min_stream_duration(&0u64);
 
-    client.set_paused(&true);
+    client.set_paused(&admin, &true);
     assert!(client.is_paused());
 
     let wasm_hash: soroban_sdk::BytesN<32> = [0u8; 32].into_val(&env);
-    let result = client.try_propose_upgrade(&wasm_hash);
+    let result = client.try_propose_upgrade(&admin, &wasm_hash, &(1, 1, 0));
     assert!(result.is_ok());
 
     let pending = client.get_pending_upgrade();
     assert!(pending.is_some());
 
-    let result = client.try_cancel_upgrade();
+    let result = client.try_cancel_upgrade(&admin);
     assert!(result.is_ok());
 
     let pending = client.get_pending_upgrade();
     assert!(pending.is_none());
 }
 
+#[test]
+fn test_roles_separate_admin_duties() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let admin = Address::generate(&env);
+    let pauser = Address::generate(&env);
+    let fee_setter = Address::generate(&env);
+
+    let contract_id = env.register(PayrollStream, ());
+    let client = PayrollStreamClient::new(&env, &contract_id);
+    client.init(&admin);
+
+    assert_eq!(
+        client.try_set_paused(&pauser, &true),
+        Err(Ok(QuipayError::InsufficientPermissions))
+    );
+    client.grant_role(&Role::Pauser, &pauser);
+    client.grant_role(&Role::FeeSetter, &fee_setter);
+    assert!(client.has_role(&Role::Pauser, &pauser));
+
+    client.set_paused(&pauser, &true);
+    assert!(client.is_paused());
+    client.set_paused(&pauser, &false);
+
+    // Roles do not overlap.
+    assert_eq!(
+        client.try_set_early_cancel_fee(&pauser, &100u32),
+        Err(Ok(QuipayError::InsufficientPermissions))
+    );
+    client.set_early_cancel_fee(&fee_setter, &100u32);
+    assert_eq!(client.get_early_cancel_fee(), 100);
+
+    client.revoke_role(&Role::Pauser, &pauser);
+    assert_eq!(
+        client.try_set_paused(&pauser, &true),
+        Err(Ok(QuipayError::InsufficientPermissions))
+    );
+    client.renounce_role(&fee_setter, &Role::FeeSetter);
+    assert!(!client.has_role(&Role::FeeSetter, &fee_setter));
+}
+
 #[test]
 fn test_stream_withdraw_and_cleanup() {
     let env = Env::default();
@@ -1147,11 +1303,7 @@ mod mock_gateway {
     pub struct MockGateway;
     #[contractimpl]
     impl MockGateway {
-        pub fn get_admin(_env: Env) -> Address {
-            // For testing, just return a dummy address not matched unless properly set
-            panic!("Not properly initialized for test")
-        }
-        pub fn is_authorized(_env: Env, _agent: Address, _action: u32) -> bool {
+        pub fn is_authorized(_env: Env, _employer: Address, _agent: Address, _action: u32) -> bool {
             true
         }
     }
@@ -1176,7 +1328,7 @@ mod auth_mock_gateway {
                 .instance()
                 .set(&soroban_sdk::Symbol::new(&env, "admin"), &admin);
         }
-        pub fn is_authorized(_env: Env, _agent: Address, _action: u32) -> bool {
+        pub fn is_authorized(_env: Env, _employer: Address, _agent: Address, _action: u32) -> bool {
             true
         }
     }
@@ -2415,10 +2567,10 @@ fn test_cooldown_uses_default_when_never_configured() {
 fn test_pause_and_cancel_interaction() {
     let env = Env::default();
     env.mock_all_auths();
-    let (client, employer, worker, token, _admin) = setup(&env);
+    let (client, employer, worker, token, admin) = setup(&env);
 
     // Set early cancellation fee to 5% (500 bps)
-    client.set_early_cancel_fee(&500u32);
+    client.set_early_cancel_fee(&admin, &500u32);
     // Disable grace period for immediate cancellation
     client.set_cancellation_grace_period(&0u64);
 
@@ -1,7 +1,7 @@
 #![cfg(test)]
 
 use super::*;
-use quipay_common::QuipayError;
+use quipay_common::{QuipayError, Role};
 use soroban_sdk::{
     Address, BytesN, Env, contract, contractimpl,
     testutils::{Address as _, Ledger},
@@ -15,6 +15,15 @@ impl DummyVault {
         true
     }
     pub fn add_liability(_env: Env, _token: Address, _amount: i128) {}
+    pub fn charge_protocol_fee(
+        _env: Env,
+        _stream_id: u64,
+        _employer: Address,
+        _token: Address,
+        _volume: i128,
+    ) -> i128 {
+        0
+    }
 }
 
 fn setup_test(env: &Env) -> (Address, PayrollStreamClient) {
@@ -54,7 +63,7 @@ fn test_upgrade_proposal_and_state_preservation() {
 
     // 2. Propose upgrade
     let v2_wasm_hash = BytesN::from_array(&env, &[1u8; 32]);
-    client.propose_upgrade(&v2_wasm_hash);
+    client.propose_upgrade(&admin, &v2_wasm_hash, &(2, 0, 0));
 
     // Verify pending upgrade state
     let pending = client.get_pending_upgrade().unwrap();
@@ -71,27 +80,27 @@ fn test_upgrade_proposal_and_state_preservation() {
 #[test]
 fn test_upgrade_timelock_enforcement() {
     let env = Env::default();
-    let (_admin, client) = setup_test(&env);
+    let (admin, client) = setup_test(&env);
 
     let v2_wasm_hash = BytesN::from_array(&env, &[1u8; 32]);
-    client.propose_upgrade(&v2_wasm_hash);
+    client.propose_upgrade(&admin, &v2_wasm_hash, &(2, 0, 0));
 
-    // Try to execute immediately - should fail with Custom (timelock not met)
-    let result = client.try_execute_upgrade();
-    assert_eq!(result, Err(Ok(QuipayError::Custom)));
+    // Try to execute immediately - should fail (timelock not met)
+    let result = client.try_execute_upgrade(&admin);
+    assert_eq!(result, Err(Ok(QuipayError::UpgradeTimelockActive)));
 
     // Wait 24 hours (halfway)
     env.ledger().set_timestamp(env.ledger().timestamp() + 24 * 60 * 60);
-    let result = client.try_execute_upgrade();
-    assert_eq!(result, Err(Ok(QuipayError::Custom)));
+    let result = client.try_execute_upgrade(&admin);
+    assert_eq!(result, Err(Ok(QuipayError::UpgradeTimelockActive)));
 
     // Wait remaining 48 hours total
     env.ledger().set_timestamp(env.ledger().timestamp() + 24 * 60 * 60);
-    let result = client.try_execute_upgrade();
+    let result = client.try_execute_upgrade(&admin);
     
     // Now it should have passed the timelock check. 
     match result {
-        Err(Ok(err)) => assert_ne!(err, QuipayError::Custom),
+        Err(Ok(err)) => assert_ne!(err, QuipayError::UpgradeTimelockActive),
         _ => {} 
     }
 }
@@ -99,18 +108,118 @@ fn test_upgrade_timelock_enforcement() {
 #[test]
 fn test_cancel_upgrade() {
     let env = Env::default();
-    let (_admin, client) = setup_test(&env);
+    let (admin, client) = setup_test(&env);
 
     let v2_wasm_hash = BytesN::from_array(&env, &[1u8; 32]);
-    client.propose_upgrade(&v2_wasm_hash);
+    client.propose_upgrade(&admin, &v2_wasm_hash, &(2, 0, 0));
 
     // Cancel
-    client.cancel_upgrade();
+    client.cancel_upgrade(&admin);
 
     // Verify it's gone
     assert!(client.get_pending_upgrade().is_none());
 
     // Try to execute - should fail as no upgrade is pending
-    let result = client.try_execute_upgrade();
-    assert_eq!(result, Err(Ok(QuipayError::Custom)));
+    let result = client.try_execute_upgrade(&admin);
+    assert_eq!(result, Err(Ok(QuipayError::NoPendingUpgrade)));
+}
+
+#[test]
+fn test_upgrade_versioning_and_migration_guards() {
+    let env = Env::default();
+    let (admin, client) = setup_test(&env);
+    let v2_wasm_hash = BytesN::from_array(&env, &[1u8; 32]);
+
+    let version = client.get_version();
+    assert_eq!((version.major, version.minor, version.patch), (1, 0, 0));
+
+    // The proposed version must be newer than the installed one.
+    assert_eq!(
+        client.try_propose_upgrade(&admin, &v2_wasm_hash, &(1, 0, 0)),
+        Err(Ok(QuipayError::VersionMismatch))
+    );
+    client.propose_upgrade(&admin, &v2_wasm_hash, &(1, 2, 0));
+    assert_eq!(client.get_pending_upgrade().unwrap().version, (1, 2, 0));
+
+    // Nothing has been upgraded yet: no migration to run, nothing to roll back.
+    assert_eq!(
+        client.try_migrate(&admin, &(1, 0, 0)),
+        Err(Ok(QuipayError::VersionMismatch))
+    );
+    assert_eq!(
+        client.try_rollback_upgrade(&admin),
+        Err(Ok(QuipayError::NoRollbackTarget))
+    );
+}
+
+#[test]
+fn test_upgrader_role_gates_upgrade_entry_points() {
+    let env = Env::default();
+    let (admin, client) = setup_test(&env);
+    let upgrader = Address::generate(&env);
+    let v2_wasm_hash = BytesN::from_array(&env, &[1u8; 32]);
+
+    assert_eq!(
+        client.try_propose_upgrade(&upgrader, &v2_wasm_hash, &(2, 0, 0)),
+        Err(Ok(QuipayError::InsufficientPermissions))
+    );
+    assert_eq!(
+        client.try_rollback_upgrade(&upgrader),
+        Err(Ok(QuipayError::InsufficientPermissions))
+    );
+
+    client.grant_role(&Role::Upgrader, &upgrader);
+    client.propose_upgrade(&upgrader, &v2_wasm_hash, &(2, 0, 0));
+    assert_eq!(client.get_pending_upgrade().unwrap().proposed_by, upgrader);
+    client.cancel_upgrade(&admin);
+    assert!(client.get_pending_upgrade().is_none());
+}
+
+#[test]
+fn test_shared_upgrade_manager_in_gateway_and_registry() {
+    use automation_gateway::{AutomationGateway, AutomationGatewayClient};
+    use workforce_registry::{WorkforceRegistryContract, WorkforceRegistryContractClient};
+
+    let env = Env::default();
+    env.mock_all_auths();
+    let admin = Address::generate(&env);
+    let v2_wasm_hash = BytesN::from_array(&env, &[2u8; 32]);
+
+    let gateway = AutomationGatewayClient::new(&env, &env.register(AutomationGateway, ()));
+    gateway.init(&admin);
+    let upgrader = Address::generate(&env);
+    assert_eq!(
+        gateway.try_propose_upgrade(&upgrader, &v2_wasm_hash, &(1, 1, 0)),
+        Err(Ok(QuipayError::InsufficientPermissions))
+    );
+    gateway.grant_role(&Role::Upgrader, &upgrader);
+    gateway.propose_upgrade(&upgrader, &v2_wasm_hash, &(1, 1, 0));
+    let pending = gateway.get_pending_upgrade().unwrap();
+    assert_eq!(pending.proposed_by, upgrader);
+    assert_eq!(
+        gateway.try_execute_upgrade(&admin),
+        Err(Ok(QuipayError::UpgradeTimelockActive))
+    );
+    assert_eq!(
+        gateway.try_migrate(&upgrader, &(1, 0, 0)),
+        Err(Ok(QuipayError::VersionMismatch))
+    );
+    gateway.cancel_upgrade(&upgrader);
+    assert!(gateway.get_pending_upgrade().is_none());
+
+    let registry = WorkforceRegistryContractClient::new(
+        &env,
+        &env.register(WorkforceRegistryContract, ()),
+    );
+    registry.initialize(&admin);
+    assert_eq!(registry.get_version().major, 1);
+    registry.propose_upgrade(&admin, &v2_wasm_hash, &(2, 0, 0));
+    assert_eq!(
+        registry.try_propose_upgrade(&admin, &v2_wasm_hash, &(3, 0, 0)),
+        Err(Ok(QuipayError::UpgradeAlreadyPending))
+    );
+    assert_eq!(
+        registry.try_rollback_upgrade(&admin),
+        Err(Ok(QuipayError::NoRollbackTarget))
+    );
 }
@@ -0,0 +1,344 @@
+//! Events published by PayrollVault, all under the `vault` namespace.
+//!
+//! Topics follow `quipay_common::events`. Payouts and liability changes name
+//! the authorized contract (PayrollStream) as the actor, multisig actions the
+//! proposer, and permissionless bookkeeping (IOUs, reconciliation, drains
+//! and strategy recalls) the vault itself.
+
+use crate::{DrainTarget, ProposalAction, ProtocolFee, TokenConfig};
+use soroban_sdk::{Address, Vec, contracttype};
+
+pub(crate) use quipay_common::events::{emit, emit_from_contract};
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Deposited {
+    pub token: Address,
+    /// Tokens actually received, net of any transfer fee.
+    pub amount: i128,
+    pub new_balance: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Withdrawn {
+    pub to: Address,
+    pub token: Address,
+    pub amount: i128,
+    pub new_balance: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FundsAllocated {
+    pub token: Address,
+    pub amount: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FundsReleased {
+    pub token: Address,
+    pub amount: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LiabilityAdded {
+    pub token: Address,
+    pub amount: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LiabilityRemoved {
+    pub token: Address,
+    pub amount: i128,
+}
+
+/// A payout not tied to a single stream. `amount` is what `to` received.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PaidOut {
+    pub to: Address,
+    pub token: Address,
+    pub amount: i128,
+    pub fee: i128,
+}
+
+/// A payout of one stream's claim. `amount` is what `to` received; any
+/// shortfall follows as an `IouRecorded`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StreamPaidOut {
+    pub stream_id: u64,
+    pub to: Address,
+    pub token: Address,
+    pub amount: i128,
+    pub fee: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct IouRecorded {
+    pub iou_id: u64,
+    pub stream_id: u64,
+    pub recipient: Address,
+    pub token: Address,
+    pub amount: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct IouSettled {
+    pub iou_id: u64,
+    pub stream_id: u64,
+    pub recipient: Address,
+    pub token: Address,
+    pub paid: i128,
+    /// Still owed after this settlement; zero once the IOU is closed.
+    pub remaining: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct DrainProposed {
+    pub recipient: Address,
+    pub targets: Vec<DrainTarget>,
+    pub protective: bool,
+    pub execute_after: u64,
+}
+
+/// One token moved by an executed drain.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DrainExecuted {
+    pub recipient: Address,
+    pub token: Address,
+    pub amount: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DrainCanceled {
+    pub recipient: Address,
+    pub execute_after: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SignerAdded {
+    pub signer: Address,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SignerRemoved {
+    pub signer: Address,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ThresholdSet {
+    pub threshold: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProposalCreated {
+    pub proposal_id: u64,
+    pub action: ProposalAction,
+    pub expires_at: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ProposalApproved {
+    pub proposal_id: u64,
+    pub approvals: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ApprovalRevoked {
+    pub proposal_id: u64,
+    pub approvals: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ProposalExecuted {
+    pub proposal_id: u64,
+    pub approvals: Vec<Address>,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ProposalCanceled {
+    pub proposal_id: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProtocolFeeSet {
+    pub fee: ProtocolFee,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FeeTierSet {
+    pub tier: u32,
+    pub discount_bps: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EmployerFeeTierSet {
+    pub employer: Address,
+    pub tier: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FeeCharged {
+    pub stream_id: u64,
+    pub employer: Address,
+    pub token: Address,
+    pub fee: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FeesClaimed {
+    pub token: Address,
+    pub collector: Address,
+    pub amount: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MandateCreated {
+    pub mandate_id: u64,
+    pub employer: Address,
+    pub token: Address,
+    pub min_runway_secs: u64,
+    pub buffer: i128,
+    pub max_per_period: i128,
+    pub period_secs: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MandateRevoked {
+    pub mandate_id: u64,
+    pub employer: Address,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MandateExecuted {
+    pub mandate_id: u64,
+    pub employer: Address,
+    pub token: Address,
+    pub amount: i128,
+    pub new_balance: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Reconciled {
+    pub token: Address,
+    pub recorded: i128,
+    pub held: i128,
+}
+
+/// `to` is `None` when the surplus was credited to the treasury.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SurplusSwept {
+    pub token: Address,
+    pub amount: i128,
+    pub to: Option<Address>,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StrategySet {
+    pub token: Address,
+    pub strategy: Address,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StrategyInvested {
+    pub token: Address,
+    pub amount: i128,
+    pub principal: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StrategyRecalled {
+    pub token: Address,
+    pub amount: i128,
+    pub principal: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct TokenConfigured {
+    pub token: Address,
+    pub config: TokenConfig,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TokenDelisted {
+    pub token: Address,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AllowlistEnforced {
+    pub enforced: bool,
+}
+
+quipay_common::event_names!("vault" {
+    Deposited => "deposited",
+    Withdrawn => "withdrawn",
+    FundsAllocated => "allocated",
+    FundsReleased => "released",
+    LiabilityAdded => "liability_added",
+    LiabilityRemoved => "liability_removed",
+    PaidOut => "paid_out",
+    StreamPaidOut => "stream_paid_out",
+    IouRecorded => "iou_recorded",
+    IouSettled => "iou_settled",
+    DrainProposed => "drain_proposed",
+    DrainExecuted => "drain_executed",
+    DrainCanceled => "drain_canceled",
+    SignerAdded => "signer_added",
+    SignerRemoved => "signer_removed",
+    ThresholdSet => "threshold_set",
+    ProposalCreated => "proposal_created",
+    ProposalApproved => "proposal_approved",
+    ApprovalRevoked => "approval_revoked",
+    ProposalExecuted => "proposal_executed",
+    ProposalCanceled => "proposal_canceled",
+    ProtocolFeeSet => "fee_set",
+    FeeTierSet => "fee_tier_set",
+    EmployerFeeTierSet => "employer_tier_set",
+    FeeCharged => "fee_charged",
+    FeesClaimed => "fees_claimed",
+    MandateCreated => "mandate_created",
+    MandateRevoked => "mandate_revoked",
+    MandateExecuted => "mandate_executed",
+    Reconciled => "reconciled",
+    SurplusSwept => "surplus_swept",
+    StrategySet => "strategy_set",
+    StrategyInvested => "strategy_invested",
+    StrategyRecalled => "strategy_recalled",
+    TokenConfigured => "token_configured",
+    TokenDelisted => "token_delisted",
+    AllowlistEnforced => "allowlist_enforced",
+});
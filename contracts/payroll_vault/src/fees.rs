@@ -0,0 +1,284 @@
+//! Protocol fee.
+//!
+//! A fee in basis points, capped at `MAX_PROTOCOL_FEE_BPS`, charged either on
+//! stream creation volume or on payouts. Employers can be placed in discount
+//! tiers. Collected fees accrue per token in `AccruedFees(token)`, outside
+//! `TreasuryBalance`, so they never count towards an employer's solvency,
+//! and the fee collector claims them with `claim_fees`.
+//!
+//! PayrollStream calls `charge_protocol_fee` once per stream when it is created
+//! or extended:
+//! - `FeeBasis::Creation`: the fee on the new volume is charged immediately
+//!   from the treasury's free balance (on top of the liability).
+//! - `FeeBasis::Payout`: the employer's discounted rate is recorded for the
+//!   stream and deducted from each payout the worker receives. The liability
+//!   released is unchanged.
+
+use super::*;
+use crate::events::{
+    self, EmployerFeeTierSet, FeeCharged, FeeTierSet, FeesClaimed, ProtocolFeeSet,
+};
+
+/// Upper bound for the protocol fee (5%).
+pub const MAX_PROTOCOL_FEE_BPS: u32 = 500;
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum FeeBasis {
+    Creation = 0,
+    Payout = 1,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ProtocolFee {
+    pub fee_bps: u32,
+    pub basis: FeeBasis,
+}
+
+#[contractimpl]
+impl PayrollVault {
+    /// Set the protocol fee. Requires the admin or a `FeeSetter`.
+    pub fn set_protocol_fee(
+        e: Env,
+        caller: Address,
+        fee_bps: u32,
+        basis: FeeBasis,
+    ) -> Result<(), QuipayError> {
+        let admin = Self::get_admin(e.clone())?;
+        rbac::require_role(&e, Role::FeeSetter, &caller, &admin)?;
+
+        if fee_bps > MAX_PROTOCOL_FEE_BPS {
+            return Err(QuipayError::FeeTooHigh);
+        }
+
+        let fee = ProtocolFee { fee_bps, basis };
+        e.storage().persistent().set(&StateKey::ProtocolFee, &fee);
+
+        events::emit(&e, &caller, ProtocolFeeSet { fee });
+        Ok(())
+    }
+
+    pub fn get_protocol_fee(e: Env) -> ProtocolFee {
+        e.storage()
+            .persistent()
+            .get(&StateKey::ProtocolFee)
+            .unwrap_or(ProtocolFee {
+                fee_bps: 0,
+                basis: FeeBasis::Creation,
+            })
+    }
+
+    /// Set the discount for fee tier `tier`. Tier 0 is the default tier.
+    /// Requires the admin or a `FeeSetter`.
+    pub fn set_fee_tier(
+        e: Env,
+        caller: Address,
+        tier: u32,
+        discount_bps: u32,
+    ) -> Result<(), QuipayError> {
+        let admin = Self::get_admin(e.clone())?;
+        rbac::require_role(&e, Role::FeeSetter, &caller, &admin)?;
+
+        if discount_bps > 10_000 {
+            return Err(QuipayError::InvalidAmount);
+        }
+        e.storage()
+            .persistent()
+            .set(&StateKey::FeeTierDiscount(tier), &discount_bps);
+
+        events::emit(&e, &caller, FeeTierSet { tier, discount_bps });
+        Ok(())
+    }
+
+    /// Place `employer` in fee tier `tier`. Requires the admin or a `FeeSetter`.
+    pub fn set_employer_fee_tier(
+        e: Env,
+        caller: Address,
+        employer: Address,
+        tier: u32,
+    ) -> Result<(), QuipayError> {
+        let admin = Self::get_admin(e.clone())?;
+        rbac::require_role(&e, Role::FeeSetter, &caller, &admin)?;
+
+        let key = StateKey::EmployerFeeTier(employer.clone());
+        if tier == 0 {
+            e.storage().persistent().remove(&key);
+        } else {
+            e.storage().persistent().set(&key, &tier);
+        }
+
+        events::emit(&e, &caller, EmployerFeeTierSet { employer, tier });
+        Ok(())
+    }
+
+    pub fn get_employer_fee_tier(e: Env, employer: Address) -> u32 {
+        e.storage()
+            .persistent()
+            .get(&StateKey::EmployerFeeTier(employer))
+            .unwrap_or(0)
+    }
+
+    /// The protocol fee rate after `employer`'s tier discount.
+    pub fn get_employer_fee_bps(e: Env, employer: Address) -> u32 {
+        let fee_bps = Self::get_protocol_fee(e.clone()).fee_bps;
+        let tier = Self::get_employer_fee_tier(e.clone(), employer);
+        let discount_bps: u32 = e
+            .storage()
+            .persistent()
+            .get(&StateKey::FeeTierDiscount(tier))
+            .unwrap_or(0);
+        fee_bps * (10_000 - discount_bps) / 10_000
+    }
+
+    /// Set the address allowed to claim accrued fees.
+    /// Only the admin can call this function.
+    pub fn set_fee_collector(e: Env, collector: Address) -> Result<(), QuipayError> {
+        let admin = Self::get_admin(e.clone())?;
+        admin.require_auth();
+
+        e.storage()
+            .persistent()
+            .set(&StateKey::FeeCollector, &collector);
+        Ok(())
+    }
+
+    pub fn get_fee_collector(e: Env) -> Option<Address> {
+        e.storage().persistent().get(&StateKey::FeeCollector)
+    }
+
+    pub fn get_accrued_fees(e: Env, token: Address) -> i128 {
+        e.storage()
+            .persistent()
+            .get(&StateKey::AccruedFees(token))
+            .unwrap_or(0)
+    }
+
+    /// Transfer all accrued `token` fees to the fee collector.
+    /// Requires the fee collector's authorization. Returns the amount claimed.
+    pub fn claim_fees(e: Env, token: Address) -> Result<i128, QuipayError> {
+        let collector = Self::get_fee_collector(e.clone()).ok_or(QuipayError::NotInitialized)?;
+        collector.require_auth();
+
+        let amount = Self::get_accrued_fees(e.clone(), token.clone());
+        if amount == 0 {
+            return Ok(0);
+        }
+        e.storage()
+            .persistent()
+            .remove(&StateKey::AccruedFees(token.clone()));
+
+        Self::ensure_liquidity(&e, &token, amount)?;
+        let token_client = token::Client::new(&e, &token);
+        token_client.transfer(&e.current_contract_address(), &collector, &amount);
+
+        events::emit(
+            &e,
+            &collector,
+            FeesClaimed {
+                token,
+                collector: collector.clone(),
+                amount,
+            },
+        );
+        Ok(amount)
+    }
+
+    /// Apply the protocol fee to `volume` of new stream liability.
+    ///
+    /// Only the authorized contract (e.g., PayrollStream) can call this, after
+    /// `add_liability`. Returns the fee charged now, which is zero under
+    /// `FeeBasis::Payout`.
+    pub fn charge_protocol_fee(
+        e: Env,
+        stream_id: u64,
+        employer: Address,
+        token: Address,
+        volume: i128,
+    ) -> Result<i128, QuipayError> {
+        let authorized: Address = e
+            .storage()
+            .persistent()
+            .get(&StateKey::AuthorizedContract)
+            .ok_or(QuipayError::NotInitialized)?;
+        authorized.require_auth();
+
+        let fee_bps = Self::get_employer_fee_bps(e.clone(), employer.clone());
+        if Self::get_protocol_fee(e.clone()).basis == FeeBasis::Payout {
+            let key = StateKey::StreamFeeBps(stream_id);
+            if fee_bps > 0 {
+                e.storage().persistent().set(&key, &fee_bps);
+            } else {
+                e.storage().persistent().remove(&key);
+            }
+            return Ok(0);
+        }
+
+        let fee = Self::fee_on(volume, fee_bps)?;
+        if fee == 0 {
+            return Ok(0);
+        }
+
+        // The fee comes out of the free balance, never out of funds that
+        // back liabilities.
+        let balance = Self::get_treasury_balance(e.clone(), token.clone());
+        let liability = Self::get_total_liability(e.clone(), token.clone());
+        if balance - liability < fee {
+            return Err(QuipayError::InsufficientBalance);
+        }
+        e.storage()
+            .persistent()
+            .set(&StateKey::TreasuryBalance(token.clone()), &(balance - fee));
+        Self::accrue_fee(&e, &token, fee)?;
+
+        events::emit(
+            &e,
+            &authorized,
+            FeeCharged {
+                stream_id,
+                employer,
+                token,
+                fee,
+            },
+        );
+        Ok(fee)
+    }
+}
+
+impl PayrollVault {
+    /// Fee owed on a payout of `amount` from `stream_id`, accrued to the
+    /// protocol. The caller transfers `amount - fee` to the recipient.
+    pub(crate) fn take_payout_fee(
+        e: &Env,
+        stream_id: u64,
+        token: &Address,
+        amount: i128,
+    ) -> Result<i128, QuipayError> {
+        let fee_bps: u32 = e
+            .storage()
+            .persistent()
+            .get(&StateKey::StreamFeeBps(stream_id))
+            .unwrap_or(0);
+        let fee = Self::fee_on(amount, fee_bps)?;
+        if fee > 0 {
+            Self::accrue_fee(e, token, fee)?;
+        }
+        Ok(fee)
+    }
+
+    fn fee_on(amount: i128, fee_bps: u32) -> Result<i128, QuipayError> {
+        Ok(amount
+            .checked_mul(fee_bps as i128)
+            .ok_or(QuipayError::Overflow)?
+            / 10_000)
+    }
+
+    fn accrue_fee(e: &Env, token: &Address, fee: i128) -> Result<(), QuipayError> {
+        let key = StateKey::AccruedFees(token.clone());
+        let accrued: i128 = e.storage().persistent().get(&key).unwrap_or(0);
+        let new_accrued = accrued.checked_add(fee).ok_or(QuipayError::Overflow)?;
+        e.storage().persistent().set(&key, &new_accrued);
+        Ok(())
+    }
+}
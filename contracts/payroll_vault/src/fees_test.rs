@@ -0,0 +1,187 @@
+use crate::{FeeBasis, PayrollVault, PayrollVaultClient, StateKey};
+use quipay_common::{QuipayError, Role};
+use soroban_sdk::{Address, Env, testutils::Address as _, token, vec};
+
+struct Setup<'a> {
+    client: PayrollVaultClient<'a>,
+    admin: Address,
+    token: Address,
+    employer: Address,
+}
+
+fn setup(env: &Env) -> Setup<'_> {
+    env.mock_all_auths();
+    let client = PayrollVaultClient::new(env, &env.register(PayrollVault, ()));
+    let admin = Address::generate(env);
+    client.initialize(&admin);
+    client.set_token_allowlist_enforced(&false);
+    client.set_authorized_contract(&Address::generate(env));
+
+    let token = env
+        .register_stellar_asset_contract_v2(Address::generate(env))
+        .address();
+    let employer = Address::generate(env);
+    token::StellarAssetClient::new(env, &token).mint(&employer, &10_000);
+    client.deposit(&employer, &token, &10_000);
+
+    Setup {
+        client,
+        admin,
+        token,
+        employer,
+    }
+}
+
+#[test]
+fn test_fee_setter_and_discount_tiers() {
+    let env = Env::default();
+    let s = setup(&env);
+    let fee_setter = Address::generate(&env);
+
+    assert_eq!(
+        s.client
+            .try_set_protocol_fee(&fee_setter, &100, &FeeBasis::Creation),
+        Err(Ok(QuipayError::InsufficientPermissions))
+    );
+    s.client.grant_role(&Role::FeeSetter, &fee_setter);
+    assert_eq!(
+        s.client
+            .try_set_protocol_fee(&fee_setter, &501, &FeeBasis::Creation),
+        Err(Ok(QuipayError::FeeTooHigh))
+    );
+    s.client
+        .set_protocol_fee(&fee_setter, &100, &FeeBasis::Creation);
+    assert_eq!(s.client.get_employer_fee_bps(&s.employer), 100);
+
+    s.client.set_fee_tier(&fee_setter, &1, &5_000);
+    s.client.set_employer_fee_tier(&fee_setter, &s.employer, &1);
+    assert_eq!(s.client.get_employer_fee_tier(&s.employer), 1);
+    assert_eq!(s.client.get_employer_fee_bps(&s.employer), 50);
+
+    s.client.set_fee_tier(&s.admin, &2, &10_000);
+    s.client.set_employer_fee_tier(&s.admin, &s.employer, &2);
+    assert_eq!(s.client.get_employer_fee_bps(&s.employer), 0);
+}
+
+#[test]
+fn test_creation_fee_accrues_outside_treasury() {
+    let env = Env::default();
+    let s = setup(&env);
+    let collector = Address::generate(&env);
+    s.client
+        .set_protocol_fee(&s.admin, &100, &FeeBasis::Creation);
+
+    assert_eq!(
+        s.client.try_claim_fees(&s.token),
+        Err(Ok(QuipayError::NotInitialized))
+    );
+
+    s.client.add_liability(&s.token, &5_000);
+    assert_eq!(
+        s.client
+            .charge_protocol_fee(&1, &s.employer, &s.token, &5_000),
+        50
+    );
+    assert_eq!(s.client.get_treasury_balance(&s.token), 9_950);
+    assert_eq!(s.client.get_total_liability(&s.token), 5_000);
+    assert_eq!(s.client.get_accrued_fees(&s.token), 50);
+    assert_eq!(s.client.get_balance_drift(&s.token).drift, 0);
+
+    // The fee is charged from the free balance, never from backed funds.
+    s.client.add_liability(&s.token, &4_950);
+    assert_eq!(
+        s.client
+            .try_charge_protocol_fee(&2, &s.employer, &s.token, &4_950),
+        Err(Ok(QuipayError::InsufficientBalance))
+    );
+
+    s.client.set_fee_collector(&collector);
+    assert_eq!(s.client.claim_fees(&s.token), 50);
+    assert_eq!(token::Client::new(&env, &s.token).balance(&collector), 50);
+    assert_eq!(s.client.get_accrued_fees(&s.token), 0);
+}
+
+#[test]
+fn test_payout_fee_deducted_from_stream_payouts() {
+    let env = Env::default();
+    let s = setup(&env);
+    let worker = Address::generate(&env);
+    let token_read = token::Client::new(&env, &s.token);
+    s.client.set_protocol_fee(&s.admin, &200, &FeeBasis::Payout);
+
+    s.client.add_liability(&s.token, &3_000);
+    assert_eq!(
+        s.client
+            .charge_protocol_fee(&7, &s.employer, &s.token, &2_000),
+        0
+    );
+    s.client.set_protocol_fee(&s.admin, &0, &FeeBasis::Payout);
+    s.client
+        .charge_protocol_fee(&8, &s.employer, &s.token, &1_000);
+
+    // Stream 7 keeps the rate it was created with.
+    assert_eq!(
+        s.client
+            .payout_stream_liability(&7, &worker, &s.token, &1_000),
+        1_000
+    );
+    assert_eq!(token_read.balance(&worker), 980);
+
+    let paid = s.client.payout_streams_liability(
+        &worker,
+        &s.token,
+        &vec![&env, 7u64, 8u64],
+        &vec![&env, 1_000i128, 1_000i128],
+    );
+    assert_eq!(paid, 1_980);
+    assert_eq!(token_read.balance(&worker), 2_960);
+    assert_eq!(s.client.get_accrued_fees(&s.token), 40);
+    assert_eq!(s.client.get_total_liability(&s.token), 0);
+    assert_eq!(s.client.get_treasury_balance(&s.token), 7_000);
+}
+
+#[test]
+fn test_refund_stream_liability_is_fee_free_and_pro_rata() {
+    let env = Env::default();
+    let s = setup(&env);
+    let worker = Address::generate(&env);
+    let token_read = token::Client::new(&env, &s.token);
+    s.client.set_protocol_fee(&s.admin, &200, &FeeBasis::Payout);
+
+    s.client.add_liability(&s.token, &2_000);
+    s.client
+        .charge_protocol_fee(&7, &s.employer, &s.token, &2_000);
+
+    // Half of the backing funds disappear: balance 1_000 vs liability 2_000.
+    env.as_contract(&s.client.address, || {
+        env.storage()
+            .persistent()
+            .set(&StateKey::TreasuryBalance(s.token.clone()), &1_000i128);
+    });
+
+    assert_eq!(
+        s.client
+            .payout_stream_liability(&7, &worker, &s.token, &1_000),
+        500
+    );
+    assert_eq!(token_read.balance(&worker), 490);
+
+    // The refund shares the balance pro-rata with the worker's claim.
+    assert_eq!(
+        s.client
+            .refund_stream_liability(&7, &s.employer, &s.token, &1_000),
+        500
+    );
+    assert_eq!(token_read.balance(&s.employer), 500);
+    assert_eq!(s.client.get_stream_iou(&7), 1_000);
+    assert_eq!(s.client.get_accrued_fees(&s.token), 10);
+
+    // Settling the refund's IOU does not charge a fee either.
+    token::StellarAssetClient::new(&env, &s.token).mint(&s.employer, &1_000);
+    s.client.deposit(&s.employer, &s.token, &1_000);
+    assert_eq!(token_read.balance(&worker), 980);
+    assert_eq!(token_read.balance(&s.employer), 1_000);
+    assert_eq!(s.client.get_accrued_fees(&s.token), 20);
+    assert_eq!(s.client.get_total_iou(&s.token), 0);
+    assert_eq!(s.client.get_total_liability(&s.token), 0);
+}
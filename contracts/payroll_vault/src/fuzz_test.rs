@@ -17,6 +17,7 @@ pub fn run_fuzz_iteration(
     let contract_id = env.register(PayrollVault, ());
     let client = PayrollVaultClient::new(env, &contract_id);
     client.initialize(admin);
+    client.set_token_allowlist_enforced(&false);
 
     let token_admin_client = token::StellarAssetClient::new(env, token_id);
     let token_client = token::Client::new(env, token_id);
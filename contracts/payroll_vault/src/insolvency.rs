@@ -0,0 +1,408 @@
+//! Insolvency mode and IOU settlement.
+//!
+//! A token is insolvent when `TreasuryBalance(token) < TotalLiability(token)`.
+//! In that state stream payouts no longer revert: each payout receives a
+//! pro-rata share `amount * balance / (liability - outstanding IOUs)` and the
+//! unpaid remainder is recorded as an IOU against the stream. IOUs stay part of
+//! `TotalLiability` until they are settled, so the solvency math never forgets
+//! them, but they are excluded from the pro-rata denominator so that earlier
+//! and later withdrawers receive the same share of what is on hand.
+//!
+//! IOUs are queued per token in creation order. Every `deposit` settles the
+//! queue first-in-first-out out of the deposited amount before the rest of the
+//! deposit becomes free treasury balance.
+
+use super::*;
+use crate::events::{self, IouRecorded, IouSettled, StreamPaidOut};
+
+/// Maximum number of IOUs settled by a single `deposit` / `settle_ious` call.
+/// Keeps CPU usage bounded; remaining IOUs are settled by subsequent calls.
+pub(crate) const MAX_IOU_SETTLEMENTS: u32 = 50;
+
+/// Outstanding shortfall owed to a stream recipient.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Iou {
+    pub id: u64,
+    pub stream_id: u64,
+    pub recipient: Address,
+    pub token: Address,
+    /// Amount still owed (decreases as the IOU is partially settled).
+    pub amount: i128,
+    pub created_at: u64,
+}
+
+#[contractimpl]
+impl PayrollVault {
+    /// Returns true when the tracked balance no longer covers the liability for `token`.
+    pub fn is_insolvent(e: Env, token: Address) -> bool {
+        let balance = Self::get_treasury_balance(e.clone(), token.clone());
+        let liability = Self::get_total_liability(e, token);
+        balance < liability
+    }
+
+    /// Insolvency-aware payout used by the authorized stream contract.
+    ///
+    /// When the token is solvent this behaves exactly like `payout_liability`.
+    /// When it is insolvent the recipient receives a pro-rata share of the
+    /// available funds and the shortfall is queued as an IOU for `stream_id`.
+    ///
+    /// Returns the amount actually transferred.
+    pub fn payout_stream_liability(
+        e: Env,
+        stream_id: u64,
+        to: Address,
+        token: Address,
+        amount: i128,
+    ) -> Result<i128, QuipayError> {
+        Self::pay_stream_claim(&e, stream_id, &to, &token, amount, true)
+    }
+
+    /// Refund part of a stream's liability to its employer, e.g. when a
+    /// dispute cancels it. Insolvency-aware like `payout_stream_liability`,
+    /// so the refund takes its pro-rata share next to the workers' claims,
+    /// but no protocol fee is charged, now or when its IOU is settled.
+    ///
+    /// Returns the amount actually transferred.
+    pub fn refund_stream_liability(
+        e: Env,
+        stream_id: u64,
+        to: Address,
+        token: Address,
+        amount: i128,
+    ) -> Result<i128, QuipayError> {
+        Self::pay_stream_claim(&e, stream_id, &to, &token, amount, false)
+    }
+
+    /// Settle queued IOUs for `token` out of the current treasury balance.
+    ///
+    /// Permissionless: anyone can call this to push pending settlements through,
+    /// e.g. after a large deposit hit the per-call settlement limit.
+    /// Returns the total amount settled.
+    pub fn settle_ious(e: Env, token: Address) -> Result<i128, QuipayError> {
+        let balance = Self::get_treasury_balance(e.clone(), token.clone());
+        Self::settle_iou_queue(&e, &token, balance)
+    }
+
+    /// Get an IOU by id.
+    pub fn get_iou(e: Env, iou_id: u64) -> Option<Iou> {
+        e.storage().persistent().get(&StateKey::Iou(iou_id))
+    }
+
+    /// Get the unsettled IOUs for `token` in settlement order.
+    pub fn get_pending_ious(e: Env, token: Address) -> Vec<Iou> {
+        let queue: Vec<u64> = e
+            .storage()
+            .persistent()
+            .get(&StateKey::IouQueue(token))
+            .unwrap_or_else(|| Vec::new(&e));
+
+        let mut out = Vec::new(&e);
+        let mut i = 0;
+        while i < queue.len() {
+            if let Some(iou) = Self::get_iou(e.clone(), queue.get(i).unwrap()) {
+                out.push_back(iou);
+            }
+            i += 1;
+        }
+        out
+    }
+
+    /// Total outstanding IOU amount for a stream.
+    pub fn get_stream_iou(e: Env, stream_id: u64) -> i128 {
+        e.storage()
+            .persistent()
+            .get(&StateKey::StreamIou(stream_id))
+            .unwrap_or(0)
+    }
+
+    /// Total outstanding IOU amount for a token.
+    pub fn get_total_iou(e: Env, token: Address) -> i128 {
+        e.storage()
+            .persistent()
+            .get(&StateKey::TotalIou(token))
+            .unwrap_or(0)
+    }
+}
+
+impl PayrollVault {
+    /// Pay a stream claim of `amount`, pro-rata while `token` is insolvent,
+    /// queueing the shortfall as an IOU. The payout fee applies when
+    /// `charge_fee` is set. Only the authorized contract can call this.
+    fn pay_stream_claim(
+        e: &Env,
+        stream_id: u64,
+        to: &Address,
+        token: &Address,
+        amount: i128,
+        charge_fee: bool,
+    ) -> Result<i128, QuipayError> {
+        let authorized: Address = e
+            .storage()
+            .persistent()
+            .get(&StateKey::AuthorizedContract)
+            .ok_or(QuipayError::NotInitialized)?;
+        authorized.require_auth();
+
+        require_positive_amount!(amount);
+
+        let balance_key = StateKey::TreasuryBalance(token.clone());
+        let liability_key = StateKey::TotalLiability(token.clone());
+
+        let balance: i128 = e.storage().persistent().get(&balance_key).unwrap_or(0);
+        let liability: i128 = e.storage().persistent().get(&liability_key).unwrap_or(0);
+
+        if amount > liability {
+            return Err(QuipayError::InvalidAmount);
+        }
+
+        // Claims not yet converted into IOUs share the current balance.
+        let unsettled = liability - Self::get_total_iou(e.clone(), token.clone());
+        let paid = if balance >= liability || balance >= unsettled {
+            amount
+        } else {
+            // unsettled > balance >= 0 here, so the division is well defined.
+            amount.checked_mul(balance).ok_or(QuipayError::Overflow)? / unsettled
+        };
+        let shortfall = amount - paid;
+
+        if paid > 0 {
+            e.storage()
+                .persistent()
+                .set(&liability_key, &(liability - paid));
+            e.storage()
+                .persistent()
+                .set(&balance_key, &(balance - paid));
+
+            let fee = if charge_fee {
+                Self::take_payout_fee(e, stream_id, token, paid)?
+            } else {
+                0
+            };
+            Self::ensure_liquidity(e, token, paid - fee)?;
+            let token_client = token::Client::new(e, token);
+            token_client.transfer(&e.current_contract_address(), to, &(paid - fee));
+
+            events::emit(
+                e,
+                &authorized,
+                StreamPaidOut {
+                    stream_id,
+                    to: to.clone(),
+                    token: token.clone(),
+                    amount: paid - fee,
+                    fee,
+                },
+            );
+        }
+
+        if shortfall > 0 {
+            Self::record_iou(e, stream_id, to.clone(), token.clone(), shortfall, !charge_fee)?;
+        }
+
+        Ok(paid)
+    }
+
+    fn record_iou(
+        e: &Env,
+        stream_id: u64,
+        recipient: Address,
+        token: Address,
+        amount: i128,
+        fee_free: bool,
+    ) -> Result<(), QuipayError> {
+        let id: u64 = e
+            .storage()
+            .persistent()
+            .get(&StateKey::NextIouId)
+            .unwrap_or(1);
+        let next = id.checked_add(1).ok_or(QuipayError::Overflow)?;
+        e.storage().persistent().set(&StateKey::NextIouId, &next);
+
+        let iou = Iou {
+            id,
+            stream_id,
+            recipient: recipient.clone(),
+            token: token.clone(),
+            amount,
+            created_at: e.ledger().timestamp(),
+        };
+        e.storage().persistent().set(&StateKey::Iou(id), &iou);
+        if fee_free {
+            e.storage().persistent().set(&StateKey::RefundIou(id), &true);
+        }
+
+        let queue_key = StateKey::IouQueue(token.clone());
+        let mut queue: Vec<u64> = e
+            .storage()
+            .persistent()
+            .get(&queue_key)
+            .unwrap_or_else(|| Vec::new(e));
+        queue.push_back(id);
+        e.storage().persistent().set(&queue_key, &queue);
+
+        Self::adjust_iou_totals(e, stream_id, &token, amount)?;
+
+        events::emit_from_contract(
+            e,
+            IouRecorded {
+                iou_id: id,
+                stream_id,
+                recipient,
+                token,
+                amount,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Pay queued IOUs for `token` in FIFO order using at most `budget` funds.
+    /// Settled amounts leave both the treasury balance and the liability.
+    pub(crate) fn settle_iou_queue(
+        e: &Env,
+        token: &Address,
+        budget: i128,
+    ) -> Result<i128, QuipayError> {
+        let queue_key = StateKey::IouQueue(token.clone());
+        let queue: Vec<u64> = match e.storage().persistent().get(&queue_key) {
+            Some(q) => q,
+            None => return Ok(0),
+        };
+
+        let balance_key = StateKey::TreasuryBalance(token.clone());
+        let liability_key = StateKey::TotalLiability(token.clone());
+        let mut balance: i128 = e.storage().persistent().get(&balance_key).unwrap_or(0);
+        let mut liability: i128 = e.storage().persistent().get(&liability_key).unwrap_or(0);
+
+        let mut remaining_budget = budget.min(balance);
+        let mut settled_total: i128 = 0;
+        let mut processed = 0u32;
+        let token_client = token::Client::new(e, token);
+
+        while processed < queue.len() && processed < MAX_IOU_SETTLEMENTS && remaining_budget > 0 {
+            let iou_id = queue.get(processed).unwrap();
+            let Some(mut iou) = e
+                .storage()
+                .persistent()
+                .get::<StateKey, Iou>(&StateKey::Iou(iou_id))
+            else {
+                processed += 1;
+                continue;
+            };
+
+            let pay = iou.amount.min(remaining_budget);
+            let fee_free = e.storage().persistent().has(&StateKey::RefundIou(iou_id));
+            let fee = if fee_free {
+                0
+            } else {
+                Self::take_payout_fee(e, iou.stream_id, token, pay)?
+            };
+            Self::ensure_liquidity(e, token, pay - fee)?;
+            token_client.transfer(
+                &e.current_contract_address(),
+                &iou.recipient,
+                &(pay - fee),
+            );
+
+            remaining_budget -= pay;
+            settled_total += pay;
+            balance -= pay;
+            liability -= pay;
+            iou.amount -= pay;
+            Self::adjust_iou_totals(e, iou.stream_id, token, -pay)?;
+
+            events::emit_from_contract(
+                e,
+                IouSettled {
+                    iou_id: iou.id,
+                    stream_id: iou.stream_id,
+                    recipient: iou.recipient.clone(),
+                    token: token.clone(),
+                    paid: pay,
+                    remaining: iou.amount,
+                },
+            );
+
+            if iou.amount > 0 {
+                // Partially settled: keep it at the head of the queue.
+                e.storage().persistent().set(&StateKey::Iou(iou_id), &iou);
+                break;
+            }
+            e.storage().persistent().remove(&StateKey::Iou(iou_id));
+            e.storage().persistent().remove(&StateKey::RefundIou(iou_id));
+            processed += 1;
+        }
+
+        if settled_total == 0 {
+            return Ok(0);
+        }
+
+        e.storage().persistent().set(&balance_key, &balance);
+        e.storage().persistent().set(&liability_key, &liability);
+
+        let remaining_queue = queue.slice(processed..);
+        if remaining_queue.is_empty() {
+            e.storage().persistent().remove(&queue_key);
+        } else {
+            e.storage().persistent().set(&queue_key, &remaining_queue);
+        }
+
+        Ok(settled_total)
+    }
+
+    /// Forget every outstanding IOU for `token`. Used by a full drain, which
+    /// wipes the liability the IOUs are part of.
+    pub(crate) fn clear_ious(e: &Env, token: &Address) {
+        let queue_key = StateKey::IouQueue(token.clone());
+        let queue: Vec<u64> = e
+            .storage()
+            .persistent()
+            .get(&queue_key)
+            .unwrap_or_else(|| Vec::new(e));
+        for iou_id in queue.iter() {
+            let key = StateKey::Iou(iou_id);
+            if let Some(iou) = e.storage().persistent().get::<StateKey, Iou>(&key) {
+                e.storage()
+                    .persistent()
+                    .remove(&StateKey::StreamIou(iou.stream_id));
+                e.storage().persistent().remove(&key);
+            }
+            e.storage().persistent().remove(&StateKey::RefundIou(iou_id));
+        }
+        e.storage().persistent().remove(&queue_key);
+        e.storage()
+            .persistent()
+            .remove(&StateKey::TotalIou(token.clone()));
+    }
+
+    fn adjust_iou_totals(
+        e: &Env,
+        stream_id: u64,
+        token: &Address,
+        delta: i128,
+    ) -> Result<(), QuipayError> {
+        let stream_key = StateKey::StreamIou(stream_id);
+        let stream_total: i128 = e.storage().persistent().get(&stream_key).unwrap_or(0);
+        let stream_total = stream_total
+            .checked_add(delta)
+            .ok_or(QuipayError::Overflow)?;
+        if stream_total == 0 {
+            e.storage().persistent().remove(&stream_key);
+        } else {
+            e.storage().persistent().set(&stream_key, &stream_total);
+        }
+
+        let token_key = StateKey::TotalIou(token.clone());
+        let token_total: i128 = e.storage().persistent().get(&token_key).unwrap_or(0);
+        let token_total = token_total
+            .checked_add(delta)
+            .ok_or(QuipayError::Overflow)?;
+        if token_total == 0 {
+            e.storage().persistent().remove(&token_key);
+        } else {
+            e.storage().persistent().set(&token_key, &token_total);
+        }
+        Ok(())
+    }
+}
@@ -1,8 +1,14 @@
 #![no_std]
 #![allow(unexpected_cfgs)]
-use quipay_common::{QuipayError, require_positive_amount};
+use events::{
+    Deposited, DrainCanceled, DrainExecuted, DrainProposed, FundsAllocated, FundsReleased,
+    LiabilityAdded, LiabilityRemoved, PaidOut, SignerAdded, SignerRemoved, ThresholdSet, Withdrawn,
+};
+use quipay_common::{
+    AdminKeys, AdminStorage, QuipayError, Role, admin, rbac, require_positive_amount, ttl, upgrade,
+};
 use soroban_sdk::{
-    Address, BytesN, Env, Symbol, Vec, contract, contractimpl, contracttype, symbol_short, token,
+    Address, BytesN, Env, IntoVal, Map, Symbol, Vec, contract, contractimpl, contracttype, token,
 };
 
 #[cfg(test)]
@@ -17,12 +23,51 @@ mod fuzz_test;
 #[cfg(test)]
 mod multisig_dedup_test;
 
+#[cfg(test)]
+mod strategy_test;
+
+#[cfg(test)]
+mod mandate_test;
+
+#[cfg(test)]
+mod proposal_test;
+
+#[cfg(test)]
+mod tokens_test;
+
+#[cfg(test)]
+mod reconcile_test;
+
+#[cfg(test)]
+mod fees_test;
+
+#[cfg(test)]
+mod storage_ttl_test;
+
 #[cfg(kani)]
 mod kani_test;
 
 #[cfg(test)]
 mod proptest;
 
+pub mod events;
+mod fees;
+mod insolvency;
+mod mandate;
+mod proposal;
+mod reconcile;
+mod storage_ttl;
+mod strategy;
+mod tokens;
+
+pub use fees::{FeeBasis, MAX_PROTOCOL_FEE_BPS, ProtocolFee};
+pub use insolvency::Iou;
+pub use mandate::{TopUpExecution, TopUpMandate};
+pub use proposal::{Proposal, ProposalAction, ProposalStatus};
+pub use reconcile::BalanceDrift;
+pub use quipay_common::{PendingUpgrade, VersionInfo};
+pub use tokens::TokenConfig;
+
 // Storage keys - using separate enums for persistent vs instance storage
 #[contracttype]
 #[derive(Clone)]
@@ -30,45 +75,65 @@ pub enum StateKey {
     // Persistent storage - survives upgrades
     Admin,
     PendingAdmin, // Pending admin address (for two-step transfer)
-    Version,
+    Version, // Legacy VersionInfo, moved into the upgrade manager on first use
     AuthorizedContract, // Contract authorized to modify liabilities (e.g., PayrollStream)
     TokenList,          // Tokens tracked by the vault
     // Additional state that should persist across upgrades
     TreasuryBalance(Address), // Funds held for payroll (Token -> Amount)
     TotalLiability(Address),  // Amount owed to recipients (Token -> Amount)
     // Timelock storage
-    PendingUpgrade, // (wasm_hash, execute_after_timestamp)
-    PendingDrain,   // Emergency drain proposal with 24-hour timelock
+    PendingDrain, // Emergency drain proposal with 24-hour timelock
     // Multi-sig storage
     Signers,             // Vec<Address> - list of authorized signers
     Threshold,           // u32 - M of N required
     WithdrawalThreshold, // i128 - amount above which multisig is required
+    // Insolvency IOUs
+    NextIouId,
+    Iou(u64),              // Outstanding shortfall record
+    RefundIou(u64),        // Marks an IOU owed to an employer refund (fee-free)
+    IouQueue(Address),     // Token -> Vec<u64> of unsettled IOU ids (FIFO)
+    StreamIou(u64),        // Stream id -> outstanding IOU amount
+    TotalIou(Address),     // Token -> outstanding IOU amount
+    // Yield strategies
+    Strategy(Address),        // Token -> strategy contract
+    StrategyBalance(Address), // Token -> principal held by the strategy
+    StrategyBufferBps,        // u32 - liquidity buffer on top of liabilities
+    // Top-up mandates
+    NextMandateId,
+    Mandate(u64),              // Standing top-up rule
+    EmployerMandates(Address), // Employer -> Vec<u64> of mandate ids
+    MandateHistory(u64),       // Mandate id -> recent executions
+    // Multisig proposal queue
+    NextProposalId,
+    Proposal(u64),    // Queued multisig action with its approvals
+    PendingProposals, // Vec<u64> of proposals not yet executed or canceled
+    // Token allowlist
+    TokenConfig(Address),   // Token -> allowlist settings
+    TokenAllowlistEnforced, // bool - reject unlisted tokens
+    // Protocol fee
+    ProtocolFee,              // ProtocolFee - rate and basis
+    FeeCollector,             // Address allowed to claim accrued fees
+    AccruedFees(Address),     // Token -> fees collected, outside the treasury
+    FeeTierDiscount(u32),     // Tier -> discount in bps
+    EmployerFeeTier(Address), // Employer -> tier
+    StreamFeeBps(u64),        // Stream id -> payout fee rate
 }
 
-#[contracttype]
-#[derive(Clone, Debug, PartialEq)]
-pub struct PendingUpgrade {
-    pub wasm_hash: BytesN<32>,
-    pub execute_after: u64,
-    pub proposed_at: u64,
-    pub proposed_by: Address,
-}
-
-#[contracttype]
-#[derive(Clone, Debug, PartialEq)]
-pub struct VersionInfo {
-    pub major: u32,
-    pub minor: u32,
-    pub patch: u32,
-    pub upgraded_at: u64,
-}
+const ADMIN_KEYS: AdminKeys<StateKey> = AdminKeys {
+    storage: AdminStorage::Persistent,
+    admin: StateKey::Admin,
+    pending: StateKey::PendingAdmin,
+};
 
 #[contracttype]
 #[derive(Clone, Debug, PartialEq)]
 pub struct TreasuryTokenSummary {
     pub token: Address,
+    /// Total tracked treasury funds, including those placed in a strategy.
     pub balance: i128,
     pub liability: i128,
+    /// Portion of `balance` currently held by the yield strategy.
+    pub strategy_balance: i128,
 }
 
 #[contracttype]
@@ -78,26 +143,29 @@ pub struct PendingDrain {
     pub execute_after: u64,
     pub proposed_at: u64,
     pub proposed_by: Address,
+    /// Tokens and amounts to move; empty drains every tracked token in full.
+    pub targets: Vec<DrainTarget>,
+    /// Keep the wages workers have vested but not withdrawn in the vault and
+    /// move only the rest.
+    pub protective: bool,
+    /// Vested but unwithdrawn wages per token, tallied from PayrollStream by
+    /// `tally_drain_reserves` for a protective drain.
+    pub reserves: Map<Address, i128>,
+    /// Next stream id to tally; `None` once the tally is complete.
+    pub tally_cursor: Option<u64>,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct DrainTarget {
+    pub token: Address,
+    /// Amount to move; 0 moves as much as the drain mode allows.
+    pub amount: i128,
 }
 
 #[contract]
 pub struct PayrollVault;
 
-// Event symbols
-const UPGRADED: Symbol = symbol_short!("upgrd");
-const UPGRADE_PROPOSED: Symbol = symbol_short!("up_prop");
-const UPGRADE_EXECUTED: Symbol = symbol_short!("up_exec");
-const UPGRADE_CANCELED: Symbol = symbol_short!("up_cancel");
-const SIGNER_ADDED: Symbol = symbol_short!("sig_add");
-const SIGNER_REMOVED: Symbol = symbol_short!("sig_rm");
-const THRESHOLD_SET: Symbol = symbol_short!("thr_set");
-const DRAIN_PROPOSED: Symbol = symbol_short!("dr_prop");
-const DRAIN_EXECUTED: Symbol = symbol_short!("dr_exec");
-const DRAIN_CANCELED: Symbol = symbol_short!("dr_cncl");
-
-// 48 hours in seconds
-const TIMELOCK_DURATION: u64 = 48 * 60 * 60;
-
 // 24 hours in seconds (emergency drain timelock)
 const DRAIN_TIMELOCK_DURATION: u64 = 24 * 60 * 60;
 
@@ -115,16 +183,8 @@ impl PayrollVault {
         // Store admin in persistent storage (survives upgrades)
         e.storage().persistent().set(&StateKey::Admin, &admin);
 
-        // Set initial version (WASM hash tracked separately via upgrade function)
-        let initial_version = VersionInfo {
-            major: 1,
-            minor: 0,
-            patch: 0,
-            upgraded_at: e.ledger().timestamp(),
-        };
-        e.storage()
-            .persistent()
-            .set(&StateKey::Version, &initial_version);
+        // Set initial version; later versions are recorded by the upgrade manager
+        upgrade::set_initial_version(&e, (1, 0, 0));
 
         // Initialize with admin as the first signer
         let mut signers = Vec::new(&e);
@@ -140,6 +200,11 @@ impl PayrollVault {
             &DEFAULT_WITHDRAWAL_THRESHOLD,
         );
 
+        // Only listed tokens are accepted until the admin says otherwise
+        e.storage()
+            .persistent()
+            .set(&StateKey::TokenAllowlistEnforced, &true);
+
         // Authorized contract starts as None - must be set by admin later
         // No need to initialize balances/liabilities as they are maps
         Ok(())
@@ -149,160 +214,98 @@ impl PayrollVault {
     /// DEPRECATED: Use propose_upgrade + execute_upgrade instead
     pub fn upgrade(
         e: Env,
+        caller: Address,
         new_wasm_hash: BytesN<32>,
         new_version: (u32, u32, u32),
     ) -> Result<(), QuipayError> {
         // For backwards compatibility, automatically propose and execute if no timelock is active
-        Self::propose_upgrade(e.clone(), new_wasm_hash.clone(), new_version)?;
+        Self::propose_upgrade(e.clone(), caller, new_wasm_hash.clone(), new_version)?;
         Self::execute_upgrade(e, new_version)
     }
 
     /// Propose an upgrade with a 48-hour timelock
-    /// Only the admin can call this function
+    /// Requires the admin or an `Upgrader`
     pub fn propose_upgrade(
         e: Env,
+        caller: Address,
         new_wasm_hash: BytesN<32>,
         new_version: (u32, u32, u32),
     ) -> Result<(), QuipayError> {
         let admin = Self::get_admin(e.clone())?;
-        admin.require_auth();
-
-        let now = e.ledger().timestamp();
-        let execute_after = now.saturating_add(TIMELOCK_DURATION);
-
-        // Check if there's already a pending upgrade
-        if e.storage().persistent().has(&StateKey::PendingUpgrade) {
-            return Err(QuipayError::Custom);
-        }
-
-        let pending_upgrade = PendingUpgrade {
-            wasm_hash: new_wasm_hash.clone(),
-            execute_after,
-            proposed_at: now,
-            proposed_by: admin.clone(),
-        };
-
-        e.storage()
-            .persistent()
-            .set(&StateKey::PendingUpgrade, &pending_upgrade);
-
-        // Emit upgrade proposed event
-        #[allow(deprecated)]
-        e.events().publish(
-            (UPGRADE_PROPOSED, admin),
-            (
-                new_wasm_hash,
-                new_version.0,
-                new_version.1,
-                new_version.2,
-                execute_after,
-            ),
-        );
+        rbac::require_role(&e, Role::Upgrader, &caller, &admin)?;
 
+        Self::adopt_legacy_version(&e);
+        upgrade::propose(&e, &caller, new_wasm_hash, new_version)?;
         Ok(())
     }
 
     /// Execute a proposed upgrade after the timelock period
-    /// Only the admin can call this function
+    /// `new_version` must match the proposed version. Requires the multisig signers
     pub fn execute_upgrade(e: Env, new_version: (u32, u32, u32)) -> Result<(), QuipayError> {
         let admin = Self::get_admin(e.clone())?;
         Self::require_multisig_auth(&e)?;
 
-        let pending_upgrade: PendingUpgrade = e
-            .storage()
-            .persistent()
-            .get(&StateKey::PendingUpgrade)
-            .ok_or(QuipayError::Custom)?;
-
-        let now = e.ledger().timestamp();
-        if now < pending_upgrade.execute_after {
-            return Err(QuipayError::Custom);
+        let pending = upgrade::get_pending(&e).ok_or(QuipayError::NoPendingUpgrade)?;
+        if pending.version != new_version {
+            return Err(QuipayError::VersionMismatch);
         }
 
-        // Get current version for event
-        let current_version = Self::get_version(e.clone())?;
-
-        // Perform the upgrade
-        e.deployer()
-            .update_current_contract_wasm(pending_upgrade.wasm_hash.clone());
-
-        // Update version info
-        let (major, minor, patch) = new_version;
-        let version_info = VersionInfo {
-            major,
-            minor,
-            patch,
-            upgraded_at: now,
-        };
-        e.storage()
-            .persistent()
-            .set(&StateKey::Version, &version_info);
-
-        // Clear pending upgrade
-        e.storage().persistent().remove(&StateKey::PendingUpgrade);
-
-        // Emit upgrade executed event
-        #[allow(deprecated)]
-        e.events().publish(
-            (UPGRADE_EXECUTED, admin),
-            (
-                pending_upgrade.wasm_hash,
-                current_version.major,
-                current_version.minor,
-                current_version.patch,
-                major,
-                minor,
-                patch,
-            ),
-        );
-
+        Self::adopt_legacy_version(&e);
+        upgrade::execute(&e, &admin)?;
         Ok(())
     }
 
     /// Cancel a pending upgrade
-    /// Only the admin can call this function
-    pub fn cancel_upgrade(e: Env) -> Result<(), QuipayError> {
+    /// Requires the admin or an `Upgrader`
+    pub fn cancel_upgrade(e: Env, caller: Address) -> Result<(), QuipayError> {
         let admin = Self::get_admin(e.clone())?;
-        admin.require_auth();
+        rbac::require_role(&e, Role::Upgrader, &caller, &admin)?;
 
-        let pending_upgrade: PendingUpgrade = e
-            .storage()
-            .persistent()
-            .get(&StateKey::PendingUpgrade)
-            .ok_or(QuipayError::Custom)?;
+        upgrade::cancel(&e, &caller)?;
+        Ok(())
+    }
 
-        // Clear pending upgrade
-        e.storage().persistent().remove(&StateKey::PendingUpgrade);
+    /// Reinstall the WASM replaced by the last upgrade, without a timelock
+    /// Requires the multisig signers
+    pub fn rollback_upgrade(e: Env) -> Result<VersionInfo, QuipayError> {
+        let admin = Self::get_admin(e.clone())?;
+        Self::require_multisig_auth(&e)?;
 
-        // Emit upgrade canceled event
-        #[allow(deprecated)]
-        e.events().publish(
-            (UPGRADE_CANCELED, admin),
-            (pending_upgrade.wasm_hash, pending_upgrade.execute_after),
-        );
+        upgrade::rollback(&e, &admin)
+    }
 
-        Ok(())
+    /// Run the data migrations of the installed version, once, after an
+    /// upgrade from `from_version`
+    /// Requires the admin or an `Upgrader`
+    pub fn migrate(
+        e: Env,
+        caller: Address,
+        from_version: (u32, u32, u32),
+    ) -> Result<VersionInfo, QuipayError> {
+        let admin = Self::get_admin(e.clone())?;
+        rbac::require_role(&e, Role::Upgrader, &caller, &admin)?;
+
+        let version = upgrade::begin_migration(&e, from_version)?;
+        Self::backfill_token_allowlist(&e, &admin, from_version);
+        Ok(version)
     }
 
     /// Get the current pending upgrade (if any)
     pub fn get_pending_upgrade(e: Env) -> Option<PendingUpgrade> {
-        e.storage().persistent().get(&StateKey::PendingUpgrade)
+        upgrade::get_pending(&e)
     }
 
-    /// Get the current version information
+    /// Get the current version information. Always `Ok`; the `Result` keeps
+    /// the interface existing clients were built against.
     pub fn get_version(e: Env) -> Result<VersionInfo, QuipayError> {
-        e.storage()
-            .persistent()
-            .get(&StateKey::Version)
-            .ok_or(QuipayError::VersionNotSet)
+        Ok(upgrade::get_version(&e)
+            .or_else(|| e.storage().persistent().get(&StateKey::Version))
+            .unwrap_or_else(|| upgrade::installed_version(&e)))
     }
 
     /// Get the current admin address
     pub fn get_admin(e: Env) -> Result<Address, QuipayError> {
-        e.storage()
-            .persistent()
-            .get(&StateKey::Admin)
-            .ok_or(QuipayError::NotInitialized)
+        admin::get_admin(&e, &ADMIN_KEYS)
     }
 
     /// Transfer admin rights to a new address
@@ -312,170 +315,80 @@ impl PayrollVault {
     /// must authorize the transfer. If the current admin is a multisig, the transaction
     /// must meet its threshold. The new admin can also be a multisig account.
     pub fn transfer_admin(e: Env, new_admin: Address) -> Result<(), QuipayError> {
-        let admin = Self::get_admin(e.clone())?;
-        admin.require_auth();
-
-        e.storage().persistent().set(&StateKey::Admin, &new_admin);
-        Ok(())
+        admin::transfer_admin(&e, &ADMIN_KEYS, &new_admin)
     }
 
     /// Propose a new admin address (first step of two-step transfer)
     /// Only the current admin can propose a new admin
     pub fn propose_admin(e: Env, new_admin: Address) -> Result<(), QuipayError> {
-        let admin = Self::get_admin(e.clone())?;
-        admin.require_auth();
-
-        e.storage()
-            .persistent()
-            .set(&StateKey::PendingAdmin, &new_admin);
-        Ok(())
+        admin::propose_admin(&e, &ADMIN_KEYS, &new_admin)
     }
 
     /// Get the pending admin address (if any)
     pub fn get_pending_admin(e: Env) -> Option<Address> {
-        e.storage().persistent().get(&StateKey::PendingAdmin)
+        admin::get_pending_admin(&e, &ADMIN_KEYS)
     }
 
     /// Accept the admin role (second step of two-step transfer)
     /// Only the pending admin can call this function
     pub fn accept_admin(e: Env) -> Result<(), QuipayError> {
-        let pending_admin =
-            Self::get_pending_admin(e.clone()).ok_or(QuipayError::NoPendingAdmin)?;
-
-        pending_admin.require_auth();
-
-        // Set the new admin
-        e.storage()
-            .persistent()
-            .set(&StateKey::Admin, &pending_admin);
-
-        // Clear the pending admin
-        e.storage().persistent().remove(&StateKey::PendingAdmin);
-
+        admin::accept_admin(&e, &ADMIN_KEYS)?;
         Ok(())
     }
 
-    // ==================== Multi-sig Admin Functions ====================
-
-    /// Add a new authorized signer
-    /// Only admin can call this function
-    pub fn add_signer(e: Env, new_signer: Address) -> Result<(), QuipayError> {
+    /// Grant a role to `account` (admin only)
+    pub fn grant_role(e: Env, role: Role, account: Address) -> Result<(), QuipayError> {
         let admin = Self::get_admin(e.clone())?;
         admin.require_auth();
-
-        let mut signers: Vec<Address> = e
-            .storage()
-            .persistent()
-            .get(&StateKey::Signers)
-            .ok_or(QuipayError::NoSigners)?;
-
-        // Check if already a signer
-        let mut i = 0;
-        while i < signers.len() {
-            if let Some(s) = signers.get(i) {
-                if s == new_signer {
-                    return Err(QuipayError::AlreadySigner);
-                }
-            }
-            i += 1;
-        }
-
-        signers.push_back(new_signer.clone());
-        e.storage().persistent().set(&StateKey::Signers, &signers);
-
-        #[allow(deprecated)]
-        e.events().publish((SIGNER_ADDED, admin), new_signer);
+        rbac::grant_role(&e, &admin, role, &account);
         Ok(())
     }
 
-    /// Remove an authorized signer
-    /// Only admin can call this function
-    pub fn remove_signer(e: Env, signer_to_remove: Address) -> Result<(), QuipayError> {
+    /// Revoke a role from `account` (admin only)
+    pub fn revoke_role(e: Env, role: Role, account: Address) -> Result<(), QuipayError> {
         let admin = Self::get_admin(e.clone())?;
         admin.require_auth();
+        rbac::revoke_role(&e, &admin, role, &account);
+        Ok(())
+    }
 
-        let signers: Vec<Address> = e
-            .storage()
-            .persistent()
-            .get(&StateKey::Signers)
-            .ok_or(QuipayError::NoSigners)?;
-
-        let threshold: u32 = e
-            .storage()
-            .persistent()
-            .get(&StateKey::Threshold)
-            .unwrap_or(1);
+    /// Give up a role held by `account`
+    pub fn renounce_role(e: Env, account: Address, role: Role) {
+        rbac::renounce_role(&e, role, &account);
+    }
 
-        // Ensure we don't go below threshold
-        if signers.len() <= threshold {
-            return Err(QuipayError::InvalidThreshold);
-        }
+    pub fn has_role(e: Env, role: Role, account: Address) -> bool {
+        rbac::has_role(&e, role, &account)
+    }
 
-        let mut found = false;
-        let mut new_signers = Vec::new(&e);
-        let mut i = 0;
-        while i < signers.len() {
-            if let Some(s) = signers.get(i) {
-                if s == signer_to_remove {
-                    found = true;
-                } else {
-                    new_signers.push_back(s);
-                }
-            }
-            i += 1;
-        }
+    // ==================== Multi-sig Admin Functions ====================
 
-        if !found {
-            return Err(QuipayError::SignerNotFound);
-        }
+    /// Add a new authorized signer
+    /// Requires the multisig signers (the admin while none are configured)
+    pub fn add_signer(e: Env, new_signer: Address) -> Result<(), QuipayError> {
+        let admin = Self::require_config_auth(&e)?;
+        Self::apply_add_signer(&e, admin, new_signer)
+    }
 
-        e.storage()
-            .persistent()
-            .set(&StateKey::Signers, &new_signers);
-        #[allow(deprecated)]
-        e.events()
-            .publish((SIGNER_REMOVED, admin), signer_to_remove);
-        Ok(())
+    /// Remove an authorized signer
+    /// Requires the multisig signers (the admin while none are configured)
+    pub fn remove_signer(e: Env, signer_to_remove: Address) -> Result<(), QuipayError> {
+        let admin = Self::require_config_auth(&e)?;
+        Self::apply_remove_signer(&e, admin, signer_to_remove)
     }
 
     /// Set the M-of-N threshold for multi-sig operations
-    /// Only admin can call this function
+    /// Requires the multisig signers (the admin while none are configured)
     pub fn set_threshold(e: Env, threshold: u32) -> Result<(), QuipayError> {
-        let admin = Self::get_admin(e.clone())?;
-        admin.require_auth();
-
-        let signers: Vec<Address> = e
-            .storage()
-            .persistent()
-            .get(&StateKey::Signers)
-            .ok_or(QuipayError::NoSigners)?;
-
-        if threshold == 0 || threshold > signers.len() {
-            return Err(QuipayError::InvalidThreshold);
-        }
-
-        e.storage()
-            .persistent()
-            .set(&StateKey::Threshold, &threshold);
-        #[allow(deprecated)]
-        e.events().publish((THRESHOLD_SET, admin), threshold);
-        Ok(())
+        let admin = Self::require_config_auth(&e)?;
+        Self::apply_set_threshold(&e, admin, threshold)
     }
 
     /// Set the withdrawal threshold (amount above which multisig is required)
-    /// Only admin can call this function
+    /// Requires the multisig signers (the admin while none are configured)
     pub fn set_withdrawal_threshold(e: Env, threshold: i128) -> Result<(), QuipayError> {
-        let admin = Self::get_admin(e.clone())?;
-        admin.require_auth();
-
-        if threshold < 0 {
-            return Err(QuipayError::InvalidAmount);
-        }
-
-        e.storage()
-            .persistent()
-            .set(&StateKey::WithdrawalThreshold, &threshold);
-        Ok(())
+        let admin = Self::require_config_auth(&e)?;
+        Self::apply_set_withdrawal_threshold(&e, admin, threshold)
     }
 
     /// Get the withdrawal threshold (amount above which multisig is required).
@@ -528,29 +441,42 @@ impl PayrollVault {
     pub fn deposit(e: Env, from: Address, token: Address, amount: i128) -> Result<(), QuipayError> {
         from.require_auth();
         require_positive_amount!(amount);
+        Self::check_deposit_allowed(&e, &token, amount)?;
+
+        // Credit what actually arrived: fee-on-transfer tokens deliver less
+        // than `amount`.
+        let held_before = Self::held_balance(&e, &token);
+        let token_client = token::Client::new(&e, &token);
+        token_client.transfer(&from, e.current_contract_address(), &amount);
+        let received = Self::held_balance(&e, &token) - held_before;
+        if received <= 0 {
+            return Err(QuipayError::TransferFailed);
+        }
 
         // Update treasury balance
         let key = StateKey::TreasuryBalance(token.clone());
         let current_balance: i128 = e.storage().persistent().get(&key).unwrap_or(0);
-        let new_total = current_balance + amount;
+        let new_total = current_balance
+            .checked_add(received)
+            .ok_or(QuipayError::Overflow)?;
         e.storage()
             .persistent()
             .set(&key, &new_total);
         Self::track_supported_token(&e, token.clone());
 
-        let token_client = token::Client::new(&e, &token);
-        token_client.transfer(&from, e.current_contract_address(), &amount);
-
-        e.events().publish(
-            (
-                symbol_short!("vault"),
-                symbol_short!("deposited"),
-                from.clone(),
-                token.clone(),
-            ),
-            (amount, new_total),
+        events::emit(
+            &e,
+            &from,
+            Deposited {
+                token: token.clone(),
+                amount: received,
+                new_balance: new_total,
+            },
         );
 
+        // Shortfalls recorded while insolvent are settled first, in order.
+        Self::settle_iou_queue(&e, &token, received)?;
+
         Ok(())
     }
 
@@ -605,33 +531,7 @@ impl PayrollVault {
             Self::require_multisig_auth(&e)?;
         }
 
-        let available = Self::get_available_balance(e.clone(), token.clone());
-        if amount > available {
-            return Err(QuipayError::InsufficientBalance);
-        }
-
-        let balance_key = StateKey::TreasuryBalance(token.clone());
-        let balance: i128 = e.storage().persistent().get(&balance_key).unwrap_or(0);
-        let new_total = balance - amount;
-        // If the invariant holds, this should never underflow.
-        e.storage()
-            .persistent()
-            .set(&balance_key, &new_total);
-
-        let token_client = token::Client::new(&e, &token);
-        token_client.transfer(&e.current_contract_address(), &to, &amount);
-
-        e.events().publish(
-            (
-                symbol_short!("vault"),
-                symbol_short!("withdrawn"),
-                to.clone(),
-                token.clone(),
-            ),
-            (amount, new_total),
-        );
-
-        Ok(())
+        Self::withdraw_free_funds(&e, &to, to.clone(), token, amount)
     }
 
     /// Adds liability to the vault (e.g., when a stream is created)
@@ -666,14 +566,13 @@ impl PayrollVault {
             .persistent()
             .set(&liability_key, &(liability + amount));
 
-        e.events().publish(
-            (
-                symbol_short!("vault"),
-                symbol_short!("alloc"),
-                token.clone(),
-                symbol_short!("admin"),
-            ),
-            amount,
+        events::emit(
+            &e,
+            &admin,
+            FundsAllocated {
+                token: token.clone(),
+                amount,
+            },
         );
 
         Ok(())
@@ -707,14 +606,13 @@ impl PayrollVault {
             .persistent()
             .set(&liability_key, &(liability - amount));
 
-        e.events().publish(
-            (
-                symbol_short!("vault"),
-                symbol_short!("release"),
-                token.clone(),
-                symbol_short!("admin"),
-            ),
-            amount,
+        events::emit(
+            &e,
+            &admin,
+            FundsReleased {
+                token: token.clone(),
+                amount,
+            },
         );
 
         Ok(())
@@ -757,17 +655,19 @@ impl PayrollVault {
             .persistent()
             .set(&balance_key, &(balance - amount));
 
+        Self::ensure_liquidity(&e, &token, amount)?;
         let token_client = token::Client::new(&e, &token);
         token_client.transfer(&e.current_contract_address(), &to, &amount);
 
-        e.events().publish(
-            (
-                symbol_short!("vault"),
-                symbol_short!("payout"),
-                to.clone(),
-                token.clone(),
-            ),
-            amount,
+        events::emit(
+            &e,
+            &admin,
+            PaidOut {
+                to: to.clone(),
+                token: token.clone(),
+                amount,
+                fee: 0,
+            },
         );
 
         Ok(())
@@ -787,42 +687,65 @@ impl PayrollVault {
         authorized.require_auth();
 
         require_positive_amount!(amount);
+        Self::release_and_pay(&e, &to, &token, amount, 0)?;
+
+        events::emit(
+            &e,
+            &authorized,
+            PaidOut {
+                to: to.clone(),
+                token: token.clone(),
+                amount,
+                fee: 0,
+            },
+        );
 
-        let balance_key = StateKey::TreasuryBalance(token.clone());
-        let liability_key = StateKey::TotalLiability(token.clone());
+        Ok(())
+    }
 
-        let balance: i128 = e.storage().persistent().get(&balance_key).unwrap_or(0);
-        let liability: i128 = e.storage().persistent().get(&liability_key).unwrap_or(0);
+    /// Pay out the combined claims of several streams on `token` in one
+    /// transfer. Each stream's protocol fee is deducted from its share.
+    /// Only the authorized contract (e.g., PayrollStream) can call this.
+    pub fn payout_streams_liability(
+        e: Env,
+        to: Address,
+        token: Address,
+        stream_ids: Vec<u64>,
+        amounts: Vec<i128>,
+    ) -> Result<i128, QuipayError> {
+        let authorized: Address = e
+            .storage()
+            .persistent()
+            .get(&StateKey::AuthorizedContract)
+            .ok_or(QuipayError::NotInitialized)?;
+        authorized.require_auth();
 
-        if amount > balance {
-            return Err(QuipayError::InsufficientBalance);
+        if stream_ids.len() != amounts.len() {
+            return Err(QuipayError::InvalidAmount);
         }
 
-        if amount > liability {
-            return Err(QuipayError::InvalidAmount);
+        let mut total: i128 = 0;
+        let mut fee: i128 = 0;
+        for (stream_id, amount) in stream_ids.iter().zip(amounts.iter()) {
+            require_positive_amount!(amount);
+            total = total.checked_add(amount).ok_or(QuipayError::Overflow)?;
+            fee += Self::take_payout_fee(&e, stream_id, &token, amount)?;
         }
+        require_positive_amount!(total);
+        Self::release_and_pay(&e, &to, &token, total, fee)?;
+
+        events::emit(
+            &e,
+            &authorized,
+            PaidOut {
+                to: to.clone(),
+                token: token.clone(),
+                amount: total - fee,
+                fee,
+            },
+        );
 
-        e.storage()
-            .persistent()
-            .set(&liability_key, &(liability - amount));
-        e.storage()
-            .persistent()
-            .set(&balance_key, &(balance - amount));
-
-        let token_client = token::Client::new(&e, &token);
-        token_client.transfer(&e.current_contract_address(), &to, &amount);
-
-        e.events().publish(
-            (
-                symbol_short!("vault"),
-                symbol_short!("payout"),
-                to.clone(),
-                token.clone(),
-            ),
-            amount,
-        );
-
-        Ok(())
+        Ok(total - fee)
     }
 
     pub fn get_balance(e: Env, token: Address) -> i128 {
@@ -869,6 +792,7 @@ impl PayrollVault {
         if amount <= 0 {
             return Err(QuipayError::InvalidAmount);
         }
+        Self::check_liability_allowed(&e, &token, amount)?;
 
         if !Self::check_solvency(e.clone(), token.clone(), amount) {
             return Err(QuipayError::InsufficientBalance);
@@ -878,15 +802,7 @@ impl PayrollVault {
         let current: i128 = e.storage().persistent().get(&key).unwrap_or(0);
         e.storage().persistent().set(&key, &(current + amount));
 
-        e.events().publish(
-            (
-                symbol_short!("vault"),
-                symbol_short!("add_lia"),
-                token,
-                authorized,
-            ),
-            amount,
-        );
+        events::emit(&e, &authorized, LiabilityAdded { token, amount });
 
         Ok(())
     }
@@ -913,15 +829,7 @@ impl PayrollVault {
         }
         e.storage().persistent().set(&key, &(current - amount));
 
-        e.events().publish(
-            (
-                symbol_short!("vault"),
-                symbol_short!("rem_lia"),
-                token,
-                authorized,
-            ),
-            amount,
-        );
+        events::emit(&e, &authorized, LiabilityRemoved { token, amount });
 
         Ok(())
     }
@@ -968,11 +876,13 @@ impl PayrollVault {
             let token = tokens.get(i).unwrap();
             let balance = Self::get_treasury_balance(e.clone(), token.clone());
             let liability = Self::get_total_liability(e.clone(), token.clone());
+            let strategy_balance = Self::get_strategy_balance(e.clone(), token.clone());
 
             summary.push_back(TreasuryTokenSummary {
                 token,
                 balance,
                 liability,
+                strategy_balance,
             });
             i += 1;
         }
@@ -984,50 +894,52 @@ impl PayrollVault {
 
     /// Propose an emergency drain of all vault funds to `recipient`.
     ///
-    /// Starts a 24-hour timelock. Only the admin can call this function.
-    /// Off-chain monitors should observe the `DRAIN_PROPOSED` event and alert
+    /// Starts a 24-hour timelock. Requires the multisig signers (the admin
+    /// while none are configured). Off-chain monitors should observe the `drain_proposed` event and alert
     /// token holders so they can exit before the drain executes.
     ///
-    /// Emits: `(DRAIN_PROPOSED, admin)` → `(recipient, execute_after)`
+    /// Emits: `DrainProposed` with the admin as actor.
     pub fn propose_emergency_drain(e: Env, recipient: Address) -> Result<(), QuipayError> {
-        let admin = Self::get_admin(e.clone())?;
-        admin.require_auth();
-
-        // Disallow stacking proposals – cancel first, then re-propose.
-        if e.storage().persistent().has(&StateKey::PendingDrain) {
-            return Err(QuipayError::Custom);
-        }
+        let admin = Self::require_config_auth(&e)?;
 
-        let now = e.ledger().timestamp();
-        let execute_after = now.saturating_add(DRAIN_TIMELOCK_DURATION);
-
-        let pending = PendingDrain {
-            recipient: recipient.clone(),
-            execute_after,
-            proposed_at: now,
-            proposed_by: admin.clone(),
-        };
-
-        e.storage()
-            .persistent()
-            .set(&StateKey::PendingDrain, &pending);
+        Self::start_emergency_drain(&e, admin, recipient, Vec::new(&e), false)
+    }
 
-        #[allow(deprecated)]
-        e.events()
-            .publish((DRAIN_PROPOSED, admin), (recipient, execute_after));
+    /// Propose a drain of specific tokens and amounts to `recipient`.
+    ///
+    /// With `protective` set, the drain keeps the wages workers have vested by
+    /// the end of the timelock, plus outstanding IOUs, in the vault; everything
+    /// else may move, including the backing of wages not yet vested. Those
+    /// wages are tallied from PayrollStream with `tally_drain_reserves` once
+    /// the timelock expires, and the token's liability is cut down to them, so
+    /// payroll should be paused before the drain executes.
+    ///
+    /// Otherwise a target amount of 0 drains the token in full and wipes its
+    /// liabilities, like `propose_emergency_drain`, and a non-zero amount may
+    /// only take unreserved funds, leaving liabilities fully backed.
+    ///
+    /// Starts the same 24-hour timelock, with the same authorisation.
+    pub fn propose_partial_drain(
+        e: Env,
+        recipient: Address,
+        targets: Vec<DrainTarget>,
+        protective: bool,
+    ) -> Result<(), QuipayError> {
+        let admin = Self::require_config_auth(&e)?;
+        Self::validate_drain_targets(&targets)?;
 
-        Ok(())
+        Self::start_emergency_drain(&e, admin, recipient, targets, protective)
     }
 
     /// Execute a pending emergency drain after the 24-hour timelock has expired.
     ///
     /// Permissionless after the timelock: anyone can call this once the window
     /// opens, ensuring liveness even if the admin key is unavailable.
-    /// Drains every tracked token's full on-chain balance to the `recipient`
-    /// recorded in the proposal.
+    /// Moves the proposal's targets to its `recipient`; a proposal without
+    /// targets drains every tracked token's full on-chain balance. A
+    /// protective drain needs its reserves fully tallied first.
     ///
-    /// Emits: `(DRAIN_EXECUTED, recipient)` → `(token, amount)` per token,
-    ///         then `(DRAIN_EXECUTED, recipient)` → `"done"` as a final marker.
+    /// Emits: `DrainExecuted` for each token moved.
     pub fn execute_emergency_drain(e: Env) -> Result<(), QuipayError> {
         let pending: PendingDrain = e
             .storage()
@@ -1040,40 +952,51 @@ impl PayrollVault {
             return Err(QuipayError::DrainTimelockActive);
         }
 
+        if pending.protective && pending.tally_cursor.is_some() {
+            return Err(QuipayError::DrainTallyIncomplete);
+        }
+
         let recipient = pending.recipient.clone();
 
-        // Drain every tracked token.
-        let tokens: Vec<Address> = e
-            .storage()
-            .persistent()
-            .get(&StateKey::TokenList)
-            .unwrap_or_else(|| Vec::new(&e));
+        // Without explicit targets, drain every tracked token in full.
+        let targets = if pending.targets.is_empty() {
+            let tokens: Vec<Address> = e
+                .storage()
+                .persistent()
+                .get(&StateKey::TokenList)
+                .unwrap_or_else(|| Vec::new(&e));
+            let mut all = Vec::new(&e);
+            for token in tokens.iter() {
+                all.push_back(DrainTarget { token, amount: 0 });
+            }
+            all
+        } else {
+            pending.targets.clone()
+        };
 
-        let mut i = 0;
-        while i < tokens.len() {
-            let token = tokens.get(i).unwrap();
-            let token_client = token::Client::new(&e, &token);
-            let on_chain_balance = token_client.balance(&e.current_contract_address());
-
-            if on_chain_balance > 0 {
-                // Wipe internal accounting for this token.
-                e.storage()
-                    .persistent()
-                    .set(&StateKey::TreasuryBalance(token.clone()), &0i128);
-                e.storage()
-                    .persistent()
-                    .set(&StateKey::TotalLiability(token.clone()), &0i128);
-
-                token_client.transfer(&e.current_contract_address(), &recipient, &on_chain_balance);
-
-                #[allow(deprecated)]
-                e.events().publish(
-                    (DRAIN_EXECUTED, recipient.clone()),
-                    (token, on_chain_balance),
+        for target in targets.iter() {
+            let moved = if pending.protective {
+                let reserve = pending.reserves.get(target.token.clone()).unwrap_or(0);
+                Self::drain_protective(&e, &target.token, target.amount, reserve)?
+            } else if target.amount == 0 {
+                Self::drain_token(&e, &target.token)?
+            } else {
+                Self::drain_treasury(&e, &target.token, target.amount)?
+            };
+
+            if moved > 0 {
+                let token_client = token::Client::new(&e, &target.token);
+                token_client.transfer(&e.current_contract_address(), &recipient, &moved);
+
+                events::emit_from_contract(
+                    &e,
+                    DrainExecuted {
+                        recipient: recipient.clone(),
+                        token: target.token,
+                        amount: moved,
+                    },
                 );
             }
-
-            i += 1;
         }
 
         // Remove the pending proposal.
@@ -1082,11 +1005,61 @@ impl PayrollVault {
         Ok(())
     }
 
+    /// Tally the wages a pending protective drain keeps in the vault, reading
+    /// up to `limit` streams from PayrollStream per call (200 at most).
+    ///
+    /// Permissionless once the timelock has expired; wages are counted as
+    /// vested at its expiry. Returns `true` once every stream is tallied.
+    pub fn tally_drain_reserves(e: Env, limit: Option<u32>) -> Result<bool, QuipayError> {
+        let mut pending: PendingDrain = e
+            .storage()
+            .persistent()
+            .get(&StateKey::PendingDrain)
+            .ok_or(QuipayError::NoDrainPending)?;
+        let Some(start_id) = pending.tally_cursor else {
+            return Ok(true);
+        };
+        if e.ledger().timestamp() < pending.execute_after {
+            return Err(QuipayError::DrainTimelockActive);
+        }
+
+        let stream_contract: Option<Address> =
+            e.storage().persistent().get(&StateKey::AuthorizedContract);
+        pending.tally_cursor = match stream_contract {
+            Some(stream_contract) => {
+                let (totals, next_id) = e.invoke_contract::<(Map<Address, i128>, Option<u64>)>(
+                    &stream_contract,
+                    &Symbol::new(&e, "get_vested_unwithdrawn"),
+                    soroban_sdk::vec![
+                        &e,
+                        pending.execute_after.into_val(&e),
+                        start_id.into_val(&e),
+                        limit.into_val(&e),
+                    ],
+                );
+                for (token, amount) in totals.iter() {
+                    let reserve = pending.reserves.get(token.clone()).unwrap_or(0);
+                    pending
+                        .reserves
+                        .set(token, reserve.checked_add(amount).ok_or(QuipayError::Overflow)?);
+                }
+                next_id
+            }
+            // Without a stream contract no wages are owed.
+            None => None,
+        };
+
+        e.storage()
+            .persistent()
+            .set(&StateKey::PendingDrain, &pending);
+        Ok(pending.tally_cursor.is_none())
+    }
+
     /// Cancel a pending emergency drain proposal.
     ///
     /// Only the admin can call this function.
     ///
-    /// Emits: `(DRAIN_CANCELED, admin)` → `(recipient, execute_after)`
+    /// Emits: `DrainCanceled` with the admin as actor.
     pub fn cancel_emergency_drain(e: Env) -> Result<(), QuipayError> {
         let admin = Self::get_admin(e.clone())?;
         admin.require_auth();
@@ -1099,10 +1072,13 @@ impl PayrollVault {
 
         e.storage().persistent().remove(&StateKey::PendingDrain);
 
-        #[allow(deprecated)]
-        e.events().publish(
-            (DRAIN_CANCELED, admin),
-            (pending.recipient, pending.execute_after),
+        events::emit(
+            &e,
+            &admin,
+            DrainCanceled {
+                recipient: pending.recipient,
+                execute_after: pending.execute_after,
+            },
         );
 
         Ok(())
@@ -1120,6 +1096,16 @@ impl PayrollVault {
 }
 
 impl PayrollVault {
+    /// Hand a version recorded by a release older than the upgrade manager
+    /// over to the manager.
+    fn adopt_legacy_version(e: &Env) {
+        let legacy: Option<VersionInfo> = e.storage().persistent().get(&StateKey::Version);
+        if let Some(version) = legacy {
+            upgrade::adopt_version(e, &version);
+            e.storage().persistent().remove(&StateKey::Version);
+        }
+    }
+
     /// Verify that the required number of signers have authorized the transaction.
     ///
     /// ### Deduplication
@@ -1174,7 +1160,356 @@ impl PayrollVault {
         Ok(())
     }
 
+    /// Authorise a signer, threshold or drain change made outside the proposal
+    /// queue. It needs the multisig threshold, so once the threshold is raised
+    /// the admin key alone can no longer bypass the queue. Vaults without
+    /// signers fall back to the admin. Returns the admin, recorded as the actor.
+    fn require_config_auth(e: &Env) -> Result<Address, QuipayError> {
+        let admin = Self::get_admin(e.clone())?;
+        if Self::get_signers(e.clone()).is_empty() {
+            admin.require_auth();
+        } else {
+            Self::require_multisig_auth(e)?;
+        }
+        Ok(admin)
+    }
+
+    // Config changes shared by the admin entry points and executed proposals.
+    // `actor` is recorded in the emitted event.
+
+    fn apply_add_signer(e: &Env, actor: Address, new_signer: Address) -> Result<(), QuipayError> {
+        let mut signers: Vec<Address> = e
+            .storage()
+            .persistent()
+            .get(&StateKey::Signers)
+            .ok_or(QuipayError::NoSigners)?;
+
+        // Check if already a signer
+        let mut i = 0;
+        while i < signers.len() {
+            if let Some(s) = signers.get(i) {
+                if s == new_signer {
+                    return Err(QuipayError::AlreadySigner);
+                }
+            }
+            i += 1;
+        }
+
+        signers.push_back(new_signer.clone());
+        e.storage().persistent().set(&StateKey::Signers, &signers);
+
+        events::emit(e, &actor, SignerAdded { signer: new_signer });
+        Ok(())
+    }
+
+    fn apply_remove_signer(
+        e: &Env,
+        actor: Address,
+        signer_to_remove: Address,
+    ) -> Result<(), QuipayError> {
+        let signers: Vec<Address> = e
+            .storage()
+            .persistent()
+            .get(&StateKey::Signers)
+            .ok_or(QuipayError::NoSigners)?;
+
+        let threshold: u32 = e
+            .storage()
+            .persistent()
+            .get(&StateKey::Threshold)
+            .unwrap_or(1);
+
+        // Ensure we don't go below threshold
+        if signers.len() <= threshold {
+            return Err(QuipayError::InvalidThreshold);
+        }
+
+        let mut found = false;
+        let mut new_signers = Vec::new(e);
+        let mut i = 0;
+        while i < signers.len() {
+            if let Some(s) = signers.get(i) {
+                if s == signer_to_remove {
+                    found = true;
+                } else {
+                    new_signers.push_back(s);
+                }
+            }
+            i += 1;
+        }
+
+        if !found {
+            return Err(QuipayError::SignerNotFound);
+        }
+
+        e.storage()
+            .persistent()
+            .set(&StateKey::Signers, &new_signers);
+        events::emit(
+            e,
+            &actor,
+            SignerRemoved {
+                signer: signer_to_remove,
+            },
+        );
+        Ok(())
+    }
+
+    fn apply_set_threshold(e: &Env, actor: Address, threshold: u32) -> Result<(), QuipayError> {
+        let signers: Vec<Address> = e
+            .storage()
+            .persistent()
+            .get(&StateKey::Signers)
+            .ok_or(QuipayError::NoSigners)?;
+
+        if threshold == 0 || threshold > signers.len() {
+            return Err(QuipayError::InvalidThreshold);
+        }
+
+        e.storage()
+            .persistent()
+            .set(&StateKey::Threshold, &threshold);
+        events::emit(e, &actor, ThresholdSet { threshold });
+        Ok(())
+    }
+
+    fn apply_set_withdrawal_threshold(
+        e: &Env,
+        _actor: Address,
+        threshold: i128,
+    ) -> Result<(), QuipayError> {
+        if threshold < 0 {
+            return Err(QuipayError::InvalidAmount);
+        }
+
+        e.storage()
+            .persistent()
+            .set(&StateKey::WithdrawalThreshold, &threshold);
+        Ok(())
+    }
+
+    /// A partial drain names at least one token and no negative amount.
+    fn validate_drain_targets(targets: &Vec<DrainTarget>) -> Result<(), QuipayError> {
+        if targets.is_empty() {
+            return Err(QuipayError::InvalidToken);
+        }
+        for target in targets.iter() {
+            if target.amount < 0 {
+                return Err(QuipayError::InvalidAmount);
+            }
+        }
+        Ok(())
+    }
+
+    /// Start the 24-hour drain timelock towards `recipient`.
+    fn start_emergency_drain(
+        e: &Env,
+        proposer: Address,
+        recipient: Address,
+        targets: Vec<DrainTarget>,
+        protective: bool,
+    ) -> Result<(), QuipayError> {
+        // Disallow stacking proposals – cancel first, then re-propose.
+        if e.storage().persistent().has(&StateKey::PendingDrain) {
+            return Err(QuipayError::DrainAlreadyPending);
+        }
+
+        let now = e.ledger().timestamp();
+        let execute_after = now.saturating_add(DRAIN_TIMELOCK_DURATION);
+
+        let pending = PendingDrain {
+            recipient: recipient.clone(),
+            execute_after,
+            proposed_at: now,
+            proposed_by: proposer.clone(),
+            targets,
+            protective,
+            reserves: Map::new(e),
+            tally_cursor: if protective { Some(1) } else { None },
+        };
+
+        e.storage()
+            .persistent()
+            .set(&StateKey::PendingDrain, &pending);
+
+        events::emit(
+            e,
+            &proposer,
+            DrainProposed {
+                recipient,
+                targets: pending.targets,
+                protective: pending.protective,
+                execute_after,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Full drain of `token`: wipe its accounting, outstanding IOUs included,
+    /// and return the whole on-chain balance to move.
+    fn drain_token(e: &Env, token: &Address) -> Result<i128, QuipayError> {
+        Self::recall_all_from_strategy(e, token)?;
+        let on_chain_balance = Self::held_balance(e, token);
+        if on_chain_balance > 0 {
+            e.storage()
+                .persistent()
+                .set(&StateKey::TreasuryBalance(token.clone()), &0i128);
+            e.storage()
+                .persistent()
+                .set(&StateKey::TotalLiability(token.clone()), &0i128);
+            e.storage()
+                .persistent()
+                .remove(&StateKey::AccruedFees(token.clone()));
+            Self::clear_ious(e, token);
+        }
+        Ok(on_chain_balance)
+    }
+
+    /// Take `amount` of `token` out of the treasury balance. Fails when that
+    /// would leave the token's liabilities unbacked.
+    fn drain_treasury(e: &Env, token: &Address, amount: i128) -> Result<i128, QuipayError> {
+        if amount > Self::get_available_balance(e.clone(), token.clone()) {
+            return Err(QuipayError::InsufficientBalance);
+        }
+        Self::take_from_treasury(e, token, amount)?;
+        Ok(amount)
+    }
+
+    /// Take everything but `reserve` (the vested, unwithdrawn wages) and the
+    /// outstanding IOUs out of `token`'s treasury balance, up to `amount` or
+    /// all of it when `amount` is 0. The liability drops to what is kept.
+    fn drain_protective(
+        e: &Env,
+        token: &Address,
+        amount: i128,
+        reserve: i128,
+    ) -> Result<i128, QuipayError> {
+        let balance = Self::get_treasury_balance(e.clone(), token.clone());
+        let liability = Self::get_total_liability(e.clone(), token.clone());
+        let kept = reserve
+            .checked_add(Self::get_total_iou(e.clone(), token.clone()))
+            .ok_or(QuipayError::Overflow)?
+            .min(liability);
+
+        let available = (balance - kept).max(0);
+        let moved = if amount == 0 {
+            available
+        } else {
+            amount.min(available)
+        };
+        Self::take_from_treasury(e, token, moved)?;
+
+        if kept < liability {
+            e.storage()
+                .persistent()
+                .set(&StateKey::TotalLiability(token.clone()), &kept);
+            events::emit_from_contract(
+                e,
+                LiabilityRemoved {
+                    token: token.clone(),
+                    amount: liability - kept,
+                },
+            );
+        }
+        Ok(moved)
+    }
+
+    fn take_from_treasury(e: &Env, token: &Address, amount: i128) -> Result<(), QuipayError> {
+        if amount > 0 {
+            let balance = Self::get_treasury_balance(e.clone(), token.clone());
+            e.storage()
+                .persistent()
+                .set(&StateKey::TreasuryBalance(token.clone()), &(balance - amount));
+            Self::ensure_liquidity(e, token, amount)?;
+        }
+        Ok(())
+    }
+
+    /// Move free (unreserved) funds out of the treasury.
+    fn withdraw_free_funds(
+        e: &Env,
+        actor: &Address,
+        to: Address,
+        token: Address,
+        amount: i128,
+    ) -> Result<(), QuipayError> {
+        let available = Self::get_available_balance(e.clone(), token.clone());
+        if amount > available {
+            return Err(QuipayError::InsufficientBalance);
+        }
+
+        let balance_key = StateKey::TreasuryBalance(token.clone());
+        let balance: i128 = e.storage().persistent().get(&balance_key).unwrap_or(0);
+        let new_total = balance - amount;
+        // If the invariant holds, this should never underflow.
+        e.storage()
+            .persistent()
+            .set(&balance_key, &new_total);
+
+        Self::ensure_liquidity(e, &token, amount)?;
+        let token_client = token::Client::new(e, &token);
+        token_client.transfer(&e.current_contract_address(), &to, &amount);
+
+        events::emit(
+            e,
+            actor,
+            Withdrawn {
+                to: to.clone(),
+                token: token.clone(),
+                amount,
+                new_balance: new_total,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Release `amount` of liability and treasury balance for `token` and
+    /// transfer it to `to`, less `fee` already accrued to the protocol.
+    fn release_and_pay(
+        e: &Env,
+        to: &Address,
+        token: &Address,
+        amount: i128,
+        fee: i128,
+    ) -> Result<(), QuipayError> {
+        let balance_key = StateKey::TreasuryBalance(token.clone());
+        let liability_key = StateKey::TotalLiability(token.clone());
+
+        let balance: i128 = e.storage().persistent().get(&balance_key).unwrap_or(0);
+        let liability: i128 = e.storage().persistent().get(&liability_key).unwrap_or(0);
+
+        if amount > balance {
+            return Err(QuipayError::InsufficientBalance);
+        }
+
+        if amount > liability {
+            return Err(QuipayError::InvalidAmount);
+        }
+
+        e.storage()
+            .persistent()
+            .set(&liability_key, &(liability - amount));
+        e.storage()
+            .persistent()
+            .set(&balance_key, &(balance - amount));
+
+        let net = amount - fee;
+        if net > 0 {
+            Self::ensure_liquidity(e, token, net)?;
+            let token_client = token::Client::new(e, token);
+            token_client.transfer(&e.current_contract_address(), to, &net);
+        }
+        Ok(())
+    }
+
     fn track_supported_token(e: &Env, token: Address) {
+        // Deposits keep the token's books alive; keepers bump idle tokens.
+        ttl::extend(e, &StateKey::TreasuryBalance(token.clone()));
+        ttl::extend(e, &StateKey::TotalLiability(token.clone()));
+        ttl::extend(e, &StateKey::TokenList);
+
         let mut tokens = e
             .storage()
             .persistent()
@@ -0,0 +1,325 @@
+//! Standing top-up mandates.
+//!
+//! An employer authorises a rule such as "keep at least 30 days of runway in
+//! token X, pulling up to Y per week from wallet W". The wallet grants the
+//! vault a token allowance; any keeper may then call `execute_top_up`, which
+//! pulls the current shortfall via `transfer_from`, capped by the mandate's
+//! per-period limit, the allowance and the wallet balance. Top-ups are
+//! deposits, so the token allowlist and deposit cap apply to them.
+//!
+//! Stream liabilities are fully funded when created, so runway is measured
+//! beyond them: the target is what the employer's running streams pay out
+//! over the next `min_runway_secs` at their current rates, plus a fixed
+//! `buffer`, as reported by the authorized stream contract's
+//! `get_employer_obligations`. It is compared with the treasury funds not
+//! reserved for other employers, less what workers can already claim.
+
+use super::*;
+use crate::events::{self, MandateCreated, MandateExecuted, MandateRevoked};
+
+/// Number of executions kept in each mandate's history.
+pub(crate) const MAX_MANDATE_HISTORY: u32 = 50;
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct TopUpMandate {
+    pub id: u64,
+    pub employer: Address,
+    /// Wallet funds are pulled from; must approve the vault as spender.
+    pub wallet: Address,
+    pub token: Address,
+    /// Runway the mandate keeps funded, in seconds.
+    pub min_runway_secs: u64,
+    /// Extra amount kept on top of the runway target.
+    pub buffer: i128,
+    /// Maximum amount pulled per period.
+    pub max_per_period: i128,
+    pub period_secs: u64,
+    pub period_start: u64,
+    pub pulled_in_period: i128,
+    pub created_at: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct TopUpExecution {
+    pub executor: Address,
+    pub amount: i128,
+    pub executed_at: u64,
+}
+
+#[contractimpl]
+impl PayrollVault {
+    /// Create a top-up mandate. Requires auth from both `employer` and `wallet`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_top_up_mandate(
+        e: Env,
+        employer: Address,
+        wallet: Address,
+        token: Address,
+        min_runway_secs: u64,
+        buffer: i128,
+        max_per_period: i128,
+        period_secs: u64,
+    ) -> Result<u64, QuipayError> {
+        employer.require_auth();
+        if wallet != employer {
+            wallet.require_auth();
+        }
+
+        require_positive_amount!(max_per_period);
+        if buffer < 0 {
+            return Err(QuipayError::InvalidAmount);
+        }
+        if min_runway_secs == 0 || period_secs == 0 {
+            return Err(QuipayError::InvalidTimeRange);
+        }
+
+        let id: u64 = e
+            .storage()
+            .persistent()
+            .get(&StateKey::NextMandateId)
+            .unwrap_or(1);
+        let next = id.checked_add(1).ok_or(QuipayError::Overflow)?;
+        e.storage()
+            .persistent()
+            .set(&StateKey::NextMandateId, &next);
+
+        let now = e.ledger().timestamp();
+        let mandate = TopUpMandate {
+            id,
+            employer: employer.clone(),
+            wallet,
+            token: token.clone(),
+            min_runway_secs,
+            buffer,
+            max_per_period,
+            period_secs,
+            period_start: now,
+            pulled_in_period: 0,
+            created_at: now,
+        };
+        e.storage()
+            .persistent()
+            .set(&StateKey::Mandate(id), &mandate);
+
+        let index_key = StateKey::EmployerMandates(employer.clone());
+        let mut ids: Vec<u64> = e
+            .storage()
+            .persistent()
+            .get(&index_key)
+            .unwrap_or_else(|| Vec::new(&e));
+        ids.push_back(id);
+        e.storage().persistent().set(&index_key, &ids);
+
+        events::emit(
+            &e,
+            &employer,
+            MandateCreated {
+                mandate_id: id,
+                employer: employer.clone(),
+                token,
+                min_runway_secs,
+                buffer,
+                max_per_period,
+                period_secs,
+            },
+        );
+
+        Ok(id)
+    }
+
+    /// Revoke a mandate. Only the employer that created it can call this.
+    pub fn revoke_top_up_mandate(e: Env, mandate_id: u64) -> Result<(), QuipayError> {
+        let mandate =
+            Self::get_top_up_mandate(e.clone(), mandate_id).ok_or(QuipayError::MandateNotFound)?;
+        mandate.employer.require_auth();
+
+        e.storage()
+            .persistent()
+            .remove(&StateKey::Mandate(mandate_id));
+        e.storage()
+            .persistent()
+            .remove(&StateKey::MandateHistory(mandate_id));
+
+        let index_key = StateKey::EmployerMandates(mandate.employer.clone());
+        let ids: Vec<u64> = e
+            .storage()
+            .persistent()
+            .get(&index_key)
+            .unwrap_or_else(|| Vec::new(&e));
+        let mut kept = Vec::new(&e);
+        for id in ids.iter() {
+            if id != mandate_id {
+                kept.push_back(id);
+            }
+        }
+        if kept.is_empty() {
+            e.storage().persistent().remove(&index_key);
+        } else {
+            e.storage().persistent().set(&index_key, &kept);
+        }
+
+        events::emit(
+            &e,
+            &mandate.employer,
+            MandateRevoked {
+                mandate_id,
+                employer: mandate.employer.clone(),
+            },
+        );
+        Ok(())
+    }
+
+    /// Execute a mandate, pulling whatever is due from the employer's wallet.
+    ///
+    /// Permissionless: any keeper may call this. Returns the amount pulled,
+    /// which is zero when the runway target is already met or the period cap
+    /// is exhausted.
+    pub fn execute_top_up(e: Env, keeper: Address, mandate_id: u64) -> Result<i128, QuipayError> {
+        keeper.require_auth();
+
+        let mut mandate =
+            Self::get_top_up_mandate(e.clone(), mandate_id).ok_or(QuipayError::MandateNotFound)?;
+        Self::roll_mandate_period(&e, &mut mandate);
+
+        let amount = Self::top_up_due(&e, &mandate)?;
+        if amount == 0 {
+            return Ok(0);
+        }
+        Self::check_deposit_allowed(&e, &mandate.token, amount)?;
+
+        let vault = e.current_contract_address();
+        let held_before = Self::held_balance(&e, &mandate.token);
+        let token_client = token::Client::new(&e, &mandate.token);
+        token_client.transfer_from(&vault, &mandate.wallet, &vault, &amount);
+        let received = Self::held_balance(&e, &mandate.token) - held_before;
+
+        let key = StateKey::TreasuryBalance(mandate.token.clone());
+        let balance: i128 = e.storage().persistent().get(&key).unwrap_or(0);
+        let new_total = balance.checked_add(received).ok_or(QuipayError::Overflow)?;
+        e.storage().persistent().set(&key, &new_total);
+        Self::track_supported_token(&e, mandate.token.clone());
+
+        mandate.pulled_in_period += amount;
+        e.storage()
+            .persistent()
+            .set(&StateKey::Mandate(mandate_id), &mandate);
+
+        let now = e.ledger().timestamp();
+        let history_key = StateKey::MandateHistory(mandate_id);
+        let mut history: Vec<TopUpExecution> = e
+            .storage()
+            .persistent()
+            .get(&history_key)
+            .unwrap_or_else(|| Vec::new(&e));
+        if history.len() >= MAX_MANDATE_HISTORY {
+            history.pop_front();
+        }
+        history.push_back(TopUpExecution {
+            executor: keeper.clone(),
+            amount,
+            executed_at: now,
+        });
+        e.storage().persistent().set(&history_key, &history);
+
+        events::emit(
+            &e,
+            &keeper,
+            MandateExecuted {
+                mandate_id,
+                employer: mandate.employer.clone(),
+                token: mandate.token.clone(),
+                amount,
+                new_balance: new_total,
+            },
+        );
+
+        // Top-ups are deposits: outstanding IOUs are settled first.
+        Self::settle_iou_queue(&e, &mandate.token, received)?;
+
+        Ok(amount)
+    }
+
+    /// Amount `execute_top_up` would pull right now.
+    pub fn get_top_up_due(e: Env, mandate_id: u64) -> Result<i128, QuipayError> {
+        let mut mandate =
+            Self::get_top_up_mandate(e.clone(), mandate_id).ok_or(QuipayError::MandateNotFound)?;
+        Self::roll_mandate_period(&e, &mut mandate);
+        Self::top_up_due(&e, &mandate)
+    }
+
+    pub fn get_top_up_mandate(e: Env, mandate_id: u64) -> Option<TopUpMandate> {
+        e.storage().persistent().get(&StateKey::Mandate(mandate_id))
+    }
+
+    pub fn get_employer_mandates(e: Env, employer: Address) -> Vec<u64> {
+        e.storage()
+            .persistent()
+            .get(&StateKey::EmployerMandates(employer))
+            .unwrap_or_else(|| Vec::new(&e))
+    }
+
+    /// Most recent executions of a mandate, oldest first.
+    pub fn get_mandate_executions(e: Env, mandate_id: u64) -> Vec<TopUpExecution> {
+        e.storage()
+            .persistent()
+            .get(&StateKey::MandateHistory(mandate_id))
+            .unwrap_or_else(|| Vec::new(&e))
+    }
+}
+
+impl PayrollVault {
+    /// Start a new cap period once the current one has elapsed.
+    fn roll_mandate_period(e: &Env, mandate: &mut TopUpMandate) {
+        let now = e.ledger().timestamp();
+        let elapsed = now.saturating_sub(mandate.period_start);
+        if elapsed >= mandate.period_secs {
+            mandate.period_start = now - elapsed % mandate.period_secs;
+            mandate.pulled_in_period = 0;
+        }
+    }
+
+    /// Runway shortfall capped by the period limit, allowance and wallet balance.
+    fn top_up_due(e: &Env, mandate: &TopUpMandate) -> Result<i128, QuipayError> {
+        let stream: Address = e
+            .storage()
+            .persistent()
+            .get(&StateKey::AuthorizedContract)
+            .ok_or(QuipayError::NotInitialized)?;
+
+        let (outstanding, claimable, burn): (i128, i128, i128) = e.invoke_contract(
+            &stream,
+            &Symbol::new(e, "get_employer_obligations"),
+            soroban_sdk::vec![
+                e,
+                mandate.employer.into_val(e),
+                mandate.token.into_val(e),
+                mandate.min_runway_secs.into_val(e),
+            ],
+        );
+
+        let treasury = Self::get_treasury_balance(e.clone(), mandate.token.clone());
+        let liability = Self::get_total_liability(e.clone(), mandate.token.clone());
+        let reserved_for_others = liability.saturating_sub(outstanding).max(0);
+        let available = treasury.saturating_sub(reserved_for_others).max(0);
+        let funded = available.saturating_sub(claimable);
+        let target = burn.saturating_add(mandate.buffer);
+        let shortfall = target.saturating_sub(funded);
+        if shortfall <= 0 {
+            return Ok(0);
+        }
+
+        let vault = e.current_contract_address();
+        let token_client = token::Client::new(e, &mandate.token);
+        let cap_left = mandate.max_per_period - mandate.pulled_in_period;
+        let allowance = token_client.allowance(&mandate.wallet, &vault);
+        let wallet_balance = token_client.balance(&mandate.wallet);
+
+        Ok(shortfall
+            .min(cap_left)
+            .min(allowance)
+            .min(wallet_balance)
+            .max(0))
+    }
+}
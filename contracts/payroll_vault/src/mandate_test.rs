@@ -0,0 +1,262 @@
+use crate::{PayrollVault, PayrollVaultClient, TokenConfig};
+use quipay_common::QuipayError;
+use soroban_sdk::{
+    Address, Env, contract, contractimpl, contracttype,
+    testutils::{Address as _, Ledger},
+    token,
+};
+
+// ---------------------------------------------------------------------------
+// Mock stream contract: reports whatever obligations the test configures.
+// ---------------------------------------------------------------------------
+
+#[contracttype]
+enum MockKey {
+    Obligations,
+}
+
+#[contract]
+pub struct MockStream;
+
+#[contractimpl]
+impl MockStream {
+    pub fn set_obligations(env: Env, outstanding: i128, claimable: i128, burn: i128) {
+        env.storage()
+            .instance()
+            .set(&MockKey::Obligations, &(outstanding, claimable, burn));
+    }
+
+    pub fn get_employer_obligations(
+        env: Env,
+        _employer: Address,
+        _token: Address,
+        _horizon_secs: u64,
+    ) -> (i128, i128, i128) {
+        env.storage()
+            .instance()
+            .get(&MockKey::Obligations)
+            .unwrap_or((0, 0, 0))
+    }
+}
+
+const WEEK: u64 = 7 * 24 * 60 * 60;
+const MONTH: u64 = 30 * 24 * 60 * 60;
+
+struct Setup<'a> {
+    client: PayrollVaultClient<'a>,
+    stream: MockStreamClient<'a>,
+    token: Address,
+    employer: Address,
+    wallet: Address,
+}
+
+fn setup(env: &Env) -> Setup<'_> {
+    env.mock_all_auths();
+
+    let vault_id = env.register(PayrollVault, ());
+    let client = PayrollVaultClient::new(env, &vault_id);
+    client.initialize(&Address::generate(env));
+    client.set_token_allowlist_enforced(&false);
+
+    let stream_id = env.register(MockStream, ());
+    client.set_authorized_contract(&stream_id);
+
+    let token = env
+        .register_stellar_asset_contract_v2(Address::generate(env))
+        .address();
+    let employer = Address::generate(env);
+    let wallet = Address::generate(env);
+    token::StellarAssetClient::new(env, &token).mint(&wallet, &100_000);
+
+    // Employer funds 1_000 up front, fully reserved for their streams.
+    client.deposit(&wallet, &token, &1_000);
+    client.add_liability(&token, &1_000);
+    let stream = MockStreamClient::new(env, &stream_id);
+    stream.set_obligations(&1_000, &0, &0);
+
+    Setup {
+        client,
+        stream,
+        token,
+        employer,
+        wallet,
+    }
+}
+
+fn approve(env: &Env, s: &Setup, amount: i128) {
+    token::Client::new(env, &s.token).approve(&s.wallet, &s.client.address, &amount, &1_000);
+}
+
+#[test]
+fn test_create_mandate_records_rule() {
+    let env = Env::default();
+    let s = setup(&env);
+
+    let id =
+        s.client
+            .create_top_up_mandate(&s.employer, &s.wallet, &s.token, &MONTH, &0, &500, &WEEK);
+    assert_eq!(id, 1);
+
+    let mandate = s.client.get_top_up_mandate(&id).unwrap();
+    assert_eq!(mandate.employer, s.employer);
+    assert_eq!(mandate.wallet, s.wallet);
+    assert_eq!(mandate.min_runway_secs, MONTH);
+    assert_eq!(mandate.buffer, 0);
+    assert_eq!(mandate.max_per_period, 500);
+    assert_eq!(mandate.pulled_in_period, 0);
+    assert_eq!(s.client.get_employer_mandates(&s.employer).len(), 1);
+
+    let result =
+        s.client
+            .try_create_top_up_mandate(&s.employer, &s.wallet, &s.token, &MONTH, &0, &0, &WEEK);
+    assert_eq!(result, Err(Ok(QuipayError::InvalidAmount)));
+    let result =
+        s.client
+            .try_create_top_up_mandate(&s.employer, &s.wallet, &s.token, &MONTH, &0, &500, &0);
+    assert_eq!(result, Err(Ok(QuipayError::InvalidTimeRange)));
+    let result = s.client.try_create_top_up_mandate(
+        &s.employer,
+        &s.wallet,
+        &s.token,
+        &MONTH,
+        &-1,
+        &500,
+        &WEEK,
+    );
+    assert_eq!(result, Err(Ok(QuipayError::InvalidAmount)));
+}
+
+#[test]
+fn test_execute_is_noop_when_runway_is_funded() {
+    let env = Env::default();
+    let s = setup(&env);
+    approve(&env, &s, 10_000);
+
+    let id =
+        s.client
+            .create_top_up_mandate(&s.employer, &s.wallet, &s.token, &MONTH, &0, &500, &WEEK);
+    let keeper = Address::generate(&env);
+
+    assert_eq!(s.client.execute_top_up(&keeper, &id), 0);
+    assert_eq!(s.client.get_mandate_executions(&id).len(), 0);
+    assert_eq!(s.client.get_treasury_balance(&s.token), 1_000);
+}
+
+#[test]
+fn test_execute_pulls_shortfall_capped_per_period() {
+    let env = Env::default();
+    let s = setup(&env);
+    approve(&env, &s, 10_000);
+    let token_read = token::Client::new(&env, &s.token);
+
+    let id =
+        s.client
+            .create_top_up_mandate(&s.employer, &s.wallet, &s.token, &MONTH, &500, &500, &WEEK);
+    let keeper = Address::generate(&env);
+
+    // Workers can claim 600 of the 1_000 held, and the streams pay out 500
+    // over the window: with a 500 buffer the employer is 600 short.
+    s.stream.set_obligations(&1_000, &600, &500);
+    assert_eq!(s.client.get_top_up_due(&id), 500);
+
+    assert_eq!(s.client.execute_top_up(&keeper, &id), 500);
+    assert_eq!(s.client.get_treasury_balance(&s.token), 1_500);
+    assert_eq!(token_read.balance(&s.wallet), 100_000 - 1_000 - 500);
+
+    let history = s.client.get_mandate_executions(&id);
+    assert_eq!(history.len(), 1);
+    assert_eq!(history.get(0).unwrap().executor, keeper);
+    assert_eq!(history.get(0).unwrap().amount, 500);
+
+    // The weekly cap is exhausted.
+    assert_eq!(s.client.execute_top_up(&keeper, &id), 0);
+
+    // A new period lets the rest through.
+    env.ledger().with_mut(|li| li.timestamp += WEEK);
+    assert_eq!(s.client.execute_top_up(&keeper, &id), 100);
+    assert_eq!(s.client.get_treasury_balance(&s.token), 1_600);
+    assert_eq!(s.client.get_top_up_due(&id), 0);
+    assert_eq!(s.client.get_mandate_executions(&id).len(), 2);
+}
+
+#[test]
+fn test_execute_respects_allowance() {
+    let env = Env::default();
+    let s = setup(&env);
+    approve(&env, &s, 50);
+
+    let id =
+        s.client
+            .create_top_up_mandate(&s.employer, &s.wallet, &s.token, &MONTH, &500, &500, &WEEK);
+    s.stream.set_obligations(&1_000, &600, &500);
+
+    assert_eq!(s.client.execute_top_up(&Address::generate(&env), &id), 50);
+    assert_eq!(s.client.get_top_up_due(&id), 0);
+}
+
+#[test]
+fn test_runway_target_counts_burn_over_window() {
+    let env = Env::default();
+    let s = setup(&env);
+    approve(&env, &s, 10_000);
+
+    let id =
+        s.client
+            .create_top_up_mandate(&s.employer, &s.wallet, &s.token, &MONTH, &0, &5_000, &WEEK);
+
+    // The streams pay out more over the runway window than the vault holds
+    // for the employer beyond current claims.
+    s.stream.set_obligations(&1_000, &0, &1_300);
+    assert_eq!(s.client.execute_top_up(&Address::generate(&env), &id), 300);
+}
+
+#[test]
+fn test_revoked_mandate_cannot_execute() {
+    let env = Env::default();
+    let s = setup(&env);
+
+    let id =
+        s.client
+            .create_top_up_mandate(&s.employer, &s.wallet, &s.token, &MONTH, &0, &500, &WEEK);
+    s.client.revoke_top_up_mandate(&id);
+
+    assert!(s.client.get_top_up_mandate(&id).is_none());
+    assert_eq!(s.client.get_employer_mandates(&s.employer).len(), 0);
+    let result = s.client.try_execute_top_up(&Address::generate(&env), &id);
+    assert_eq!(result, Err(Ok(QuipayError::MandateNotFound)));
+}
+
+#[test]
+fn test_execute_applies_token_allowlist() {
+    let env = Env::default();
+    let s = setup(&env);
+    approve(&env, &s, 10_000);
+
+    let id =
+        s.client
+            .create_top_up_mandate(&s.employer, &s.wallet, &s.token, &MONTH, &500, &500, &WEEK);
+    s.stream.set_obligations(&1_000, &600, &500);
+    let keeper = Address::generate(&env);
+
+    s.client.set_token_allowlist_enforced(&true);
+    assert_eq!(
+        s.client.try_execute_top_up(&keeper, &id),
+        Err(Ok(QuipayError::InvalidToken))
+    );
+
+    // Pulling 500 would take the treasury past the deposit cap.
+    s.client.set_token_config(
+        &s.token,
+        &TokenConfig {
+            enabled: true,
+            deposit_cap: 1_200,
+            min_liability: 0,
+        },
+    );
+    assert_eq!(
+        s.client.try_execute_top_up(&keeper, &id),
+        Err(Ok(QuipayError::DepositCapExceeded))
+    );
+    assert_eq!(s.client.get_treasury_balance(&s.token), 1_000);
+    assert_eq!(s.client.get_mandate_executions(&id).len(), 0);
+}
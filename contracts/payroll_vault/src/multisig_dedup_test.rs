@@ -22,7 +22,7 @@ fn test_duplicate_signers_rejected() {
 
     // Propose an upgrade first
     let new_wasm_hash = soroban_sdk::BytesN::from_array(&env, &[0u8; 32]);
-    client.propose_upgrade(&new_wasm_hash, &(2, 0, 0));
+    client.propose_upgrade(&admin, &new_wasm_hash, &(2, 0, 0));
 
     // Fast forward time past the timelock (48 hours)
     env.ledger()
@@ -0,0 +1,308 @@
+//! On-chain multisig proposal queue.
+//!
+//! `require_multisig_auth` needs every signer to authorise the same
+//! transaction. Proposals let signers approve asynchronously instead: any
+//! signer queues an action, the others approve (or revoke their approval) in
+//! separate transactions, and anyone can execute it once the number of
+//! approvals from current signers reaches the threshold. Proposals expire
+//! after `PROPOSAL_TTL` seconds.
+//!
+//! The direct entry points for the same signer, threshold and drain changes
+//! also require the threshold, so the queue cannot be bypassed with the
+//! admin key alone.
+
+use super::*;
+use crate::events::{
+    self, ApprovalRevoked, ProposalApproved, ProposalCanceled, ProposalCreated, ProposalExecuted,
+};
+
+/// Lifetime of a proposal before it can no longer be approved or executed (7 days).
+pub(crate) const PROPOSAL_TTL: u64 = 7 * 24 * 60 * 60;
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub enum ProposalAction {
+    /// Withdraw free funds: (to, token, amount).
+    Withdraw(Address, Address, i128),
+    /// Start the emergency drain timelock towards the recipient.
+    EmergencyDrain(Address),
+    /// Start a partial drain timelock: (recipient, targets, protective).
+    /// See `propose_partial_drain`.
+    PartialDrain(Address, Vec<DrainTarget>, bool),
+    AddSigner(Address),
+    RemoveSigner(Address),
+    SetThreshold(u32),
+    SetWithdrawalThreshold(i128),
+}
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ProposalStatus {
+    Pending,
+    Executed,
+    Canceled,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Proposal {
+    pub id: u64,
+    pub action: ProposalAction,
+    pub proposer: Address,
+    pub approvals: Vec<Address>,
+    pub created_at: u64,
+    pub expires_at: u64,
+    pub status: ProposalStatus,
+}
+
+#[contractimpl]
+impl PayrollVault {
+    /// Queue a multisig action. The proposer must be a signer and counts as
+    /// the first approval.
+    pub fn create_proposal(
+        e: Env,
+        proposer: Address,
+        action: ProposalAction,
+    ) -> Result<u64, QuipayError> {
+        proposer.require_auth();
+        if !Self::is_signer(e.clone(), proposer.clone()) {
+            return Err(QuipayError::SignerNotFound);
+        }
+
+        let id: u64 = e
+            .storage()
+            .persistent()
+            .get(&StateKey::NextProposalId)
+            .unwrap_or(1);
+        let next = id.checked_add(1).ok_or(QuipayError::Overflow)?;
+        e.storage()
+            .persistent()
+            .set(&StateKey::NextProposalId, &next);
+
+        let now = e.ledger().timestamp();
+        let mut approvals = Vec::new(&e);
+        approvals.push_back(proposer.clone());
+        let proposal = Proposal {
+            id,
+            action,
+            proposer: proposer.clone(),
+            approvals,
+            created_at: now,
+            expires_at: now.saturating_add(PROPOSAL_TTL),
+            status: ProposalStatus::Pending,
+        };
+        e.storage()
+            .persistent()
+            .set(&StateKey::Proposal(id), &proposal);
+
+        let mut pending = Self::get_pending_proposals(e.clone());
+        pending.push_back(id);
+        e.storage()
+            .persistent()
+            .set(&StateKey::PendingProposals, &pending);
+
+        events::emit(
+            &e,
+            &proposer,
+            ProposalCreated {
+                proposal_id: id,
+                action: proposal.action,
+                expires_at: proposal.expires_at,
+            },
+        );
+
+        Ok(id)
+    }
+
+    /// Approve a pending proposal.
+    pub fn approve_proposal(e: Env, signer: Address, proposal_id: u64) -> Result<(), QuipayError> {
+        signer.require_auth();
+        if !Self::is_signer(e.clone(), signer.clone()) {
+            return Err(QuipayError::SignerNotFound);
+        }
+
+        let mut proposal = Self::load_open_proposal(&e, proposal_id)?;
+        if proposal.approvals.contains(signer.clone()) {
+            return Err(QuipayError::AlreadyApproved);
+        }
+        proposal.approvals.push_back(signer.clone());
+        e.storage()
+            .persistent()
+            .set(&StateKey::Proposal(proposal_id), &proposal);
+
+        events::emit(
+            &e,
+            &signer,
+            ProposalApproved {
+                proposal_id,
+                approvals: proposal.approvals.len(),
+            },
+        );
+        Ok(())
+    }
+
+    /// Withdraw a previously given approval.
+    pub fn revoke_approval(e: Env, signer: Address, proposal_id: u64) -> Result<(), QuipayError> {
+        signer.require_auth();
+
+        let mut proposal = Self::load_open_proposal(&e, proposal_id)?;
+        let index = proposal
+            .approvals
+            .first_index_of(signer.clone())
+            .ok_or(QuipayError::NotApproved)?;
+        proposal.approvals.remove(index);
+        e.storage()
+            .persistent()
+            .set(&StateKey::Proposal(proposal_id), &proposal);
+
+        events::emit(
+            &e,
+            &signer,
+            ApprovalRevoked {
+                proposal_id,
+                approvals: proposal.approvals.len(),
+            },
+        );
+        Ok(())
+    }
+
+    /// Execute a proposal whose approvals from current signers meet the threshold.
+    ///
+    /// Permissionless: anyone can trigger execution once enough signers approved.
+    pub fn execute_proposal(e: Env, proposal_id: u64) -> Result<(), QuipayError> {
+        let mut proposal = Self::load_open_proposal(&e, proposal_id)?;
+
+        if Self::count_valid_approvals(&e, &proposal) < Self::get_threshold(e.clone()) {
+            return Err(QuipayError::InsufficientSignatures);
+        }
+
+        // Mark executed before acting so the action cannot be replayed.
+        proposal.status = ProposalStatus::Executed;
+        e.storage()
+            .persistent()
+            .set(&StateKey::Proposal(proposal_id), &proposal);
+        Self::remove_pending_proposal(&e, proposal_id);
+
+        let actor = proposal.proposer.clone();
+        match proposal.action.clone() {
+            ProposalAction::Withdraw(to, token, amount) => {
+                require_positive_amount!(amount);
+                Self::withdraw_free_funds(&e, &actor, to, token, amount)?;
+            }
+            ProposalAction::EmergencyDrain(recipient) => {
+                Self::start_emergency_drain(&e, actor, recipient, Vec::new(&e), false)?;
+            }
+            ProposalAction::PartialDrain(recipient, targets, protective) => {
+                Self::validate_drain_targets(&targets)?;
+                Self::start_emergency_drain(&e, actor, recipient, targets, protective)?;
+            }
+            ProposalAction::AddSigner(signer) => Self::apply_add_signer(&e, actor, signer)?,
+            ProposalAction::RemoveSigner(signer) => Self::apply_remove_signer(&e, actor, signer)?,
+            ProposalAction::SetThreshold(threshold) => {
+                Self::apply_set_threshold(&e, actor, threshold)?
+            }
+            ProposalAction::SetWithdrawalThreshold(threshold) => {
+                Self::apply_set_withdrawal_threshold(&e, actor, threshold)?
+            }
+        }
+
+        events::emit(
+            &e,
+            &proposal.proposer,
+            ProposalExecuted {
+                proposal_id,
+                approvals: proposal.approvals,
+            },
+        );
+        Ok(())
+    }
+
+    /// Cancel a pending proposal. Only the proposer or the admin can call this.
+    pub fn cancel_proposal(e: Env, caller: Address, proposal_id: u64) -> Result<(), QuipayError> {
+        caller.require_auth();
+
+        let mut proposal =
+            Self::get_proposal(e.clone(), proposal_id).ok_or(QuipayError::ProposalNotFound)?;
+        if proposal.status != ProposalStatus::Pending {
+            return Err(QuipayError::ProposalNotPending);
+        }
+        if caller != proposal.proposer && caller != Self::get_admin(e.clone())? {
+            return Err(QuipayError::InsufficientPermissions);
+        }
+
+        proposal.status = ProposalStatus::Canceled;
+        e.storage()
+            .persistent()
+            .set(&StateKey::Proposal(proposal_id), &proposal);
+        Self::remove_pending_proposal(&e, proposal_id);
+
+        events::emit(&e, &caller, ProposalCanceled { proposal_id });
+        Ok(())
+    }
+
+    pub fn get_proposal(e: Env, proposal_id: u64) -> Option<Proposal> {
+        e.storage()
+            .persistent()
+            .get(&StateKey::Proposal(proposal_id))
+    }
+
+    /// Ids of proposals that have been neither executed nor canceled and
+    /// have not expired. Expired ids are dropped from storage on the next
+    /// `create_proposal`.
+    pub fn get_pending_proposals(e: Env) -> Vec<u64> {
+        let stored: Vec<u64> = e
+            .storage()
+            .persistent()
+            .get(&StateKey::PendingProposals)
+            .unwrap_or_else(|| Vec::new(&e));
+
+        let now = e.ledger().timestamp();
+        let mut pending = Vec::new(&e);
+        for id in stored.iter() {
+            if let Some(proposal) = Self::get_proposal(e.clone(), id)
+                && now < proposal.expires_at
+            {
+                pending.push_back(id);
+            }
+        }
+        pending
+    }
+}
+
+impl PayrollVault {
+    /// Load a proposal that can still be approved or executed.
+    fn load_open_proposal(e: &Env, proposal_id: u64) -> Result<Proposal, QuipayError> {
+        let proposal =
+            Self::get_proposal(e.clone(), proposal_id).ok_or(QuipayError::ProposalNotFound)?;
+        if proposal.status != ProposalStatus::Pending {
+            return Err(QuipayError::ProposalNotPending);
+        }
+        if e.ledger().timestamp() >= proposal.expires_at {
+            return Err(QuipayError::ProposalExpired);
+        }
+        Ok(proposal)
+    }
+
+    /// Approvals only count while the approver is still a signer.
+    fn count_valid_approvals(e: &Env, proposal: &Proposal) -> u32 {
+        let signers = Self::get_signers(e.clone());
+        let mut count = 0;
+        for approver in proposal.approvals.iter() {
+            if signers.contains(approver) {
+                count += 1;
+            }
+        }
+        count
+    }
+
+    fn remove_pending_proposal(e: &Env, proposal_id: u64) {
+        let pending = Self::get_pending_proposals(e.clone());
+        if let Some(index) = pending.first_index_of(proposal_id) {
+            let mut pending = pending;
+            pending.remove(index);
+            e.storage()
+                .persistent()
+                .set(&StateKey::PendingProposals, &pending);
+        }
+    }
+}
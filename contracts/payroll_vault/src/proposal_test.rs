@@ -0,0 +1,343 @@
+use crate::events::ProposalApproved;
+use crate::{
+    DrainTarget, PayrollVault, PayrollVaultClient, ProposalAction, ProposalStatus, StateKey,
+};
+use quipay_common::{QuipayError, events};
+use soroban_sdk::{
+    Address, Env, IntoVal, Symbol, Val, Vec,
+    testutils::{Address as _, Events as _, Ledger, MockAuth, MockAuthInvoke},
+    token,
+};
+
+const DAY: u64 = 24 * 60 * 60;
+
+struct Setup<'a> {
+    client: PayrollVaultClient<'a>,
+    token: Address,
+    admin: Address,
+    signer2: Address,
+    signer3: Address,
+}
+
+/// Vault with three signers (admin, signer2, signer3), a 2-of-3 threshold
+/// and 10_000 of free funds.
+fn setup(env: &Env) -> Setup<'_> {
+    env.mock_all_auths();
+
+    let client = PayrollVaultClient::new(env, &env.register(PayrollVault, ()));
+    let admin = Address::generate(env);
+    client.initialize(&admin);
+    client.set_token_allowlist_enforced(&false);
+
+    let signer2 = Address::generate(env);
+    let signer3 = Address::generate(env);
+    client.add_signer(&signer2);
+    client.add_signer(&signer3);
+    client.set_threshold(&2);
+
+    let token = env
+        .register_stellar_asset_contract_v2(Address::generate(env))
+        .address();
+    let depositor = Address::generate(env);
+    token::StellarAssetClient::new(env, &token).mint(&depositor, &10_000);
+    client.deposit(&depositor, &token, &10_000);
+
+    Setup {
+        client,
+        token,
+        admin,
+        signer2,
+        signer3,
+    }
+}
+
+#[test]
+fn test_withdraw_proposal_executes_after_async_approvals() {
+    let env = Env::default();
+    let s = setup(&env);
+    let recipient = Address::generate(&env);
+
+    let action = ProposalAction::Withdraw(recipient.clone(), s.token.clone(), 4_000);
+    let id = s.client.create_proposal(&s.admin, &action);
+    assert_eq!(s.client.get_pending_proposals().len(), 1);
+
+    // Only the proposer has approved so far.
+    let result = s.client.try_execute_proposal(&id);
+    assert_eq!(result, Err(Ok(QuipayError::InsufficientSignatures)));
+
+    // A second signer approves hours later, in a separate transaction.
+    env.ledger().with_mut(|li| li.timestamp += 6 * 60 * 60);
+    s.client.approve_proposal(&s.signer2, &id);
+    s.client.execute_proposal(&id);
+
+    assert_eq!(
+        token::Client::new(&env, &s.token).balance(&recipient),
+        4_000
+    );
+    assert_eq!(s.client.get_treasury_balance(&s.token), 6_000);
+
+    let proposal = s.client.get_proposal(&id).unwrap();
+    assert_eq!(proposal.status, ProposalStatus::Executed);
+    assert_eq!(proposal.approvals.len(), 2);
+    assert_eq!(s.client.get_pending_proposals().len(), 0);
+
+    let result = s.client.try_execute_proposal(&id);
+    assert_eq!(result, Err(Ok(QuipayError::ProposalNotPending)));
+}
+
+#[test]
+fn test_approval_revocation() {
+    let env = Env::default();
+    let s = setup(&env);
+
+    let id = s
+        .client
+        .create_proposal(&s.admin, &ProposalAction::SetWithdrawalThreshold(500));
+    s.client.approve_proposal(&s.signer2, &id);
+
+    let result = s.client.try_approve_proposal(&s.signer2, &id);
+    assert_eq!(result, Err(Ok(QuipayError::AlreadyApproved)));
+
+    s.client.revoke_approval(&s.signer2, &id);
+    assert_eq!(s.client.get_proposal(&id).unwrap().approvals.len(), 1);
+    let result = s.client.try_execute_proposal(&id);
+    assert_eq!(result, Err(Ok(QuipayError::InsufficientSignatures)));
+
+    let result = s.client.try_revoke_approval(&s.signer3, &id);
+    assert_eq!(result, Err(Ok(QuipayError::NotApproved)));
+
+    s.client.approve_proposal(&s.signer3, &id);
+    s.client.execute_proposal(&id);
+    assert_eq!(s.client.get_withdrawal_threshold(), 500);
+}
+
+#[test]
+fn test_expired_proposal_cannot_be_approved_or_executed() {
+    let env = Env::default();
+    let s = setup(&env);
+
+    let id = s
+        .client
+        .create_proposal(&s.admin, &ProposalAction::SetThreshold(3));
+    env.ledger().with_mut(|li| li.timestamp += 7 * DAY);
+
+    let result = s.client.try_approve_proposal(&s.signer2, &id);
+    assert_eq!(result, Err(Ok(QuipayError::ProposalExpired)));
+    let result = s.client.try_execute_proposal(&id);
+    assert_eq!(result, Err(Ok(QuipayError::ProposalExpired)));
+    assert_eq!(s.client.get_threshold(), 2);
+
+    // Expired ids are hidden at once and pruned by the next proposal.
+    assert_eq!(s.client.get_pending_proposals().len(), 0);
+    let next = s
+        .client
+        .create_proposal(&s.admin, &ProposalAction::SetThreshold(1));
+    let stored: Vec<u64> = env.as_contract(&s.client.address, || {
+        env.storage()
+            .persistent()
+            .get(&StateKey::PendingProposals)
+            .unwrap()
+    });
+    assert_eq!(stored, soroban_sdk::vec![&env, next]);
+}
+
+#[test]
+fn test_signer_set_changes_through_proposals() {
+    let env = Env::default();
+    let s = setup(&env);
+    let signer4 = Address::generate(&env);
+
+    let id = s
+        .client
+        .create_proposal(&s.signer3, &ProposalAction::AddSigner(signer4.clone()));
+    s.client.approve_proposal(&s.signer2, &id);
+    s.client.execute_proposal(&id);
+    assert!(s.client.is_signer(&signer4));
+
+    let id = s
+        .client
+        .create_proposal(&signer4, &ProposalAction::RemoveSigner(s.signer3.clone()));
+    s.client.approve_proposal(&s.admin, &id);
+    s.client.execute_proposal(&id);
+    assert!(!s.client.is_signer(&s.signer3));
+    assert_eq!(s.client.get_signers().len(), 3);
+}
+
+#[test]
+fn test_approvals_from_removed_signers_do_not_count() {
+    let env = Env::default();
+    let s = setup(&env);
+
+    let id = s
+        .client
+        .create_proposal(&s.signer3, &ProposalAction::SetWithdrawalThreshold(1));
+    s.client.remove_signer(&s.signer3);
+
+    let result = s.client.try_execute_proposal(&id);
+    assert_eq!(result, Err(Ok(QuipayError::InsufficientSignatures)));
+
+    s.client.approve_proposal(&s.signer2, &id);
+    let result = s.client.try_execute_proposal(&id);
+    assert_eq!(result, Err(Ok(QuipayError::InsufficientSignatures)));
+
+    s.client.approve_proposal(&s.admin, &id);
+    s.client.execute_proposal(&id);
+    assert_eq!(s.client.get_withdrawal_threshold(), 1);
+}
+
+/// Authorise the next call to `fn_name` with the admin's signature only.
+fn admin_signs_alone(env: &Env, s: &Setup, fn_name: &str, args: Vec<Val>) {
+    env.mock_auths(&[MockAuth {
+        address: &s.admin,
+        invoke: &MockAuthInvoke {
+            contract: &s.client.address,
+            fn_name,
+            args,
+            sub_invokes: &[],
+        },
+    }]);
+}
+
+#[test]
+fn test_admin_alone_cannot_bypass_threshold() {
+    let env = Env::default();
+    let s = setup(&env);
+    let outsider = Address::generate(&env);
+
+    admin_signs_alone(&env, &s, "set_threshold", (1u32,).into_val(&env));
+    assert!(s.client.try_set_threshold(&1).is_err());
+
+    admin_signs_alone(&env, &s, "add_signer", (outsider.clone(),).into_val(&env));
+    assert!(s.client.try_add_signer(&outsider).is_err());
+
+    let args = (outsider.clone(),).into_val(&env);
+    admin_signs_alone(&env, &s, "propose_emergency_drain", args);
+    assert!(s.client.try_propose_emergency_drain(&outsider).is_err());
+
+    assert_eq!(s.client.get_threshold(), 2);
+    assert!(!s.client.is_signer(&outsider));
+    assert!(s.client.get_pending_drain().is_none());
+
+    // With a second signer the direct entry point goes through.
+    env.mock_all_auths();
+    s.client.set_threshold(&1);
+    assert_eq!(s.client.get_threshold(), 1);
+}
+
+#[test]
+fn test_drain_proposal_starts_timelock() {
+    let env = Env::default();
+    let s = setup(&env);
+    let recipient = Address::generate(&env);
+
+    let id = s.client.create_proposal(
+        &s.signer2,
+        &ProposalAction::EmergencyDrain(recipient.clone()),
+    );
+    s.client.approve_proposal(&s.signer3, &id);
+    s.client.execute_proposal(&id);
+
+    let pending = s.client.get_pending_drain().unwrap();
+    assert_eq!(pending.recipient, recipient);
+    assert_eq!(pending.proposed_by, s.signer2);
+    assert_eq!(pending.execute_after, env.ledger().timestamp() + DAY);
+}
+
+#[test]
+fn test_partial_drain_proposal_starts_protective_timelock() {
+    let env = Env::default();
+    let s = setup(&env);
+    let recipient = Address::generate(&env);
+
+    let empty = s.client.create_proposal(
+        &s.signer2,
+        &ProposalAction::PartialDrain(recipient.clone(), Vec::new(&env), true),
+    );
+    s.client.approve_proposal(&s.signer3, &empty);
+    assert_eq!(
+        s.client.try_execute_proposal(&empty),
+        Err(Ok(QuipayError::InvalidToken))
+    );
+
+    let targets = soroban_sdk::vec![
+        &env,
+        DrainTarget {
+            token: s.token.clone(),
+            amount: 0,
+        },
+    ];
+    let id = s.client.create_proposal(
+        &s.signer2,
+        &ProposalAction::PartialDrain(recipient.clone(), targets.clone(), true),
+    );
+    s.client.approve_proposal(&s.signer3, &id);
+    s.client.execute_proposal(&id);
+
+    let pending = s.client.get_pending_drain().unwrap();
+    assert_eq!(pending.recipient, recipient);
+    assert_eq!(pending.targets, targets);
+    assert!(pending.protective);
+    assert_eq!(pending.tally_cursor, Some(1));
+}
+
+#[test]
+fn test_only_signers_propose_and_proposer_can_cancel() {
+    let env = Env::default();
+    let s = setup(&env);
+    let outsider = Address::generate(&env);
+
+    let result = s
+        .client
+        .try_create_proposal(&outsider, &ProposalAction::SetThreshold(1));
+    assert_eq!(result, Err(Ok(QuipayError::SignerNotFound)));
+
+    let id = s
+        .client
+        .create_proposal(&s.signer2, &ProposalAction::SetThreshold(1));
+    let result = s.client.try_cancel_proposal(&s.signer3, &id);
+    assert_eq!(result, Err(Ok(QuipayError::InsufficientPermissions)));
+
+    s.client.cancel_proposal(&s.signer2, &id);
+    assert_eq!(
+        s.client.get_proposal(&id).unwrap().status,
+        ProposalStatus::Canceled
+    );
+    assert_eq!(s.client.get_pending_proposals().len(), 0);
+
+    let result = s.client.try_approve_proposal(&s.admin, &id);
+    assert_eq!(result, Err(Ok(QuipayError::ProposalNotPending)));
+    assert!(s.client.get_proposal(&99).is_none());
+}
+
+#[test]
+fn test_approval_emits_audit_event() {
+    let env = Env::default();
+    let s = setup(&env);
+
+    let id = s
+        .client
+        .create_proposal(&s.admin, &ProposalAction::SetThreshold(3));
+    s.client.approve_proposal(&s.signer2, &id);
+
+    let events = env.events().all();
+    let (contract, topics, data) = events.last().unwrap();
+    assert_eq!(contract, s.client.address);
+    assert_eq!(
+        topics,
+        (
+            Symbol::new(&env, "vault"),
+            Symbol::new(&env, "proposal_approved"),
+            events::EVENT_SCHEMA_VERSION,
+            s.signer2.clone(),
+        )
+            .into_val(&env)
+    );
+    let approved: ProposalApproved = data.into_val(&env);
+    assert_eq!(
+        approved,
+        ProposalApproved {
+            proposal_id: id,
+            approvals: 2,
+        }
+    );
+}
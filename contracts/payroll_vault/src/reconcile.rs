@@ -0,0 +1,114 @@
+//! Reconciliation of recorded treasury balances with the tokens actually held.
+//!
+//! `TreasuryBalance(token)` is what the vault believes it holds; the funds it
+//! really holds are its on-chain token balance plus any principal placed in a
+//! yield strategy, less the protocol fees it holds for the fee collector.
+//! Direct transfers, fee-on-transfer tokens and clawbacks make the two drift
+//! apart. Deposits credit the measured balance change rather than the nominal
+//! amount, `reconcile` writes the record down when tokens went missing, and
+//! the admin can sweep any surplus into the treasury or out to an address.
+
+use super::*;
+use crate::events::{self, Reconciled, SurplusSwept};
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BalanceDrift {
+    /// `TreasuryBalance(token)` as recorded by the vault.
+    pub recorded: i128,
+    /// On-chain balance plus strategy principal, less accrued protocol fees.
+    pub held: i128,
+    /// `held - recorded`; positive for a surplus, negative for a deficit.
+    pub drift: i128,
+}
+
+#[contractimpl]
+impl PayrollVault {
+    /// Compare the recorded treasury balance with the tokens actually held.
+    pub fn get_balance_drift(e: Env, token: Address) -> BalanceDrift {
+        let recorded = Self::get_treasury_balance(e.clone(), token.clone());
+        // Accrued protocol fees are held too but belong to the fee collector.
+        let held = Self::held_balance(&e, &token)
+            .saturating_add(Self::get_strategy_balance(e.clone(), token.clone()))
+            .saturating_sub(Self::get_accrued_fees(e.clone(), token.clone()));
+        BalanceDrift {
+            recorded,
+            held,
+            drift: held.saturating_sub(recorded),
+        }
+    }
+
+    /// Write the recorded treasury balance down to the tokens actually held.
+    ///
+    /// Permissionless: it only brings the record in line with the ledger.
+    /// A surplus is left in place for `sweep_surplus`. Returns the drift
+    /// found before reconciling.
+    pub fn reconcile(e: Env, token: Address) -> BalanceDrift {
+        let drift = Self::get_balance_drift(e.clone(), token.clone());
+        if drift.drift < 0 {
+            e.storage()
+                .persistent()
+                .set(&StateKey::TreasuryBalance(token.clone()), &drift.held);
+        }
+
+        events::emit_from_contract(
+            &e,
+            Reconciled {
+                token,
+                recorded: drift.recorded,
+                held: drift.held,
+            },
+        );
+        drift
+    }
+
+    /// Move the surplus of `token` into the treasury (`to = None`) or
+    /// transfer it to `to`. Crediting the treasury settles pending IOUs
+    /// first, like a deposit. Returns the amount swept.
+    /// Only the admin can call this function.
+    pub fn sweep_surplus(e: Env, token: Address, to: Option<Address>) -> Result<i128, QuipayError> {
+        let admin = Self::get_admin(e.clone())?;
+        admin.require_auth();
+
+        let surplus = Self::get_balance_drift(e.clone(), token.clone()).drift;
+        if surplus <= 0 {
+            return Ok(0);
+        }
+
+        match &to {
+            Some(recipient) => {
+                Self::ensure_liquidity(&e, &token, surplus)?;
+                let token_client = token::Client::new(&e, &token);
+                token_client.transfer(&e.current_contract_address(), recipient, &surplus);
+            }
+            None => {
+                let key = StateKey::TreasuryBalance(token.clone());
+                let balance: i128 = e.storage().persistent().get(&key).unwrap_or(0);
+                let new_total = balance.checked_add(surplus).ok_or(QuipayError::Overflow)?;
+                e.storage().persistent().set(&key, &new_total);
+            }
+        }
+
+        events::emit(
+            &e,
+            &admin,
+            SurplusSwept {
+                token: token.clone(),
+                amount: surplus,
+                to: to.clone(),
+            },
+        );
+
+        if to.is_none() {
+            Self::settle_iou_queue(&e, &token, surplus)?;
+        }
+        Ok(surplus)
+    }
+}
+
+impl PayrollVault {
+    /// The vault's on-chain balance of `token`.
+    pub(crate) fn held_balance(e: &Env, token: &Address) -> i128 {
+        token::Client::new(e, token).balance(&e.current_contract_address())
+    }
+}
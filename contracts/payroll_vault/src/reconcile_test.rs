@@ -0,0 +1,124 @@
+use crate::{BalanceDrift, PayrollVault, PayrollVaultClient, StateKey};
+use soroban_sdk::{
+    Address, Env, contract, contractimpl, contracttype, testutils::Address as _, token,
+};
+
+// ---------------------------------------------------------------------------
+// Mock token that burns 1% of every transfer.
+// ---------------------------------------------------------------------------
+
+#[contracttype]
+enum FeeTokenKey {
+    Balance(Address),
+}
+
+#[contract]
+pub struct FeeToken;
+
+#[contractimpl]
+impl FeeToken {
+    pub fn mint(env: Env, to: Address, amount: i128) {
+        let balance = Self::balance(env.clone(), to.clone());
+        env.storage()
+            .instance()
+            .set(&FeeTokenKey::Balance(to), &(balance + amount));
+    }
+
+    pub fn balance(env: Env, id: Address) -> i128 {
+        env.storage()
+            .instance()
+            .get(&FeeTokenKey::Balance(id))
+            .unwrap_or(0)
+    }
+
+    pub fn transfer(env: Env, from: Address, to: Address, amount: i128) {
+        from.require_auth();
+        let from_balance = Self::balance(env.clone(), from.clone());
+        env.storage()
+            .instance()
+            .set(&FeeTokenKey::Balance(from), &(from_balance - amount));
+        Self::mint(env, to, amount - amount / 100);
+    }
+}
+
+fn setup(env: &Env) -> (PayrollVaultClient<'_>, Address, Address) {
+    env.mock_all_auths();
+    let client = PayrollVaultClient::new(env, &env.register(PayrollVault, ()));
+    client.initialize(&Address::generate(env));
+    client.set_token_allowlist_enforced(&false);
+
+    let token = env
+        .register_stellar_asset_contract_v2(Address::generate(env))
+        .address();
+    let employer = Address::generate(env);
+    token::StellarAssetClient::new(env, &token).mint(&employer, &10_000);
+    client.deposit(&employer, &token, &1_000);
+    (client, token, employer)
+}
+
+#[test]
+fn test_deposit_credits_received_amount() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = PayrollVaultClient::new(&env, &env.register(PayrollVault, ()));
+    client.initialize(&Address::generate(&env));
+    client.set_token_allowlist_enforced(&false);
+
+    let fee_token = FeeTokenClient::new(&env, &env.register(FeeToken, ()));
+    let employer = Address::generate(&env);
+    fee_token.mint(&employer, &10_000);
+
+    client.deposit(&employer, &fee_token.address, &1_000);
+    assert_eq!(client.get_treasury_balance(&fee_token.address), 990);
+    assert_eq!(client.get_balance_drift(&fee_token.address).drift, 0);
+}
+
+#[test]
+fn test_sweep_surplus_from_direct_transfer() {
+    let env = Env::default();
+    let (client, token, employer) = setup(&env);
+
+    // Tokens sent straight to the vault are not credited by `deposit`.
+    token::Client::new(&env, &token).transfer(&employer, &client.address, &300);
+    assert_eq!(
+        client.get_balance_drift(&token),
+        BalanceDrift {
+            recorded: 1_000,
+            held: 1_300,
+            drift: 300,
+        }
+    );
+
+    // `reconcile` leaves a surplus for the admin to sweep.
+    client.reconcile(&token);
+    assert_eq!(client.get_treasury_balance(&token), 1_000);
+
+    let recipient = Address::generate(&env);
+    token::Client::new(&env, &token).transfer(&employer, &client.address, &200);
+    assert_eq!(client.sweep_surplus(&token, &Some(recipient.clone())), 500);
+    assert_eq!(token::Client::new(&env, &token).balance(&recipient), 500);
+
+    token::Client::new(&env, &token).transfer(&employer, &client.address, &100);
+    assert_eq!(client.sweep_surplus(&token, &None), 100);
+    assert_eq!(client.get_treasury_balance(&token), 1_100);
+    assert_eq!(client.sweep_surplus(&token, &None), 0);
+}
+
+#[test]
+fn test_reconcile_writes_down_deficit() {
+    let env = Env::default();
+    let (client, token, _employer) = setup(&env);
+
+    // Simulate a clawback: the record claims more than the vault holds.
+    env.as_contract(&client.address, || {
+        env.storage()
+            .persistent()
+            .set(&StateKey::TreasuryBalance(token.clone()), &1_250i128);
+    });
+    assert_eq!(client.get_balance_drift(&token).drift, -250);
+
+    let drift = client.reconcile(&token);
+    assert_eq!(drift.recorded, 1_250);
+    assert_eq!(client.get_treasury_balance(&token), 1_000);
+    assert_eq!(client.get_balance_drift(&token).drift, 0);
+}
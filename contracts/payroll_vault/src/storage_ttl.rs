@@ -0,0 +1,115 @@
+//! Keeper entry points for the vault's storage TTL.
+//!
+//! Deposits extend the books of the deposited token. Everything else the
+//! vault keeps is extended by keepers through `bump_ttl`. Supported scopes:
+//!
+//! - `Contract`: instance storage, admin and multisig configuration, the
+//!   token list, fee settings, pending multisig proposals and upgrade state.
+//! - `Token(token)`: the token's treasury balance, liability, IOU queue and
+//!   queued IOUs, strategy, allowlist settings and accrued fees.
+//! - `Account(address)`: the employer's top-up mandates and their history,
+//!   its fee tier, and any roles the address holds.
+//! - `Stream(id)`: the stream's outstanding IOU and payout fee rate.
+//! - `Proposal(id)`: one multisig proposal.
+
+use super::*;
+use quipay_common::TtlScope;
+
+#[contractimpl]
+impl PayrollVault {
+    /// Extend every entry of `scope` to the full policy TTL. Permissionless.
+    /// Returns the ledger the entries stay live until.
+    pub fn bump_ttl(e: Env, scope: TtlScope) -> Result<u32, QuipayError> {
+        match &scope {
+            TtlScope::Contract => Self::extend_contract_ttl(&e),
+            TtlScope::Token(token) => Self::extend_token_ttl(&e, token),
+            TtlScope::Account(account) => Self::extend_account_ttl(&e, account),
+            TtlScope::Stream(stream_id) => {
+                ttl::extend_fully(&e, &StateKey::StreamIou(*stream_id));
+                ttl::extend_fully(&e, &StateKey::StreamFeeBps(*stream_id));
+            }
+            TtlScope::Proposal(proposal_id) => {
+                if !ttl::extend_fully(&e, &StateKey::Proposal(*proposal_id)) {
+                    return Err(QuipayError::ProposalNotFound);
+                }
+            }
+            TtlScope::Receipt(_) => return Err(QuipayError::UnsupportedTtlScope),
+        }
+        Ok(ttl::record_bump(&e, &scope))
+    }
+
+    /// Ledger `scope`'s entries were last extended to by `bump_ttl`, or
+    /// `None` if no keeper has bumped them.
+    pub fn get_ttl_expiry(e: Env, scope: TtlScope) -> Option<u32> {
+        ttl::expiry(&e, &scope)
+    }
+}
+
+impl PayrollVault {
+    fn extend_contract_ttl(e: &Env) {
+        ttl::extend_instance(e);
+        upgrade::extend_ttl(e);
+        for key in [
+            StateKey::Admin,
+            StateKey::PendingAdmin,
+            StateKey::Version,
+            StateKey::AuthorizedContract,
+            StateKey::TokenList,
+            StateKey::PendingDrain,
+            StateKey::Signers,
+            StateKey::Threshold,
+            StateKey::WithdrawalThreshold,
+            StateKey::NextIouId,
+            StateKey::StrategyBufferBps,
+            StateKey::NextMandateId,
+            StateKey::NextProposalId,
+            StateKey::PendingProposals,
+            StateKey::TokenAllowlistEnforced,
+            StateKey::ProtocolFee,
+            StateKey::FeeCollector,
+        ] {
+            ttl::extend_fully(e, &key);
+        }
+        for proposal_id in Self::get_pending_proposals(e.clone()).iter() {
+            ttl::extend_fully(e, &StateKey::Proposal(proposal_id));
+        }
+    }
+
+    fn extend_token_ttl(e: &Env, token: &Address) {
+        for key in [
+            StateKey::TreasuryBalance(token.clone()),
+            StateKey::TotalLiability(token.clone()),
+            StateKey::IouQueue(token.clone()),
+            StateKey::TotalIou(token.clone()),
+            StateKey::Strategy(token.clone()),
+            StateKey::StrategyBalance(token.clone()),
+            StateKey::TokenConfig(token.clone()),
+            StateKey::AccruedFees(token.clone()),
+        ] {
+            ttl::extend_fully(e, &key);
+        }
+        let queue: Vec<u64> = e
+            .storage()
+            .persistent()
+            .get(&StateKey::IouQueue(token.clone()))
+            .unwrap_or_else(|| Vec::new(e));
+        for iou_id in queue.iter() {
+            ttl::extend_fully(e, &StateKey::Iou(iou_id));
+            ttl::extend_fully(e, &StateKey::RefundIou(iou_id));
+        }
+    }
+
+    fn extend_account_ttl(e: &Env, account: &Address) {
+        ttl::extend_fully(e, &StateKey::EmployerMandates(account.clone()));
+        for mandate_id in Self::get_employer_mandates(e.clone(), account.clone()).iter() {
+            ttl::extend_fully(e, &StateKey::Mandate(mandate_id));
+            ttl::extend_fully(e, &StateKey::MandateHistory(mandate_id));
+        }
+        let tier_key = StateKey::EmployerFeeTier(account.clone());
+        if let Some(tier) = e.storage().persistent().get::<StateKey, u32>(&tier_key) {
+            ttl::extend_fully(e, &tier_key);
+            ttl::extend_fully(e, &StateKey::FeeTierDiscount(tier));
+        }
+        rbac::extend_roles_ttl(e, account);
+    }
+}
@@ -0,0 +1,67 @@
+use crate::{PayrollVault, PayrollVaultClient, StateKey};
+use quipay_common::{QuipayError, TtlScope, ttl::TTL_EXTEND_TO};
+use soroban_sdk::{
+    Address, Env,
+    testutils::{Address as _, Ledger as _, storage::Persistent as _},
+    token,
+};
+
+fn persistent_ttl(env: &Env, client: &PayrollVaultClient, key: &StateKey) -> u32 {
+    env.as_contract(&client.address, || env.storage().persistent().get_ttl(key))
+}
+
+#[test]
+fn test_bump_ttl_keeps_token_books_and_config_alive() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = PayrollVaultClient::new(&env, &env.register(PayrollVault, ()));
+    client.initialize(&Address::generate(&env));
+    client.set_token_allowlist_enforced(&false);
+    client.set_authorized_contract(&Address::generate(&env));
+
+    let token = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+    let employer = Address::generate(&env);
+    token::StellarAssetClient::new(&env, &token).mint(&employer, &10_000);
+    client.deposit(&employer, &token, &1_000);
+    client.add_liability(&token, &400);
+
+    env.ledger().set_sequence_number(10);
+    assert_eq!(client.get_ttl_expiry(&TtlScope::Token(token.clone())), None);
+    assert_eq!(
+        client.bump_ttl(&TtlScope::Token(token.clone())),
+        10 + TTL_EXTEND_TO
+    );
+    client.bump_ttl(&TtlScope::Contract);
+    client.bump_ttl(&TtlScope::Account(employer.clone()));
+    assert_eq!(
+        client.get_ttl_expiry(&TtlScope::Token(token.clone())),
+        Some(10 + TTL_EXTEND_TO)
+    );
+    for key in [
+        StateKey::TreasuryBalance(token.clone()),
+        StateKey::TotalLiability(token.clone()),
+        StateKey::Admin,
+        StateKey::TokenList,
+    ] {
+        assert_eq!(persistent_ttl(&env, &client, &key), TTL_EXTEND_TO);
+    }
+
+    assert_eq!(
+        client.try_bump_ttl(&TtlScope::Proposal(99)),
+        Err(Ok(QuipayError::ProposalNotFound))
+    );
+    assert_eq!(
+        client.try_bump_ttl(&TtlScope::Receipt(1)),
+        Err(Ok(QuipayError::UnsupportedTtlScope))
+    );
+
+    // Archived balances are restored when read and stay consistent.
+    env.ledger()
+        .with_mut(|li| li.sequence_number += TTL_EXTEND_TO + 1);
+    assert_eq!(client.get_treasury_balance(&token), 1_000);
+    assert_eq!(client.get_total_liability(&token), 400);
+    client.payout_liability(&employer, &token, &400);
+    assert_eq!(client.get_treasury_balance(&token), 600);
+}
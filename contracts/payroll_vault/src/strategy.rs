@@ -0,0 +1,232 @@
+//! Yield strategy adapter for idle treasury funds.
+//!
+//! The admin can attach one external strategy contract per token. Only the
+//! excess above `TotalLiability` plus a liquidity buffer may be moved into it;
+//! the vault recalls funds automatically whenever a payout or withdrawal needs
+//! more liquidity than it holds on hand.
+//!
+//! Funds placed in a strategy stay part of `TreasuryBalance`, so solvency and
+//! IOU accounting are unaffected. `StrategyBalance(token)` tracks the principal
+//! currently held by the strategy and is reported separately in
+//! `get_treasury_summary`.
+//!
+//! A strategy contract must expose:
+//! - `deposit(token: Address, amount: i128)` — called after the vault has
+//!   transferred `amount` of `token` to the strategy.
+//! - `withdraw(token: Address, amount: i128, to: Address)` — must transfer
+//!   `amount` of `token` to `to`; should require auth from the vault.
+
+use super::*;
+use crate::events::{self, StrategyInvested, StrategyRecalled, StrategySet};
+
+/// Default liquidity buffer kept in the vault on top of liabilities (10%).
+pub(crate) const DEFAULT_STRATEGY_BUFFER_BPS: u32 = 1_000;
+
+#[contractimpl]
+impl PayrollVault {
+    /// Attach (or replace) the yield strategy used for `token`.
+    ///
+    /// Any principal held by the previous strategy is recalled first.
+    /// Only the admin can call this function.
+    pub fn set_strategy(e: Env, token: Address, strategy: Address) -> Result<(), QuipayError> {
+        let admin = Self::get_admin(e.clone())?;
+        admin.require_auth();
+
+        Self::recall_all_from_strategy(&e, &token)?;
+        e.storage()
+            .persistent()
+            .set(&StateKey::Strategy(token.clone()), &strategy);
+
+        events::emit(&e, &admin, StrategySet { token, strategy });
+        Ok(())
+    }
+
+    /// Detach the yield strategy for `token`, recalling all of its principal.
+    /// Only the admin can call this function.
+    pub fn remove_strategy(e: Env, token: Address) -> Result<(), QuipayError> {
+        let admin = Self::get_admin(e.clone())?;
+        admin.require_auth();
+
+        Self::recall_all_from_strategy(&e, &token)?;
+        e.storage().persistent().remove(&StateKey::Strategy(token));
+        Ok(())
+    }
+
+    /// Set the liquidity buffer, in basis points of `TotalLiability`, that must
+    /// stay in the vault in addition to the liability itself.
+    /// Only the admin can call this function.
+    pub fn set_strategy_buffer_bps(e: Env, buffer_bps: u32) -> Result<(), QuipayError> {
+        let admin = Self::get_admin(e.clone())?;
+        admin.require_auth();
+
+        if buffer_bps > 10_000 {
+            return Err(QuipayError::InvalidAmount);
+        }
+        e.storage()
+            .persistent()
+            .set(&StateKey::StrategyBufferBps, &buffer_bps);
+        Ok(())
+    }
+
+    pub fn get_strategy(e: Env, token: Address) -> Option<Address> {
+        e.storage().persistent().get(&StateKey::Strategy(token))
+    }
+
+    pub fn get_strategy_buffer_bps(e: Env) -> u32 {
+        e.storage()
+            .persistent()
+            .get(&StateKey::StrategyBufferBps)
+            .unwrap_or(DEFAULT_STRATEGY_BUFFER_BPS)
+    }
+
+    /// Principal of `token` currently held by its strategy.
+    pub fn get_strategy_balance(e: Env, token: Address) -> i128 {
+        e.storage()
+            .persistent()
+            .get(&StateKey::StrategyBalance(token))
+            .unwrap_or(0)
+    }
+
+    /// Funds of `token` held by the vault itself (treasury minus strategy principal).
+    pub fn get_liquid_balance(e: Env, token: Address) -> i128 {
+        Self::get_treasury_balance(e.clone(), token.clone()) - Self::get_strategy_balance(e, token)
+    }
+
+    /// Amount of `token` that may currently be moved into the strategy:
+    /// liquid funds above `TotalLiability` plus the buffer.
+    pub fn get_investable_amount(e: Env, token: Address) -> i128 {
+        let liquid = Self::get_liquid_balance(e.clone(), token.clone());
+        let liability = Self::get_total_liability(e.clone(), token);
+        let buffer = liability.saturating_mul(Self::get_strategy_buffer_bps(e) as i128) / 10_000;
+        let reserved = liability.saturating_add(buffer);
+        if liquid > reserved {
+            liquid - reserved
+        } else {
+            0
+        }
+    }
+
+    /// Move `amount` of idle `token` into its strategy.
+    ///
+    /// Fails with `InsufficientBalance` if `amount` exceeds
+    /// `get_investable_amount`. Only the admin can call this function.
+    pub fn invest_idle_funds(e: Env, token: Address, amount: i128) -> Result<(), QuipayError> {
+        let admin = Self::get_admin(e.clone())?;
+        admin.require_auth();
+
+        require_positive_amount!(amount);
+
+        let strategy = Self::get_strategy(e.clone(), token.clone())
+            .ok_or(QuipayError::StrategyNotConfigured)?;
+
+        if amount > Self::get_investable_amount(e.clone(), token.clone()) {
+            return Err(QuipayError::InsufficientBalance);
+        }
+
+        let strategy_key = StateKey::StrategyBalance(token.clone());
+        let principal: i128 = e.storage().persistent().get(&strategy_key).unwrap_or(0);
+        let new_principal = principal.checked_add(amount).ok_or(QuipayError::Overflow)?;
+        e.storage().persistent().set(&strategy_key, &new_principal);
+
+        let token_client = token::Client::new(&e, &token);
+        token_client.transfer(&e.current_contract_address(), &strategy, &amount);
+        e.invoke_contract::<()>(
+            &strategy,
+            &Symbol::new(&e, "deposit"),
+            soroban_sdk::vec![&e, token.into_val(&e), amount.into_val(&e)],
+        );
+
+        events::emit(
+            &e,
+            &admin,
+            StrategyInvested {
+                token,
+                amount,
+                principal: new_principal,
+            },
+        );
+        Ok(())
+    }
+
+    /// Recall `amount` of `token` principal from its strategy back into the vault.
+    /// Only the admin can call this function.
+    pub fn recall_strategy_funds(e: Env, token: Address, amount: i128) -> Result<(), QuipayError> {
+        let admin = Self::get_admin(e.clone())?;
+        admin.require_auth();
+
+        require_positive_amount!(amount);
+        if Self::get_strategy(e.clone(), token.clone()).is_none() {
+            return Err(QuipayError::StrategyNotConfigured);
+        }
+        if amount > Self::get_strategy_balance(e.clone(), token.clone()) {
+            return Err(QuipayError::InsufficientBalance);
+        }
+
+        Self::recall_from_strategy(&e, &token, amount)
+    }
+}
+
+impl PayrollVault {
+    /// Make sure the vault holds at least `amount` of `token` on-chain before a
+    /// transfer out, recalling the shortfall from the strategy if necessary.
+    pub(crate) fn ensure_liquidity(
+        e: &Env,
+        token: &Address,
+        amount: i128,
+    ) -> Result<(), QuipayError> {
+        let on_hand = token::Client::new(e, token).balance(&e.current_contract_address());
+        if amount <= on_hand {
+            return Ok(());
+        }
+
+        let principal = Self::get_strategy_balance(e.clone(), token.clone());
+        let needed = (amount - on_hand).min(principal);
+        if needed > 0 {
+            Self::recall_from_strategy(e, token, needed)?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn recall_all_from_strategy(e: &Env, token: &Address) -> Result<(), QuipayError> {
+        let principal = Self::get_strategy_balance(e.clone(), token.clone());
+        if principal > 0 {
+            Self::recall_from_strategy(e, token, principal)?;
+        }
+        Ok(())
+    }
+
+    fn recall_from_strategy(e: &Env, token: &Address, amount: i128) -> Result<(), QuipayError> {
+        let strategy = Self::get_strategy(e.clone(), token.clone())
+            .ok_or(QuipayError::StrategyNotConfigured)?;
+
+        let strategy_key = StateKey::StrategyBalance(token.clone());
+        let principal: i128 = e.storage().persistent().get(&strategy_key).unwrap_or(0);
+        let remaining = principal - amount;
+        if remaining > 0 {
+            e.storage().persistent().set(&strategy_key, &remaining);
+        } else {
+            e.storage().persistent().remove(&strategy_key);
+        }
+
+        e.invoke_contract::<()>(
+            &strategy,
+            &Symbol::new(e, "withdraw"),
+            soroban_sdk::vec![
+                e,
+                token.into_val(e),
+                amount.into_val(e),
+                e.current_contract_address().into_val(e),
+            ],
+        );
+
+        events::emit_from_contract(
+            e,
+            StrategyRecalled {
+                token: token.clone(),
+                amount,
+                principal: remaining.max(0),
+            },
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,250 @@
+use crate::{PayrollVault, PayrollVaultClient};
+use quipay_common::QuipayError;
+use soroban_sdk::{
+    Address, Env, contract, contractimpl, contracttype,
+    testutils::{Address as _, Ledger},
+    token,
+};
+
+// ---------------------------------------------------------------------------
+// Mock yield strategy: holds whatever it is sent and returns it to the owner.
+// ---------------------------------------------------------------------------
+
+#[contracttype]
+enum MockKey {
+    Owner,
+    Deposited(Address),
+}
+
+#[contract]
+pub struct MockStrategy;
+
+#[contractimpl]
+impl MockStrategy {
+    pub fn init(env: Env, owner: Address) {
+        env.storage().instance().set(&MockKey::Owner, &owner);
+    }
+
+    pub fn deposit(env: Env, token: Address, amount: i128) {
+        let key = MockKey::Deposited(token);
+        let current: i128 = env.storage().instance().get(&key).unwrap_or(0);
+        env.storage().instance().set(&key, &(current + amount));
+    }
+
+    pub fn withdraw(env: Env, token: Address, amount: i128, to: Address) {
+        let owner: Address = env.storage().instance().get(&MockKey::Owner).unwrap();
+        owner.require_auth();
+
+        let key = MockKey::Deposited(token.clone());
+        let current: i128 = env.storage().instance().get(&key).unwrap_or(0);
+        env.storage().instance().set(&key, &(current - amount));
+
+        token::Client::new(&env, &token).transfer(&env.current_contract_address(), &to, &amount);
+    }
+
+    pub fn deposited(env: Env, token: Address) -> i128 {
+        env.storage()
+            .instance()
+            .get(&MockKey::Deposited(token))
+            .unwrap_or(0)
+    }
+}
+
+fn setup(
+    env: &Env,
+) -> (
+    PayrollVaultClient<'_>,
+    Address, // token
+    Address, // strategy
+    Address, // depositor
+) {
+    let vault_id = env.register(PayrollVault, ());
+    let client = PayrollVaultClient::new(env, &vault_id);
+    let admin = Address::generate(env);
+    client.initialize(&admin);
+    client.set_token_allowlist_enforced(&false);
+    client.set_authorized_contract(&Address::generate(env));
+
+    let token_admin = Address::generate(env);
+    let token_id = env
+        .register_stellar_asset_contract_v2(token_admin)
+        .address();
+    let depositor = Address::generate(env);
+    token::StellarAssetClient::new(env, &token_id).mint(&depositor, &100_000);
+
+    let strategy = env.register(MockStrategy, ());
+    MockStrategyClient::new(env, &strategy).init(&vault_id);
+
+    // 10_000 in treasury, 4_000 owed; default 10% buffer reserves 4_400.
+    client.deposit(&depositor, &token_id, &10_000);
+    client.add_liability(&token_id, &4_000);
+    client.set_strategy(&token_id, &strategy);
+
+    (client, token_id, strategy, depositor)
+}
+
+#[test]
+fn test_invest_only_excess_above_liability_and_buffer() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, token_id, strategy, _) = setup(&env);
+    let token_read = token::Client::new(&env, &token_id);
+
+    assert_eq!(client.get_strategy_buffer_bps(), 1_000);
+    assert_eq!(client.get_investable_amount(&token_id), 5_600);
+
+    let result = client.try_invest_idle_funds(&token_id, &5_601);
+    assert_eq!(result, Err(Ok(QuipayError::InsufficientBalance)));
+
+    client.invest_idle_funds(&token_id, &5_600);
+    assert_eq!(client.get_strategy_balance(&token_id), 5_600);
+    assert_eq!(client.get_liquid_balance(&token_id), 4_400);
+    assert_eq!(client.get_investable_amount(&token_id), 0);
+    assert_eq!(token_read.balance(&strategy), 5_600);
+    assert_eq!(token_read.balance(&client.address), 4_400);
+    assert_eq!(
+        MockStrategyClient::new(&env, &strategy).deposited(&token_id),
+        5_600
+    );
+
+    // Strategy funds remain part of the treasury for solvency purposes.
+    assert_eq!(client.get_treasury_balance(&token_id), 10_000);
+    assert!(client.check_solvency(&token_id, &6_000));
+}
+
+#[test]
+fn test_treasury_summary_reports_strategy_funds_separately() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, token_id, _, _) = setup(&env);
+    client.invest_idle_funds(&token_id, &3_000);
+
+    let summary = client.get_treasury_summary();
+    assert_eq!(summary.len(), 1);
+    let entry = summary.get(0).unwrap();
+    assert_eq!(entry.token, token_id);
+    assert_eq!(entry.balance, 10_000);
+    assert_eq!(entry.liability, 4_000);
+    assert_eq!(entry.strategy_balance, 3_000);
+}
+
+#[test]
+fn test_buffer_bps_changes_investable_amount() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, token_id, _, _) = setup(&env);
+
+    client.set_strategy_buffer_bps(&5_000);
+    assert_eq!(client.get_investable_amount(&token_id), 4_000);
+
+    client.set_strategy_buffer_bps(&0);
+    assert_eq!(client.get_investable_amount(&token_id), 6_000);
+
+    let result = client.try_set_strategy_buffer_bps(&10_001);
+    assert_eq!(result, Err(Ok(QuipayError::InvalidAmount)));
+}
+
+#[test]
+fn test_invest_without_strategy_fails() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, token_id, _, _) = setup(&env);
+    client.remove_strategy(&token_id);
+
+    let result = client.try_invest_idle_funds(&token_id, &1_000);
+    assert_eq!(result, Err(Ok(QuipayError::StrategyNotConfigured)));
+    let result = client.try_recall_strategy_funds(&token_id, &1_000);
+    assert_eq!(result, Err(Ok(QuipayError::StrategyNotConfigured)));
+}
+
+#[test]
+fn test_withdraw_recalls_shortfall_from_strategy() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, token_id, strategy, _) = setup(&env);
+    let token_read = token::Client::new(&env, &token_id);
+    client.invest_idle_funds(&token_id, &5_600);
+
+    // 4_400 on hand; withdrawing 5_000 of free funds needs 600 from the strategy.
+    let treasurer = Address::generate(&env);
+    client.withdraw(&treasurer, &token_id, &5_000);
+
+    assert_eq!(token_read.balance(&treasurer), 5_000);
+    assert_eq!(client.get_strategy_balance(&token_id), 5_000);
+    assert_eq!(token_read.balance(&strategy), 5_000);
+    assert_eq!(token_read.balance(&client.address), 0);
+    assert_eq!(client.get_treasury_balance(&token_id), 5_000);
+}
+
+#[test]
+fn test_stream_payout_recalls_from_strategy_when_needed() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, token_id, _, _) = setup(&env);
+    let token_read = token::Client::new(&env, &token_id);
+    client.set_strategy_buffer_bps(&0);
+    client.invest_idle_funds(&token_id, &6_000);
+
+    // Withdraw free funds so the vault holds less than the upcoming payout.
+    let worker = Address::generate(&env);
+    client.withdraw(&worker, &token_id, &1_000);
+    assert_eq!(token_read.balance(&client.address), 3_000);
+
+    let paid = client.payout_stream_liability(&1, &worker, &token_id, &4_000);
+    assert_eq!(paid, 4_000);
+    assert_eq!(token_read.balance(&worker), 5_000);
+    assert_eq!(client.get_strategy_balance(&token_id), 5_000);
+    assert_eq!(client.get_total_liability(&token_id), 0);
+}
+
+#[test]
+fn test_recall_and_replace_strategy() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, token_id, strategy, _) = setup(&env);
+    let token_read = token::Client::new(&env, &token_id);
+    client.invest_idle_funds(&token_id, &5_000);
+
+    let result = client.try_recall_strategy_funds(&token_id, &5_001);
+    assert_eq!(result, Err(Ok(QuipayError::InsufficientBalance)));
+
+    client.recall_strategy_funds(&token_id, &2_000);
+    assert_eq!(client.get_strategy_balance(&token_id), 3_000);
+    assert_eq!(token_read.balance(&client.address), 7_000);
+
+    // Swapping strategies pulls the old principal home first.
+    let new_strategy = env.register(MockStrategy, ());
+    MockStrategyClient::new(&env, &new_strategy).init(&client.address);
+    client.set_strategy(&token_id, &new_strategy);
+
+    assert_eq!(client.get_strategy(&token_id), Some(new_strategy));
+    assert_eq!(client.get_strategy_balance(&token_id), 0);
+    assert_eq!(token_read.balance(&strategy), 0);
+    assert_eq!(token_read.balance(&client.address), 10_000);
+}
+
+#[test]
+fn test_emergency_drain_recalls_strategy_funds() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, token_id, strategy, _) = setup(&env);
+    let token_read = token::Client::new(&env, &token_id);
+    client.invest_idle_funds(&token_id, &5_000);
+
+    let recipient = Address::generate(&env);
+    client.propose_emergency_drain(&recipient);
+    env.ledger().with_mut(|li| li.timestamp += 24 * 60 * 60 + 1);
+    client.execute_emergency_drain();
+
+    assert_eq!(token_read.balance(&recipient), 10_000);
+    assert_eq!(token_read.balance(&strategy), 0);
+    assert_eq!(client.get_strategy_balance(&token_id), 0);
+}
@@ -83,6 +83,7 @@ fn test_xlm_deposit_withdraw_and_payout() {
     let recipient = make_account_address(&env, 3);
 
     client.initialize(&admin);
+    client.set_token_allowlist_enforced(&false);
 
     let xlm_token_id = register_native_token_contract(&env, admin);
     let xlm_token_client = token::Client::new(&env, &xlm_token_id);
@@ -116,6 +117,7 @@ fn test_solvency_enforcement() {
     let admin = Address::generate(&env);
 
     client.initialize(&admin);
+    client.set_token_allowlist_enforced(&false);
 
     let token_admin = Address::generate(&env);
     let token_contract = env.register_stellar_asset_contract_v2(token_admin.clone());
@@ -150,6 +152,7 @@ fn test_release_funds() {
     let admin = Address::generate(&env);
 
     client.initialize(&admin);
+    client.set_token_allowlist_enforced(&false);
 
     let token_admin = Address::generate(&env);
     let token_contract = env.register_stellar_asset_contract_v2(token_admin.clone());
@@ -184,6 +187,7 @@ fn test_multi_token_tracking() {
     let admin = Address::generate(&env);
 
     client.initialize(&admin);
+    client.set_token_allowlist_enforced(&false);
 
     // Setup Token A
     let token_a_admin = Address::generate(&env);
@@ -234,6 +238,7 @@ fn test_supported_tokens_and_treasury_summary() {
     let admin = Address::generate(&env);
 
     client.initialize(&admin);
+    client.set_token_allowlist_enforced(&false);
 
     let token_a_admin = Address::generate(&env);
     let token_a = env.register_stellar_asset_contract_v2(token_a_admin.clone());
@@ -284,6 +289,7 @@ fn test_payout_without_allocation() {
     let client = PayrollVaultClient::new(&env, &contract_id);
     let admin = Address::generate(&env);
     client.initialize(&admin);
+    client.set_token_allowlist_enforced(&false);
 
     let token_admin = Address::generate(&env);
     let token_contract = env.register_stellar_asset_contract_v2(token_admin.clone());
@@ -310,6 +316,7 @@ fn test_complex_scenario_multiple_streams() {
     let client = PayrollVaultClient::new(&env, &contract_id);
     let admin = Address::generate(&env);
     client.initialize(&admin);
+    client.set_token_allowlist_enforced(&false);
 
     let token_admin = Address::generate(&env);
     let token_contract = env.register_stellar_asset_contract_v2(token_admin.clone());
@@ -413,6 +420,7 @@ fn test_liability_tracking() {
 
     // Initialize
     client.initialize(&admin);
+    client.set_token_allowlist_enforced(&false);
 
     // Set authorized contract
     client.set_authorized_contract(&authorized_contract);
@@ -462,6 +470,7 @@ fn test_available_balance_and_withdraw_enforcement() {
     let employer = Address::generate(&env);
 
     client.initialize(&admin);
+    client.set_token_allowlist_enforced(&false);
 
     let token_admin = Address::generate(&env);
     let token_contract = env.register_stellar_asset_contract_v2(token_admin.clone());
@@ -494,6 +503,7 @@ fn test_check_solvency_prevents_unfunded_liability() {
     let admin = Address::generate(&env);
 
     client.initialize(&admin);
+    client.set_token_allowlist_enforced(&false);
 
     let token_admin = Address::generate(&env);
     let token_contract = env.register_stellar_asset_contract_v2(token_admin.clone());
@@ -550,6 +560,7 @@ fn test_remove_more_liability_than_exists_returns_error() {
 
     // Initialize and set authorized contract
     client.initialize(&admin);
+    client.set_token_allowlist_enforced(&false);
     client.set_authorized_contract(&authorized_contract);
 
     // Fund vault so solvency checks pass
@@ -605,6 +616,7 @@ fn test_remove_zero_liability_returns_error() {
 
     // Initialize and set authorized contract
     client.initialize(&admin);
+    client.set_token_allowlist_enforced(&false);
     client.set_authorized_contract(&authorized_contract);
 
     // Fund vault so solvency checks pass
@@ -673,6 +685,7 @@ fn test_require_auth_enforces_admin_authorization() {
 
     // With mock_all_auths, operations succeed (simulates multisig threshold met)
     env.mock_all_auths();
+    client.set_token_allowlist_enforced(&false);
     token_admin_client.mint(&depositor, &1000);
     client.deposit(&depositor, &token, &1000);
     client.allocate_funds(&token, &100);
@@ -694,7 +707,7 @@ fn test_require_auth_for_upgrade_with_multisig() {
     client.initialize(&admin);
 
     let new_wasm_hash = BytesN::from_array(&env, &[0u8; 32]);
-    let result = client.try_upgrade(&new_wasm_hash, &(1, 1, 0));
+    let result = client.try_upgrade(&admin, &new_wasm_hash, &(1, 1, 0));
     assert!(result.is_err());
 
     // Authorized call (mocked) should also return Err here because we don't have a real uploaded wasm.
@@ -706,7 +719,7 @@ fn test_require_auth_for_upgrade_with_multisig() {
     let admin2 = Address::generate(&env2);
     client2.initialize(&admin2);
     let new_wasm_hash2 = BytesN::from_array(&env2, &[0u8; 32]);
-    let result2 = client2.try_upgrade(&new_wasm_hash2, &(1, 1, 0));
+    let result2 = client2.try_upgrade(&admin2, &new_wasm_hash2, &(1, 1, 0));
     assert!(result2.is_err());
 }
 
@@ -751,6 +764,7 @@ fn test_require_auth_for_payout_with_multisig() {
     let _unauthorized = Address::generate(&env);
 
     client.initialize(&admin);
+    client.set_token_allowlist_enforced(&false);
 
     // Setup token and deposit
     let token_admin = Address::generate(&env);
@@ -826,6 +840,7 @@ fn test_multisig_admin_can_perform_all_operations() {
     let recipient = Address::generate(&env);
 
     client.initialize(&multisig_admin);
+    client.set_token_allowlist_enforced(&false);
 
     // Setup token
     let token_admin = Address::generate(&env);
@@ -999,6 +1014,7 @@ fn test_high_value_withdraw_requires_multisig_signers() {
 
     let admin = Address::generate(&env);
     client.initialize(&admin);
+    client.set_token_allowlist_enforced(&false);
 
     let signer2 = Address::generate(&env);
     client.add_signer(&signer2);
@@ -1087,6 +1103,14 @@ fn setup_vault_with_token(
     let token_contract = env.register_stellar_asset_contract_v2(token_admin.clone());
     let token_id = token_contract.address();
     let token_client = token::StellarAssetClient::new(env, &token_id);
+    client.set_token_config(
+        &token_id,
+        &TokenConfig {
+            enabled: true,
+            deposit_cap: 0,
+            min_liability: 0,
+        },
+    );
 
     let user = Address::generate(env);
     (client, admin, token_id, token_client, user)
@@ -1129,10 +1153,13 @@ fn test_propose_emergency_drain_only_admin() {
     let recipient = Address::generate(&env);
     client.propose_emergency_drain(&recipient);
 
-    // Second proposal while one is active should fail (Custom error).
+    // Second proposal while one is active should fail.
     let recipient2 = Address::generate(&env);
     let result = client.try_propose_emergency_drain(&recipient2);
-    assert!(result.is_err());
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        QuipayError::DrainAlreadyPending
+    );
 }
 
 #[test]
@@ -1257,6 +1284,7 @@ fn test_drain_clears_multiple_tokens() {
     let client = PayrollVaultClient::new(&env, &contract_id);
     let admin = Address::generate(&env);
     client.initialize(&admin);
+    client.set_token_allowlist_enforced(&false);
 
     // Token A
     let ta_admin = Address::generate(&env);
@@ -1295,6 +1323,105 @@ fn test_drain_clears_multiple_tokens() {
     assert_eq!(tb_read.balance(&recipient), 7_000);
 }
 
+#[test]
+fn test_partial_drain_targets_tokens_and_amounts() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, _admin, token_id, token_client, user) = setup_vault_with_token(&env);
+    let other_id = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+    client.set_token_config(
+        &other_id,
+        &TokenConfig {
+            enabled: true,
+            deposit_cap: 0,
+            min_liability: 0,
+        },
+    );
+    token_client.mint(&user, &10_000);
+    token::StellarAssetClient::new(&env, &other_id).mint(&user, &5_000);
+    client.deposit(&user, &token_id, &10_000);
+    client.deposit(&user, &other_id, &5_000);
+
+    let stream_contract = Address::generate(&env);
+    client.set_authorized_contract(&stream_contract);
+    client.add_liability(&token_id, &6_000);
+
+    let recipient = Address::generate(&env);
+    assert_eq!(
+        client.try_propose_partial_drain(&recipient, &soroban_sdk::vec![&env], &false),
+        Err(Ok(QuipayError::InvalidToken))
+    );
+    let targets = soroban_sdk::vec![
+        &env,
+        DrainTarget { token: token_id.clone(), amount: 3_000 },
+    ];
+    client.propose_partial_drain(&recipient, &targets, &false);
+    assert_eq!(client.get_pending_drain().unwrap().targets, targets);
+
+    env.ledger().with_mut(|li| {
+        li.timestamp += 24 * 60 * 60 + 1;
+    });
+    client.execute_emergency_drain();
+
+    // Only the targeted amount moves; liabilities and other tokens stay.
+    assert_eq!(token::Client::new(&env, &token_id).balance(&recipient), 3_000);
+    assert_eq!(client.get_treasury_balance(&token_id), 7_000);
+    assert_eq!(client.get_total_liability(&token_id), 6_000);
+    assert_eq!(client.get_treasury_balance(&other_id), 5_000);
+
+    // A partial drain may not dip into the funds backing liabilities.
+    let targets = soroban_sdk::vec![
+        &env,
+        DrainTarget { token: token_id.clone(), amount: 1_001 },
+    ];
+    client.propose_partial_drain(&recipient, &targets, &false);
+    env.ledger().with_mut(|li| {
+        li.timestamp += 24 * 60 * 60 + 1;
+    });
+    assert_eq!(
+        client.try_execute_emergency_drain(),
+        Err(Ok(QuipayError::InsufficientBalance))
+    );
+}
+
+#[test]
+fn test_protective_drain_requires_tally() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, _admin, token_id, token_client, user) = setup_vault_with_token(&env);
+    token_client.mint(&user, &10_000);
+    client.deposit(&user, &token_id, &10_000);
+
+    let recipient = Address::generate(&env);
+    let targets = soroban_sdk::vec![
+        &env,
+        DrainTarget { token: token_id.clone(), amount: 0 },
+    ];
+    client.propose_partial_drain(&recipient, &targets, &true);
+    assert_eq!(client.get_pending_drain().unwrap().tally_cursor, Some(1));
+    assert_eq!(
+        client.try_tally_drain_reserves(&None),
+        Err(Ok(QuipayError::DrainTimelockActive))
+    );
+
+    env.ledger().with_mut(|li| {
+        li.timestamp += 24 * 60 * 60 + 1;
+    });
+    assert_eq!(
+        client.try_execute_emergency_drain(),
+        Err(Ok(QuipayError::DrainTallyIncomplete))
+    );
+
+    // Without a stream contract there are no wages to keep.
+    assert!(client.tally_drain_reserves(&None));
+    client.execute_emergency_drain();
+    assert_eq!(token::Client::new(&env, &token_id).balance(&recipient), 10_000);
+}
+
 #[test]
 fn test_vault_tvl_tracking_events() {
     let env = Env::default();
@@ -1304,6 +1431,7 @@ fn test_vault_tvl_tracking_events() {
     let client = PayrollVaultClient::new(&env, &contract_id);
     let admin = Address::generate(&env);
     client.initialize(&admin);
+    client.set_token_allowlist_enforced(&false);
 
     let token_admin = Address::generate(&env);
     let token_contract = env.register_stellar_asset_contract_v2(token_admin.clone());
@@ -1320,13 +1448,16 @@ fn test_vault_tvl_tracking_events() {
     assert_eq!(last_event.0, contract_id);
     
     let topics = last_event.1.clone();
-    assert_eq!(Symbol::try_from_val(&env, &topics.get(0).unwrap()).unwrap(), symbol_short!("vault"));
-    assert_eq!(Symbol::try_from_val(&env, &topics.get(1).unwrap()).unwrap(), symbol_short!("deposited"));
-    assert_eq!(Address::try_from_val(&env, &topics.get(2).unwrap()).unwrap(), user);
-    assert_eq!(Address::try_from_val(&env, &topics.get(3).unwrap()).unwrap(), token_id);
+    assert_eq!(Symbol::try_from_val(&env, &topics.get(0).unwrap()).unwrap(), Symbol::new(&env, "vault"));
+    assert_eq!(Symbol::try_from_val(&env, &topics.get(1).unwrap()).unwrap(), Symbol::new(&env, "deposited"));
+    assert_eq!(u32::try_from_val(&env, &topics.get(2).unwrap()).unwrap(), quipay_common::events::EVENT_SCHEMA_VERSION);
+    assert_eq!(Address::try_from_val(&env, &topics.get(3).unwrap()).unwrap(), user);
 
-    let data: (i128, i128) = last_event.2.clone().try_into_val(&env).unwrap();
-    assert_eq!(data, (1000i128, 1000i128));
+    let data: events::Deposited = last_event.2.clone().try_into_val(&env).unwrap();
+    assert_eq!(
+        data,
+        events::Deposited { token: token_id.clone(), amount: 1000, new_balance: 1000 }
+    );
 
     // Second deposit: total 1500
     client.deposit(&user, &token_id, &500);
@@ -1334,9 +1465,9 @@ fn test_vault_tvl_tracking_events() {
     let last_event = events.last().unwrap();
     assert_eq!(last_event.0, contract_id);
     let topics = last_event.1.clone();
-    assert_eq!(Symbol::try_from_val(&env, &topics.get(1).unwrap()).unwrap(), symbol_short!("deposited"));
-    let data: (i128, i128) = last_event.2.clone().try_into_val(&env).unwrap();
-    assert_eq!(data, (500i128, 1500i128));
+    assert_eq!(Symbol::try_from_val(&env, &topics.get(1).unwrap()).unwrap(), Symbol::new(&env, "deposited"));
+    let data: events::Deposited = last_event.2.clone().try_into_val(&env).unwrap();
+    assert_eq!((data.amount, data.new_balance), (500i128, 1500i128));
 
     // Withdrawal: total 1200
     client.withdraw(&user, &token_id, &300);
@@ -1344,7 +1475,217 @@ fn test_vault_tvl_tracking_events() {
     let last_event = events.last().unwrap();
     assert_eq!(last_event.0, contract_id);
     let topics = last_event.1.clone();
-    assert_eq!(Symbol::try_from_val(&env, &topics.get(1).unwrap()).unwrap(), symbol_short!("withdrawn"));
-    let data: (i128, i128) = last_event.2.clone().try_into_val(&env).unwrap();
-    assert_eq!(data, (300i128, 1200i128));
+    assert_eq!(Symbol::try_from_val(&env, &topics.get(1).unwrap()).unwrap(), Symbol::new(&env, "withdrawn"));
+    assert_eq!(Address::try_from_val(&env, &topics.get(3).unwrap()).unwrap(), user);
+    let data: events::Withdrawn = last_event.2.clone().try_into_val(&env).unwrap();
+    assert_eq!((data.amount, data.new_balance), (300i128, 1200i128));
+}
+
+// ============================================================================
+// Insolvency / IOU Tests
+// ============================================================================
+
+/// Simulate an out-of-band loss (e.g. a clawback) by lowering the tracked balance.
+fn force_treasury_balance(env: &Env, vault: &Address, token: &Address, balance: i128) {
+    env.as_contract(vault, || {
+        env.storage()
+            .persistent()
+            .set(&StateKey::TreasuryBalance(token.clone()), &balance);
+    });
+}
+
+fn setup_insolvent_vault(
+    env: &Env,
+) -> (
+    PayrollVaultClient<'_>,
+    Address, // token_id
+    token::StellarAssetClient<'_>,
+    Address, // depositor
+) {
+    let (client, _admin, token_id, token_client, user) = setup_vault_with_token(env);
+    let stream_contract = Address::generate(env);
+    client.set_authorized_contract(&stream_contract);
+
+    token_client.mint(&user, &10_000);
+    client.deposit(&user, &token_id, &1_000);
+    client.add_liability(&token_id, &1_000);
+
+    // Half of the backing funds disappear: balance 500 vs liability 1_000.
+    force_treasury_balance(env, &client.address, &token_id, 500);
+    (client, token_id, token_client, user)
+}
+
+#[test]
+fn test_payout_stream_liability_pays_in_full_when_solvent() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, _admin, token_id, token_client, user) = setup_vault_with_token(&env);
+    client.set_authorized_contract(&Address::generate(&env));
+    token_client.mint(&user, &1_000);
+    client.deposit(&user, &token_id, &1_000);
+    client.add_liability(&token_id, &1_000);
+
+    let worker = Address::generate(&env);
+    assert!(!client.is_insolvent(&token_id));
+    assert_eq!(client.payout_stream_liability(&1, &worker, &token_id, &400), 400);
+    assert_eq!(client.get_stream_iou(&1), 0);
+    assert_eq!(client.get_total_liability(&token_id), 600);
+    assert_eq!(client.get_treasury_balance(&token_id), 600);
+}
+
+#[test]
+fn test_payout_stream_liability_is_pro_rata_when_insolvent() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, token_id, _token_client, _user) = setup_insolvent_vault(&env);
+    let worker = Address::generate(&env);
+    let token_read = token::Client::new(&env, &token_id);
+
+    assert!(client.is_insolvent(&token_id));
+
+    // 500 / 1_000 = 50% of the requested amount is paid.
+    let paid = client.payout_stream_liability(&7, &worker, &token_id, &400);
+    assert_eq!(paid, 200);
+    assert_eq!(token_read.balance(&worker), 200);
+
+    // The shortfall stays in the liability as an IOU for stream 7.
+    assert_eq!(client.get_stream_iou(&7), 200);
+    assert_eq!(client.get_total_iou(&token_id), 200);
+    assert_eq!(client.get_total_liability(&token_id), 800);
+    assert_eq!(client.get_treasury_balance(&token_id), 300);
+
+    let pending = client.get_pending_ious(&token_id);
+    assert_eq!(pending.len(), 1);
+    let iou = pending.get(0).unwrap();
+    assert_eq!(iou.stream_id, 7);
+    assert_eq!(iou.recipient, worker);
+    assert_eq!(iou.amount, 200);
+}
+
+#[test]
+fn test_first_withdrawer_cannot_drain_insolvent_vault() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, token_id, _token_client, _user) = setup_insolvent_vault(&env);
+    let worker_a = Address::generate(&env);
+    let worker_b = Address::generate(&env);
+
+    // Each worker is owed 500; the vault only holds 500 in total.
+    assert_eq!(client.payout_stream_liability(&1, &worker_a, &token_id, &500), 250);
+    assert_eq!(client.payout_stream_liability(&2, &worker_b, &token_id, &500), 250);
+
+    assert_eq!(client.get_stream_iou(&1), 250);
+    assert_eq!(client.get_stream_iou(&2), 250);
+    assert_eq!(client.get_treasury_balance(&token_id), 0);
+    assert_eq!(client.get_total_liability(&token_id), 500);
+}
+
+#[test]
+fn test_full_drain_clears_ious() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, token_id, token_client, user) = setup_insolvent_vault(&env);
+    let worker = Address::generate(&env);
+    assert_eq!(client.payout_stream_liability(&1, &worker, &token_id, &1_000), 500);
+    assert_eq!(client.get_total_iou(&token_id), 500);
+
+    client.propose_emergency_drain(&Address::generate(&env));
+    env.ledger().with_mut(|li| {
+        li.timestamp += 24 * 60 * 60 + 1;
+    });
+    client.execute_emergency_drain();
+
+    assert_eq!(client.get_total_iou(&token_id), 0);
+    assert_eq!(client.get_stream_iou(&1), 0);
+    assert_eq!(client.get_pending_ious(&token_id).len(), 0);
+
+    // A later deposit is free balance rather than paying forgotten IOUs.
+    token_client.mint(&user, &300);
+    client.deposit(&user, &token_id, &300);
+    assert!(client.get_total_liability(&token_id) >= 0);
+    assert_eq!(client.get_total_liability(&token_id), 0);
+    assert_eq!(client.get_treasury_balance(&token_id), 300);
+    assert_eq!(client.get_available_balance(&token_id), 300);
+}
+
+#[test]
+fn test_deposit_settles_ious_first_in_order() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, token_id, _token_client, user) = setup_insolvent_vault(&env);
+    let worker_a = Address::generate(&env);
+    let worker_b = Address::generate(&env);
+    let token_read = token::Client::new(&env, &token_id);
+
+    client.payout_stream_liability(&1, &worker_a, &token_id, &500);
+    client.payout_stream_liability(&2, &worker_b, &token_id, &500);
+
+    // 300 covers the first IOU (250) fully and the second one partially.
+    client.deposit(&user, &token_id, &300);
+
+    assert_eq!(token_read.balance(&worker_a), 500);
+    assert_eq!(token_read.balance(&worker_b), 300);
+    assert_eq!(client.get_stream_iou(&1), 0);
+    assert_eq!(client.get_stream_iou(&2), 200);
+    assert_eq!(client.get_total_iou(&token_id), 200);
+
+    let pending = client.get_pending_ious(&token_id);
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending.get(0).unwrap().stream_id, 2);
+    assert_eq!(pending.get(0).unwrap().amount, 200);
+
+    // The next deposit finishes the remaining IOU and the rest stays in treasury.
+    client.deposit(&user, &token_id, &1_000);
+    assert_eq!(token_read.balance(&worker_b), 500);
+    assert_eq!(client.get_total_iou(&token_id), 0);
+    assert_eq!(client.get_pending_ious(&token_id).len(), 0);
+    assert_eq!(client.get_total_liability(&token_id), 0);
+    assert_eq!(client.get_treasury_balance(&token_id), 800);
+}
+
+#[test]
+fn test_settle_ious_uses_available_balance() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let (client, token_id, _token_client, _user) = setup_insolvent_vault(&env);
+    let worker = Address::generate(&env);
+
+    client.payout_stream_liability(&1, &worker, &token_id, &200);
+    assert_eq!(client.get_stream_iou(&1), 100);
+
+    // Funds recovered out of band restore the tracked balance.
+    force_treasury_balance(&env, &client.address, &token_id, 1_000);
+    assert_eq!(client.settle_ious(&token_id), 100);
+    assert_eq!(client.get_stream_iou(&1), 0);
+    assert!(client.get_iou(&1).is_none());
+}
+
+#[test]
+fn test_upgrader_role_manages_upgrades() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(PayrollVault, ());
+    let client = PayrollVaultClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    let upgrader = Address::generate(&env);
+    client.initialize(&admin);
+
+    let new_wasm_hash = BytesN::from_array(&env, &[0u8; 32]);
+    assert_eq!(
+        client.try_propose_upgrade(&upgrader, &new_wasm_hash, &(1, 1, 0)),
+        Err(Ok(QuipayError::InsufficientPermissions))
+    );
+
+    client.grant_role(&Role::Upgrader, &upgrader);
+    client.propose_upgrade(&upgrader, &new_wasm_hash, &(1, 1, 0));
+    assert_eq!(client.get_pending_upgrade().unwrap().proposed_by, upgrader);
+    client.cancel_upgrade(&upgrader);
+    assert!(client.get_pending_upgrade().is_none());
 }
@@ -0,0 +1,197 @@
+//! Token allowlist.
+//!
+//! The admin lists the tokens the vault is prepared to fund streams in, each
+//! with an enabled flag, a deposit cap and a minimum liability size. Listed
+//! tokens' settings always apply; unlisted tokens are rejected while
+//! enforcement is on, which it is from `initialize`. Deployments older than
+//! `TOKEN_ALLOWLIST_VERSION` have no setting: migrating from one lists every
+//! token the vault already tracks, so open streams keep working, and then
+//! turns enforcement on. The admin may turn it off again at any time.
+//!
+//! Delisting a token is a wind-down: new liabilities (and so new streams) are
+//! refused, but existing liabilities stay payable and employers may still
+//! deposit to cover them.
+//!
+//! PayrollStream also consults `is_token_allowed` when it picks a worker's
+//! preferred token from the WorkforceRegistry.
+
+use super::*;
+use crate::events::{self, AllowlistEnforced, TokenConfigured, TokenDelisted};
+
+/// First vault version with the token allowlist.
+pub(crate) const TOKEN_ALLOWLIST_VERSION: (u32, u32, u32) = (1, 1, 0);
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TokenConfig {
+    /// False once the token is delisted.
+    pub enabled: bool,
+    /// Maximum treasury balance deposits may reach; 0 for no cap.
+    pub deposit_cap: i128,
+    /// Smallest liability a single `add_liability` may add, i.e. the
+    /// smallest stream or extension; 0 for no minimum.
+    pub min_liability: i128,
+}
+
+#[contractimpl]
+impl PayrollVault {
+    /// List `token` or replace its settings.
+    /// Only the admin can call this function.
+    pub fn set_token_config(
+        e: Env,
+        token: Address,
+        config: TokenConfig,
+    ) -> Result<(), QuipayError> {
+        let admin = Self::get_admin(e.clone())?;
+        admin.require_auth();
+
+        if config.deposit_cap < 0 || config.min_liability < 0 {
+            return Err(QuipayError::InvalidAmount);
+        }
+
+        e.storage()
+            .persistent()
+            .set(&StateKey::TokenConfig(token.clone()), &config);
+
+        events::emit(&e, &admin, TokenConfigured { token, config });
+        Ok(())
+    }
+
+    /// Disable a listed token. Existing liabilities stay payable.
+    /// Only the admin can call this function.
+    pub fn delist_token(e: Env, token: Address) -> Result<(), QuipayError> {
+        let admin = Self::get_admin(e.clone())?;
+        admin.require_auth();
+
+        let key = StateKey::TokenConfig(token.clone());
+        let mut config: TokenConfig = e
+            .storage()
+            .persistent()
+            .get(&key)
+            .ok_or(QuipayError::InvalidToken)?;
+        config.enabled = false;
+        e.storage().persistent().set(&key, &config);
+
+        events::emit(&e, &admin, TokenDelisted { token });
+        Ok(())
+    }
+
+    pub fn get_token_config(e: Env, token: Address) -> Option<TokenConfig> {
+        e.storage().persistent().get(&StateKey::TokenConfig(token))
+    }
+
+    /// True if `token` is listed and enabled.
+    pub fn is_token_allowed(e: Env, token: Address) -> bool {
+        Self::get_token_config(e, token).is_some_and(|c| c.enabled)
+    }
+
+    /// Reject unlisted tokens in `deposit` and `add_liability`.
+    /// Only the admin can call this function.
+    pub fn set_token_allowlist_enforced(e: Env, enforced: bool) -> Result<(), QuipayError> {
+        let admin = Self::get_admin(e.clone())?;
+        admin.require_auth();
+
+        e.storage()
+            .persistent()
+            .set(&StateKey::TokenAllowlistEnforced, &enforced);
+
+        events::emit(&e, &admin, AllowlistEnforced { enforced });
+        Ok(())
+    }
+
+    pub fn is_token_allowlist_enforced(e: Env) -> bool {
+        e.storage()
+            .persistent()
+            .get(&StateKey::TokenAllowlistEnforced)
+            .unwrap_or(false)
+    }
+}
+
+impl PayrollVault {
+    /// List every tracked token that has no settings yet, with no limits,
+    /// then enforce the allowlist. Run by `migrate`; only applies to
+    /// upgrades from before `TOKEN_ALLOWLIST_VERSION` that have no
+    /// enforcement setting yet, so an admin's choice is never overridden.
+    pub(crate) fn backfill_token_allowlist(
+        e: &Env,
+        admin: &Address,
+        from_version: (u32, u32, u32),
+    ) {
+        if from_version >= TOKEN_ALLOWLIST_VERSION
+            || e.storage()
+                .persistent()
+                .has(&StateKey::TokenAllowlistEnforced)
+        {
+            return;
+        }
+
+        for token in Self::get_supported_tokens(e.clone()).iter() {
+            let key = StateKey::TokenConfig(token.clone());
+            if e.storage().persistent().has(&key) {
+                continue;
+            }
+            let config = TokenConfig {
+                enabled: true,
+                deposit_cap: 0,
+                min_liability: 0,
+            };
+            e.storage().persistent().set(&key, &config);
+            events::emit(e, admin, TokenConfigured { token, config });
+        }
+
+        e.storage()
+            .persistent()
+            .set(&StateKey::TokenAllowlistEnforced, &true);
+        events::emit(e, admin, AllowlistEnforced { enforced: true });
+    }
+
+    /// Apply the token's allowlist settings to a deposit of `amount`.
+    /// Delisted tokens still accept deposits while liabilities are open.
+    pub(crate) fn check_deposit_allowed(
+        e: &Env,
+        token: &Address,
+        amount: i128,
+    ) -> Result<(), QuipayError> {
+        let Some(config) = Self::get_token_config(e.clone(), token.clone()) else {
+            if Self::is_token_allowlist_enforced(e.clone()) {
+                return Err(QuipayError::InvalidToken);
+            }
+            return Ok(());
+        };
+
+        if !config.enabled && Self::get_total_liability(e.clone(), token.clone()) == 0 {
+            return Err(QuipayError::InvalidToken);
+        }
+
+        if config.deposit_cap > 0 {
+            let balance = Self::get_treasury_balance(e.clone(), token.clone());
+            let new_balance = balance.checked_add(amount).ok_or(QuipayError::Overflow)?;
+            if new_balance > config.deposit_cap {
+                return Err(QuipayError::DepositCapExceeded);
+            }
+        }
+        Ok(())
+    }
+
+    /// Apply the token's allowlist settings to a new liability of `amount`.
+    pub(crate) fn check_liability_allowed(
+        e: &Env,
+        token: &Address,
+        amount: i128,
+    ) -> Result<(), QuipayError> {
+        let Some(config) = Self::get_token_config(e.clone(), token.clone()) else {
+            if Self::is_token_allowlist_enforced(e.clone()) {
+                return Err(QuipayError::InvalidToken);
+            }
+            return Ok(());
+        };
+
+        if !config.enabled {
+            return Err(QuipayError::InvalidToken);
+        }
+        if amount < config.min_liability {
+            return Err(QuipayError::LiabilityBelowMinimum);
+        }
+        Ok(())
+    }
+}
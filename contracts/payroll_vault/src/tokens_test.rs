@@ -0,0 +1,187 @@
+use crate::{PayrollVault, PayrollVaultClient, StateKey, TokenConfig};
+use quipay_common::QuipayError;
+use soroban_sdk::{Address, Env, testutils::Address as _, token};
+
+struct Setup<'a> {
+    client: PayrollVaultClient<'a>,
+    token: Address,
+    employer: Address,
+}
+
+fn setup(env: &Env) -> Setup<'_> {
+    env.mock_all_auths();
+    let client = PayrollVaultClient::new(env, &env.register(PayrollVault, ()));
+    client.initialize(&Address::generate(env));
+    client.set_authorized_contract(&Address::generate(env));
+
+    let token = env
+        .register_stellar_asset_contract_v2(Address::generate(env))
+        .address();
+    let employer = Address::generate(env);
+    token::StellarAssetClient::new(env, &token).mint(&employer, &10_000);
+
+    Setup {
+        client,
+        token,
+        employer,
+    }
+}
+
+fn config(enabled: bool, deposit_cap: i128, min_liability: i128) -> TokenConfig {
+    TokenConfig {
+        enabled,
+        deposit_cap,
+        min_liability,
+    }
+}
+
+#[test]
+fn test_enforced_allowlist_rejects_unlisted_tokens() {
+    let env = Env::default();
+    let s = setup(&env);
+
+    // Enforcement is on from `initialize`.
+    assert!(s.client.is_token_allowlist_enforced());
+    assert_eq!(
+        s.client.try_deposit(&s.employer, &s.token, &1_000),
+        Err(Ok(QuipayError::InvalidToken))
+    );
+
+    // Unlisted tokens are accepted while the admin turns it off.
+    s.client.set_token_allowlist_enforced(&false);
+    s.client.deposit(&s.employer, &s.token, &1_000);
+    s.client.set_token_allowlist_enforced(&true);
+    assert_eq!(
+        s.client.try_deposit(&s.employer, &s.token, &1_000),
+        Err(Ok(QuipayError::InvalidToken))
+    );
+    assert_eq!(
+        s.client.try_add_liability(&s.token, &100),
+        Err(Ok(QuipayError::InvalidToken))
+    );
+
+    s.client.set_token_config(&s.token, &config(true, 0, 0));
+    assert!(s.client.is_token_allowed(&s.token));
+    s.client.deposit(&s.employer, &s.token, &1_000);
+    s.client.add_liability(&s.token, &100);
+    assert_eq!(s.client.get_total_liability(&s.token), 100);
+}
+
+#[test]
+fn test_deposit_cap_and_min_liability() {
+    let env = Env::default();
+    let s = setup(&env);
+    s.client
+        .set_token_config(&s.token, &config(true, 1_500, 50));
+
+    s.client.deposit(&s.employer, &s.token, &1_000);
+    assert_eq!(
+        s.client.try_deposit(&s.employer, &s.token, &501),
+        Err(Ok(QuipayError::DepositCapExceeded))
+    );
+    s.client.deposit(&s.employer, &s.token, &500);
+
+    assert_eq!(
+        s.client.try_add_liability(&s.token, &49),
+        Err(Ok(QuipayError::LiabilityBelowMinimum))
+    );
+    s.client.add_liability(&s.token, &50);
+
+    assert_eq!(
+        s.client
+            .try_set_token_config(&s.token, &config(true, -1, 0)),
+        Err(Ok(QuipayError::InvalidAmount))
+    );
+}
+
+#[test]
+fn test_delisted_token_winds_down() {
+    let env = Env::default();
+    let s = setup(&env);
+    let worker = Address::generate(&env);
+    s.client.set_token_config(&s.token, &config(true, 0, 0));
+    s.client.deposit(&s.employer, &s.token, &1_000);
+    s.client.add_liability(&s.token, &600);
+
+    s.client.delist_token(&s.token);
+    assert!(!s.client.is_token_allowed(&s.token));
+    assert_eq!(
+        s.client.try_add_liability(&s.token, &100),
+        Err(Ok(QuipayError::InvalidToken))
+    );
+
+    // Open liabilities stay payable and can still be funded.
+    s.client.deposit(&s.employer, &s.token, &100);
+    s.client.payout_liability(&worker, &s.token, &600);
+    assert_eq!(token::Client::new(&env, &s.token).balance(&worker), 600);
+    assert_eq!(
+        s.client.try_deposit(&s.employer, &s.token, &100),
+        Err(Ok(QuipayError::InvalidToken))
+    );
+
+    assert_eq!(
+        s.client.try_delist_token(&Address::generate(&env)),
+        Err(Ok(QuipayError::InvalidToken))
+    );
+}
+
+#[test]
+fn test_migration_lists_tracked_tokens_and_enforces_allowlist() {
+    let env = Env::default();
+    let s = setup(&env);
+    let admin = s.client.get_admin();
+
+    // A deployment from before the allowlist has no enforcement setting.
+    env.as_contract(&s.client.address, || {
+        env.storage()
+            .persistent()
+            .remove(&StateKey::TokenAllowlistEnforced);
+    });
+    s.client.deposit(&s.employer, &s.token, &1_000);
+    s.client.add_liability(&s.token, &600);
+    assert!(!s.client.is_token_allowlist_enforced());
+
+    // Migrating from a version that already had it changes nothing.
+    env.as_contract(&s.client.address, || {
+        PayrollVault::backfill_token_allowlist(&env, &admin, (1, 1, 0));
+    });
+    assert!(!s.client.is_token_allowlist_enforced());
+    assert_eq!(s.client.get_token_config(&s.token), None);
+
+    // The step `migrate` runs for an upgrade from 1.0.0.
+    env.as_contract(&s.client.address, || {
+        PayrollVault::backfill_token_allowlist(&env, &admin, (1, 0, 0));
+    });
+
+    assert!(s.client.is_token_allowlist_enforced());
+    assert_eq!(
+        s.client.get_token_config(&s.token),
+        Some(config(true, 0, 0))
+    );
+    s.client.deposit(&s.employer, &s.token, &100);
+    s.client.add_liability(&s.token, &100);
+
+    let unlisted = env
+        .register_stellar_asset_contract_v2(Address::generate(&env))
+        .address();
+    assert_eq!(
+        s.client.try_add_liability(&unlisted, &100),
+        Err(Ok(QuipayError::InvalidToken))
+    );
+}
+
+#[test]
+fn test_migration_keeps_admin_allowlist_setting() {
+    let env = Env::default();
+    let s = setup(&env);
+    let admin = s.client.get_admin();
+    s.client.set_token_allowlist_enforced(&false);
+    s.client.deposit(&s.employer, &s.token, &1_000);
+
+    env.as_contract(&s.client.address, || {
+        PayrollVault::backfill_token_allowlist(&env, &admin, (1, 0, 0));
+    });
+
+    assert!(!s.client.is_token_allowlist_enforced());
+    assert_eq!(s.client.get_token_config(&s.token), None);
+}
@@ -281,6 +281,7 @@ fn test_basic_flow() {
 
     // Initialize
     client.initialize(&admin);
+    client.set_token_allowlist_enforced(&false);
 
     // Verify initial version
     let version = client.get_version();
@@ -367,6 +368,7 @@ fn test_upgrade_structure_verification() {
 
     // Initialize v1
     v1_client.initialize(&admin);
+    v1_client.set_token_allowlist_enforced(&false);
 
     // Setup Token
     let token_admin = Address::generate(&env);
@@ -425,6 +427,7 @@ fn test_state_persistence_across_contract_instances() {
 
     // Initialize
     client.initialize(&admin);
+    client.set_token_allowlist_enforced(&false);
 
     // Setup tokens
     let token_admin = Address::generate(&env);
@@ -0,0 +1,322 @@
+//! Employer records, KYB status and payroll delegates.
+//!
+//! An employer registers a record holding a hash of its legal name. KYB
+//! status is set by the registry admin or an approved KYB verifier, a list
+//! kept apart from the workers' KYC attesters. The
+//! employer's admins (initially just the employer key) appoint payroll
+//! managers with a role, and PayrollStream asks `can_act_for` whether a
+//! delegate may create, cancel or pause streams for the employer, so HR
+//! staff no longer need the company's root key. Delegates only act for
+//! employers whose KYB status is `Verified`; the employer key itself always
+//! can.
+//!
+//! | Role    | Create | Cancel | Pause/Resume |
+//! |---------|--------|--------|--------------|
+//! | Admin   | yes    | yes    | yes          |
+//! | Hr      | yes    | yes    | no           |
+//! | Finance | yes    | no     | yes          |
+//! | Viewer  | no     | no     | no           |
+
+use super::*;
+use crate::events::{
+    self, EmployerAdminAdded, EmployerAdminRemoved, EmployerRegistered, KybStatusSet,
+    KybVerifierSet, ManagerRemoved, ManagerSet,
+};
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum KybStatus {
+    Pending = 0,
+    Verified = 1,
+    Rejected = 2,
+    Suspended = 3,
+}
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum EmployerRole {
+    Hr = 1,
+    Finance = 2,
+    Viewer = 3,
+}
+
+/// Stream operations an employer's delegates can perform. PayrollStream asks
+/// `can_act_for` before acting and passes its own copy of this enum, so the
+/// discriminants must stay in step.
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum EmployerAction {
+    CreateStream = 1,
+    CancelStream = 2,
+    PauseStream = 3,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct EmployerRecord {
+    pub employer: Address,
+    pub name_hash: BytesN<32>,
+    pub kyb_status: KybStatus,
+    /// Addresses that manage the record and its delegates; always includes `employer`.
+    pub admins: Vec<Address>,
+    pub registered_at: u64,
+}
+
+#[contractimpl]
+impl WorkforceRegistryContract {
+    /// Register an employer record with KYB status `Pending`.
+    pub fn register_employer(
+        e: Env,
+        employer: Address,
+        name_hash: BytesN<32>,
+    ) -> Result<(), QuipayError> {
+        employer.require_auth();
+
+        let key = DataKey::Employer(employer.clone());
+        require!(
+            !e.storage().persistent().has(&key),
+            QuipayError::AlreadyInitialized
+        );
+
+        let record = EmployerRecord {
+            employer: employer.clone(),
+            name_hash: name_hash.clone(),
+            kyb_status: KybStatus::Pending,
+            admins: Vec::from_array(&e, [employer.clone()]),
+            registered_at: e.ledger().timestamp(),
+        };
+        e.storage().persistent().set(&key, &record);
+
+        events::emit(
+            &e,
+            &employer,
+            EmployerRegistered {
+                employer: employer.clone(),
+                name_hash,
+            },
+        );
+        Ok(())
+    }
+
+    pub fn get_employer(e: Env, employer: Address) -> Option<EmployerRecord> {
+        e.storage().persistent().get(&DataKey::Employer(employer))
+    }
+
+    /// Approve or remove a KYB verifier (admin only).
+    pub fn set_kyb_verifier(e: Env, verifier: Address, approved: bool) -> Result<(), QuipayError> {
+        let admin = Self::get_admin(e.clone())?;
+        admin.require_auth();
+
+        let key = DataKey::KybVerifier(verifier.clone());
+        if approved {
+            e.storage().persistent().set(&key, &true);
+        } else {
+            e.storage().persistent().remove(&key);
+        }
+
+        events::emit(&e, &admin, KybVerifierSet { verifier, approved });
+        Ok(())
+    }
+
+    pub fn is_kyb_verifier(e: Env, verifier: Address) -> bool {
+        e.storage()
+            .persistent()
+            .get(&DataKey::KybVerifier(verifier))
+            .unwrap_or(false)
+    }
+
+    /// Set an employer's KYB status. Callable by the registry admin or an
+    /// approved KYB verifier.
+    pub fn set_kyb_status(
+        e: Env,
+        caller: Address,
+        employer: Address,
+        status: KybStatus,
+    ) -> Result<(), QuipayError> {
+        caller.require_auth();
+        require!(
+            caller == Self::get_admin(e.clone())?
+                || Self::is_kyb_verifier(e.clone(), caller.clone()),
+            QuipayError::InsufficientPermissions
+        );
+
+        let mut record = Self::load_employer(&e, &employer)?;
+        record.kyb_status = status;
+        Self::save_employer(&e, &record);
+
+        events::emit(&e, &caller, KybStatusSet { employer, status });
+        Ok(())
+    }
+
+    /// Add an employer admin. Only an existing employer admin can call this.
+    pub fn add_employer_admin(
+        e: Env,
+        caller: Address,
+        employer: Address,
+        admin: Address,
+    ) -> Result<(), QuipayError> {
+        let mut record = Self::load_employer_as_admin(&e, &caller, &employer)?;
+        if !record.admins.contains(admin.clone()) {
+            record.admins.push_back(admin.clone());
+            Self::save_employer(&e, &record);
+        }
+
+        events::emit(&e, &caller, EmployerAdminAdded { employer, admin });
+        Ok(())
+    }
+
+    /// Remove an employer admin. The employer key itself cannot be removed.
+    /// Only an employer admin can call this.
+    pub fn remove_employer_admin(
+        e: Env,
+        caller: Address,
+        employer: Address,
+        admin: Address,
+    ) -> Result<(), QuipayError> {
+        let mut record = Self::load_employer_as_admin(&e, &caller, &employer)?;
+        require!(admin != employer, QuipayError::InvalidAddress);
+        if let Some(index) = record.admins.first_index_of(admin.clone()) {
+            record.admins.remove(index);
+            Self::save_employer(&e, &record);
+        }
+
+        events::emit(&e, &caller, EmployerAdminRemoved { employer, admin });
+        Ok(())
+    }
+
+    /// Appoint (or re-assign) a payroll manager. Only an employer admin can call this.
+    pub fn set_manager(
+        e: Env,
+        caller: Address,
+        employer: Address,
+        manager: Address,
+        role: EmployerRole,
+    ) -> Result<(), QuipayError> {
+        Self::load_employer_as_admin(&e, &caller, &employer)?;
+
+        e.storage().persistent().set(
+            &DataKey::EmployerManager(employer.clone(), manager.clone()),
+            &role,
+        );
+        let list_key = DataKey::EmployerManagers(employer.clone());
+        let mut managers = Self::get_managers(e.clone(), employer.clone());
+        if !managers.contains(manager.clone()) {
+            managers.push_back(manager.clone());
+            e.storage().persistent().set(&list_key, &managers);
+        }
+
+        events::emit(
+            &e,
+            &caller,
+            ManagerSet {
+                employer,
+                manager,
+                role,
+            },
+        );
+        Ok(())
+    }
+
+    /// Remove a payroll manager. Only an employer admin can call this.
+    pub fn remove_manager(
+        e: Env,
+        caller: Address,
+        employer: Address,
+        manager: Address,
+    ) -> Result<(), QuipayError> {
+        Self::load_employer_as_admin(&e, &caller, &employer)?;
+
+        e.storage()
+            .persistent()
+            .remove(&DataKey::EmployerManager(employer.clone(), manager.clone()));
+        let managers = Self::get_managers(e.clone(), employer.clone());
+        if let Some(index) = managers.first_index_of(manager.clone()) {
+            let mut managers = managers;
+            managers.remove(index);
+            e.storage()
+                .persistent()
+                .set(&DataKey::EmployerManagers(employer.clone()), &managers);
+        }
+
+        events::emit(&e, &caller, ManagerRemoved { employer, manager });
+        Ok(())
+    }
+
+    pub fn get_manager_role(e: Env, employer: Address, manager: Address) -> Option<EmployerRole> {
+        e.storage()
+            .persistent()
+            .get(&DataKey::EmployerManager(employer, manager))
+    }
+
+    pub fn get_managers(e: Env, employer: Address) -> Vec<Address> {
+        e.storage()
+            .persistent()
+            .get(&DataKey::EmployerManagers(employer))
+            .unwrap_or_else(|| Vec::new(&e))
+    }
+
+    /// Whether the employer has a record with KYB status `Verified`.
+    pub fn is_employer_verified(e: Env, employer: Address) -> bool {
+        Self::get_employer(e, employer)
+            .is_some_and(|record| record.kyb_status == KybStatus::Verified)
+    }
+
+    /// Whether `caller` may perform `action` on the employer's streams: the
+    /// employer itself, or, once the employer is KYB-verified, its admins and
+    /// managers whose role allows it.
+    pub fn can_act_for(e: Env, employer: Address, caller: Address, action: EmployerAction) -> bool {
+        if caller == employer {
+            return true;
+        }
+        let Some(record) = Self::get_employer(e.clone(), employer.clone()) else {
+            return false;
+        };
+        if record.kyb_status != KybStatus::Verified {
+            return false;
+        }
+        if record.admins.contains(caller.clone()) {
+            return true;
+        }
+        match Self::get_manager_role(e, employer, caller) {
+            Some(EmployerRole::Hr) => matches!(
+                action,
+                EmployerAction::CreateStream | EmployerAction::CancelStream
+            ),
+            Some(EmployerRole::Finance) => matches!(
+                action,
+                EmployerAction::CreateStream | EmployerAction::PauseStream
+            ),
+            Some(EmployerRole::Viewer) | None => false,
+        }
+    }
+}
+
+impl WorkforceRegistryContract {
+    fn load_employer(e: &Env, employer: &Address) -> Result<EmployerRecord, QuipayError> {
+        Self::get_employer(e.clone(), employer.clone()).ok_or(QuipayError::EmployerNotFound)
+    }
+
+    /// Authenticate `caller` as one of the employer's admins.
+    fn load_employer_as_admin(
+        e: &Env,
+        caller: &Address,
+        employer: &Address,
+    ) -> Result<EmployerRecord, QuipayError> {
+        caller.require_auth();
+        let record = Self::load_employer(e, employer)?;
+        require!(
+            record.admins.contains(caller.clone()),
+            QuipayError::InsufficientPermissions
+        );
+        Ok(record)
+    }
+
+    fn save_employer(e: &Env, record: &EmployerRecord) {
+        e.storage()
+            .persistent()
+            .set(&DataKey::Employer(record.employer.clone()), record);
+    }
+}
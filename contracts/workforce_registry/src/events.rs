@@ -0,0 +1,168 @@
+//! Events published by WorkforceRegistry, all under the `registry` namespace.
+//!
+//! Topics follow `quipay_common::events`. Stream activity synced by
+//! PayrollStream names that contract as the actor.
+
+use crate::{EmployerRole, KybStatus, TerminationReason};
+use soroban_sdk::{Address, BytesN, String, contracttype};
+
+pub(crate) use quipay_common::events::emit;
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WorkerRegistered {
+    pub worker: Address,
+    pub preferred_token: Address,
+    pub metadata_hash: String,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WorkerUpdated {
+    pub worker: Address,
+    pub preferred_token: Address,
+    pub metadata_hash: String,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WorkerRemoved {
+    pub employer: Address,
+    pub worker: Address,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BlacklistSet {
+    pub worker: Address,
+    pub blacklisted: bool,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StreamActiveSet {
+    pub employer: Address,
+    pub worker: Address,
+    pub active: bool,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EmployerRegistered {
+    pub employer: Address,
+    pub name_hash: BytesN<32>,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct KybStatusSet {
+    pub employer: Address,
+    pub status: KybStatus,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct KybVerifierSet {
+    pub verifier: Address,
+    pub approved: bool,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EmployerAdminAdded {
+    pub employer: Address,
+    pub admin: Address,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EmployerAdminRemoved {
+    pub employer: Address,
+    pub admin: Address,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ManagerSet {
+    pub employer: Address,
+    pub manager: Address,
+    pub role: EmployerRole,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ManagerRemoved {
+    pub employer: Address,
+    pub manager: Address,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EmploymentStarted {
+    pub employment_id: u32,
+    pub employer: Address,
+    pub worker: Address,
+    pub start: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EmploymentConfirmed {
+    pub employment_id: u32,
+    pub employer: Address,
+    pub worker: Address,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EmploymentEnded {
+    pub employment_id: u32,
+    pub employer: Address,
+    pub worker: Address,
+    pub end: u64,
+    pub reason: TerminationReason,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AttesterSet {
+    pub attester: Address,
+    pub approved: bool,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct KycAttested {
+    pub worker: Address,
+    pub attester: Address,
+    pub level: u32,
+    pub expires_at: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct KycRevoked {
+    pub worker: Address,
+    pub attester: Address,
+}
+
+quipay_common::event_names!("registry" {
+    WorkerRegistered => "worker_registered",
+    WorkerUpdated => "worker_updated",
+    WorkerRemoved => "worker_removed",
+    BlacklistSet => "blacklist_set",
+    StreamActiveSet => "stream_active_set",
+    EmployerRegistered => "employer_registered",
+    KybStatusSet => "kyb_status_set",
+    KybVerifierSet => "kyb_verifier_set",
+    EmployerAdminAdded => "employer_admin_added",
+    EmployerAdminRemoved => "employer_admin_removed",
+    ManagerSet => "manager_set",
+    ManagerRemoved => "manager_removed",
+    EmploymentStarted => "employment_started",
+    EmploymentConfirmed => "employment_confirmed",
+    EmploymentEnded => "employment_ended",
+    AttesterSet => "attester_set",
+    KycAttested => "kyc_attested",
+    KycRevoked => "kyc_revoked",
+});
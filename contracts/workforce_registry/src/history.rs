@@ -0,0 +1,328 @@
+//! Employment records and profile versions.
+//!
+//! Employers append an employment record when they hire a worker and close
+//! it with an end time and termination reason when the engagement ends.
+//! A record stays unconfirmed until the worker co-signs it with
+//! `confirm_employment`; the worker can instead decline an unconfirmed open
+//! record, which closes it as `Declined`. A worker holds at most
+//! `MAX_OPEN_EMPLOYMENTS` open records. Records are never deleted, and every
+//! profile registration or update is kept as a numbered version, so a worker
+//! can present a verifiable work history alongside their `payroll_receipt`
+//! receipts even after `remove_worker`.
+
+use super::*;
+use crate::events::{self, EmploymentConfirmed, EmploymentEnded, EmploymentStarted};
+
+/// Maximum open employment records per worker, across all employers.
+pub const MAX_OPEN_EMPLOYMENTS: u32 = 10;
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum TerminationReason {
+    Resigned = 1,
+    Terminated = 2,
+    ContractEnded = 3,
+    /// Closed by `remove_worker`.
+    Removed = 4,
+    /// Closed by the worker with `decline_employment`.
+    Declined = 5,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct EmploymentRecord {
+    pub id: u32,
+    pub employer: Address,
+    /// Hash of the off-chain role description.
+    pub role_hash: BytesN<32>,
+    pub start: u64,
+    pub status: EmploymentStatus,
+    /// Whether the worker has co-signed the record.
+    pub confirmed: bool,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub enum EmploymentStatus {
+    Active,
+    /// Ended at the given time for the given reason.
+    Ended(u64, TerminationReason),
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProfileVersion {
+    pub version: u32,
+    pub profile: WorkerProfile,
+    pub recorded_at: u64,
+}
+
+#[contractimpl]
+impl WorkforceRegistryContract {
+    /// Append an unconfirmed employment record for a registered worker and
+    /// return its id. Only the employer can call this; one open record per
+    /// employer and worker, and at most `MAX_OPEN_EMPLOYMENTS` per worker.
+    pub fn start_employment(
+        e: Env,
+        employer: Address,
+        worker: Address,
+        role_hash: BytesN<32>,
+        start: u64,
+    ) -> Result<u32, QuipayError> {
+        employer.require_auth();
+        require!(
+            Self::is_registered(e.clone(), worker.clone()),
+            QuipayError::WorkerNotFound
+        );
+        let open_key = DataKey::OpenEmployment(employer.clone(), worker.clone());
+        require!(
+            !e.storage().persistent().has(&open_key),
+            QuipayError::EmploymentActive
+        );
+        let open_count = Self::get_open_employment_count(e.clone(), worker.clone());
+        require!(
+            open_count < MAX_OPEN_EMPLOYMENTS,
+            QuipayError::TooManyOpenEmployments
+        );
+
+        let id = Self::get_employment_count(e.clone(), worker.clone());
+        let record = EmploymentRecord {
+            id,
+            employer: employer.clone(),
+            role_hash,
+            start,
+            status: EmploymentStatus::Active,
+            confirmed: false,
+        };
+        e.storage()
+            .persistent()
+            .set(&DataKey::Employment(worker.clone(), id), &record);
+        e.storage()
+            .persistent()
+            .set(&DataKey::EmploymentCount(worker.clone()), &(id + 1));
+        e.storage().persistent().set(&open_key, &id);
+        e.storage().persistent().set(
+            &DataKey::OpenEmploymentCount(worker.clone()),
+            &(open_count + 1),
+        );
+
+        events::emit(
+            &e,
+            &employer,
+            EmploymentStarted {
+                employment_id: id,
+                employer: employer.clone(),
+                worker,
+                start,
+            },
+        );
+        Ok(id)
+    }
+
+    /// Close the employer's open employment record for `worker`.
+    /// Only the employer can call this.
+    pub fn end_employment(
+        e: Env,
+        employer: Address,
+        worker: Address,
+        end: u64,
+        reason: TerminationReason,
+    ) -> Result<(), QuipayError> {
+        employer.require_auth();
+
+        let open_key = DataKey::OpenEmployment(employer.clone(), worker.clone());
+        let id: u32 = e
+            .storage()
+            .persistent()
+            .get(&open_key)
+            .ok_or(QuipayError::EmploymentNotFound)?;
+        Self::close_employment(&e, &employer, &worker, id, end, reason)
+    }
+
+    /// Co-sign one of the worker's employment records. Only the worker can
+    /// call this.
+    pub fn confirm_employment(e: Env, worker: Address, id: u32) -> Result<(), QuipayError> {
+        worker.require_auth();
+
+        let key = DataKey::Employment(worker.clone(), id);
+        let mut record: EmploymentRecord = e
+            .storage()
+            .persistent()
+            .get(&key)
+            .ok_or(QuipayError::EmploymentNotFound)?;
+        if record.confirmed {
+            return Ok(());
+        }
+        record.confirmed = true;
+        e.storage().persistent().set(&key, &record);
+
+        events::emit(
+            &e,
+            &worker,
+            EmploymentConfirmed {
+                employment_id: id,
+                employer: record.employer,
+                worker: worker.clone(),
+            },
+        );
+        Ok(())
+    }
+
+    /// Close an open record the worker has not confirmed, ending it at its
+    /// start time as `Declined`. Only the worker can call this.
+    pub fn decline_employment(e: Env, worker: Address, id: u32) -> Result<(), QuipayError> {
+        worker.require_auth();
+
+        let record = Self::get_employment(e.clone(), worker.clone(), id)
+            .ok_or(QuipayError::EmploymentNotFound)?;
+        require!(
+            record.status == EmploymentStatus::Active,
+            QuipayError::EmploymentNotFound
+        );
+        require!(!record.confirmed, QuipayError::Unauthorized);
+        Self::close_employment(
+            &e,
+            &record.employer,
+            &worker,
+            id,
+            record.start,
+            TerminationReason::Declined,
+        )
+    }
+
+    pub fn get_employment(e: Env, worker: Address, id: u32) -> Option<EmploymentRecord> {
+        e.storage()
+            .persistent()
+            .get(&DataKey::Employment(worker, id))
+    }
+
+    /// Number of employment records ever appended for `worker`.
+    pub fn get_employment_count(e: Env, worker: Address) -> u32 {
+        e.storage()
+            .persistent()
+            .get(&DataKey::EmploymentCount(worker))
+            .unwrap_or(0)
+    }
+
+    /// Number of the worker's employment records that are still open.
+    pub fn get_open_employment_count(e: Env, worker: Address) -> u32 {
+        e.storage()
+            .persistent()
+            .get(&DataKey::OpenEmploymentCount(worker))
+            .unwrap_or(0)
+    }
+
+    /// Page through a worker's employment records, oldest first.
+    pub fn get_employment_history(
+        e: Env,
+        worker: Address,
+        start: u32,
+        limit: u32,
+    ) -> Vec<EmploymentRecord> {
+        let count = Self::get_employment_count(e.clone(), worker.clone());
+        let end = start.saturating_add(limit).min(count);
+
+        let mut out = Vec::new(&e);
+        let mut id = start;
+        while id < end {
+            if let Some(record) = Self::get_employment(e.clone(), worker.clone(), id) {
+                out.push_back(record);
+            }
+            id += 1;
+        }
+        out
+    }
+
+    /// Number of profile versions recorded for `worker`.
+    pub fn get_profile_version_count(e: Env, worker: Address) -> u32 {
+        e.storage()
+            .persistent()
+            .get(&DataKey::ProfileVersionCount(worker))
+            .unwrap_or(0)
+    }
+
+    /// A past (or the current) version of the worker's profile.
+    pub fn get_profile_version(e: Env, worker: Address, version: u32) -> Option<ProfileVersion> {
+        e.storage()
+            .persistent()
+            .get(&DataKey::ProfileVersion(worker, version))
+    }
+}
+
+impl WorkforceRegistryContract {
+    /// Keep a copy of `profile` as the worker's next profile version.
+    pub(crate) fn record_profile_version(e: &Env, worker: &Address, profile: &WorkerProfile) {
+        let version = Self::get_profile_version_count(e.clone(), worker.clone());
+        let entry = ProfileVersion {
+            version,
+            profile: profile.clone(),
+            recorded_at: e.ledger().timestamp(),
+        };
+        e.storage()
+            .persistent()
+            .set(&DataKey::ProfileVersion(worker.clone(), version), &entry);
+        e.storage().persistent().set(
+            &DataKey::ProfileVersionCount(worker.clone()),
+            &(version + 1),
+        );
+    }
+
+    /// End the employer's open record for `worker`, if any, when the worker
+    /// is removed. A record that has not started yet ends at its start time.
+    pub(crate) fn end_open_employment(
+        e: &Env,
+        employer: &Address,
+        worker: &Address,
+    ) -> Result<(), QuipayError> {
+        let open_key = DataKey::OpenEmployment(employer.clone(), worker.clone());
+        let Some(id) = e.storage().persistent().get::<DataKey, u32>(&open_key) else {
+            return Ok(());
+        };
+        let record = Self::get_employment(e.clone(), worker.clone(), id)
+            .ok_or(QuipayError::EmploymentNotFound)?;
+        let end = e.ledger().timestamp().max(record.start);
+        Self::close_employment(e, employer, worker, id, end, TerminationReason::Removed)
+    }
+
+    fn close_employment(
+        e: &Env,
+        employer: &Address,
+        worker: &Address,
+        id: u32,
+        end: u64,
+        reason: TerminationReason,
+    ) -> Result<(), QuipayError> {
+        let key = DataKey::Employment(worker.clone(), id);
+        let mut record: EmploymentRecord = e
+            .storage()
+            .persistent()
+            .get(&key)
+            .ok_or(QuipayError::EmploymentNotFound)?;
+        require!(end >= record.start, QuipayError::InvalidTimeRange);
+
+        record.status = EmploymentStatus::Ended(end, reason);
+        e.storage().persistent().set(&key, &record);
+        e.storage()
+            .persistent()
+            .remove(&DataKey::OpenEmployment(employer.clone(), worker.clone()));
+        let count_key = DataKey::OpenEmploymentCount(worker.clone());
+        let open_count: u32 = e.storage().persistent().get(&count_key).unwrap_or(0);
+        e.storage()
+            .persistent()
+            .set(&count_key, &open_count.saturating_sub(1));
+
+        events::emit(
+            e,
+            employer,
+            EmploymentEnded {
+                employment_id: id,
+                employer: employer.clone(),
+                worker: worker.clone(),
+                end,
+                reason,
+            },
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,150 @@
+//! KYC attestations from external providers.
+//!
+//! The admin approves attesters (provider accounts or contracts). An approved
+//! attester records one attestation per worker: a KYC level, the
+//! jurisdiction, an expiry and a hash referencing the provider's record. An
+//! attestation only counts while it has not expired and its attester is
+//! still approved, so de-listing a provider invalidates its attestations.
+
+use super::*;
+use crate::events::{self, AttesterSet, KycAttested, KycRevoked};
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Attestation {
+    pub attester: Address,
+    pub level: u32,
+    pub jurisdiction: String,
+    /// Hash of the provider's verification record.
+    pub provider_ref: BytesN<32>,
+    pub issued_at: u64,
+    pub expires_at: u64,
+}
+
+#[contractimpl]
+impl WorkforceRegistryContract {
+    /// Approve or remove a KYC attester (admin only).
+    pub fn set_attester(e: Env, attester: Address, approved: bool) -> Result<(), QuipayError> {
+        let admin = Self::get_admin(e.clone())?;
+        admin.require_auth();
+
+        let key = DataKey::Attester(attester.clone());
+        if approved {
+            e.storage().persistent().set(&key, &true);
+        } else {
+            e.storage().persistent().remove(&key);
+        }
+
+        events::emit(&e, &admin, AttesterSet { attester, approved });
+        Ok(())
+    }
+
+    pub fn is_attester(e: Env, attester: Address) -> bool {
+        e.storage()
+            .persistent()
+            .get(&DataKey::Attester(attester))
+            .unwrap_or(false)
+    }
+
+    /// Record an attestation for a registered worker, replacing any previous one.
+    /// Only approved attesters can call this.
+    pub fn attest(
+        e: Env,
+        attester: Address,
+        worker: Address,
+        level: u32,
+        jurisdiction: String,
+        expires_at: u64,
+        provider_ref: BytesN<32>,
+    ) -> Result<(), QuipayError> {
+        attester.require_auth();
+        require!(
+            Self::is_attester(e.clone(), attester.clone()),
+            QuipayError::AttesterNotApproved
+        );
+        require!(
+            Self::is_registered(e.clone(), worker.clone()),
+            QuipayError::WorkerNotFound
+        );
+        let now = e.ledger().timestamp();
+        require!(expires_at > now, QuipayError::InvalidTimeRange);
+
+        let attestation = Attestation {
+            attester: attester.clone(),
+            level,
+            jurisdiction,
+            provider_ref,
+            issued_at: now,
+            expires_at,
+        };
+        e.storage()
+            .persistent()
+            .set(&DataKey::Attestation(worker.clone()), &attestation);
+
+        events::emit(
+            &e,
+            &attester,
+            KycAttested {
+                worker,
+                attester: attester.clone(),
+                level,
+                expires_at,
+            },
+        );
+        Ok(())
+    }
+
+    /// Remove a worker's attestation. Callable by its attester or the admin.
+    pub fn revoke_attestation(e: Env, caller: Address, worker: Address) -> Result<(), QuipayError> {
+        caller.require_auth();
+
+        let attestation = Self::get_attestation(e.clone(), worker.clone())
+            .ok_or(QuipayError::AttestationNotFound)?;
+        require!(
+            caller == attestation.attester || caller == Self::get_admin(e.clone())?,
+            QuipayError::Unauthorized
+        );
+        e.storage()
+            .persistent()
+            .remove(&DataKey::Attestation(worker.clone()));
+
+        events::emit(
+            &e,
+            &caller,
+            KycRevoked {
+                worker,
+                attester: attestation.attester,
+            },
+        );
+        Ok(())
+    }
+
+    /// The worker's recorded attestation, whether or not it is still valid.
+    pub fn get_attestation(e: Env, worker: Address) -> Option<Attestation> {
+        e.storage().persistent().get(&DataKey::Attestation(worker))
+    }
+
+    /// Level of the worker's valid attestation, or 0 if there is none.
+    pub fn get_kyc_level(e: Env, worker: Address) -> u32 {
+        Self::valid_attestation(&e, worker)
+            .map(|attestation| attestation.level)
+            .unwrap_or(0)
+    }
+
+    /// True if the worker holds a valid attestation of at least `min_level`.
+    pub fn has_valid_attestation(e: Env, worker: Address, min_level: u32) -> bool {
+        Self::valid_attestation(&e, worker)
+            .is_some_and(|attestation| attestation.level >= min_level)
+    }
+}
+
+impl WorkforceRegistryContract {
+    /// The worker's attestation if it has not expired and its attester is
+    /// still approved.
+    fn valid_attestation(e: &Env, worker: Address) -> Option<Attestation> {
+        Self::get_attestation(e.clone(), worker).filter(|attestation| {
+            e.ledger().timestamp() < attestation.expires_at
+                && Self::is_attester(e.clone(), attestation.attester.clone())
+        })
+    }
+}
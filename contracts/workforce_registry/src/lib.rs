@@ -1,6 +1,19 @@
 #![no_std]
-use quipay_common::{QuipayError, require};
-use soroban_sdk::{Address, Env, String, Vec, contract, contractimpl, contracttype, symbol_short};
+use events::{BlacklistSet, StreamActiveSet, WorkerRegistered, WorkerRemoved, WorkerUpdated};
+use quipay_common::{
+    AdminKeys, AdminStorage, PendingUpgrade, QuipayError, Role, VersionInfo, admin, rbac, require,
+    upgrade,
+};
+use soroban_sdk::{Address, BytesN, Env, String, Vec, contract, contractimpl, contracttype};
+
+mod employer;
+pub mod events;
+mod history;
+mod kyc;
+mod storage_ttl;
+pub use employer::{EmployerAction, EmployerRecord, EmployerRole, KybStatus};
+pub use history::{EmploymentRecord, EmploymentStatus, ProfileVersion, TerminationReason};
+pub use kyc::Attestation;
 
 #[contracttype]
 #[derive(Clone, Debug, PartialEq)]
@@ -20,8 +33,27 @@ pub enum DataKey {
     EmployerActiveWorkerByIndex(Address, u32),
     EmployerActiveWorkerIndex(Address, Address),
     BlacklistedWorker(Address),
+    StreamContract,
+    Attester(Address),
+    Attestation(Address),
+    EmploymentCount(Address),
+    Employment(Address, u32),
+    OpenEmployment(Address, Address), // (employer, worker) -> open record id
+    OpenEmploymentCount(Address),     // worker
+    ProfileVersionCount(Address),
+    ProfileVersion(Address, u32),
+    Employer(Address),
+    EmployerManager(Address, Address), // (employer, manager) -> role
+    EmployerManagers(Address),
+    KybVerifier(Address),
 }
 
+const ADMIN_KEYS: AdminKeys<DataKey> = AdminKeys {
+    storage: AdminStorage::Persistent,
+    admin: DataKey::Admin,
+    pending: DataKey::PendingAdmin,
+};
+
 #[contract]
 pub struct WorkforceRegistryContract;
 
@@ -34,60 +66,123 @@ impl WorkforceRegistryContract {
         }
 
         e.storage().persistent().set(&DataKey::Admin, &admin);
+        upgrade::set_initial_version(&e, (1, 0, 0));
         Ok(())
     }
 
     /// Get the current admin address
     pub fn get_admin(e: Env) -> Result<Address, QuipayError> {
-        e.storage()
-            .persistent()
-            .get(&DataKey::Admin)
-            .ok_or(QuipayError::NotInitialized)
+        admin::get_admin(&e, &ADMIN_KEYS)
     }
 
     /// Propose a new admin address (first step of two-step transfer)
     pub fn propose_admin(e: Env, new_admin: Address) -> Result<(), QuipayError> {
-        let admin = Self::get_admin(e.clone())?;
-        admin.require_auth();
-
-        e.storage()
-            .persistent()
-            .set(&DataKey::PendingAdmin, &new_admin);
-        Ok(())
+        admin::propose_admin(&e, &ADMIN_KEYS, &new_admin)
     }
 
     /// Get the pending admin address (if any)
     pub fn get_pending_admin(e: Env) -> Option<Address> {
-        e.storage().persistent().get(&DataKey::PendingAdmin)
+        admin::get_pending_admin(&e, &ADMIN_KEYS)
     }
 
     /// Accept the admin role (second step of two-step transfer)
     pub fn accept_admin(e: Env) -> Result<(), QuipayError> {
-        let pending_admin =
-            Self::get_pending_admin(e.clone()).ok_or(QuipayError::NoPendingAdmin)?;
+        admin::accept_admin(&e, &ADMIN_KEYS)?;
+        Ok(())
+    }
 
-        pending_admin.require_auth();
+    /// Transfer admin rights to a new address (one-step, backward compatible)
+    pub fn transfer_admin(e: Env, new_admin: Address) -> Result<(), QuipayError> {
+        admin::transfer_admin(&e, &ADMIN_KEYS, &new_admin)
+    }
 
-        // Set the new admin
-        e.storage()
-            .persistent()
-            .set(&DataKey::Admin, &pending_admin);
+    /// Propose an upgrade to `new_version` with a 48-hour timelock.
+    /// Requires the admin or an `Upgrader`.
+    pub fn propose_upgrade(
+        e: Env,
+        caller: Address,
+        new_wasm_hash: BytesN<32>,
+        new_version: (u32, u32, u32),
+    ) -> Result<(), QuipayError> {
+        let admin = Self::get_admin(e.clone())?;
+        rbac::require_role(&e, Role::Upgrader, &caller, &admin)?;
+        upgrade::propose(&e, &caller, new_wasm_hash, new_version)?;
+        Ok(())
+    }
 
-        // Clear the pending admin
-        e.storage().persistent().remove(&DataKey::PendingAdmin);
+    /// Execute the pending upgrade once its timelock has passed.
+    /// Requires the admin or an `Upgrader`.
+    pub fn execute_upgrade(e: Env, caller: Address) -> Result<(), QuipayError> {
+        let admin = Self::get_admin(e.clone())?;
+        rbac::require_role(&e, Role::Upgrader, &caller, &admin)?;
+        upgrade::execute(&e, &caller)?;
+        Ok(())
+    }
 
+    /// Cancel the pending upgrade.
+    /// Requires the admin or an `Upgrader`.
+    pub fn cancel_upgrade(e: Env, caller: Address) -> Result<(), QuipayError> {
+        let admin = Self::get_admin(e.clone())?;
+        rbac::require_role(&e, Role::Upgrader, &caller, &admin)?;
+        upgrade::cancel(&e, &caller)?;
         Ok(())
     }
 
-    /// Transfer admin rights to a new address (one-step, backward compatible)
-    pub fn transfer_admin(e: Env, new_admin: Address) -> Result<(), QuipayError> {
+    /// Reinstall the WASM replaced by the last upgrade, without a timelock.
+    /// Requires the admin or an `Upgrader`.
+    pub fn rollback_upgrade(e: Env, caller: Address) -> Result<VersionInfo, QuipayError> {
+        let admin = Self::get_admin(e.clone())?;
+        rbac::require_role(&e, Role::Upgrader, &caller, &admin)?;
+        upgrade::rollback(&e, &caller)
+    }
+
+    /// Run the data migrations of the installed version, once, after an
+    /// upgrade from `from_version`.
+    /// Requires the admin or an `Upgrader`.
+    pub fn migrate(
+        e: Env,
+        caller: Address,
+        from_version: (u32, u32, u32),
+    ) -> Result<VersionInfo, QuipayError> {
+        let admin = Self::get_admin(e.clone())?;
+        rbac::require_role(&e, Role::Upgrader, &caller, &admin)?;
+        // No storage layout changes to migrate yet.
+        upgrade::begin_migration(&e, from_version)
+    }
+
+    pub fn get_pending_upgrade(e: Env) -> Option<PendingUpgrade> {
+        upgrade::get_pending(&e)
+    }
+
+    pub fn get_version(e: Env) -> VersionInfo {
+        upgrade::installed_version(&e)
+    }
+
+    /// Grant a role to `account` (admin only).
+    pub fn grant_role(e: Env, role: Role, account: Address) -> Result<(), QuipayError> {
         let admin = Self::get_admin(e.clone())?;
         admin.require_auth();
+        rbac::grant_role(&e, &admin, role, &account);
+        Ok(())
+    }
 
-        e.storage().persistent().set(&DataKey::Admin, &new_admin);
+    /// Revoke a role from `account` (admin only).
+    pub fn revoke_role(e: Env, role: Role, account: Address) -> Result<(), QuipayError> {
+        let admin = Self::get_admin(e.clone())?;
+        admin.require_auth();
+        rbac::revoke_role(&e, &admin, role, &account);
         Ok(())
     }
 
+    /// Give up a role held by `account`.
+    pub fn renounce_role(e: Env, account: Address, role: Role) {
+        rbac::renounce_role(&e, role, &account);
+    }
+
+    pub fn has_role(e: Env, role: Role, account: Address) -> bool {
+        rbac::has_role(&e, role, &account)
+    }
+
     /// Registers a new worker profile.
     ///
     /// # Arguments
@@ -126,15 +221,16 @@ impl WorkforceRegistryContract {
         };
 
         e.storage().persistent().set(&key, &profile);
-
-        e.events().publish(
-            (
-                symbol_short!("w_reg"),
-                symbol_short!("reg"),
-                worker.clone(),
-                preferred_token.clone(),
-            ),
-            metadata_hash.clone(),
+        Self::record_profile_version(&e, &worker, &profile);
+
+        events::emit(
+            &e,
+            &worker,
+            WorkerRegistered {
+                worker: worker.clone(),
+                preferred_token: preferred_token.clone(),
+                metadata_hash: metadata_hash.clone(),
+            },
         );
 
         Ok(())
@@ -178,15 +274,16 @@ impl WorkforceRegistryContract {
         };
 
         e.storage().persistent().set(&key, &profile);
-
-        e.events().publish(
-            (
-                symbol_short!("w_reg"),
-                symbol_short!("upd"),
-                worker.clone(),
-                preferred_token.clone(),
-            ),
-            metadata_hash,
+        Self::record_profile_version(&e, &worker, &profile);
+
+        events::emit(
+            &e,
+            &worker,
+            WorkerUpdated {
+                worker: worker.clone(),
+                preferred_token: preferred_token.clone(),
+                metadata_hash,
+            },
         );
 
         Ok(())
@@ -205,6 +302,15 @@ impl WorkforceRegistryContract {
         e.storage().persistent().get(&key)
     }
 
+    /// Returns the token a registered worker prefers to be paid in.
+    ///
+    /// # Arguments
+    /// * `e` - The environment.
+    /// * `worker` - The address of the worker to look up.
+    pub fn get_preferred_token(e: Env, worker: Address) -> Option<Address> {
+        Self::get_worker(e, worker).map(|profile| profile.preferred_token)
+    }
+
     /// Checks if a worker is registered.
     ///
     /// # Arguments
@@ -242,91 +348,60 @@ impl WorkforceRegistryContract {
             QuipayError::WorkerNotFound
         );
 
-        let idx_key = DataKey::EmployerActiveWorkerIndex(employer.clone(), worker.clone());
-        let is_active = e.storage().persistent().has(&idx_key);
-
-        if active {
-            if is_active {
-                return Ok(());
-            }
-
-            let count_key = DataKey::EmployerActiveWorkerCount(employer.clone());
-            let count: u32 = e.storage().persistent().get(&count_key).unwrap_or(0);
-
-            let by_index_key = DataKey::EmployerActiveWorkerByIndex(employer.clone(), count);
-            e.storage().persistent().set(&by_index_key, &worker);
-
-            let stored_index: u32 = count + 1;
-            e.storage().persistent().set(&idx_key, &stored_index);
-            e.storage().persistent().set(&count_key, &(count + 1));
-
-            e.events().publish(
-                (
-                    symbol_short!("w_reg"),
-                    symbol_short!("st_act"),
-                    employer.clone(),
-                    worker.clone(),
-                ),
-                true,
-            );
-        } else {
-            if !is_active {
-                return Ok(());
-            }
+        Self::update_stream_active(&e, &employer, employer.clone(), worker, active)
+    }
 
-            let count_key = DataKey::EmployerActiveWorkerCount(employer.clone());
-            let count: u32 = e.storage().persistent().get(&count_key).unwrap_or(0);
-            if count == 0 {
-                e.storage().persistent().remove(&idx_key);
-                return Ok(());
-            }
+    /// Set the PayrollStream contract allowed to call `sync_stream_active`
+    /// (admin only). Pass `None` to revoke it.
+    pub fn set_stream_contract(
+        e: Env,
+        stream_contract: Option<Address>,
+    ) -> Result<(), QuipayError> {
+        let admin = Self::get_admin(e.clone())?;
+        admin.require_auth();
 
-            let stored_index: u32 = e
+        match stream_contract {
+            Some(addr) => e
                 .storage()
                 .persistent()
-                .get(&idx_key)
-                .ok_or(QuipayError::StorageError)?;
-            let remove_pos: u32 = stored_index - 1;
-            let last_pos: u32 = count - 1;
-
-            if remove_pos != last_pos {
-                let last_key = DataKey::EmployerActiveWorkerByIndex(employer.clone(), last_pos);
-                let last_worker: Address = e
-                    .storage()
-                    .persistent()
-                    .get(&last_key)
-                    .ok_or(QuipayError::StorageError)?;
-
-                let remove_key = DataKey::EmployerActiveWorkerByIndex(employer.clone(), remove_pos);
-                e.storage().persistent().set(&remove_key, &last_worker);
-
-                let last_worker_idx_key =
-                    DataKey::EmployerActiveWorkerIndex(employer.clone(), last_worker.clone());
-                e.storage()
-                    .persistent()
-                    .set(&last_worker_idx_key, &(remove_pos + 1));
+                .set(&DataKey::StreamContract, &addr),
+            None => e.storage().persistent().remove(&DataKey::StreamContract),
+        }
+        Ok(())
+    }
 
-                e.storage().persistent().remove(&last_key);
-            } else {
-                let last_key = DataKey::EmployerActiveWorkerByIndex(employer.clone(), last_pos);
-                e.storage().persistent().remove(&last_key);
-            }
+    pub fn get_stream_contract(e: Env) -> Option<Address> {
+        e.storage().persistent().get(&DataKey::StreamContract)
+    }
 
-            e.storage().persistent().remove(&idx_key);
-            e.storage().persistent().set(&count_key, &(count - 1));
+    /// Keeps the active-worker index in sync with PayrollStream.
+    ///
+    /// Called by the configured stream contract when a stream is created or
+    /// closed, so it works even when the employer did not sign the
+    /// transaction (e.g. a worker's final withdrawal). Activation requires a
+    /// registered, non-blacklisted worker; deactivation always succeeds.
+    pub fn sync_stream_active(
+        e: Env,
+        employer: Address,
+        worker: Address,
+        active: bool,
+    ) -> Result<(), QuipayError> {
+        let stream_contract =
+            Self::get_stream_contract(e.clone()).ok_or(QuipayError::NotInitialized)?;
+        stream_contract.require_auth();
 
-            e.events().publish(
-                (
-                    symbol_short!("w_reg"),
-                    symbol_short!("st_act"),
-                    employer.clone(),
-                    worker.clone(),
-                ),
-                false,
+        if active {
+            require!(
+                !Self::is_blacklisted(e.clone(), worker.clone()),
+                QuipayError::AddressBlacklisted
+            );
+            require!(
+                Self::is_registered(e.clone(), worker.clone()),
+                QuipayError::WorkerNotFound
             );
         }
 
-        Ok(())
+        Self::update_stream_active(&e, &stream_contract, employer, worker, active)
     }
 
     pub fn get_workers_by_employer(
@@ -386,31 +461,38 @@ impl WorkforceRegistryContract {
         if e.storage().persistent().has(&idx_key) {
             Self::set_stream_active(e.clone(), employer.clone(), worker.clone(), false)?;
         }
+        Self::end_open_employment(&e, &employer, &worker)?;
 
+        // Employment records and profile versions are kept.
         e.storage().persistent().remove(&key);
 
-        e.events().publish(
-            (
-                symbol_short!("w_reg"),
-                symbol_short!("rem"),
-                employer,
+        events::emit(
+            &e,
+            &employer,
+            WorkerRemoved {
+                employer: employer.clone(),
                 worker,
-            ),
-            (),
+            },
         );
 
         Ok(())
     }
 
-    /// Sets blacklist status for a worker (admin only)
+    /// Sets blacklist status for a worker (admin or `Blacklister`)
     ///
     /// # Arguments
     /// * `e` - The environment.
+    /// * `caller` - The admin or a `Blacklister`.
     /// * `worker` - The worker address to blacklist/unblacklist.
     /// * `blacklisted` - True to blacklist, false to unblacklist.
-    pub fn set_blacklisted(e: Env, worker: Address, blacklisted: bool) -> Result<(), QuipayError> {
+    pub fn set_blacklisted(
+        e: Env,
+        caller: Address,
+        worker: Address,
+        blacklisted: bool,
+    ) -> Result<(), QuipayError> {
         let admin = Self::get_admin(e.clone())?;
-        admin.require_auth();
+        rbac::require_role(&e, Role::Blacklister, &caller, &admin)?;
 
         let key = DataKey::BlacklistedWorker(worker.clone());
 
@@ -420,14 +502,13 @@ impl WorkforceRegistryContract {
             e.storage().persistent().remove(&key);
         }
 
-        e.events().publish(
-            (
-                symbol_short!("registry"),
-                symbol_short!("blacklist"),
-                worker.clone(),
+        events::emit(
+            &e,
+            &caller,
+            BlacklistSet {
+                worker: worker.clone(),
                 blacklisted,
-            ),
-            (),
+            },
         );
 
         Ok(())
@@ -447,4 +528,101 @@ impl WorkforceRegistryContract {
     }
 }
 
+impl WorkforceRegistryContract {
+    /// Add or remove `worker` from `employer`'s active-worker index.
+    fn update_stream_active(
+        e: &Env,
+        actor: &Address,
+        employer: Address,
+        worker: Address,
+        active: bool,
+    ) -> Result<(), QuipayError> {
+        let idx_key = DataKey::EmployerActiveWorkerIndex(employer.clone(), worker.clone());
+        let is_active = e.storage().persistent().has(&idx_key);
+
+        if active {
+            if is_active {
+                return Ok(());
+            }
+
+            let count_key = DataKey::EmployerActiveWorkerCount(employer.clone());
+            let count: u32 = e.storage().persistent().get(&count_key).unwrap_or(0);
+
+            let by_index_key = DataKey::EmployerActiveWorkerByIndex(employer.clone(), count);
+            e.storage().persistent().set(&by_index_key, &worker);
+
+            let stored_index: u32 = count + 1;
+            e.storage().persistent().set(&idx_key, &stored_index);
+            e.storage().persistent().set(&count_key, &(count + 1));
+
+            events::emit(
+                e,
+                actor,
+                StreamActiveSet {
+                    employer: employer.clone(),
+                    worker: worker.clone(),
+                    active,
+                },
+            );
+        } else {
+            if !is_active {
+                return Ok(());
+            }
+
+            let count_key = DataKey::EmployerActiveWorkerCount(employer.clone());
+            let count: u32 = e.storage().persistent().get(&count_key).unwrap_or(0);
+            if count == 0 {
+                e.storage().persistent().remove(&idx_key);
+                return Ok(());
+            }
+
+            let stored_index: u32 = e
+                .storage()
+                .persistent()
+                .get(&idx_key)
+                .ok_or(QuipayError::StorageError)?;
+            let remove_pos: u32 = stored_index - 1;
+            let last_pos: u32 = count - 1;
+
+            if remove_pos != last_pos {
+                let last_key = DataKey::EmployerActiveWorkerByIndex(employer.clone(), last_pos);
+                let last_worker: Address = e
+                    .storage()
+                    .persistent()
+                    .get(&last_key)
+                    .ok_or(QuipayError::StorageError)?;
+
+                let remove_key = DataKey::EmployerActiveWorkerByIndex(employer.clone(), remove_pos);
+                e.storage().persistent().set(&remove_key, &last_worker);
+
+                let last_worker_idx_key =
+                    DataKey::EmployerActiveWorkerIndex(employer.clone(), last_worker.clone());
+                e.storage()
+                    .persistent()
+                    .set(&last_worker_idx_key, &(remove_pos + 1));
+
+                e.storage().persistent().remove(&last_key);
+            } else {
+                let last_key = DataKey::EmployerActiveWorkerByIndex(employer.clone(), last_pos);
+                e.storage().persistent().remove(&last_key);
+            }
+
+            e.storage().persistent().remove(&idx_key);
+            e.storage().persistent().set(&count_key, &(count - 1));
+
+            events::emit(
+                e,
+                actor,
+                StreamActiveSet {
+                    employer: employer.clone(),
+                    worker: worker.clone(),
+                    active,
+                },
+            );
+        }
+
+        Ok(())
+    }
+}
+
 mod test;
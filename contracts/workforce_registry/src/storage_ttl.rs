@@ -0,0 +1,95 @@
+//! Keeper entry points for the registry's storage TTL.
+//!
+//! A worker's profile, attestation and employment history must outlive the
+//! employments they describe, so `bump_ttl` lets any caller willing to pay
+//! the rent keep them alive. Supported scopes:
+//!
+//! - `Contract`: instance storage, the admin, the linked stream contract and
+//!   the upgrade state.
+//! - `Account(worker)`: the profile and its versions, blacklist flag,
+//!   attestation and employment records.
+//! - `Account(employer)`: the employer record, managers, active worker list
+//!   and open employments.
+//! - `Account(attester)`: the attester approval.
+//!
+//! An address that is several of these gets all of them extended, along with
+//! any roles it holds.
+
+use super::*;
+use quipay_common::{TtlScope, ttl};
+
+#[contractimpl]
+impl WorkforceRegistryContract {
+    /// Extend every entry of `scope` to the full policy TTL. Permissionless.
+    /// Returns the ledger the entries stay live until.
+    pub fn bump_ttl(e: Env, scope: TtlScope) -> Result<u32, QuipayError> {
+        match &scope {
+            TtlScope::Contract => {
+                ttl::extend_instance(&e);
+                upgrade::extend_ttl(&e);
+                for key in [
+                    DataKey::Admin,
+                    DataKey::PendingAdmin,
+                    DataKey::StreamContract,
+                ] {
+                    ttl::extend_fully(&e, &key);
+                }
+            }
+            TtlScope::Account(account) => {
+                Self::extend_worker_ttl(&e, account);
+                Self::extend_employer_ttl(&e, account);
+                ttl::extend_fully(&e, &DataKey::Attester(account.clone()));
+                ttl::extend_fully(&e, &DataKey::KybVerifier(account.clone()));
+                rbac::extend_roles_ttl(&e, account);
+            }
+            _ => return Err(QuipayError::UnsupportedTtlScope),
+        }
+        Ok(ttl::record_bump(&e, &scope))
+    }
+
+    /// Ledger `scope`'s entries were last extended to by `bump_ttl`, or
+    /// `None` if no keeper has bumped them.
+    pub fn get_ttl_expiry(e: Env, scope: TtlScope) -> Option<u32> {
+        ttl::expiry(&e, &scope)
+    }
+}
+
+impl WorkforceRegistryContract {
+    fn extend_worker_ttl(e: &Env, worker: &Address) {
+        ttl::extend_fully(e, &DataKey::Worker(worker.clone()));
+        ttl::extend_fully(e, &DataKey::BlacklistedWorker(worker.clone()));
+        ttl::extend_fully(e, &DataKey::Attestation(worker.clone()));
+
+        ttl::extend_fully(e, &DataKey::EmploymentCount(worker.clone()));
+        ttl::extend_fully(e, &DataKey::OpenEmploymentCount(worker.clone()));
+        for id in 0..Self::get_employment_count(e.clone(), worker.clone()) {
+            ttl::extend_fully(e, &DataKey::Employment(worker.clone(), id));
+        }
+        ttl::extend_fully(e, &DataKey::ProfileVersionCount(worker.clone()));
+        for version in 0..Self::get_profile_version_count(e.clone(), worker.clone()) {
+            ttl::extend_fully(e, &DataKey::ProfileVersion(worker.clone(), version));
+        }
+    }
+
+    fn extend_employer_ttl(e: &Env, employer: &Address) {
+        ttl::extend_fully(e, &DataKey::Employer(employer.clone()));
+        ttl::extend_fully(e, &DataKey::EmployerManagers(employer.clone()));
+        for manager in Self::get_managers(e.clone(), employer.clone()).iter() {
+            ttl::extend_fully(e, &DataKey::EmployerManager(employer.clone(), manager));
+        }
+
+        let count_key = DataKey::EmployerActiveWorkerCount(employer.clone());
+        let count: u32 = e.storage().persistent().get(&count_key).unwrap_or(0);
+        ttl::extend_fully(e, &count_key);
+        for i in 0..count {
+            let by_index_key = DataKey::EmployerActiveWorkerByIndex(employer.clone(), i);
+            let Some(worker) = e.storage().persistent().get::<_, Address>(&by_index_key) else {
+                continue;
+            };
+            ttl::extend_fully(e, &by_index_key);
+            let index_key = DataKey::EmployerActiveWorkerIndex(employer.clone(), worker.clone());
+            ttl::extend_fully(e, &index_key);
+            ttl::extend_fully(e, &DataKey::OpenEmployment(employer.clone(), worker));
+        }
+    }
+}
@@ -3,8 +3,11 @@
 extern crate std;
 
 use super::*;
-use quipay_common::QuipayError;
-use soroban_sdk::{Address, Env, String, testutils::Address as _};
+use quipay_common::{QuipayError, Role};
+use soroban_sdk::{
+    Address, BytesN, Env, IntoVal, String, TryFromVal,
+    testutils::{Address as _, Events as _, Ledger},
+};
 use std::vec::Vec as StdVec;
 
 #[test]
@@ -27,6 +30,21 @@ fn test_register_and_get_worker() {
         .try_register_worker(&worker, &preferred_token, &metadata_hash)
         .unwrap();
 
+    let (contract, topics, data) = e.events().all().last().unwrap();
+    assert_eq!(contract, contract_id);
+    assert_eq!(
+        topics,
+        quipay_common::events::topics::<events::WorkerRegistered>(&e, &worker).into_val(&e)
+    );
+    assert_eq!(
+        events::WorkerRegistered::try_from_val(&e, &data).unwrap(),
+        events::WorkerRegistered {
+            worker: worker.clone(),
+            preferred_token: preferred_token.clone(),
+            metadata_hash: metadata_hash.clone(),
+        }
+    );
+
     // Verify registration
     assert_eq!(client.is_registered(&worker), true);
 
@@ -34,6 +52,7 @@ fn test_register_and_get_worker() {
     assert_eq!(profile.wallet, worker);
     assert_eq!(profile.preferred_token, preferred_token);
     assert_eq!(profile.metadata_hash, metadata_hash);
+    assert_eq!(client.get_preferred_token(&worker), Some(preferred_token));
 }
 
 #[test]
@@ -372,10 +391,466 @@ fn test_set_blacklisted_requires_admin() {
     client.initialize(&admin);
 
     // Admin can blacklist
-    client.set_blacklisted(&worker, &true);
+    client.set_blacklisted(&admin, &worker, &true);
     assert_eq!(client.is_blacklisted(&worker), true);
 
     // Admin can unblacklist
-    client.set_blacklisted(&worker, &false);
+    client.set_blacklisted(&admin, &worker, &false);
     assert_eq!(client.is_blacklisted(&worker), false);
+
+    // So can a Blacklister, until the role is revoked
+    let blacklister = Address::generate(&env);
+    assert_eq!(
+        client.try_set_blacklisted(&blacklister, &worker, &true),
+        Err(Ok(QuipayError::InsufficientPermissions))
+    );
+    client.grant_role(&Role::Blacklister, &blacklister);
+    client.set_blacklisted(&blacklister, &worker, &true);
+    assert_eq!(client.is_blacklisted(&worker), true);
+    client.revoke_role(&Role::Blacklister, &blacklister);
+    assert_eq!(
+        client.try_set_blacklisted(&blacklister, &worker, &false),
+        Err(Ok(QuipayError::InsufficientPermissions))
+    );
+}
+
+#[test]
+fn test_sync_stream_active_by_stream_contract() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(WorkforceRegistryContract, ());
+    let client = WorkforceRegistryContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    let stream_contract = Address::generate(&env);
+    let employer = Address::generate(&env);
+    let worker = Address::generate(&env);
+    client.initialize(&admin);
+    client
+        .try_register_worker(
+            &worker,
+            &Address::generate(&env),
+            &String::from_str(&env, "QmHash"),
+        )
+        .unwrap();
+
+    // No stream contract configured yet
+    assert_eq!(
+        client.try_sync_stream_active(&employer, &worker, &true),
+        Err(Ok(QuipayError::NotInitialized))
+    );
+
+    client.set_stream_contract(&Some(stream_contract.clone()));
+    client.sync_stream_active(&employer, &worker, &true);
+    assert_eq!(env.auths()[0].0, stream_contract);
+    assert_eq!(
+        client
+            .get_workers_by_employer(&employer, &0u32, &10u32)
+            .len(),
+        1
+    );
+
+    // A blacklisted worker can no longer be activated, but can still be deactivated
+    client.set_blacklisted(&admin, &worker, &true);
+    assert_eq!(
+        client.try_set_stream_active(&employer, &worker, &false),
+        Err(Ok(QuipayError::AddressBlacklisted))
+    );
+    let other_employer = Address::generate(&env);
+    assert_eq!(
+        client.try_sync_stream_active(&other_employer, &worker, &true),
+        Err(Ok(QuipayError::AddressBlacklisted))
+    );
+    client.sync_stream_active(&employer, &worker, &false);
+    assert_eq!(
+        client
+            .get_workers_by_employer(&employer, &0u32, &10u32)
+            .len(),
+        0
+    );
+}
+
+/// Stand-in for an external KYC provider contract.
+mod mock_attester {
+    use soroban_sdk::{Address, BytesN, Env, IntoVal, String, Symbol, contract, contractimpl, vec};
+
+    #[contract]
+    pub struct MockAttester;
+
+    #[contractimpl]
+    impl MockAttester {
+        pub fn verify(env: Env, registry: Address, worker: Address, level: u32, expires_at: u64) {
+            let attester = env.current_contract_address();
+            env.invoke_contract::<()>(
+                &registry,
+                &Symbol::new(&env, "attest"),
+                vec![
+                    &env,
+                    attester.into_val(&env),
+                    worker.into_val(&env),
+                    level.into_val(&env),
+                    String::from_str(&env, "US").into_val(&env),
+                    expires_at.into_val(&env),
+                    BytesN::from_array(&env, &[7; 32]).into_val(&env),
+                ],
+            );
+        }
+    }
+}
+
+#[test]
+fn test_attestation_from_approved_attester() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let client =
+        WorkforceRegistryContractClient::new(&e, &e.register(WorkforceRegistryContract, ()));
+    let attester =
+        mock_attester::MockAttesterClient::new(&e, &e.register(mock_attester::MockAttester, ()));
+    client.initialize(&Address::generate(&e));
+
+    let worker = Address::generate(&e);
+    client.register_worker(
+        &worker,
+        &Address::generate(&e),
+        &String::from_str(&e, "QmHash"),
+    );
+
+    let result = client.try_attest(
+        &attester.address,
+        &worker,
+        &2,
+        &String::from_str(&e, "US"),
+        &1_000,
+        &BytesN::from_array(&e, &[7; 32]),
+    );
+    assert_eq!(result, Err(Ok(QuipayError::AttesterNotApproved)));
+
+    client.set_attester(&attester.address, &true);
+    attester.verify(&client.address, &worker, &2, &1_000);
+
+    let attestation = client.get_attestation(&worker).unwrap();
+    assert_eq!(attestation.attester, attester.address);
+    assert_eq!(attestation.level, 2);
+    assert_eq!(attestation.jurisdiction, String::from_str(&e, "US"));
+    assert_eq!(client.get_kyc_level(&worker), 2);
+    assert!(client.has_valid_attestation(&worker, &2));
+    assert!(!client.has_valid_attestation(&worker, &3));
+
+    // Attestations lapse at expiry and when the attester is de-listed.
+    e.ledger().with_mut(|li| li.timestamp = 1_000);
+    assert_eq!(client.get_kyc_level(&worker), 0);
+    e.ledger().with_mut(|li| li.timestamp = 0);
+    client.set_attester(&attester.address, &false);
+    assert!(!client.has_valid_attestation(&worker, &1));
+    assert!(client.get_attestation(&worker).is_some());
+}
+
+#[test]
+fn test_revoke_attestation() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let client =
+        WorkforceRegistryContractClient::new(&e, &e.register(WorkforceRegistryContract, ()));
+    client.initialize(&Address::generate(&e));
+    let attester = Address::generate(&e);
+    let other = Address::generate(&e);
+    client.set_attester(&attester, &true);
+    client.set_attester(&other, &true);
+
+    let worker = Address::generate(&e);
+    let unregistered = Address::generate(&e);
+    client.register_worker(
+        &worker,
+        &Address::generate(&e),
+        &String::from_str(&e, "QmHash"),
+    );
+    let jurisdiction = String::from_str(&e, "FR");
+    let provider_ref = BytesN::from_array(&e, &[1; 32]);
+
+    let result = client.try_attest(
+        &attester,
+        &unregistered,
+        &1,
+        &jurisdiction,
+        &100,
+        &provider_ref,
+    );
+    assert_eq!(result, Err(Ok(QuipayError::WorkerNotFound)));
+    let result = client.try_attest(&attester, &worker, &1, &jurisdiction, &0, &provider_ref);
+    assert_eq!(result, Err(Ok(QuipayError::InvalidTimeRange)));
+
+    client.attest(&attester, &worker, &1, &jurisdiction, &100, &provider_ref);
+    let result = client.try_revoke_attestation(&other, &worker);
+    assert_eq!(result, Err(Ok(QuipayError::Unauthorized)));
+
+    client.revoke_attestation(&attester, &worker);
+    assert!(client.get_attestation(&worker).is_none());
+    let result = client.try_revoke_attestation(&attester, &worker);
+    assert_eq!(result, Err(Ok(QuipayError::AttestationNotFound)));
+}
+
+#[test]
+fn test_employment_history_survives_removal() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let client =
+        WorkforceRegistryContractClient::new(&e, &e.register(WorkforceRegistryContract, ()));
+    client.initialize(&Address::generate(&e));
+
+    let worker = Address::generate(&e);
+    let first_employer = Address::generate(&e);
+    let second_employer = Address::generate(&e);
+    let role = BytesN::from_array(&e, &[3; 32]);
+    client.register_worker(
+        &worker,
+        &Address::generate(&e),
+        &String::from_str(&e, "QmHash"),
+    );
+
+    let first = client.start_employment(&first_employer, &worker, &role, &100);
+    let result = client.try_start_employment(&first_employer, &worker, &role, &200);
+    assert_eq!(result, Err(Ok(QuipayError::EmploymentActive)));
+    let result =
+        client.try_end_employment(&first_employer, &worker, &50, &TerminationReason::Resigned);
+    assert_eq!(result, Err(Ok(QuipayError::InvalidTimeRange)));
+    client.end_employment(&first_employer, &worker, &500, &TerminationReason::Resigned);
+
+    let second = client.start_employment(&second_employer, &worker, &role, &600);
+    e.ledger().with_mut(|li| li.timestamp = 900);
+    client.remove_worker(&second_employer, &worker);
+    assert!(!client.is_registered(&worker));
+
+    let history = client.get_employment_history(&worker, &0, &10);
+    assert_eq!(history.len(), 2);
+    let record = history.get(first).unwrap();
+    assert_eq!(record.employer, first_employer);
+    assert_eq!(record.start, 100);
+    assert_eq!(
+        record.status,
+        EmploymentStatus::Ended(500, TerminationReason::Resigned)
+    );
+    let record = history.get(second).unwrap();
+    assert_eq!(record.employer, second_employer);
+    assert_eq!(
+        record.status,
+        EmploymentStatus::Ended(900, TerminationReason::Removed)
+    );
+
+    let result = client.try_end_employment(
+        &second_employer,
+        &worker,
+        &950,
+        &TerminationReason::Terminated,
+    );
+    assert_eq!(result, Err(Ok(QuipayError::EmploymentNotFound)));
+}
+
+#[test]
+fn test_worker_confirms_or_declines_employment() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let client =
+        WorkforceRegistryContractClient::new(&e, &e.register(WorkforceRegistryContract, ()));
+    client.initialize(&Address::generate(&e));
+
+    let worker = Address::generate(&e);
+    let role = BytesN::from_array(&e, &[4; 32]);
+    client.register_worker(
+        &worker,
+        &Address::generate(&e),
+        &String::from_str(&e, "QmHash"),
+    );
+
+    let employer = Address::generate(&e);
+    let id = client.start_employment(&employer, &worker, &role, &100);
+    assert!(!client.get_employment(&worker, &id).unwrap().confirmed);
+    client.confirm_employment(&worker, &id);
+    assert!(client.get_employment(&worker, &id).unwrap().confirmed);
+    let result = client.try_decline_employment(&worker, &id);
+    assert_eq!(result, Err(Ok(QuipayError::Unauthorized)));
+
+    // Unsolicited records count against the cap until the worker declines one.
+    for _ in 1..history::MAX_OPEN_EMPLOYMENTS {
+        client.start_employment(&Address::generate(&e), &worker, &role, &100);
+    }
+    assert_eq!(
+        client.get_open_employment_count(&worker),
+        history::MAX_OPEN_EMPLOYMENTS
+    );
+    let result = client.try_start_employment(&Address::generate(&e), &worker, &role, &100);
+    assert_eq!(result, Err(Ok(QuipayError::TooManyOpenEmployments)));
+
+    client.decline_employment(&worker, &(id + 1));
+    assert_eq!(
+        client.get_employment(&worker, &(id + 1)).unwrap().status,
+        EmploymentStatus::Ended(100, TerminationReason::Declined)
+    );
+    client.start_employment(&Address::generate(&e), &worker, &role, &100);
+    let result = client.try_decline_employment(&worker, &(id + 1));
+    assert_eq!(result, Err(Ok(QuipayError::EmploymentNotFound)));
+}
+
+#[test]
+fn test_profile_versions_are_kept() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let client =
+        WorkforceRegistryContractClient::new(&e, &e.register(WorkforceRegistryContract, ()));
+    let worker = Address::generate(&e);
+    let token1 = Address::generate(&e);
+    let token2 = Address::generate(&e);
+
+    client.register_worker(&worker, &token1, &String::from_str(&e, "QmV1"));
+    e.ledger().with_mut(|li| li.timestamp = 1_000);
+    client.update_worker(&worker, &token2, &String::from_str(&e, "QmV2"));
+
+    assert_eq!(client.get_profile_version_count(&worker), 2);
+    let v0 = client.get_profile_version(&worker, &0).unwrap();
+    assert_eq!(v0.profile.preferred_token, token1);
+    assert_eq!(v0.profile.metadata_hash, String::from_str(&e, "QmV1"));
+    assert_eq!(v0.recorded_at, 0);
+    let v1 = client.get_profile_version(&worker, &1).unwrap();
+    assert_eq!(v1.profile, client.get_worker(&worker).unwrap());
+    assert_eq!(v1.recorded_at, 1_000);
+    assert!(client.get_profile_version(&worker, &2).is_none());
+}
+
+#[test]
+fn test_employer_record_and_kyb() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let client =
+        WorkforceRegistryContractClient::new(&e, &e.register(WorkforceRegistryContract, ()));
+    let admin = Address::generate(&e);
+    client.initialize(&admin);
+
+    let employer = Address::generate(&e);
+    let name_hash = BytesN::from_array(&e, &[5; 32]);
+    client.register_employer(&employer, &name_hash);
+    let result = client.try_register_employer(&employer, &name_hash);
+    assert_eq!(result, Err(Ok(QuipayError::AlreadyInitialized)));
+
+    let record = client.get_employer(&employer).unwrap();
+    assert_eq!(record.kyb_status, KybStatus::Pending);
+    assert_eq!(record.admins.len(), 1);
+
+    let stranger = Address::generate(&e);
+    let result = client.try_set_kyb_status(&stranger, &employer, &KybStatus::Verified);
+    assert_eq!(result, Err(Ok(QuipayError::InsufficientPermissions)));
+
+    // KYC attesters vouch for workers only; KYB needs a separate verifier.
+    let attester = Address::generate(&e);
+    client.set_attester(&attester, &true);
+    let result = client.try_set_kyb_status(&attester, &employer, &KybStatus::Verified);
+    assert_eq!(result, Err(Ok(QuipayError::InsufficientPermissions)));
+    let verifier = Address::generate(&e);
+    client.set_kyb_verifier(&verifier, &true);
+    assert!(client.is_kyb_verifier(&verifier));
+    client.set_kyb_status(&verifier, &employer, &KybStatus::Verified);
+    assert_eq!(
+        client.get_employer(&employer).unwrap().kyb_status,
+        KybStatus::Verified
+    );
+
+    let result = client.try_set_kyb_status(&admin, &stranger, &KybStatus::Verified);
+    assert_eq!(result, Err(Ok(QuipayError::EmployerNotFound)));
+}
+
+#[test]
+fn test_employer_admins_and_manager_roles() {
+    let e = Env::default();
+    e.mock_all_auths();
+    let client =
+        WorkforceRegistryContractClient::new(&e, &e.register(WorkforceRegistryContract, ()));
+    let employer = Address::generate(&e);
+    client.register_employer(&employer, &BytesN::from_array(&e, &[5; 32]));
+
+    let cfo = Address::generate(&e);
+    let hr = Address::generate(&e);
+    let result = client.try_set_manager(&cfo, &employer, &hr, &EmployerRole::Hr);
+    assert_eq!(result, Err(Ok(QuipayError::InsufficientPermissions)));
+
+    client.add_employer_admin(&employer, &employer, &cfo);
+    client.set_manager(&cfo, &employer, &hr, &EmployerRole::Hr);
+    assert_eq!(
+        client.get_manager_role(&employer, &hr),
+        Some(EmployerRole::Hr)
+    );
+    assert_eq!(client.get_managers(&employer).len(), 1);
+
+    // Delegates act only for KYB-verified employers; the employer always can.
+    assert!(!client.can_act_for(&employer, &hr, &EmployerAction::CreateStream));
+    assert!(client.can_act_for(&employer, &employer, &EmployerAction::CreateStream));
+    assert!(!client.is_employer_verified(&employer));
+    client.initialize(&Address::generate(&e));
+    client.set_kyb_status(&client.get_admin(), &employer, &KybStatus::Verified);
+    assert!(client.is_employer_verified(&employer));
+
+    assert!(client.can_act_for(&employer, &cfo, &EmployerAction::PauseStream));
+    assert!(client.can_act_for(&employer, &hr, &EmployerAction::CreateStream));
+    assert!(client.can_act_for(&employer, &hr, &EmployerAction::CancelStream));
+    assert!(!client.can_act_for(&employer, &hr, &EmployerAction::PauseStream));
+
+    client.set_kyb_status(&client.get_admin(), &employer, &KybStatus::Suspended);
+    assert!(!client.can_act_for(&employer, &cfo, &EmployerAction::PauseStream));
+    client.set_kyb_status(&client.get_admin(), &employer, &KybStatus::Verified);
+
+    client.set_manager(&cfo, &employer, &hr, &EmployerRole::Viewer);
+    assert!(!client.can_act_for(&employer, &hr, &EmployerAction::CreateStream));
+    assert_eq!(client.get_managers(&employer).len(), 1);
+
+    let result = client.try_remove_employer_admin(&cfo, &employer, &employer);
+    assert_eq!(result, Err(Ok(QuipayError::InvalidAddress)));
+    client.remove_employer_admin(&employer, &employer, &cfo);
+    assert!(!client.can_act_for(&employer, &cfo, &EmployerAction::CreateStream));
+
+    client.remove_manager(&employer, &employer, &hr);
+    assert_eq!(client.get_manager_role(&employer, &hr), None);
+    assert_eq!(client.get_managers(&employer).len(), 0);
+}
+
+#[test]
+fn test_bump_ttl_keeps_worker_history_alive() {
+    use quipay_common::{TtlScope, ttl::TTL_EXTEND_TO};
+    use soroban_sdk::testutils::storage::Persistent as _;
+
+    let e = Env::default();
+    e.mock_all_auths();
+    let client =
+        WorkforceRegistryContractClient::new(&e, &e.register(WorkforceRegistryContract, ()));
+    client.initialize(&Address::generate(&e));
+    let employer = Address::generate(&e);
+    let worker = Address::generate(&e);
+    client.register_worker(
+        &worker,
+        &Address::generate(&e),
+        &String::from_str(&e, "QmHash"),
+    );
+    client.set_stream_active(&employer, &worker, &true);
+    let role = BytesN::from_array(&e, &[1; 32]);
+    let id = client.start_employment(&employer, &worker, &role, &100);
+
+    e.ledger().set_sequence_number(20);
+    let live_until = client.bump_ttl(&TtlScope::Account(worker.clone()));
+    assert_eq!(live_until, 20 + TTL_EXTEND_TO);
+    client.bump_ttl(&TtlScope::Account(employer.clone()));
+    client.bump_ttl(&TtlScope::Contract);
+    assert_eq!(
+        client.get_ttl_expiry(&TtlScope::Account(worker.clone())),
+        Some(live_until)
+    );
+    for key in [
+        DataKey::Worker(worker.clone()),
+        DataKey::Employment(worker.clone(), id),
+        DataKey::ProfileVersion(worker.clone(), 0),
+        DataKey::EmployerActiveWorkerByIndex(employer.clone(), 0),
+        DataKey::OpenEmployment(employer.clone(), worker.clone()),
+        DataKey::Admin,
+    ] {
+        let ttl = e.as_contract(&client.address, || e.storage().persistent().get_ttl(&key));
+        assert_eq!(ttl, TTL_EXTEND_TO);
+    }
+
+    let result = client.try_bump_ttl(&TtlScope::Stream(1));
+    assert_eq!(result, Err(Ok(QuipayError::UnsupportedTtlScope)));
 }
@@ -19,6 +19,18 @@ mod dummy_vault {
         pub fn add_liability(_env: Env, _token: Address, _amount: i128) {}
         pub fn remove_liability(_env: Env, _token: Address, _amount: i128) {}
         pub fn payout_liability(_env: Env, _to: Address, _token: Address, _amount: i128) {}
+        pub fn payout_stream_liability(
+            _env: Env,
+            _stream_id: u64,
+            _to: Address,
+            _token: Address,
+            amount: i128,
+        ) -> i128 {
+            amount
+        }
+        pub fn is_insolvent(_env: Env, _token: Address) -> bool {
+            false
+        }
     }
 }
 
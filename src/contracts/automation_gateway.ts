@@ -20,10 +20,12 @@ export enum Permission {
   CreateStream = 4,
   CancelStream = 5,
   RebalanceTreasury = 6,
+  ProposeActions = 7,
 }
 
 export interface Agent {
   address: string;
+  employer: string;
   permissions: Permission[];
   registered_at: number;
 }
@@ -52,11 +54,18 @@ export async function getAdmin(): Promise<string> {
   throw new Error("Failed to get admin");
 }
 
-export async function getAgent(agentAddress: string): Promise<Agent | null> {
+export async function getAgent(
+  employerAddress: string,
+  agentAddress: string,
+): Promise<Agent | null> {
   const server = getRpcServer();
   const contract = new Contract(AUTOMATION_GATEWAY_CONTRACT_ID);
 
-  const tx = contract.call("get_agent", new Address(agentAddress).toScVal());
+  const tx = contract.call(
+    "get_agent",
+    new Address(employerAddress).toScVal(),
+    new Address(agentAddress).toScVal(),
+  );
   const response = await server.simulateTransaction(
     new TransactionBuilder(
       await server.getAccount(AUTOMATION_GATEWAY_CONTRACT_ID),
@@ -70,6 +79,7 @@ export async function getAgent(agentAddress: string): Promise<Agent | null> {
     const native = scValToNative(response.result!.retval);
     return {
       address: native.address,
+      employer: native.employer,
       permissions: native.permissions,
       registered_at: Number(native.registered_at),
     };
@@ -78,6 +88,7 @@ export async function getAgent(agentAddress: string): Promise<Agent | null> {
 }
 
 export async function isAuthorized(
+  employerAddress: string,
   agentAddress: string,
   permission: Permission,
 ): Promise<boolean> {
@@ -86,6 +97,7 @@ export async function isAuthorized(
 
   const tx = contract.call(
     "is_authorized",
+    new Address(employerAddress).toScVal(),
     new Address(agentAddress).toScVal(),
     nativeToScVal(permission, { type: "u32" }),
   );
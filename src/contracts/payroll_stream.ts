@@ -589,11 +589,19 @@ export async function getWorkerWithdrawalEvents(
       try {
         if (ev.topic.length < 4) continue;
 
+        // Topics: "stream", "withdrawn", schema version, actor (the worker).
         const workerFromEvent = scValToNative(ev.topic[3]) as string;
         if (workerFromEvent !== workerAddress) continue;
 
-        const streamId = scValToNative(ev.topic[2]) as bigint;
-        const [amount, token] = scValToNative(ev.value) as [bigint, string];
+        const {
+          stream_id: streamId,
+          amount,
+          token,
+        } = scValToNative(ev.value) as {
+          stream_id: bigint;
+          amount: bigint;
+          token: string;
+        };
 
         results.push({
           streamId,
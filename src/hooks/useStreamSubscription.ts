@@ -36,8 +36,13 @@ export function useStreamSubscription(
     try {
       if (!event.topic || event.topic.length < 4) return;
 
-      const streamId = String(scValToNative(event.topic[2]) as bigint);
-      const [amount] = scValToNative(event.value) as [bigint, string];
+      // Topics: namespace, name, schema version, actor. The payload is a
+      // `StreamWithdrawn` struct, decoded as an object keyed by field name.
+      const { stream_id: streamIdRaw, amount } = scValToNative(event.value) as {
+        stream_id: bigint;
+        amount: bigint;
+      };
+      const streamId = String(streamIdRaw);
 
       onWithdrawalRef.current({
         streamId,
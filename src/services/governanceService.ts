@@ -190,7 +190,10 @@ export async function getExecutionHistory(
     const { sequence: latestLedger } = await server.getLatestLedger();
     const startLedger = Math.max(1, latestLedger - 17280);
 
-    const symUpgradeExec = nativeToScVal("up_exec", { type: "symbol" }).toXDR(
+    const symUpgrade = nativeToScVal("upgrade", { type: "symbol" }).toXDR(
+      "base64",
+    );
+    const symExecuted = nativeToScVal("executed", { type: "symbol" }).toXDR(
       "base64",
     );
 
@@ -200,7 +203,7 @@ export async function getExecutionHistory(
         {
           type: "contract",
           contractIds: [PAYROLL_VAULT_CONTRACT_ID],
-          topics: [["*", symUpgradeExec]],
+          topics: [[symUpgrade, symExecuted, "*", "*"]],
         },
       ],
       limit: 50,
@@ -209,7 +212,7 @@ export async function getExecutionHistory(
     return response.events.map((ev, idx) => {
       let executedBy = "";
       try {
-        executedBy = scValToNative(ev.topic[0]) as string;
+        executedBy = scValToNative(ev.topic[3]) as string;
       } catch {
         executedBy = "Unknown";
       }